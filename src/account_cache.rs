@@ -0,0 +1,113 @@
+use anyhow::Result;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::account::Account;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use crate::rpc::SendCounter;
+
+/// Accounts fetched during one invocation, keyed by pubkey (`None` = confirmed missing).
+/// Cleared whenever a transaction is sent, since it may have changed any of them.
+pub struct AccountCache {
+    state: Mutex<AccountCacheState>,
+    sends: SendCounter,
+}
+
+struct AccountCacheState {
+    sends_seen: u64,
+    entries: HashMap<Pubkey, Option<Account>>,
+}
+
+impl AccountCache {
+    pub fn new() -> Self {
+        Self {
+            state: Mutex::new(AccountCacheState {
+                sends_seen: 0,
+                entries: HashMap::new(),
+            }),
+            sends: SendCounter::default(),
+        }
+    }
+
+    /// Clear the cache whenever `sends` records a transaction (see `SendSettings::sends`).
+    pub fn with_sends(mut self, sends: SendCounter) -> Self {
+        self.sends = sends;
+        self
+    }
+
+    /// The account at `address` as of `commitment`, or `None` if it doesn't exist. RPC errors are returned, not cached.
+    /// Entries aren't keyed by commitment: callers sharing a cache read at the same one (`--read-commitment`).
+    pub fn get_account(&self, rpc_client: &RpcClient, address: &Pubkey, commitment: CommitmentConfig) -> Result<Option<Account>> {
+        self.get_or_fetch(address, self.sends.get(), || {
+            Ok(rpc_client.get_account_with_commitment(address, commitment)?.value)
+        })
+    }
+
+    fn get_or_fetch<F>(&self, address: &Pubkey, sends: u64, fetch: F) -> Result<Option<Account>>
+    where
+        F: FnOnce() -> Result<Option<Account>>,
+    {
+        let mut state = self.state.lock().unwrap();
+        if state.sends_seen != sends {
+            state.entries.clear();
+            state.sends_seen = sends;
+        } else if let Some(account) = state.entries.get(address) {
+            return Ok(account.clone());
+        }
+
+        let account = fetch()?;
+        state.entries.insert(*address, account.clone());
+        Ok(account)
+    }
+}
+
+impl Default for AccountCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    fn account() -> Account {
+        Account { lamports: 1_000, data: vec![1, 2, 3], owner: Pubkey::new_unique(), executable: false, rent_epoch: 0 }
+    }
+
+    #[test]
+    fn test_second_resolve_is_cached() {
+        let cache = AccountCache::new();
+        let address = Pubkey::new_unique();
+        let missing = Pubkey::new_unique();
+        let calls = Cell::new(0);
+        let fetch = |result: Option<Account>| || { calls.set(calls.get() + 1); Ok(result) };
+
+        // e.g. smart_resolve checks existence, then the build path reads the same account
+        for _ in 0..2 {
+            assert!(cache.get_or_fetch(&address, 0, fetch(Some(account()))).unwrap().is_some());
+        }
+        assert_eq!(calls.get(), 1);
+
+        // Missing accounts are cached too; errors are not
+        assert!(cache.get_or_fetch(&missing, 0, || Err(anyhow::anyhow!("timed out"))).is_err());
+        for _ in 0..2 {
+            assert!(cache.get_or_fetch(&missing, 0, fetch(None)).unwrap().is_none());
+        }
+        assert_eq!(calls.get(), 2);
+    }
+
+    #[test]
+    fn test_send_invalidates() {
+        let cache = AccountCache::new();
+        let address = Pubkey::new_unique();
+        let calls = Cell::new(0);
+
+        for sends in [0, 1, 1] {
+            cache.get_or_fetch(&address, sends, || { calls.set(calls.get() + 1); Ok(None) }).unwrap();
+        }
+        assert_eq!(calls.get(), 2);
+    }
+}
@@ -0,0 +1,131 @@
+use anyhow::{anyhow, Result};
+use solana_sdk::account::Account;
+use solana_sdk::pubkey::Pubkey;
+use crate::idl_loader::IdlLoader;
+
+const BYTES_PER_LINE: usize = 16;
+
+/// Select `[offset, offset + length)` from account data, clamping length to the data end.
+pub fn slice_data(data: &[u8], offset: usize, length: Option<usize>) -> Result<&[u8]> {
+    if offset > data.len() {
+        return Err(anyhow!("Offset {} is past the end of account data ({} bytes)", offset, data.len()));
+    }
+    let end = match length {
+        Some(len) => offset.saturating_add(len).min(data.len()),
+        None => data.len(),
+    };
+    Ok(&data[offset..end])
+}
+
+/// Render bytes like `xxd`: offset, 16 hex bytes in pairs, then printable ASCII.
+pub fn hex_dump(data: &[u8], base_offset: usize) -> String {
+    let mut out = String::new();
+    for (line_idx, chunk) in data.chunks(BYTES_PER_LINE).enumerate() {
+        out.push_str(&format!("{:08x}: ", base_offset + line_idx * BYTES_PER_LINE));
+
+        let mut hex = String::new();
+        for (i, byte) in chunk.iter().enumerate() {
+            hex.push_str(&format!("{:02x}", byte));
+            if i % 2 == 1 {
+                hex.push(' ');
+            }
+        }
+        // 16 bytes => 32 hex chars + 8 group separators
+        out.push_str(&format!("{:<40} ", hex));
+
+        for byte in chunk {
+            out.push(if byte.is_ascii_graphic() || *byte == b' ' { *byte as char } else { '.' });
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// Describe `--return-accounts` post-simulation state, decoding fields via the owner's IDL when known.
+pub fn format_post_accounts(idl_loader: Option<&IdlLoader>, accounts: &[(Pubkey, Option<Account>)]) -> String {
+    let mut out = String::new();
+    if accounts.is_empty() {
+        return out;
+    }
+    out.push_str("📦 Post-simulation accounts:\n");
+    for (pubkey, account) in accounts {
+        let Some(account) = account else {
+            out.push_str(&format!("  {}: <does not exist>\n", pubkey));
+            continue;
+        };
+        out.push_str(&format!("  {}: {} lamports, owner {}, {} bytes\n",
+                              pubkey, account.lamports, account.owner, account.data.len()));
+        let decoded = idl_loader.map(|l| l.decode_account(&account.owner.to_string(), &account.data));
+        match decoded {
+            Some(Ok(Some((name, fields)))) => {
+                out.push_str(&format!("    {}:\n", name));
+                for (field, value) in fields {
+                    out.push_str(&format!("      {} = {}\n", field, value));
+                }
+            }
+            Some(Err(e)) => out.push_str(&format!("    <IDL decode failed: {}>\n", e)),
+            _ => {
+                for line in hex_dump(&account.data, 0).lines() {
+                    out.push_str(&format!("    {}\n", line));
+                }
+            }
+        }
+    }
+    out
+}
+
+/// `field` of an account decoded as `account_type`, erroring with the layout's field names when it has no such field.
+pub fn decoded_field<'a>(account_type: &str, fields: &'a [(String, serde_json::Value)], field: &str) -> Result<&'a serde_json::Value> {
+    fields.iter().find(|(name, _)| name == field).map(|(_, value)| value).ok_or_else(|| {
+        let names: Vec<&str> = fields.iter().map(|(name, _)| name.as_str()).collect();
+        anyhow!("Field '{}' is not in the {} account layout (fields: {})", field, account_type, names.join(", "))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hex_dump_matches_xxd_layout() {
+        let data: Vec<u8> = b"Hello, Solana!\x00\x01ABC".to_vec();
+        let dump = hex_dump(&data, 0);
+        let lines: Vec<&str> = dump.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0], "00000000: 4865 6c6c 6f2c 2053 6f6c 616e 6121 0001  Hello, Solana!..");
+        assert_eq!(lines[1], "00000010: 4142 43                                  ABC");
+    }
+
+    #[test]
+    fn test_format_post_accounts_without_idl() {
+        let missing = Pubkey::new_unique();
+        let present = Pubkey::new_unique();
+        let account = Account { lamports: 42, data: vec![0xab, 0xcd], owner: Pubkey::default(), executable: false, rent_epoch: 0 };
+
+        let out = format_post_accounts(None, &[(missing, None), (present, Some(account))]);
+        assert!(out.contains(&format!("{}: <does not exist>", missing)));
+        assert!(out.contains(&format!("{}: 42 lamports", present)));
+        assert!(out.contains("00000000: abcd"));
+        assert!(format_post_accounts(None, &[]).is_empty());
+    }
+
+    #[test]
+    fn test_decoded_field() {
+        let fields = vec![
+            ("owner".to_string(), serde_json::json!("11111111111111111111111111111111")),
+            ("total_sent".to_string(), serde_json::json!(1_500)),
+        ];
+        assert_eq!(decoded_field("SendAccount", &fields, "total_sent").unwrap(), &serde_json::json!(1_500));
+        let err = decoded_field("SendAccount", &fields, "totalSent").unwrap_err().to_string();
+        assert!(err.contains("'totalSent'") && err.contains("SendAccount") && err.contains("owner, total_sent"));
+    }
+
+    #[test]
+    fn test_slice_data() {
+        let data = [0u8, 1, 2, 3, 4];
+        assert_eq!(slice_data(&data, 1, Some(2)).unwrap(), &[1, 2]);
+        assert_eq!(slice_data(&data, 3, Some(100)).unwrap(), &[3, 4]);
+        assert_eq!(slice_data(&data, 5, None).unwrap(), &[] as &[u8]);
+        assert!(slice_data(&data, 6, None).is_err());
+    }
+}
@@ -1,16 +1,69 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use solana_client::rpc_client::RpcClient;
+use solana_sdk::account::Account;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::instruction::AccountMeta;
 use solana_sdk::pubkey::Pubkey;
 use solana_sdk::signature::Keypair;
+use solana_sdk::system_program;
 use std::str::FromStr;
+use std::sync::Arc;
+use crate::account_cache::AccountCache;
+use crate::rent_cache::RentCache;
+use crate::outcome::{note, Notes};
+
+/// Lamports missing for a send to leave a recipient rent-exempt; only new accounts can fall short.
+pub fn rent_shortfall(recipient_exists: bool, lamports: u64, rent_exempt_minimum: u64) -> Option<u64> {
+    (!recipient_exists && lamports < rent_exempt_minimum).then(|| rent_exempt_minimum - lamports)
+}
 
 pub struct AccountResolver {
     rpc_client: RpcClient,
+    commitment: CommitmentConfig,
+    rent_cache: Arc<RentCache>,
+    account_cache: Arc<AccountCache>,
+    top_up_new_recipients: bool,
+    notes: Notes,
 }
 
 impl AccountResolver {
-    pub fn new(rpc_client: RpcClient) -> Self {
-        Self { rpc_client }
+    /// Account and balance reads during resolution use `commitment` (`--read-commitment`).
+    pub fn new(rpc_client: RpcClient, commitment: CommitmentConfig) -> Self {
+        Self {
+            rpc_client,
+            commitment,
+            rent_cache: Arc::new(RentCache::new()),
+            account_cache: Arc::new(AccountCache::new()),
+            top_up_new_recipients: false,
+            notes: Notes::default(),
+        }
+    }
+
+    pub fn commitment(&self) -> CommitmentConfig {
+        self.commitment
+    }
+
+    pub fn with_rent_cache(mut self, rent_cache: Arc<RentCache>) -> Self {
+        self.rent_cache = rent_cache;
+        self
+    }
+
+    pub fn with_account_cache(mut self, account_cache: Arc<AccountCache>) -> Self {
+        self.account_cache = account_cache;
+        self
+    }
+
+    /// Raise SOL sends to a new recipient up to its rent-exempt minimum instead of only warning
+    /// (`--top-up-new-recipient`).
+    pub fn with_top_up_new_recipients(mut self, enabled: bool) -> Self {
+        self.top_up_new_recipients = enabled;
+        self
+    }
+
+    /// Where rent warnings for new recipients go.
+    pub fn with_notes(mut self, notes: Notes) -> Self {
+        self.notes = notes;
+        self
     }
 
 
@@ -30,21 +83,38 @@ impl AccountResolver {
 
    
     pub fn get_minimum_rent(&self, account_size: usize) -> Result<u64> {
-        let rent = self.rpc_client.get_minimum_balance_for_rent_exemption(account_size)?;
-        Ok(rent)
+        self.rent_cache.minimum_balance(&self.rpc_client, account_size)
     }
 
     
     pub fn account_exists(&self, address: &Pubkey) -> Result<bool> {
-        match self.rpc_client.get_account(address) {
-            Ok(_) => Ok(true),
+        match self.account_cache.get_account(&self.rpc_client, address, self.commitment) {
+            Ok(account) => Ok(account.as_ref().is_some_and(is_live_account)),
             Err(_) => Ok(false),
         }
     }
 
+    /// Amount to send to `recipient`: warns when a new account would be left below the rent-exempt minimum,
+    /// and under `--top-up-new-recipient` raises the amount to that minimum.
+    pub fn fund_new_recipient(&self, recipient: &Pubkey, lamports: u64) -> Result<u64> {
+        let rent_exempt_minimum = self.get_minimum_rent(0)?;
+        let Some(shortfall) = rent_shortfall(self.account_exists(recipient)?, lamports, rent_exempt_minimum) else {
+            return Ok(lamports);
+        };
+        if self.top_up_new_recipients {
+            note!(self.notes, "🪙 {} is a new account: topping the send up by {} lamports to the rent-exempt minimum ({} lamports)",
+                 recipient, shortfall, rent_exempt_minimum);
+            return Ok(rent_exempt_minimum);
+        }
+        note!(self.notes, "⚠️  {} is a new account and {} lamports is below its rent-exempt minimum ({} lamports); \
+                  the transfer may be rejected. Pass --top-up-new-recipient to send the minimum instead",
+             recipient, lamports, rent_exempt_minimum);
+        Ok(lamports)
+    }
+
   
     pub fn get_balance(&self, address: &Pubkey) -> Result<u64> {
-        let balance = self.rpc_client.get_balance(address)?;
+        let balance = self.rpc_client.get_balance_with_commitment(address, self.commitment)?.value;
         Ok(balance)
     }
 
@@ -162,6 +232,46 @@ impl AccountResolver {
     }
 }
 
+/// A fetched account counts as existing unless it's a closed husk: zero lamports, no data,
+/// system-owned. The RPC can still return one of those briefly after the account is closed.
+pub fn is_live_account(account: &Account) -> bool {
+    !(account.lamports == 0 && account.data.is_empty() && system_program::check_id(&account.owner))
+}
+
+/// Error unless `account` is owned by `program_id`; view instructions sent to the wrong account
+/// otherwise fail on-chain with an unhelpful constraint error.
+pub fn check_owner(address: &Pubkey, account: &Account, program_id: &Pubkey) -> Result<()> {
+    if account.owner != *program_id {
+        return Err(anyhow!("Account {} is not owned by program {} (owner: {})", address, program_id, account.owner));
+    }
+    Ok(())
+}
+
+/// Fetch `address` at the client's commitment and `check_owner` it.
+pub fn fetch_owned_account(rpc_client: &RpcClient, address: &Pubkey, program_id: &Pubkey) -> Result<Account> {
+    let account = rpc_client.get_account_with_commitment(address, rpc_client.commitment())?.value
+        .filter(is_live_account)
+        .ok_or_else(|| anyhow!("Account {} not found", address))?;
+    check_owner(address, &account, program_id)?;
+    Ok(account)
+}
+
+/// Collapse duplicate pubkeys into one meta, keeping first-seen order.
+/// Signer/writable flags are OR-ed so the most privileged use wins.
+/// Only for assembled account lists; `tx_sender` applies the merged flags to instructions without collapsing positions.
+pub fn dedup_account_metas(metas: Vec<AccountMeta>) -> Vec<AccountMeta> {
+    let mut result: Vec<AccountMeta> = Vec::with_capacity(metas.len());
+    for meta in metas {
+        if let Some(existing) = result.iter_mut().find(|m| m.pubkey == meta.pubkey) {
+            existing.is_signer |= meta.is_signer;
+            existing.is_writable |= meta.is_writable;
+        } else {
+            result.push(meta);
+        }
+    }
+    result
+}
+
 #[derive(Debug)]
 pub struct SendAccountInfo {
     pub address: Pubkey,
@@ -215,8 +325,10 @@ mod tests {
         let user = Pubkey::new_unique();
         
         let resolver = AccountResolver::new(
-            RpcClient::new("https://api.devnet.solana.com".to_string())
+            RpcClient::new("https://api.devnet.solana.com".to_string()),
+            CommitmentConfig::processed(),
         );
+        assert_eq!(resolver.commitment(), CommitmentConfig::processed());
         
         let (pda, bump) = resolver.derive_user_pda(&user, &program_id, "send").unwrap();
         
@@ -225,4 +337,52 @@ mod tests {
       
         assert!(bump < 256);
     }
+
+    #[test]
+    fn test_dedup_account_metas() {
+        let a = Pubkey::new_unique();
+        let b = Pubkey::new_unique();
+
+        let metas = dedup_account_metas(vec![
+            AccountMeta::new_readonly(a, false),
+            AccountMeta::new_readonly(b, true),
+            AccountMeta::new(a, false),
+        ]);
+
+        assert_eq!(metas.len(), 2);
+        assert_eq!(metas[0].pubkey, a);
+        assert!(metas[0].is_writable);
+        assert!(!metas[0].is_signer);
+        assert_eq!(metas[1].pubkey, b);
+        assert!(metas[1].is_signer);
+        assert!(!metas[1].is_writable);
+    }
+
+    #[test]
+    fn test_zeroed_account_is_not_live() {
+        let program_id = Pubkey::new_unique();
+        let pda = Account { lamports: 1_280_640, data: vec![7; 56], owner: program_id, executable: false, rent_epoch: 0 };
+        let closed = Account { lamports: 0, data: Vec::new(), owner: system_program::id(), executable: false, rent_epoch: 0 };
+        assert!(is_live_account(&pda));
+        assert!(!is_live_account(&closed));
+        // Empty and unfunded, but still program-owned: not a closed account
+        assert!(is_live_account(&Account { owner: program_id, ..closed.clone() }));
+    }
+
+    #[test]
+    fn test_check_owner() {
+        let program_id = Pubkey::new_unique();
+        let address = Pubkey::new_unique();
+        let account = Account { lamports: 1_000_000, data: vec![0; 16], owner: program_id, executable: false, rent_epoch: 0 };
+        assert!(check_owner(&address, &account, &program_id).is_ok());
+        let err = check_owner(&address, &Account { owner: system_program::id(), ..account }, &program_id).unwrap_err();
+        assert_eq!(err.to_string(), format!("Account {} is not owned by program {} (owner: {})", address, program_id, system_program::id()));
+    }
+
+    #[test]
+    fn test_rent_shortfall() {
+        assert_eq!(rent_shortfall(false, 100_000, 890_880), Some(790_880));
+        assert_eq!(rent_shortfall(false, 890_880, 890_880), None);
+        assert_eq!(rent_shortfall(true, 100_000, 890_880), None);
+    }
 }
@@ -1,20 +1,45 @@
 use anyhow::{anyhow, Result};
 use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_request::TokenAccountsFilter;
 use solana_sdk::{
+    account::Account,
+    commitment_config::CommitmentConfig,
     instruction::Instruction,
     pubkey::Pubkey,
     signature::Signer,
     signer::keypair::Keypair,
-    transaction::Transaction,
     program_pack::Pack,
 };
-use spl_associated_token_account::get_associated_token_address;
-use spl_token::state::Account as TokenAccount;
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+use spl_token::state::{Account as TokenAccount, Mint};
+use std::collections::HashMap;
 use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use crate::account_cache::AccountCache;
+use crate::tx_sender::{self, SendSettings};
+use crate::lamports::lamports_to_sol;
+use crate::rent_cache::RentCache;
+use crate::rpc::get_multiple_accounts_chunked;
+use crate::outcome::note;
 
 
+const TOKEN_2022_PROGRAM_ID: &str = "TokenzQdBNbLqU2YPbVmjYVBRhCF9dDid1i9QpZ5dKQ";
+const NATIVE_SOL_DECIMALS: u8 = 9;
+const ACCOUNT_TYPE_MINT: u8 = 1;
+const ACCOUNT_TYPE_ACCOUNT: u8 = 2;
+const TRANSFER_FEE_CONFIG_EXTENSION: u16 = 1;
+const MEMO_TRANSFER_EXTENSION: u16 = 8;
+const CPI_GUARD_EXTENSION: u16 = 11;
+const NON_TRANSFERABLE_ACCOUNT_EXTENSION: u16 = 13;
+
 pub struct AtaManager {
     rpc_client: RpcClient,
+    commitment: CommitmentConfig,
+    rent_cache: Arc<RentCache>,
+    account_cache: Arc<AccountCache>,
+    mint_decimals: Mutex<HashMap<Pubkey, u8>>,
+    token_programs: Mutex<HashMap<Pubkey, Pubkey>>,
+    settings: Arc<SendSettings>,
 }
 
 #[derive(Debug)]
@@ -25,35 +50,140 @@ pub struct AtaInfo {
     pub owner: Pubkey,
     pub balance: Option<u64>,
     pub rent_exemption_required: u64,
+    /// `AccountState::Frozen`: the freeze authority has blocked all transfers in and out
+    pub frozen: bool,
+    pub extensions: TokenAccountExtensions,
+}
+
+/// Token-2022 account extensions that can make a swap's transfers fail.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TokenAccountExtensions {
+    /// CPI Guard is on: programs (like a swap router) can't transfer out with the owner's signature
+    pub cpi_guard: bool,
+    /// Incoming transfers must be preceded by a memo instruction
+    pub memo_required: bool,
+    pub non_transferable: bool,
+}
+
+impl TokenAccountExtensions {
+    /// Why tokens can't be moved out of this account by a program, if they can't.
+    pub fn outgoing_blocker(&self) -> Option<&'static str> {
+        if self.non_transferable {
+            Some("holds non-transferable tokens")
+        } else if self.cpi_guard {
+            Some("has CPI Guard enabled, which blocks transfers made by other programs; disable it to swap")
+        } else {
+            None
+        }
+    }
+
+    /// Why this account can't receive a swap's output, if it can't.
+    pub fn incoming_blocker(&self) -> Option<&'static str> {
+        self.memo_required.then_some("requires a memo on incoming transfers, which swap routes don't add")
+    }
+}
+
+/// A Token-2022 transfer fee, withheld from the amount transferred.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TransferFee {
+    pub basis_points: u16,
+    pub maximum_fee: u64,
+}
+
+impl TransferFee {
+    /// Fee on a transfer of `amount`, rounded up and capped at `maximum_fee` as Token-2022 computes it.
+    pub fn fee_for(&self, amount: u64) -> u64 {
+        let fee = (amount as u128 * self.basis_points as u128).div_ceil(10_000);
+        (fee as u64).min(self.maximum_fee)
+    }
+}
+
+/// A token account held by an owner, with its mint's decimals when the mint could be read.
+#[derive(Debug)]
+pub struct TokenBalance {
+    pub address: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub decimals: Option<u8>,
+}
+
+impl TokenBalance {
+    /// Amount in UI units; raw when the decimals are unknown.
+    pub fn ui_value(&self) -> f64 {
+        self.amount as f64 / 10f64.powi(self.decimals.unwrap_or(0) as i32)
+    }
+}
+
+/// Largest UI amount first, dropping balances below `min_balance` and keeping at most `limit`.
+/// Returns the kept balances and how many were hidden.
+pub fn filter_balances(mut balances: Vec<TokenBalance>, min_balance: Option<f64>, limit: Option<usize>) -> (Vec<TokenBalance>, usize) {
+    let total = balances.len();
+    balances.sort_by(|a, b| b.ui_value().total_cmp(&a.ui_value()).then(a.mint.cmp(&b.mint)));
+    if let Some(min_balance) = min_balance {
+        balances.retain(|balance| balance.ui_value() >= min_balance);
+    }
+    if let Some(limit) = limit {
+        balances.truncate(limit);
+    }
+    let hidden = total - balances.len();
+    (balances, hidden)
 }
 
 impl AtaManager {
-    pub fn new(rpc_client: RpcClient) -> Self {
-        Self { rpc_client }
+    /// Token account, mint, and balance reads use `commitment` (`--read-commitment`).
+    pub fn new(rpc_client: RpcClient, commitment: CommitmentConfig) -> Self {
+        Self {
+            rpc_client,
+            commitment,
+            rent_cache: Arc::new(RentCache::new()),
+            account_cache: Arc::new(AccountCache::new()),
+            mint_decimals: Mutex::new(HashMap::new()),
+            token_programs: Mutex::new(HashMap::new()),
+            settings: Arc::new(SendSettings::default()),
+        }
+    }
+
+    /// Run-wide send settings for ATA creation
+    pub fn with_send_settings(mut self, settings: Arc<SendSettings>) -> Self {
+        self.settings = settings;
+        self
+    }
+
+    pub fn with_rent_cache(mut self, rent_cache: Arc<RentCache>) -> Self {
+        self.rent_cache = rent_cache;
+        self
+    }
+
+    pub fn with_account_cache(mut self, account_cache: Arc<AccountCache>) -> Self {
+        self.account_cache = account_cache;
+        self
     }
 
     pub async fn check_ata(&self, owner: &Pubkey, mint: &Pubkey) -> Result<AtaInfo> {
-        let ata_address = get_associated_token_address(owner, mint);
+        let token_program = self.token_program(mint);
+        let ata_address = get_associated_token_address_with_program_id(owner, mint, &token_program);
         
        
-        let account_info = self.rpc_client.get_account(&ata_address);
+        let account_info = self.account_cache.get_account(&self.rpc_client, &ata_address, self.commitment);
         
-        let rent_exemption_required = self.rpc_client
-            .get_minimum_balance_for_rent_exemption(TokenAccount::LEN)?;
+        let rent_exemption_required = self.rent_cache
+            .minimum_balance(&self.rpc_client, TokenAccount::LEN)?;
         
         match account_info {
-            Ok(account) => {
+            Ok(Some(account)) => {
                
-                if account.owner != spl_token::id() {
+                if account.owner != token_program {
                     return Err(anyhow!(
-                        "Account {} exists but is not owned by SPL Token program", 
-                        ata_address
+                        "Account {} exists but is not owned by the mint's token program ({})", 
+                        ata_address, token_program
                     ));
                 }
                 
-               
-                let token_account = TokenAccount::unpack(&account.data)
-                    .map_err(|e| anyhow!("Failed to parse token account data: {}", e))?;
+                // Token-2022 extensions follow the base account layout
+                let token_account = account.data.get(..TokenAccount::LEN)
+                    .ok_or_else(|| anyhow!("Failed to parse token account data: too short"))
+                    .and_then(|base| TokenAccount::unpack(base)
+                        .map_err(|e| anyhow!("Failed to parse token account data: {}", e)))?;
                 
                
                 if token_account.mint != *mint {
@@ -77,9 +207,11 @@ impl AtaManager {
                     owner: *owner,
                     balance: Some(token_account.amount),
                     rent_exemption_required,
+                    frozen: token_account.is_frozen(),
+                    extensions: parse_account_extensions(&account.data)?,
                 })
             }
-            Err(_) => {
+            _ => {
                
                 Ok(AtaInfo {
                     address: ata_address,
@@ -88,6 +220,8 @@ impl AtaManager {
                     owner: *owner,
                     balance: None,
                     rent_exemption_required,
+                    frozen: false,
+                    extensions: TokenAccountExtensions::default(),
                 })
             }
         }
@@ -100,15 +234,11 @@ impl AtaManager {
         owner: &Pubkey,
         mint: &Pubkey,
     ) -> Result<Instruction> {
-        let _ata_address = get_associated_token_address(owner, mint);
-        
-       
-       
         let instruction = spl_associated_token_account::instruction::create_associated_token_account(
             payer,    // Fee payer
             owner,    // Token account owner
             mint,     // Mint address
-            &spl_token::id(), // SPL Token program ID
+            &self.token_program(mint), // SPL Token or Token-2022, per the mint
         );
         
         Ok(instruction)
@@ -124,18 +254,18 @@ impl AtaManager {
         let ata_info = self.check_ata(owner, mint).await?;
         
         if ata_info.exists {
-            println!("✅ ATA already exists: {}", ata_info.address);
-            println!("  💰 Balance: {} tokens", ata_info.balance.unwrap_or(0));
+            note!(self.settings.notes, "✅ ATA already exists: {}", ata_info.address);
+            note!(self.settings.notes, "  💰 Balance: {} tokens", ata_info.balance.unwrap_or(0));
             return Ok(ata_info);
         }
         
-        println!("🔧 ATA does not exist, creating: {}", ata_info.address);
-        println!("  💰 Rent required: {} lamports ({} SOL)", 
-                 ata_info.rent_exemption_required, 
-                 ata_info.rent_exemption_required as f64 / 1_000_000_000.0);
+        note!(self.settings.notes, "🔧 ATA does not exist, creating: {}", ata_info.address);
+        note!(self.settings.notes, "  💰 Rent required: {} lamports ({} SOL)", 
+             ata_info.rent_exemption_required, 
+             lamports_to_sol(ata_info.rent_exemption_required));
         
        
-        let payer_balance = self.rpc_client.get_balance(&payer.pubkey())?;
+        let payer_balance = self.rpc_client.get_balance_with_commitment(&payer.pubkey(), self.commitment)?.value;
         if payer_balance < ata_info.rent_exemption_required {
             return Err(anyhow!(
                 "Insufficient balance for ATA creation. Need {} lamports, have {}",
@@ -147,17 +277,13 @@ impl AtaManager {
        
         let create_instruction = self.create_ata_instruction(&payer.pubkey(), owner, mint)?;
         
-        let recent_blockhash = self.rpc_client.get_latest_blockhash()?;
-        let transaction = Transaction::new_signed_with_payer(
-            &[create_instruction],
-            Some(&payer.pubkey()),
-            &[payer],
-            recent_blockhash,
-        );
+        let recent_blockhash = tx_sender::latest_blockhash(&self.rpc_client, &self.settings)?.blockhash;
+        let transaction = tx_sender::sign_transaction(&self.settings, &[create_instruction], &[payer], recent_blockhash)?;
         
-        let signature = self.rpc_client.send_and_confirm_transaction(&transaction)?;
-        println!("✅ ATA created successfully!");
-        println!("  🔍 Transaction: {}", signature);
+        let signature = tx_sender::send_and_confirm(&self.rpc_client, &self.settings, &transaction)?;
+        note!(self.settings.notes, "✅ ATA created successfully!");
+        note!(self.settings.notes, "  🔍 Transaction: {}", signature);
+        self.settings.explorer.note_transaction_url(&self.settings.notes, &signature);
         
        
         let updated_info = self.check_ata(owner, mint).await?;
@@ -171,21 +297,269 @@ impl AtaManager {
         mints: &[Pubkey],
     ) -> Result<Vec<AtaInfo>> {
         let mut results = Vec::new();
+        let bar = self.settings.notes.progress_bar(mints.len() as u64, "Checking ATAs");
         
         for mint in mints {
             let ata_info = self.check_ata(owner, mint).await?;
             results.push(ata_info);
+            bar.inc(1);
         }
+        bar.finish_and_clear();
         
         Ok(results)
     }
 
+    /// Confirm `mint` is a Token or Token-2022 mint and return its decimals (cached per mint).
+    pub fn validate_mint(&self, mint: &Pubkey) -> Result<u8> {
+        if *mint == CommonMints::sol() {
+            return Ok(NATIVE_SOL_DECIMALS);
+        }
+        if let Some(decimals) = self.mint_decimals.lock().unwrap().get(mint) {
+            return Ok(*decimals);
+        }
+
+        let account = self.account_cache.get_account(&self.rpc_client, mint, self.commitment).ok().flatten()
+            .ok_or_else(|| anyhow!("{} is not a valid token mint: account not found", mint))?;
+        let decimals = parse_mint_decimals(mint, &account)?;
+        self.mint_decimals.lock().unwrap().insert(*mint, decimals);
+        self.token_programs.lock().unwrap().insert(*mint, account.owner);
+        Ok(decimals)
+    }
+
+    /// Token program that owns `mint`, as seen by `validate_mint`; SPL Token until then.
+    fn token_program(&self, mint: &Pubkey) -> Pubkey {
+        self.token_programs.lock().unwrap().get(mint).copied().unwrap_or_else(spl_token::id)
+    }
+
+    /// The transfer fee `mint` charges this epoch, or `None` if it has no Token-2022 transfer-fee extension.
+    pub fn transfer_fee(&self, mint: &Pubkey) -> Result<Option<TransferFee>> {
+        if *mint == CommonMints::sol() {
+            return Ok(None);
+        }
+        let account = self.account_cache.get_account(&self.rpc_client, mint, self.commitment)
+            .map_err(|e| anyhow!("Failed to fetch mint {}: {}", mint, e))?
+            .ok_or_else(|| anyhow!("Mint {} not found", mint))?;
+        let epoch = self.rpc_client.get_epoch_info()?.epoch;
+        parse_transfer_fee(&account, epoch)
+    }
+
+    /// Mint decimals for display: SOL/USDC/USDT from a fixed table, else read from the mint (cached per mint);
+    /// `None` if the mint can't be read.
+    pub fn mint_decimals(&self, mint: &Pubkey) -> Option<u8> {
+        CommonMints::known_decimals(mint).or_else(|| self.validate_mint(mint).ok())
+    }
+
+    /// All Token and Token-2022 accounts owned by `owner`, in RPC order per program. Balances come straight
+    /// from the listing; only mints the RPC didn't report decimals for are looked up, in batches.
+    pub fn list_token_accounts(&self, owner: &Pubkey) -> Result<Vec<TokenBalance>> {
+        let mut balances = Vec::new();
+        for program_id in [spl_token::id(), Pubkey::from_str(TOKEN_2022_PROGRAM_ID)?] {
+            let keyed = self.rpc_client
+                .get_token_accounts_by_owner_with_commitment(owner, TokenAccountsFilter::ProgramId(program_id), self.commitment)
+                .map_err(|e| anyhow!("Failed to list token accounts for {}: {}", owner, e))?
+                .value;
+            for keyed in keyed {
+                let address = Pubkey::from_str(&keyed.pubkey)?;
+                let balance = serde_json::to_value(&keyed.account).ok()
+                    .and_then(|account| parse_token_account_json(address, &account))
+                    .or_else(|| {
+                        // RPCs that don't parse return the raw account; Token-2022 extensions follow the base layout
+                        let data = keyed.account.decode::<Account>()?.data;
+                        let token_account = TokenAccount::unpack(data.get(..TokenAccount::LEN)?).ok()?;
+                        Some(TokenBalance { address, mint: token_account.mint, amount: token_account.amount, decimals: None })
+                    })
+                    .ok_or_else(|| anyhow!("Failed to parse token account {}", address))?;
+                balances.push(balance);
+            }
+        }
+
+        let mut mints: Vec<Pubkey> = balances.iter().filter(|b| b.decimals.is_none()).map(|b| b.mint).collect();
+        mints.sort();
+        mints.dedup();
+        if mints.is_empty() {
+            return Ok(balances);
+        }
+        let mint_accounts = get_multiple_accounts_chunked(&self.rpc_client, &mints, self.commitment)?;
+        for balance in balances.iter_mut().filter(|b| b.decimals.is_none()) {
+            let idx = mints.binary_search(&balance.mint).expect("mint collected above");
+            balance.decimals = mint_accounts[idx].as_ref()
+                .and_then(|account| parse_mint_decimals(&balance.mint, account).ok());
+        }
+
+        Ok(balances)
+    }
+
+    /// Of `atas`, the ones that are empty Token or Token-2022 accounts of `owner`, with their mint and the
+    /// lamports closing each reclaims.
+    pub fn closable_atas(&self, owner: &Pubkey, atas: &[Pubkey]) -> Result<Vec<(Pubkey, Pubkey, u64)>> {
+        if atas.is_empty() {
+            return Ok(Vec::new());
+        }
+        let accounts = self.rpc_client.get_multiple_accounts_with_commitment(atas, self.commitment)?.value;
+        Ok(atas.iter().zip(accounts)
+            .filter_map(|(address, account)| {
+                let account = account?;
+                let mint = closable_mint(&account, owner)?;
+                self.token_programs.lock().unwrap().insert(mint, account.owner);
+                Some((*address, mint, account.lamports))
+            })
+            .collect())
+    }
+
+    /// Create `recipient`'s ATA if missing (idempotently, so a race with another creator can't fail the
+    /// transaction) and `transfer_checked` `amount` raw units from `owner`'s ATA. Call `validate_mint`
+    /// first so Token-2022 mints use their program.
+    pub fn transfer_checked_instructions(
+        &self,
+        payer: &Pubkey,
+        owner: &Pubkey,
+        recipient: &Pubkey,
+        mint: &Pubkey,
+        amount: u64,
+        decimals: u8,
+    ) -> Result<Vec<Instruction>> {
+        transfer_checked_instructions(&self.token_program(mint), payer, owner, recipient, mint, amount, decimals)
+    }
+
+    /// Close `ata` of `mint` under its token program, sending its rent to `owner`.
+    pub fn close_ata_instruction(&self, ata: &Pubkey, mint: &Pubkey, owner: &Pubkey) -> Result<Instruction> {
+        // spl-token only builds for its own program ID; Token-2022 shares the CloseAccount layout
+        let mut close = spl_token::instruction::close_account(&spl_token::id(), ata, owner, owner, &[])
+            .map_err(|e| anyhow!("Failed to build close instruction for {}: {}", ata, e))?;
+        close.program_id = self.token_program(mint);
+        Ok(close)
+    }
+
     
     pub fn get_common_mints() -> CommonMints {
         CommonMints::new()
     }
 }
 
+/// A token account in `getTokenAccountsByOwner`'s `jsonParsed` shape, or `None` if `account` isn't one.
+/// The parsed form carries the mint's decimals too.
+fn parse_token_account_json(address: Pubkey, account: &serde_json::Value) -> Option<TokenBalance> {
+    let info = account.pointer("/data/parsed/info")?;
+    Some(TokenBalance {
+        address,
+        mint: Pubkey::from_str(info.get("mint")?.as_str()?).ok()?,
+        amount: info.pointer("/tokenAmount/amount")?.as_str()?.parse().ok()?,
+        decimals: info.pointer("/tokenAmount/decimals").and_then(|d| d.as_u64()).and_then(|d| u8::try_from(d).ok()),
+    })
+}
+
+/// The Token-2022 transfer fee in effect at `epoch`, or `None` for mints without the extension.
+pub fn parse_transfer_fee(account: &Account, epoch: u64) -> Result<Option<TransferFee>> {
+    if account.owner != Pubkey::from_str(TOKEN_2022_PROGRAM_ID)? {
+        return Ok(None);
+    }
+    // Extensions start after the base mint (padded to the token account length) and an account-type byte
+    let Some((&ACCOUNT_TYPE_MINT, mut tlv)) = account.data.get(TokenAccount::LEN..).and_then(|d| d.split_first()) else {
+        return Ok(None);
+    };
+    while tlv.len() >= 4 {
+        let extension = u16::from_le_bytes([tlv[0], tlv[1]]);
+        let len = u16::from_le_bytes([tlv[2], tlv[3]]) as usize;
+        let value = tlv.get(4..4 + len)
+            .ok_or_else(|| anyhow!("Truncated Token-2022 extension data"))?;
+        if extension == TRANSFER_FEE_CONFIG_EXTENSION {
+            // Two authorities and the withheld amount, then older and newer fees of (epoch, maximum_fee, basis_points)
+            let fee_at = |offset: usize| -> Result<(u64, TransferFee)> {
+                let fee = value.get(offset..offset + 18)
+                    .ok_or_else(|| anyhow!("Truncated transfer fee config"))?;
+                Ok((
+                    u64::from_le_bytes(fee[..8].try_into()?),
+                    TransferFee {
+                        maximum_fee: u64::from_le_bytes(fee[8..16].try_into()?),
+                        basis_points: u16::from_le_bytes(fee[16..18].try_into()?),
+                    },
+                ))
+            };
+            let (_, older) = fee_at(72)?;
+            let (newer_epoch, newer) = fee_at(90)?;
+            return Ok(Some(if epoch >= newer_epoch { newer } else { older }));
+        }
+        tlv = &tlv[4 + len..];
+    }
+    Ok(None)
+}
+
+/// Transfer-blocking Token-2022 extensions of a token account; all off for SPL Token accounts.
+pub fn parse_account_extensions(data: &[u8]) -> Result<TokenAccountExtensions> {
+    let mut extensions = TokenAccountExtensions::default();
+    let Some((&ACCOUNT_TYPE_ACCOUNT, mut tlv)) = data.get(TokenAccount::LEN..).and_then(|d| d.split_first()) else {
+        return Ok(extensions);
+    };
+    while tlv.len() >= 4 {
+        let extension = u16::from_le_bytes([tlv[0], tlv[1]]);
+        let len = u16::from_le_bytes([tlv[2], tlv[3]]) as usize;
+        let value = tlv.get(4..4 + len)
+            .ok_or_else(|| anyhow!("Truncated Token-2022 extension data"))?;
+        // MemoTransfer and CpiGuard are a single enabled flag
+        let enabled = value.first().is_some_and(|flag| *flag != 0);
+        match extension {
+            MEMO_TRANSFER_EXTENSION => extensions.memo_required = enabled,
+            CPI_GUARD_EXTENSION => extensions.cpi_guard = enabled,
+            NON_TRANSFERABLE_ACCOUNT_EXTENSION => extensions.non_transferable = true,
+            _ => {}
+        }
+        tlv = &tlv[4 + len..];
+    }
+    Ok(extensions)
+}
+
+/// The mint of a Token or Token-2022 account owned by `owner` holding no tokens, so closing it loses nothing.
+fn closable_mint(account: &Account, owner: &Pubkey) -> Option<Pubkey> {
+    let token_2022 = Pubkey::from_str(TOKEN_2022_PROGRAM_ID).ok()?;
+    if account.owner != spl_token::id() && account.owner != token_2022 {
+        return None;
+    }
+    // Token-2022 extensions follow the base account layout
+    let token_account = TokenAccount::unpack(account.data.get(..TokenAccount::LEN)?).ok()?;
+    (token_account.owner == *owner && token_account.amount == 0).then_some(token_account.mint)
+}
+
+/// Idempotent ATA creation for `recipient` followed by `transfer_checked` under `token_program`.
+pub fn transfer_checked_instructions(
+    token_program: &Pubkey,
+    payer: &Pubkey,
+    owner: &Pubkey,
+    recipient: &Pubkey,
+    mint: &Pubkey,
+    amount: u64,
+    decimals: u8,
+) -> Result<Vec<Instruction>> {
+    let source = get_associated_token_address_with_program_id(owner, mint, token_program);
+    let destination = get_associated_token_address_with_program_id(recipient, mint, token_program);
+    let create = spl_associated_token_account::instruction::create_associated_token_account_idempotent(
+        payer, recipient, mint, token_program,
+    );
+    // spl-token only builds for its own program ID; Token-2022 shares the TransferChecked layout
+    let mut transfer = spl_token::instruction::transfer_checked(
+        &spl_token::id(), &source, mint, &destination, owner, &[], amount, decimals,
+    ).map_err(|e| anyhow!("Failed to build transfer for {}: {}", mint, e))?;
+    transfer.program_id = *token_program;
+    Ok(vec![create, transfer])
+}
+
+/// Decimals of a mint account, erroring if it isn't owned by a token program or doesn't parse as a `Mint`.
+pub fn parse_mint_decimals(mint: &Pubkey, account: &Account) -> Result<u8> {
+    let token_2022 = Pubkey::from_str(TOKEN_2022_PROGRAM_ID)?;
+    if account.owner != spl_token::id() && account.owner != token_2022 {
+        return Err(anyhow!(
+            "{} is not a valid token mint: owned by {}, not the Token or Token-2022 program",
+            mint, account.owner
+        ));
+    }
+    // Token-2022 extensions follow the base mint layout
+    let base = account.data.get(..Mint::LEN)
+        .ok_or_else(|| anyhow!("{} is not a valid token mint: data too short", mint))?;
+    let parsed = Mint::unpack_unchecked(base)
+        .ok()
+        .filter(|m| m.is_initialized)
+        .ok_or_else(|| anyhow!("{} is not a valid token mint: data does not parse as a mint", mint))?;
+    Ok(parsed.decimals)
+}
 
 pub struct CommonMints;
 
@@ -209,6 +583,17 @@ impl CommonMints {
         Pubkey::from_str("Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB").unwrap()
     }
     
+    /// Decimals of the mints above, without an RPC round trip.
+    pub fn known_decimals(mint: &Pubkey) -> Option<u8> {
+        if *mint == Self::sol() {
+            Some(NATIVE_SOL_DECIMALS)
+        } else if *mint == Self::usdc() || *mint == Self::usdt() {
+            Some(6)
+        } else {
+            None
+        }
+    }
+
    
     pub fn from_name(name: &str) -> Result<Pubkey> {
         match name.to_uppercase().as_str() {
@@ -237,6 +622,167 @@ mod tests {
         assert_eq!(usdc_mint.to_string(), "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v");
     }
     
+    #[test]
+    fn test_filter_balances() {
+        let balance = |amount, decimals| TokenBalance { address: Pubkey::new_unique(), mint: Pubkey::new_unique(), amount, decimals };
+        let balances = vec![
+            balance(5_000_000, Some(6)),   // 5
+            balance(1, Some(9)),           // dust
+            balance(2_000_000_000, Some(9)), // 2
+            balance(3, None),              // 3 (raw)
+        ];
+
+        let (all, hidden) = filter_balances(balances, None, None);
+        assert_eq!(hidden, 0);
+        assert_eq!(all.iter().map(|b| b.amount).collect::<Vec<_>>(), vec![5_000_000, 3, 2_000_000_000, 1]);
+
+        let (shown, hidden) = filter_balances(all, Some(0.01), Some(2));
+        assert_eq!(shown.iter().map(|b| b.amount).collect::<Vec<_>>(), vec![5_000_000, 3]);
+        assert_eq!(hidden, 2);
+    }
+
+    #[test]
+    fn test_known_decimals() {
+        assert_eq!(CommonMints::known_decimals(&CommonMints::sol()), Some(9));
+        assert_eq!(CommonMints::known_decimals(&CommonMints::usdc()), Some(6));
+        assert_eq!(CommonMints::known_decimals(&CommonMints::usdt()), Some(6));
+        assert_eq!(CommonMints::known_decimals(&Pubkey::new_unique()), None);
+    }
+
+    #[test]
+    fn test_parse_mint_decimals() {
+        let mint = Pubkey::new_unique();
+        let mut data = vec![0u8; Mint::LEN];
+        Mint::pack(Mint { decimals: 6, is_initialized: true, ..Mint::default() }, &mut data).unwrap();
+
+        let account = Account { lamports: 1, data: data.clone(), owner: spl_token::id(), executable: false, rent_epoch: 0 };
+        assert_eq!(parse_mint_decimals(&mint, &account).unwrap(), 6);
+
+        let wrong_owner = Account { owner: solana_sdk::system_program::id(), ..account.clone() };
+        assert!(parse_mint_decimals(&mint, &wrong_owner).unwrap_err().to_string().contains("not a valid token mint"));
+
+        let token_account = Account { data: vec![0u8; TokenAccount::LEN], ..account };
+        assert!(parse_mint_decimals(&mint, &token_account).is_err());
+    }
+
+    #[test]
+    fn test_parse_token_account_json() {
+        let address = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let account = serde_json::json!({
+            "lamports": 2039280,
+            "data": { "program": "spl-token-2022", "parsed": { "type": "account", "info": {
+                "mint": mint.to_string(),
+                "tokenAmount": { "amount": "1500000", "decimals": 6, "uiAmountString": "1.5" }
+            } }, "space": 170 },
+            "owner": TOKEN_2022_PROGRAM_ID,
+        });
+        let balance = parse_token_account_json(address, &account).unwrap();
+        assert_eq!((balance.address, balance.mint, balance.amount, balance.decimals), (address, mint, 1_500_000, Some(6)));
+
+        // Binary encodings are left to the raw-account path
+        assert!(parse_token_account_json(address, &serde_json::json!({ "data": ["AAAA", "base64"] })).is_none());
+    }
+
+    #[test]
+    fn test_parse_transfer_fee() {
+        let fee_bytes = |epoch: u64, maximum_fee: u64, basis_points: u16| {
+            [epoch.to_le_bytes().as_slice(), &maximum_fee.to_le_bytes(), &basis_points.to_le_bytes()].concat()
+        };
+        let mut data = vec![0u8; TokenAccount::LEN];
+        data.push(ACCOUNT_TYPE_MINT);
+        data.extend_from_slice(&TRANSFER_FEE_CONFIG_EXTENSION.to_le_bytes());
+        data.extend_from_slice(&108u16.to_le_bytes());
+        data.extend_from_slice(&[0u8; 72]);
+        data.extend(fee_bytes(0, 1_000, 100));
+        data.extend(fee_bytes(50, 5_000, 250));
+        let token_2022 = Pubkey::from_str(TOKEN_2022_PROGRAM_ID).unwrap();
+        let account = Account { lamports: 1, data, owner: token_2022, executable: false, rent_epoch: 0 };
+
+        let older = parse_transfer_fee(&account, 10).unwrap().unwrap();
+        assert_eq!(older, TransferFee { basis_points: 100, maximum_fee: 1_000 });
+        assert_eq!(parse_transfer_fee(&account, 50).unwrap().unwrap().basis_points, 250);
+
+        // 1% of 1,050 rounds up to 11; large transfers hit the cap
+        assert_eq!(older.fee_for(1_050), 11);
+        assert_eq!(older.fee_for(10_000_000), 1_000);
+        assert_eq!(older.fee_for(0), 0);
+
+        let spl = Account { owner: spl_token::id(), ..account };
+        assert_eq!(parse_transfer_fee(&spl, 10).unwrap(), None);
+    }
+
+    #[test]
+    fn test_parse_account_extensions() {
+        let extension = |extension_type: u16, value: &[u8]| {
+            [extension_type.to_le_bytes().as_slice(), &(value.len() as u16).to_le_bytes(), value].concat()
+        };
+        let mut data = vec![0u8; TokenAccount::LEN];
+        assert_eq!(parse_account_extensions(&data).unwrap(), TokenAccountExtensions::default());
+
+        data.push(ACCOUNT_TYPE_ACCOUNT);
+        data.extend(extension(MEMO_TRANSFER_EXTENSION, &[0]));
+        data.extend(extension(CPI_GUARD_EXTENSION, &[1]));
+        let extensions = parse_account_extensions(&data).unwrap();
+        assert!(extensions.cpi_guard && !extensions.memo_required && !extensions.non_transferable);
+        assert!(extensions.outgoing_blocker().unwrap().contains("CPI Guard"));
+        assert_eq!(extensions.incoming_blocker(), None);
+
+        data.extend(extension(NON_TRANSFERABLE_ACCOUNT_EXTENSION, &[]));
+        assert!(parse_account_extensions(&data).unwrap().non_transferable);
+        data.extend_from_slice(&[MEMO_TRANSFER_EXTENSION as u8, 0, 1]);
+        assert!(parse_account_extensions(&data).is_ok());
+        data.push(0);
+        assert!(parse_account_extensions(&data).is_err());
+    }
+
+    #[test]
+    fn test_closable_mint() {
+        let owner = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let token_account = |amount| {
+            let mut data = vec![0u8; TokenAccount::LEN];
+            TokenAccount::pack(TokenAccount {
+                mint,
+                owner,
+                amount,
+                state: spl_token::state::AccountState::Initialized,
+                ..TokenAccount::default()
+            }, &mut data).unwrap();
+            Account { lamports: 2_039_280, data, owner: spl_token::id(), executable: false, rent_epoch: 0 }
+        };
+
+        assert_eq!(closable_mint(&token_account(0), &owner), Some(mint));
+        assert_eq!(closable_mint(&token_account(1), &owner), None);
+        assert_eq!(closable_mint(&token_account(0), &Pubkey::new_unique()), None);
+        let system_owned = Account { owner: solana_sdk::system_program::id(), ..token_account(0) };
+        assert_eq!(closable_mint(&system_owned, &owner), None);
+
+        // Token-2022 account with an extension after the base layout
+        let mut token_2022 = Account { owner: Pubkey::from_str(TOKEN_2022_PROGRAM_ID).unwrap(), ..token_account(0) };
+        token_2022.data.extend_from_slice(&[ACCOUNT_TYPE_ACCOUNT, MEMO_TRANSFER_EXTENSION as u8, 0, 1, 0, 0]);
+        assert_eq!(closable_mint(&token_2022, &owner), Some(mint));
+    }
+
+    #[test]
+    fn test_transfer_checked_instructions() {
+        let (payer, recipient, mint) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let token_2022 = Pubkey::from_str(TOKEN_2022_PROGRAM_ID).unwrap();
+        let ixs = transfer_checked_instructions(&token_2022, &payer, &payer, &recipient, &mint, 2_500_000, 6).unwrap();
+        assert_eq!(ixs.len(), 2);
+        // CreateIdempotent
+        assert_eq!(ixs[0].data, vec![1]);
+        assert_eq!(ixs[1].program_id, token_2022);
+        let destination = get_associated_token_address_with_program_id(&recipient, &mint, &token_2022);
+        assert_eq!(ixs[0].accounts[1].pubkey, destination);
+        assert_eq!(ixs[1].accounts[2].pubkey, destination);
+        assert_eq!(ixs[1].accounts[1].pubkey, mint);
+        // TransferChecked: tag 12, amount, decimals
+        assert_eq!(ixs[1].data[0], 12);
+        assert_eq!(u64::from_le_bytes(ixs[1].data[1..9].try_into().unwrap()), 2_500_000);
+        assert_eq!(ixs[1].data[9], 6);
+    }
+
     #[test]
     fn test_from_name() {
         assert!(CommonMints::from_name("SOL").is_ok());
@@ -0,0 +1,219 @@
+use anyhow::{anyhow, Result};
+use futures::stream::{self, StreamExt};
+use indicatif::ProgressBar;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use crate::lamports::{lamports_to_sol, sol_to_lamports};
+use crate::outcome::{note, Notes};
+
+/// Outcome of one transaction in [`send_all`].
+#[derive(Debug)]
+pub enum BatchResult {
+    Sent(Signature),
+    Failed(anyhow::Error),
+    /// Never started because an earlier transaction failed (`--abort-on-first-failure`)
+    Skipped,
+}
+
+impl BatchResult {
+    pub fn signature(&self) -> Option<&Signature> {
+        match self {
+            BatchResult::Sent(signature) => Some(signature),
+            _ => None,
+        }
+    }
+
+    /// Why the transaction wasn't sent, for reports; `None` once sent.
+    pub fn error(&self) -> Option<String> {
+        match self {
+            BatchResult::Sent(_) => None,
+            BatchResult::Failed(e) => Some(e.to_string()),
+            BatchResult::Skipped => Some("Skipped: an earlier transaction failed (--abort-on-first-failure)".to_string()),
+        }
+    }
+}
+
+/// One `recipient,amount` line of a batch file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchEntry {
+    /// 1-based line in the file, for error reports
+    pub line: usize,
+    pub recipient: Pubkey,
+    pub lamports: u64,
+}
+
+/// Parse `recipient,amount_sol` lines; blank lines, `#` comments, and a `recipient,...` header are skipped.
+pub fn parse_batch_file(contents: &str) -> Result<Vec<BatchEntry>> {
+    let mut entries = Vec::new();
+    for (i, raw) in contents.lines().enumerate() {
+        let line = raw.trim();
+        if line.is_empty() || line.starts_with('#') || (i == 0 && line.to_lowercase().starts_with("recipient")) {
+            continue;
+        }
+        let (recipient, amount) = line.split_once(',')
+            .ok_or_else(|| anyhow!("Line {}: expected 'recipient,amount', got '{}'", i + 1, line))?;
+        entries.push(BatchEntry {
+            line: i + 1,
+            recipient: Pubkey::from_str(recipient.trim())
+                .map_err(|_| anyhow!("Line {}: invalid recipient '{}'", i + 1, recipient.trim()))?,
+            lamports: sol_to_lamports(amount)
+                .map_err(|e| anyhow!("Line {}: {}", i + 1, e))?,
+        });
+    }
+    if entries.is_empty() {
+        return Err(anyhow!("Batch file has no entries"));
+    }
+    Ok(entries)
+}
+
+/// `--simulate-before-batch` totals: what the batch would send and pay, and which lines would fail.
+#[derive(Debug, Default)]
+pub struct BatchPreview {
+    pub transfers: usize,
+    pub total_lamports: u64,
+    pub total_fees: u64,
+    /// (line, simulation error)
+    pub failures: Vec<(usize, String)>,
+}
+
+impl BatchPreview {
+    pub fn record(&mut self, entry: &BatchEntry, fee: u64, error: Option<String>) {
+        self.transfers += 1;
+        self.total_fees += fee;
+        match error {
+            Some(error) => self.failures.push((entry.line, error)),
+            None => self.total_lamports += entry.lamports,
+        }
+    }
+
+    /// Human-readable summary; failing lines aren't counted in the amount to be sent.
+    pub fn summary(&self) -> String {
+        let mut out = format!(
+            "🧪 Batch simulation: {} of {} transfer(s) would succeed\n  💰 {} SOL to send\n  ⛽ {} SOL in fees\n",
+            self.transfers - self.failures.len(), self.transfers,
+            lamports_to_sol(self.total_lamports), lamports_to_sol(self.total_fees),
+        );
+        for (line, error) in &self.failures {
+            out.push_str(&format!("  ❌ Line {}: {}\n", line, error));
+        }
+        out
+    }
+}
+
+/// Ask `prompt` and read the answer from stdin; only `y`/`yes` proceeds.
+pub fn confirm(notes: &Notes, prompt: &str) -> Result<bool> {
+    note!(notes, "{} [y/N]", prompt);
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+/// Run the blocking `send` on up to `concurrency` transactions at once; results come back in input order.
+/// A failed send only fails its own slot, unless `abort` is set: then transactions not yet started are
+/// [`BatchResult::Skipped`] (ones already in flight still finish).
+pub async fn send_all<T, F>(transactions: Vec<T>, concurrency: usize, abort: bool, bar: &ProgressBar, send: F) -> Vec<BatchResult>
+where
+    T: Send + 'static,
+    F: Fn(T) -> Result<Signature> + Send + Sync + 'static,
+{
+    let send = Arc::new(send);
+    let failed = Arc::new(AtomicBool::new(false));
+    let mut results: Vec<(usize, BatchResult)> = stream::iter(transactions.into_iter().enumerate())
+        .map(|(i, transaction)| {
+            let send = send.clone();
+            let failed = failed.clone();
+            async move {
+                let result = if abort && failed.load(Ordering::SeqCst) {
+                    BatchResult::Skipped
+                } else {
+                    match tokio::task::spawn_blocking(move || send(transaction)).await
+                        .unwrap_or_else(|e| Err(anyhow!("Send task failed: {}", e))) {
+                        Ok(signature) => BatchResult::Sent(signature),
+                        Err(e) => {
+                            failed.store(true, Ordering::SeqCst);
+                            BatchResult::Failed(e)
+                        }
+                    }
+                };
+                bar.inc(1);
+                (i, result)
+            }
+        })
+        .buffer_unordered(concurrency.max(1))
+        .collect()
+        .await;
+    results.sort_by_key(|(i, _)| *i);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicUsize;
+    use std::time::Duration;
+
+    #[test]
+    fn test_parse_batch_file() {
+        let recipient = Pubkey::new_unique();
+        let contents = format!("recipient,amount\n# payroll\n{},0.5\n\n{}, 0.001\n", recipient, recipient);
+        let entries = parse_batch_file(&contents).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0], BatchEntry { line: 3, recipient, lamports: 500_000_000 });
+        assert_eq!(entries[1].line, 5);
+
+        assert!(parse_batch_file("not-a-pubkey,1").unwrap_err().to_string().contains("Line 1"));
+        assert!(parse_batch_file(&format!("{}", recipient)).is_err());
+        assert!(parse_batch_file("# nothing\n").is_err());
+    }
+
+    #[test]
+    fn test_batch_preview_summary() {
+        let entry = |line, lamports| BatchEntry { line, recipient: Pubkey::new_unique(), lamports };
+        let mut preview = BatchPreview::default();
+        preview.record(&entry(1, 500_000_000), 5_000, None);
+        preview.record(&entry(2, 250_000_000), 5_000, None);
+        preview.record(&entry(3, 100_000_000), 5_000, Some("insufficient funds".to_string()));
+
+        assert_eq!(preview.total_lamports, 750_000_000);
+        assert_eq!(preview.total_fees, 15_000);
+        let summary = preview.summary();
+        assert!(summary.starts_with("🧪 Batch simulation: 2 of 3 transfer(s) would succeed"));
+        assert!(summary.contains("0.75 SOL to send"));
+        assert!(summary.contains("❌ Line 3: insufficient funds"));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_send_all_limits_concurrency() {
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let (in_flight_c, peak_c) = (in_flight.clone(), peak.clone());
+
+        let results = send_all((0..8).collect(), 3, false, &ProgressBar::hidden(), move |i: u32| {
+            let now = in_flight_c.fetch_add(1, Ordering::SeqCst) + 1;
+            peak_c.fetch_max(now, Ordering::SeqCst);
+            std::thread::sleep(Duration::from_millis(20));
+            in_flight_c.fetch_sub(1, Ordering::SeqCst);
+            if i == 5 { Err(anyhow!("blockhash not found")) } else { Ok(Signature::default()) }
+        }).await;
+
+        assert_eq!(results.len(), 8);
+        assert!(matches!(results[5], BatchResult::Failed(_)));
+        assert_eq!(results.iter().filter(|r| matches!(r, BatchResult::Sent(_))).count(), 7);
+        assert!(peak.load(Ordering::SeqCst) <= 3);
+        assert!(peak.load(Ordering::SeqCst) > 1);
+    }
+
+    #[tokio::test]
+    async fn test_send_all_abort_on_failure() {
+        let results = send_all((0..5).collect(), 1, true, &ProgressBar::hidden(), |i: u32| {
+            if i == 1 { Err(anyhow!("insufficient funds")) } else { Ok(Signature::default()) }
+        }).await;
+
+        assert!(matches!(results[0], BatchResult::Sent(_)));
+        assert!(matches!(&results[1], BatchResult::Failed(e) if e.to_string().contains("insufficient funds")));
+        assert!(results[2..].iter().all(|r| matches!(r, BatchResult::Skipped)));
+    }
+}
@@ -4,11 +4,25 @@ use solana_sdk::pubkey::Pubkey;
 use std::collections::HashMap;
 use crate::idl_loader::IdlLoader;
 
-pub struct BorshEncoder;
+/// Argument types `encode_value` accepts.
+pub const SUPPORTED_TYPES: &[&str] = &[
+    "u8", "u16", "u32", "u64", "i8", "i16", "i32", "i64", "f32", "f64", "bool", "string", "pubkey", "bytes",
+];
+
+#[derive(Default)]
+pub struct BorshEncoder {
+    /// Decode `pubkey` values as 32-element byte arrays instead of base58
+    raw_pubkeys: bool,
+}
 
 impl BorshEncoder {
     pub fn new() -> Self {
-        Self
+        Self::default()
+    }
+
+    pub fn with_raw_pubkeys(mut self) -> Self {
+        self.raw_pubkeys = true;
+        self
     }
 
     pub fn encode_instruction(
@@ -93,11 +107,13 @@ impl BorshEncoder {
                 result.extend_from_slice(string_val.as_bytes());
                 Ok(result)
             }
-            "pubkey" => {
-                let pubkey_str = value.as_str().ok_or_else(|| anyhow::anyhow!("Expected pubkey string"))?;
-                let pubkey = Pubkey::try_from(pubkey_str)
-                    .map_err(|_| anyhow::anyhow!("Invalid pubkey: {}", pubkey_str))?;
-                Ok(pubkey.to_bytes().to_vec())
+            "pubkey" => Ok(parse_pubkey_value(value)?.to_bytes().to_vec()),
+            "bytes" => {
+                let bytes = parse_bytes_value(value)?;
+                let mut result = Vec::with_capacity(4 + bytes.len());
+                result.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+                result.extend_from_slice(&bytes);
+                Ok(result)
             }
             _ => {
                
@@ -105,6 +121,115 @@ impl BorshEncoder {
             }
         }
     }
+
+    /// Decode one Borsh value of `field_type` from the front of `data`, returning it and the bytes consumed.
+    pub fn decode_value(&self, data: &[u8], field_type: &str) -> Result<(Value, usize)> {
+        fn take<'a>(data: &'a [u8], len: usize, field_type: &str) -> Result<&'a [u8]> {
+            data.get(..len)
+                .ok_or_else(|| anyhow::anyhow!("Not enough data to decode {}: need {} bytes, have {}", field_type, len, data.len()))
+        }
+
+        match field_type {
+            "u8" => Ok((Value::from(take(data, 1, field_type)?[0]), 1)),
+            "u16" => Ok((Value::from(u16::from_le_bytes(take(data, 2, field_type)?.try_into()?)), 2)),
+            "u32" => Ok((Value::from(u32::from_le_bytes(take(data, 4, field_type)?.try_into()?)), 4)),
+            "u64" => Ok((Value::from(u64::from_le_bytes(take(data, 8, field_type)?.try_into()?)), 8)),
+            "i8" => Ok((Value::from(take(data, 1, field_type)?[0] as i8), 1)),
+            "i16" => Ok((Value::from(i16::from_le_bytes(take(data, 2, field_type)?.try_into()?)), 2)),
+            "i32" => Ok((Value::from(i32::from_le_bytes(take(data, 4, field_type)?.try_into()?)), 4)),
+            "i64" => Ok((Value::from(i64::from_le_bytes(take(data, 8, field_type)?.try_into()?)), 8)),
+            "f32" => Ok((Value::from(f32::from_le_bytes(take(data, 4, field_type)?.try_into()?)), 4)),
+            "f64" => Ok((Value::from(f64::from_le_bytes(take(data, 8, field_type)?.try_into()?)), 8)),
+            "bool" => match take(data, 1, field_type)?[0] {
+                0 => Ok((Value::Bool(false), 1)),
+                1 => Ok((Value::Bool(true), 1)),
+                other => Err(anyhow::anyhow!("Invalid bool byte: {}", other)),
+            },
+            "string" => {
+                let len = u32::from_le_bytes(take(data, 4, field_type)?.try_into()?) as usize;
+                let bytes = take(&data[4..], len, field_type)?;
+                let string_val = String::from_utf8(bytes.to_vec())
+                    .map_err(|e| anyhow::anyhow!("Invalid UTF-8 in string: {}", e))?;
+                Ok((Value::String(string_val), 4 + len))
+            }
+            "pubkey" => {
+                let bytes = take(data, 32, field_type)?;
+                if self.raw_pubkeys {
+                    return Ok((Value::from(bytes.to_vec()), 32));
+                }
+                let pubkey = Pubkey::try_from(bytes)
+                    .map_err(|_| anyhow::anyhow!("Invalid pubkey bytes"))?;
+                Ok((Value::String(pubkey.to_string()), 32))
+            }
+            "bytes" => {
+                let len = u32::from_le_bytes(take(data, 4, field_type)?.try_into()?) as usize;
+                let bytes = take(&data[4..], len, field_type)?;
+                Ok((Value::String(format!("hex:{}", to_hex(bytes))), 4 + len))
+            }
+            _ => Err(anyhow::anyhow!("Unsupported type: {}", field_type)),
+        }
+    }
+}
+
+/// Accept `pubkey` as a base58 string or a JSON array of exactly 32 bytes.
+fn parse_pubkey_value(value: &Value) -> Result<Pubkey> {
+    match value {
+        Value::String(s) => Pubkey::try_from(s.as_str())
+            .map_err(|_| anyhow::anyhow!("Invalid pubkey: {}", s)),
+        Value::Array(items) => {
+            if items.len() != 32 {
+                return Err(anyhow::anyhow!("Expected pubkey byte array of length 32, got {}", items.len()));
+            }
+            let bytes = parse_bytes_value(value)?;
+            Pubkey::try_from(bytes.as_slice()).map_err(|_| anyhow::anyhow!("Invalid pubkey bytes"))
+        }
+        _ => Err(anyhow::anyhow!("Expected pubkey as base58 string or array of 32 bytes")),
+    }
+}
+
+/// Accept `bytes` as a JSON array of u8, or a `hex:`/`base64:` prefixed string.
+fn parse_bytes_value(value: &Value) -> Result<Vec<u8>> {
+    match value {
+        Value::Array(items) => items.iter()
+            .map(|item| item.as_u64()
+                .filter(|b| *b <= u8::MAX as u64)
+                .map(|b| b as u8)
+                .ok_or_else(|| anyhow::anyhow!("Expected byte value (0-255), got {}", item)))
+            .collect(),
+        Value::String(s) => {
+            if let Some(hex) = s.strip_prefix("hex:") {
+                from_hex(hex)
+            } else if let Some(b64) = s.strip_prefix("base64:") {
+                use base64::Engine;
+                base64::engine::general_purpose::STANDARD.decode(b64)
+                    .map_err(|e| anyhow::anyhow!("Invalid base64 bytes: {}", e))
+            } else {
+                Err(anyhow::anyhow!("Expected bytes string prefixed with 'hex:' or 'base64:'"))
+            }
+        }
+        _ => Err(anyhow::anyhow!("Expected bytes as array or 'hex:'/'base64:' string")),
+    }
+}
+
+fn from_hex(hex: &str) -> Result<Vec<u8>> {
+    let hex = hex.strip_prefix("0x").unwrap_or(hex);
+    // Checked up front: byte slicing below would panic inside a multi-byte char, and
+    // from_str_radix would accept a '+' sign
+    if let Some(c) = hex.chars().find(|c| !c.is_ascii_hexdigit()) {
+        return Err(anyhow::anyhow!("Invalid hex character: {:?}", c));
+    }
+    if hex.len() % 2 != 0 {
+        return Err(anyhow::anyhow!("Hex string has odd length: {}", hex.len()));
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16)
+            .map_err(|_| anyhow::anyhow!("Invalid hex byte: {}", &hex[i..i + 2])))
+        .collect()
+}
+
+pub fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
 }
 
 
@@ -145,4 +270,81 @@ mod tests {
         let result = encoder.encode_value(&json!(true), "bool").unwrap();
         assert_eq!(result, vec![1u8]);
     }
+
+    #[test]
+    fn test_bytes_round_trip() {
+        let encoder = BorshEncoder::new();
+        let expected = vec![3u8, 0, 0, 0, 0xde, 0xad, 0xbe];
+
+        let from_array = encoder.encode_value(&json!([222, 173, 190]), "bytes").unwrap();
+        assert_eq!(from_array, expected);
+
+        let from_hex = encoder.encode_value(&json!("hex:deadbe"), "bytes").unwrap();
+        assert_eq!(from_hex, expected);
+
+        let from_base64 = encoder.encode_value(&json!("base64:3q2+"), "bytes").unwrap();
+        assert_eq!(from_base64, expected);
+
+        assert!(encoder.encode_value(&json!("deadbe"), "bytes").is_err());
+        assert!(encoder.encode_value(&json!("hex:é0"), "bytes").is_err());
+        assert!(encoder.encode_value(&json!("hex:+f"), "bytes").is_err());
+        assert!(encoder.encode_value(&json!([256]), "bytes").is_err());
+
+        let (decoded, consumed) = encoder.decode_value(&expected, "bytes").unwrap();
+        assert_eq!(decoded, json!("hex:deadbe"));
+        assert_eq!(consumed, 7);
+    }
+
+    #[test]
+    fn test_pubkey_base58_or_bytes() {
+        let encoder = BorshEncoder::new();
+        let pubkey = Pubkey::new_unique();
+
+        let from_base58 = encoder.encode_value(&json!(pubkey.to_string()), "pubkey").unwrap();
+        let from_array = encoder.encode_value(&json!(pubkey.to_bytes().to_vec()), "pubkey").unwrap();
+        assert_eq!(from_base58, pubkey.to_bytes().to_vec());
+        assert_eq!(from_array, from_base58);
+
+        let short = encoder.encode_value(&json!([1, 2, 3]), "pubkey").unwrap_err();
+        assert!(short.to_string().contains("length 32, got 3"));
+        assert!(encoder.encode_value(&json!(vec![256; 32]), "pubkey").is_err());
+        assert!(encoder.encode_value(&json!(7), "pubkey").is_err());
+
+        assert_eq!(encoder.decode_value(&from_array, "pubkey").unwrap(), (json!(pubkey.to_string()), 32));
+        let (raw, _) = BorshEncoder::new().with_raw_pubkeys().decode_value(&from_array, "pubkey").unwrap();
+        assert_eq!(raw, json!(pubkey.to_bytes().to_vec()));
+    }
+
+    #[test]
+    fn test_encode_instruction_with_in_memory_idl() {
+        let program_id = "11111111111111111111111111111112";
+        let loader = IdlLoader::from_value(program_id, json!({
+            "address": program_id,
+            "instructions": [{
+                "name": "deposit",
+                "discriminator": [1, 2, 3, 4, 5, 6, 7, 8],
+                "accounts": [],
+                "args": [
+                    { "name": "amount", "type": "u64" },
+                    { "name": "memo", "type": "string" }
+                ]
+            }],
+            "errors": null
+        })).unwrap();
+
+        let args = HashMap::from([
+            ("amount".to_string(), json!(42)),
+            ("memo".to_string(), json!("hi")),
+        ]);
+        let data = BorshEncoder::new().encode_instruction(&loader, program_id, "deposit", args).unwrap();
+
+        let mut expected = vec![1, 2, 3, 4, 5, 6, 7, 8];
+        expected.extend_from_slice(&42u64.to_le_bytes());
+        expected.extend_from_slice(&2u32.to_le_bytes());
+        expected.extend_from_slice(b"hi");
+        assert_eq!(data, expected);
+
+        let missing = HashMap::from([("amount".to_string(), json!(42))]);
+        assert!(BorshEncoder::new().encode_instruction(&loader, program_id, "deposit", missing).is_err());
+    }
 }
@@ -0,0 +1,118 @@
+use anyhow::{anyhow, Result};
+use serde::Deserialize;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::pubkey::Pubkey;
+use std::str::FromStr;
+use crate::borsh_encoder::BorshEncoder;
+use crate::idl_loader::{IdlAccount, IdlLoader};
+
+/// One instruction of a `bundle` file: an IDL instruction with its JSON args and accounts in IDL order.
+#[derive(Debug, Deserialize)]
+pub struct InstructionSpec {
+    pub program: String,
+    pub instruction: String,
+    #[serde(default = "empty_args")]
+    pub args: serde_json::Value,
+    #[serde(default)]
+    pub accounts: Vec<String>,
+}
+
+fn empty_args() -> serde_json::Value {
+    serde_json::json!({})
+}
+
+/// Parse a bundle file: a non-empty JSON array of instruction specs.
+pub fn parse_bundle(json: &str) -> Result<Vec<InstructionSpec>> {
+    let specs: Vec<InstructionSpec> = serde_json::from_str(json)
+        .map_err(|e| anyhow!("Invalid bundle JSON (expected an array of {{program, instruction, args, accounts}}): {}", e))?;
+    if specs.is_empty() {
+        return Err(anyhow!("Bundle has no instructions"));
+    }
+    Ok(specs)
+}
+
+/// Encode `instruction_name` of `program_id` via its IDL. `accounts` are pubkeys in IDL order;
+/// signer/writable flags come from the IDL. Trailing accounts with a fixed IDL `address` may be omitted.
+pub fn build_instruction(
+    idl_loader: &IdlLoader,
+    encoder: &BorshEncoder,
+    program_id: &str,
+    instruction_name: &str,
+    args: serde_json::Value,
+    accounts: &[String],
+) -> Result<Instruction> {
+    let program_pubkey = Pubkey::from_str(program_id)
+        .map_err(|_| anyhow!("Invalid program ID: {}", program_id))?;
+    let args = idl_loader.check_args(program_id, instruction_name, args)?;
+    let data = encoder.encode_instruction(idl_loader, program_id, instruction_name, args)?;
+
+    let spec = idl_loader.get_instruction(program_id, instruction_name)?;
+    if accounts.len() > spec.accounts.len() {
+        return Err(anyhow!("Too many accounts: '{}' takes {}, got {}", instruction_name, spec.accounts.len(), accounts.len()));
+    }
+    let mut metas = Vec::with_capacity(accounts.len());
+    for (idl_account, address) in spec.accounts.iter().zip(accounts) {
+        let pubkey = Pubkey::from_str(address)
+            .map_err(|_| anyhow!("Invalid pubkey for account '{}': {}", idl_account.name, address))?;
+        metas.push(account_meta(idl_account, pubkey));
+    }
+    // Accounts left out after the last given one take their fixed address, if the IDL has one
+    for idl_account in &spec.accounts[accounts.len()..] {
+        let Some(address) = &idl_account.address else { break };
+        let pubkey = Pubkey::from_str(address)
+            .map_err(|_| anyhow!("Invalid default address for account '{}': {}", idl_account.name, address))?;
+        metas.push(account_meta(idl_account, pubkey));
+    }
+    idl_loader.validate_account_metas(program_id, instruction_name, &metas)?;
+
+    Ok(Instruction { program_id: program_pubkey, accounts: metas, data })
+}
+
+fn account_meta(idl_account: &IdlAccount, pubkey: Pubkey) -> AccountMeta {
+    if idl_account.writable {
+        AccountMeta::new(pubkey, idl_account.signer)
+    } else {
+        AccountMeta::new_readonly(pubkey, idl_account.signer)
+    }
+}
+
+/// Map a failed transaction instruction index back to the bundle entry, skipping the
+/// `prepended` compute budget instructions.
+pub fn bundle_index(failed_instruction: usize, prepended: usize) -> Option<usize> {
+    failed_instruction.checked_sub(prepended)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundle_build_and_index() {
+        let program_id = "Bj4vH3tVu1GjCHeU3peRfYyxJpAzooyZCTU6rRFR4AnY";
+        let mut loader = IdlLoader::new();
+        loader.load_from_file(concat!(env!("CARGO_MANIFEST_DIR"), "/send_program.json"), program_id).unwrap();
+
+        let send_account = Pubkey::new_unique();
+        let user = Pubkey::new_unique();
+        let json = format!(r#"[
+            {{"program": "{p}", "instruction": "initialize", "accounts": ["{a}", "{u}", "11111111111111111111111111111111"]}},
+            {{"program": "{p}", "instruction": "get_stats", "accounts": ["{a}"]}}
+        ]"#, p = program_id, a = send_account, u = user);
+        let specs = parse_bundle(&json).unwrap();
+        assert_eq!(specs.len(), 2);
+        assert_eq!(specs[1].args, serde_json::json!({}));
+
+        let encoder = BorshEncoder::new();
+        let ix = build_instruction(&loader, &encoder, &specs[0].program, &specs[0].instruction,
+                                   specs[0].args.clone(), &specs[0].accounts).unwrap();
+        assert_eq!(ix.accounts.len(), 3);
+        assert!(ix.accounts[1].is_signer && ix.accounts[1].pubkey == user);
+        assert_eq!(ix.data.len(), 8);
+
+        assert!(build_instruction(&loader, &encoder, program_id, "get_stats", empty_args(),
+                                  &[send_account.to_string(), user.to_string()]).is_err());
+        assert!(parse_bundle("[]").is_err());
+        assert_eq!(bundle_index(3, 2), Some(1));
+        assert_eq!(bundle_index(0, 2), None);
+    }
+}
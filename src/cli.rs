@@ -0,0 +1,759 @@
+use anyhow::Result;
+use clap::{Parser, Subcommand};
+use solana_sdk::pubkey::Pubkey;
+use std::str::FromStr;
+use crate::commands::SEND_PROGRAM_ID;
+use crate::explorer::Explorer;
+use crate::jupiter_client::SwapPriorityFee;
+use crate::output::OutputFormat;
+use crate::priority_fee::PriorityTier;
+use crate::program_command::ProgramCommands;
+use crate::rpc::{self, Commitment};
+use crate::tx_sender::TxVersion;
+use crate::idempotency;
+
+#[derive(Parser)]
+#[command(name = "solana-program-cli")]
+#[command(about = "A CLI tool to interact with Solana programs using their Program IDs")]
+pub struct Cli {
+    #[command(subcommand)]
+    pub(crate) command: Commands,
+
+    #[arg(long, global = true, value_enum, default_value = "human")]
+    pub(crate) output: OutputFormat,
+
+    /// Print JSON on one line instead of pretty-printed (for scripts)
+    #[arg(long, global = true)]
+    pub(crate) json_compact: bool,
+
+    /// RPC endpoint; overrides HELIUS_RPC_URL / SOLANA_RPC_URL
+    #[arg(long, global = true)]
+    pub(crate) url: Option<String>,
+
+    /// Extra HTTP header for RPC requests, e.g. "X-API-Key: <key>" (repeatable)
+    #[arg(long = "rpc-header", global = true)]
+    pub(crate) rpc_headers: Vec<String>,
+
+    /// Simulate with the real blockhash and signature verification instead of a replaced blockhash
+    #[arg(long, global = true)]
+    pub(crate) strict_simulate: bool,
+
+    /// Keypair that pays transaction fees; defaults to the signing authority (SOLANA_PRIVATE_KEY or ~/.config/solana/id.json)
+    #[arg(long, global = true)]
+    pub(crate) fee_payer: Option<String>,
+
+    /// Skip registry refresh and network checks; only local commands (registry, idl) can run
+    #[arg(long, global = true)]
+    pub(crate) offline: bool,
+
+    /// Never write the program registry cache (implied by --offline, --dry-run, --build-only and --sign-only); registry edits last for this run only
+    #[arg(long, global = true)]
+    pub(crate) no_cache_write: bool,
+
+    /// Print each instruction's program, accounts, data hex, and IDL-decoded args before signing
+    #[arg(long, global = true)]
+    pub(crate) dump_instruction: bool,
+
+    /// Refuse to send a program instruction unless a loaded IDL validates its discriminator, accounts, and args
+    #[arg(long, global = true)]
+    pub(crate) require_idl: bool,
+
+    /// Record the one transaction this command sends under KEY (in ./cache); re-running with the same key within
+    /// the window prints the earlier signature instead of sending again
+    #[arg(long, global = true)]
+    pub(crate) idempotency_key: Option<String>,
+
+    /// How long an --idempotency-key blocks a repeat send, in seconds
+    #[arg(long, global = true, requires = "idempotency_key", default_value_t = idempotency::DEFAULT_WINDOW_SECS)]
+    pub(crate) idempotency_window_secs: u64,
+
+    /// Compute unit price in micro-lamports added to every transaction
+    #[arg(long, global = true, conflicts_with = "priority")]
+    pub(crate) priority_fee: Option<u64>,
+
+    /// Priority fee tier from recent fees on the transaction's writable accounts (p25/p50/p90)
+    #[arg(long, global = true, value_enum)]
+    pub(crate) priority: Option<PriorityTier>,
+
+    /// Build, sign, and simulate every transaction without sending it
+    #[arg(long, global = true)]
+    pub(crate) dry_run: bool,
+
+    /// Priority fee for Jupiter swaps: `auto` (Jupiter picks), total lamports, or `percentile:N` of recent fees
+    #[arg(long, global = true, default_value = "auto")]
+    pub(crate) swap_priority_fee: SwapPriorityFee,
+
+    /// Reject Jupiter quotes more than this many slots behind the cluster (warn past a third of it)
+    #[arg(long, global = true, default_value = "150")]
+    pub(crate) max_quote_slot_drift: u64,
+
+    /// Reject Jupiter quotes older than this many seconds (warn past a third of it)
+    #[arg(long, global = true, default_value = "30")]
+    pub(crate) max_quote_age_secs: u64,
+
+    /// Abort any send whose estimated fee (base + priority, from the RPC) exceeds this many lamports
+    #[arg(long, global = true)]
+    pub(crate) max_fee_lamports: Option<u64>,
+
+    /// Print built instructions as a proposal JSON for this multisig (e.g. Squads) instead of sending
+    #[arg(long, global = true, value_name = "MULTISIG_PUBKEY")]
+    pub(crate) propose_to: Option<String>,
+
+    /// Print an explorer link (for the RPC endpoint's cluster) after every sent transaction
+    #[arg(long, global = true)]
+    pub(crate) print_transaction_url: bool,
+
+    /// Explorer used for transaction links
+    #[arg(long, global = true, value_enum, default_value = "solscan")]
+    pub(crate) explorer: Explorer,
+
+    /// Run commands against programs disabled in the registry. Not `--force`: that stays `keygen`'s
+    /// overwrite flag, so a keygen script can't silently bypass the disabled-program guard
+    #[arg(long, global = true)]
+    pub(crate) allow_disabled: bool,
+
+    /// Resubmit a signed transaction up to N more times if sending or confirming fails; one this command signed
+    /// is re-signed with a fresh blockhash once its own expires
+    #[arg(long, global = true, default_value = "0")]
+    pub(crate) send_retries: u32,
+
+    /// Message format for native program transactions; v0 can reference lookup tables (--alt)
+    #[arg(long, global = true, value_enum, default_value = "legacy")]
+    pub(crate) tx_version: TxVersion,
+
+    /// After each send, fetch the confirmed transaction and print its decoded program logs
+    /// (always on for view instructions such as get-message and get-stats)
+    #[arg(long, global = true)]
+    pub(crate) confirm_and_show_logs: bool,
+
+    /// Sign transactions but print them (base64, with the blockhash's last valid block height) instead of sending
+    #[arg(long, global = true, conflicts_with = "dry_run")]
+    pub(crate) build_only: bool,
+
+    /// Sign with the local keypairs only and print the message with its partial signatures, for signers on other
+    /// machines to sign too; merge the outputs with `combine`
+    #[arg(long, global = true, conflicts_with_all = ["dry_run", "build_only"])]
+    pub(crate) sign_only: bool,
+
+    /// Retry a simulation this many times when the RPC can't be reached (program failures are never retried)
+    #[arg(long, global = true, default_value = "2")]
+    pub(crate) simulate_retries: u32,
+
+    /// In batch operations, stop submitting once a transaction fails; remaining ones are reported as skipped
+    #[arg(long, global = true, conflicts_with = "continue_on_failure")]
+    pub(crate) abort_on_first_failure: bool,
+
+    /// In batch operations, log a failed transaction and keep sending the rest (the default)
+    #[arg(long, global = true)]
+    pub(crate) continue_on_failure: bool,
+
+    /// When a SOL send would leave a new recipient below the rent-exempt minimum, send the minimum instead of just warning
+    #[arg(long, global = true)]
+    pub(crate) top_up_new_recipient: bool,
+
+    /// Treat pre-send warnings (high compute, high fee, moderate quote price impact) as failures and don't send
+    #[arg(long, global = true)]
+    pub(crate) fail_on_warning: bool,
+
+    /// Address lookup table used to compress v0 messages (repeatable; requires --tx-version v0)
+    #[arg(long = "alt", global = true)]
+    pub(crate) alts: Vec<String>,
+
+    /// Comma-separated accounts whose post-simulation state is fetched and printed (IDL-decoded when known)
+    #[arg(long, global = true, value_delimiter = ',')]
+    pub(crate) return_accounts: Vec<String>,
+
+    /// Per-request RPC timeout in seconds, for every client (raise for heavy simulations, lower for quick checks)
+    #[arg(long, global = true, default_value_t = rpc::DEFAULT_RPC_TIMEOUT_SECS)]
+    pub(crate) rpc_timeout_secs: u64,
+
+    /// Commitment for account and balance reads
+    #[arg(long, global = true, value_enum, default_value = "confirmed")]
+    pub(crate) read_commitment: Commitment,
+
+    /// Commitment sent transactions must reach before they count as confirmed
+    #[arg(long, global = true, value_enum, default_value = "confirmed")]
+    pub(crate) confirm_commitment: Commitment,
+}
+
+#[derive(Subcommand)]
+pub(crate) enum Commands {
+ 
+    HelloWorld {
+        #[command(subcommand)]
+        action: HelloWorldActions,
+    },
+    Calculator {
+        #[command(subcommand)]
+        action: CalculatorActions,
+    },
+    Send {
+        #[command(subcommand)]
+        action: SendActions,
+    },
+    Swap {
+        #[command(subcommand)]
+        action: SwapActions,
+    },
+    Registry {
+        #[command(subcommand)]
+        action: RegistryActions,
+    },
+    Account {
+        #[command(subcommand)]
+        action: AccountActions,
+    },
+    Idl {
+        #[command(subcommand)]
+        action: IdlActions,
+    },
+    /// Show SOL, token, and program PDA balances for the payer (tokens largest first)
+    Balances {
+        /// Show at most this many token accounts
+        #[arg(long)]
+        limit: Option<usize>,
+        /// Hide token accounts holding less than this (UI units, e.g. 0.01) to skip dust
+        #[arg(long)]
+        min_balance: Option<f64>,
+    },
+    Jupiter {
+        #[command(subcommand)]
+        action: JupiterActions,
+    },
+    /// SPL Token and Token-2022 transfers
+    Token {
+        #[command(subcommand)]
+        action: TokenActions,
+    },
+    /// Generate a keypair file (JSON byte array, same as solana-keygen) for --account-keypair flags
+    Keygen {
+        outfile: String,
+        /// Overwrite an existing file
+        #[arg(long)]
+        force: bool,
+    },
+    /// Offline developer helpers
+    Util {
+        #[command(subcommand)]
+        action: UtilActions,
+    },
+    /// Stream a program's transaction logs live over WebSocket (Ctrl-C to stop)
+    Logs {
+        program_id: String,
+        /// Commitment for the subscription; defaults to --read-commitment
+        #[arg(long, value_enum)]
+        commitment: Option<Commitment>,
+        /// WebSocket endpoint; derived from the RPC URL when omitted
+        #[arg(long)]
+        ws_url: Option<String>,
+    },
+    /// Call any instruction of a program whose IDL is loaded
+    Invoke {
+        program_id: String,
+        instruction: String,
+        /// Instruction args as a JSON object, e.g. '{"amount": 1000000}'
+        #[arg(long, conflicts_with = "args_file")]
+        args: Option<String>,
+        /// Path to a JSON file holding the args object; easier than --args for nested values
+        #[arg(long)]
+        args_file: Option<String>,
+        /// Account pubkeys in IDL order (repeatable); signer/writable flags come from the IDL
+        #[arg(long = "account")]
+        accounts: Vec<String>,
+        /// Extra signer keypair file for IDL signer accounts other than the payer (repeatable)
+        #[arg(long = "signer")]
+        signers: Vec<String>,
+    },
+    /// Send several IDL instructions as one atomic transaction, simulated first
+    Bundle {
+        /// JSON array of {"program", "instruction", "args", "accounts"} objects, run in order
+        file: String,
+        /// Extra signer keypair file for IDL signer accounts other than the payer (repeatable)
+        #[arg(long = "signer")]
+        signers: Vec<String>,
+    },
+    /// Merge `--sign-only` outputs of one transaction into a fully signed transaction and send it
+    /// (with --build-only, print it instead)
+    Combine {
+        /// `--sign-only` JSON files, one per signing machine, in any order
+        #[arg(required = true)]
+        parts: Vec<String>,
+    },
+}
+
+#[derive(Subcommand, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "action", rename_all = "kebab-case")]
+pub(crate) enum HelloWorldActions {
+
+    Initialize {
+        #[arg(long)]
+        message: String,
+        #[arg(long)]
+        account_keypair: String,
+    },
+ 
+    UpdateMessage {
+        #[arg(long)]
+        account_pubkey: String,
+        #[arg(long)]
+        new_message: String,
+    },
+                
+    GetMessage {
+        #[arg(long)]
+        account_pubkey: String,
+    },
+}
+
+#[derive(Subcommand, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "action", rename_all = "kebab-case")]
+pub(crate) enum CalculatorActions {
+
+    Initialize {
+        #[arg(long)]
+        account_keypair: String,
+    },
+
+    Add {
+        #[arg(long)]
+        account_pubkey: String,
+        #[arg(long)]
+        a: i64,
+        #[arg(long)]
+        b: i64,
+    },
+
+    Ping {
+        #[arg(long)]
+        account_pubkey: String,
+        #[arg(long)]
+        message: String,
+    },
+    GetResult {
+        #[arg(long)]
+        account_pubkey: String,
+    },
+    /// Simulate `add` and print the result the program would log, without sending
+    Simulate {
+        #[arg(long)]
+        account_pubkey: String,
+        #[arg(long)]
+        a: i64,
+        #[arg(long)]
+        b: i64,
+    },
+}
+
+#[derive(Subcommand)]
+pub(crate) enum SendActions {
+   
+    Initialize {
+        #[arg(long)]
+        account_keypair: String,
+    },
+   
+    SendSol {
+        #[arg(long)]
+        account_pubkey: String,
+        #[arg(long)]
+        amount: String, 
+        #[arg(long)]
+        recipient: String,
+    },
+   
+    GetStats {
+        #[arg(long)]
+        account_pubkey: String,
+    },
+
+    SmartInit,
+                
+    SmartSend {
+        #[arg(long)]
+        amount: String, 
+        #[arg(long)]
+        recipient: String,
+    },
+
+    SmartStats,
+    /// Program-wide totals from the send program's `global_stats` PDA, if it keeps one
+    GlobalStats,
+    /// One smart-send per `recipient,amount_sol` line of a CSV file, several in flight at once
+    Batch {
+        #[arg(long)]
+        file: String,
+        /// Transactions submitted and awaiting confirmation at the same time
+        #[arg(long, default_value = "4")]
+        concurrency: usize,
+        /// Simulate every transfer first and show totals, fees, and failing lines before sending
+        #[arg(long)]
+        simulate_before_batch: bool,
+        /// Send after --simulate-before-batch without asking for confirmation
+        #[arg(long, requires = "simulate_before_batch")]
+        yes: bool,
+    },
+    /// Transaction signatures touching your send account (or --address), newest first
+    History {
+        /// Address to list instead of your derived send account
+        #[arg(long)]
+        address: Option<String>,
+        /// Only signatures at or after this time (RFC 3339, e.g. 2025-01-01T00:00:00Z)
+        #[arg(long)]
+        since: Option<String>,
+        /// Only signatures at or before this time (RFC 3339)
+        #[arg(long)]
+        until: Option<String>,
+        /// Maximum signatures to list (default 20, or the whole window with --since)
+        #[arg(long)]
+        limit: Option<usize>,
+    },
+    CodegenStats,
+   
+    Resolve,
+
+    Simulate {
+        #[arg(long)]
+        amount: String, 
+        #[arg(long)]
+        recipient: String,
+    },
+
+    SafeSend {
+        #[arg(long)]
+        amount: String, 
+        #[arg(long)]
+        recipient: String,
+    },
+
+    JupiterSwap {
+        #[arg(long, required_unless_present = "profile")]
+        input_mint: Option<String>,
+        #[arg(long, required_unless_present = "profile")]
+        output_mint: Option<String>,
+        #[arg(long)]
+        amount: String, 
+        /// Defaults to the profile's slippage, else 50
+        #[arg(long)]
+        slippage_bps: Option<u16>,
+        /// Load mints, slippage, and price impact cap from a registry swap profile; explicit flags override it
+        #[arg(long)]
+        profile: Option<String>,
+        /// Abort when the quote's price impact is above this percent
+        #[arg(long)]
+        max_price_impact: Option<f64>,
+        #[arg(long)]
+        simulate_only: bool,
+        /// Hard cap on slippage; escalation never exceeds it (defaults to --slippage-bps)
+        #[arg(long)]
+        max_slippage_bps: Option<u16>,
+        /// Retry a failed build at wider slippage, up to --max-slippage-bps
+        #[arg(long, requires = "max_slippage_bps")]
+        allow_slippage_escalation: bool,
+        /// Let Jupiter pick slippage per route (dynamicSlippage); --slippage-bps then only applies to the quote
+        #[arg(long, conflicts_with = "allow_slippage_escalation")]
+        dynamic_slippage: bool,
+        /// Lower bound for --dynamic-slippage
+        #[arg(long, requires = "dynamic_slippage")]
+        dynamic_slippage_min_bps: Option<u16>,
+        /// Upper bound for --dynamic-slippage
+        #[arg(long, requires = "dynamic_slippage")]
+        dynamic_slippage_max_bps: Option<u16>,
+        /// Print Jupiter's component instructions (/swap-instructions) instead of swapping
+        #[arg(long, alias = "instructions-only")]
+        decompose: bool,
+        /// If the swap fails, close the empty ATAs this run created and reclaim their rent
+        #[arg(long)]
+        cleanup_on_failure: bool,
+        /// Rebuild at a doubled priority fee when the swap is dropped or times out (not on slippage or funds errors)
+        #[arg(long, default_value = "2")]
+        congestion_retries: u32,
+        /// Cap in lamports for the escalated swap priority fee
+        #[arg(long, default_value = "1000000")]
+        max_swap_priority_fee: u64,
+    },
+
+    JupiterQuote {
+        #[arg(long)]
+        input_mint: String,
+        #[arg(long)]
+        output_mint: String,
+        #[arg(long)]
+        amount: String,
+        #[arg(long, default_value = "50")]
+        slippage_bps: u16,
+    },
+}
+
+#[derive(Subcommand, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "action", rename_all = "kebab-case")]
+pub(crate) enum SwapActions {
+
+    Initialize {
+        #[arg(long)]
+        account_keypair: String,
+        #[arg(long)]
+        initial_sol_pool: String, 
+        #[arg(long)]
+        initial_token_pool: String, 
+    },
+
+    SwapSolForTokens {
+        #[arg(long)]
+        account_pubkey: String,
+        #[arg(long)]
+        sol_amount: String, 
+    },
+
+    SwapTokensForSol {
+        #[arg(long)]
+        account_pubkey: String,
+        #[arg(long)]
+        token_amount: String, 
+    },
+
+    GetPoolInfo {
+        #[arg(long)]
+        account_pubkey: String,
+    },
+
+    /// Estimate a swap's output from the pool's current reserves (no transaction)
+    Quote {
+        #[arg(long)]
+        account_pubkey: String,
+        /// SOL to swap for tokens
+        #[arg(long, conflicts_with = "token_amount", required_unless_present = "token_amount")]
+        sol_amount: Option<String>,
+        /// Tokens to swap for SOL
+        #[arg(long)]
+        token_amount: Option<u64>,
+    },
+
+    Ping {
+        #[arg(long)]
+        account_pubkey: String,
+        #[arg(long)]
+        message: String,
+    },
+
+    /// Recent swaps against the pool, decoded from its transactions via the swap program IDL
+    History {
+        #[arg(long)]
+        account_pubkey: String,
+        /// How many of the pool's most recent transactions to scan
+        #[arg(long, default_value_t = 20)]
+        limit: usize,
+    },
+}
+
+#[derive(Subcommand)]
+pub(crate) enum RegistryActions {
+    List,
+    Stats,
+    Refresh,
+    Validate {
+        /// Also load each IDL (file or URL) concurrently and check its address matches the program ID
+        #[arg(long)]
+        deep: bool,
+    },
+    Add {
+        #[arg(long)]
+        program_id: String,
+        #[arg(long)]
+        name: String,
+        #[arg(long)]
+        idl_url: String,
+        #[arg(long)]
+        client_version: String,
+        #[arg(long)]
+        client_type: String,
+        #[arg(long, default_value = "5")]
+        priority: u8,
+        /// Compute unit price (micro-lamports) for this program when no --priority-fee/--priority is given
+        #[arg(long)]
+        default_priority_fee: Option<u64>,
+    },
+    Update {
+        #[arg(long)]
+        program_id: String,
+        #[arg(long)]
+        idl_url: Option<String>,
+        #[arg(long)]
+        client_version: Option<String>,
+        #[arg(long)]
+        priority: Option<u8>,
+        #[arg(long, conflicts_with = "clear_priority_fee")]
+        default_priority_fee: Option<u64>,
+        /// Remove the program's default priority fee
+        #[arg(long)]
+        clear_priority_fee: bool,
+    },
+    Remove {
+        #[arg(long)]
+        program_id: String,
+    },
+    /// Save (or replace) a named swap profile for `send jupiter-swap --profile`
+    AddSwapProfile {
+        #[arg(long)]
+        name: String,
+        /// Input mint pubkey or SOL/USDC/USDT
+        #[arg(long)]
+        input: String,
+        #[arg(long)]
+        output: String,
+        #[arg(long, default_value = "50")]
+        slippage_bps: u16,
+        /// Abort swaps whose price impact is above this percent
+        #[arg(long)]
+        max_price_impact: Option<f64>,
+    },
+    RemoveSwapProfile {
+        #[arg(long)]
+        name: String,
+    },
+    Enable {
+        #[arg(long)]
+        program_id: String,
+    },
+    Disable {
+        #[arg(long)]
+        program_id: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub(crate) enum TokenActions {
+    /// `transfer_checked` from the payer's ATA, creating the recipient's ATA if missing; simulated first
+    Transfer {
+        /// Mint address or SOL/USDC/USDT
+        mint: String,
+        /// Amount in UI units (e.g. 2.5), scaled by the mint's decimals
+        amount: String,
+        /// Wallet to receive the tokens; its ATA is derived
+        recipient: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub(crate) enum JupiterActions {
+    /// Quote one swap at several slippage settings side by side (read-only)
+    Compare {
+        #[arg(long)]
+        input_mint: String,
+        #[arg(long)]
+        output_mint: String,
+        /// Amount in the input token's base units
+        #[arg(long)]
+        amount: u64,
+        #[arg(long, value_delimiter = ',', default_value = "10,25,50,100")]
+        slippage_bps: Vec<u16>,
+    },
+    /// Spot price of one `base` token in `quote` tokens, from a Jupiter quote (read-only)
+    Price {
+        /// Mint or symbol (SOL, USDC, USDT) being priced
+        base: String,
+        /// Mint or symbol the price is given in
+        quote: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub(crate) enum AccountActions {
+    Dump {
+        address: String,
+        #[arg(long, default_value = "0")]
+        offset: usize,
+        #[arg(long)]
+        length: Option<usize>,
+    },
+    /// Stream an account's changes over WebSocket, decoded via its owner's IDL (Ctrl-C to stop)
+    Watch {
+        address: String,
+        /// Print only this IDL field, with a timestamp, each time its value changes (e.g. total_sent)
+        #[arg(long)]
+        field: Option<String>,
+        /// WebSocket endpoint; derived from the RPC URL when omitted
+        #[arg(long)]
+        ws_url: Option<String>,
+    },
+    /// Allocate a plain (non-PDA) account owned by a program via system create_account
+    Create {
+        /// Program that will own the new account
+        #[arg(long)]
+        owner_program: String,
+        /// Account data size in bytes
+        #[arg(long, required_unless_present = "account_type")]
+        size: Option<usize>,
+        /// Derive the size from this IDL account type of the owner program instead of --size
+        #[arg(long, conflicts_with = "size")]
+        account_type: Option<String>,
+        /// Keypair for the new account; generated and written here if the file doesn't exist
+        #[arg(long)]
+        keypair: Option<String>,
+    },
+}
+#[derive(Subcommand)]
+pub(crate) enum UtilActions {
+    /// Anchor discriminator `sha256("<namespace>:<name>")[..8]` as bytes and hex
+    Discriminator {
+        /// Instruction name (or account type with --namespace account)
+        name: String,
+        #[arg(long, default_value = "global")]
+        namespace: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub(crate) enum IdlActions {
+    Errors {
+        program_id: String,
+        #[arg(long)]
+        code: Option<u32>,
+    },
+    /// Field names, types, byte offsets, and sizes of an IDL account type
+    AccountLayout {
+        program_id: String,
+        account_name: String,
+    },
+}
+
+impl Cli {
+    /// `--output`
+    pub fn output(&self) -> OutputFormat {
+        self.output
+    }
+
+    /// `--json-compact`
+    pub fn json_compact(&self) -> bool {
+        self.json_compact
+    }
+}
+
+impl Commands {
+    /// Whether the command talks to an RPC node or remote API.
+    pub(crate) fn requires_network(&self) -> bool {
+        match self {
+            // Deep validation may fetch IDLs over HTTP
+            Commands::Registry { action } => matches!(action, RegistryActions::Refresh | RegistryActions::Validate { deep: true }),
+            Commands::Idl { .. } | Commands::Keygen { .. } | Commands::Util { .. } => false,
+            _ => true,
+        }
+    }
+
+    /// Program the command builds transactions for, if the registry could have disabled it.
+    pub(crate) fn target_program(&self, program_commands: &ProgramCommands) -> Result<Option<Pubkey>> {
+        let handler_program = |name: &str| program_commands.get(name).map(|handler| handler.program_id());
+        Ok(match self {
+            Commands::HelloWorld { .. } => handler_program("hello-world"),
+            Commands::Calculator { .. } => handler_program("calculator"),
+            Commands::Swap { .. } => handler_program("swap"),
+            // Read-only lookups and Jupiter never touch the send program
+            Commands::Send { action: SendActions::History { .. } | SendActions::GlobalStats | SendActions::CodegenStats
+                | SendActions::Resolve | SendActions::JupiterQuote { .. } | SendActions::JupiterSwap { .. } } => None,
+            Commands::Send { .. } => Some(Pubkey::from_str(SEND_PROGRAM_ID)?),
+            Commands::Invoke { program_id, .. } => Some(Pubkey::from_str(program_id)
+                .map_err(|_| anyhow::anyhow!("Invalid program ID: {}", program_id))?),
+            _ => None,
+        })
+    }
+}
+
@@ -0,0 +1,2632 @@
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, read_keypair_file, write_keypair_file};
+use solana_sdk::signer::Signer;
+use solana_sdk::system_program;
+use solana_sdk::transaction::VersionedTransaction;
+use anyhow::Result;
+use std::str::FromStr;
+
+use crate::cli::*;
+use crate::{
+    account_inspector, account_resolver, ata_manager, batch, borsh_encoder, bundle, error_decoder, explorer, generated, history,
+    idempotency, idl_loader, instruction_dump, jupiter_client, lamports, outcome, output, payer, priority_fee, program_command, program_defs,
+    program_handlers, proposal, program_registry, rent_cache, account_cache, rpc, sign_only, transaction_simulator, tx_sender,
+};
+use idl_loader::{IdlLoader, ACCOUNT_DISCRIMINATOR_LEN};
+use borsh_encoder::BorshEncoder;
+use account_resolver::{AccountResolver, AccountResolution, fetch_owned_account};
+use transaction_simulator::{TransactionSimulator, SimulationMode};
+use jupiter_client::{classify_swap_failure, DynamicSlippage, JupiterClient, QuoteFreshnessConfig, QuoteRequest, SwapFailure, SwapRequest, RouteSummary, SlippagePolicy, display_amount};
+use ata_manager::{AtaManager, CommonMints};
+use program_registry::{ProgramRegistry, ProgramRoute, ProgramManifest, SwapProfile};
+use lamports::{sol_to_lamports, lamports_to_sol, format_token_amount, parse_token_amount};
+use batch::BatchResult;
+use output::OutputFormat;
+use outcome::CommandOutcome;
+use rent_cache::RentCache;
+use account_cache::AccountCache;
+use rpc::{Commitment, RpcSettings};
+use explorer::ExplorerLinks;
+use tx_sender::{signer_set, SendOptions, SendSettings, TxVersion};
+use priority_fee::{PriorityFees, PrioritySetting};
+use program_command::{CommandContext, ProgramAction};
+use std::sync::Arc;
+use std::collections::HashMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+use solana_client::rpc_config::{RpcAccountInfoConfig, RpcTransactionLogsConfig, RpcTransactionLogsFilter, UiAccountEncoding};
+use solana_client::nonblocking::pubsub_client::PubsubClient;
+use futures::StreamExt;
+use crate::outcome::{note, Notes};
+
+fn program_label(program_id: &Pubkey) -> &'static str {
+    match program_id.to_string().as_str() {
+       
+        "11111111111111111111111111111111" => "System Program",
+       
+        "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA" => "SPL Token",
+       
+        "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL" => "SPL Associated Token Account",
+       
+        "TokenzQdBNbLqU2YPbVmjYVBRhCF9dDid1i9QpZ5dKQ" => "SPL Token-2022",
+        _ => "Unknown Program",
+    }
+}
+
+const REGISTRY_REFRESH_ATTEMPTS: u32 = 2;
+
+/// Refresh the registry with a short retry; failures are logged and the cached registry is kept.
+async fn try_refresh_registry(notes: &Notes, program_registry: &mut ProgramRegistry) -> bool {
+    for attempt in 1..=REGISTRY_REFRESH_ATTEMPTS {
+        match program_registry.refresh().await {
+            Ok(()) => return true,
+            Err(e) => {
+                note!(notes, "⚠️  Registry refresh attempt {}/{} failed: {}", attempt, REGISTRY_REFRESH_ATTEMPTS, e);
+                if attempt < REGISTRY_REFRESH_ATTEMPTS {
+                    tokio::time::sleep(std::time::Duration::from_millis(500 * attempt as u64)).await;
+                }
+            }
+        }
+    }
+    note!(notes, "⚠️  Continuing with cached registry");
+    false
+}
+
+// Two signatures at the base fee
+const MIN_FEE_PAYER_LAMPORTS: u64 = 10_000;
+
+pub(crate) const HELLO_WORLD_PROGRAM_ID: &str = "5PiuXarsz2F7Q6NpSCtdBbK6vroQWiGSdJZW3fPkjWHw";
+pub(crate) const CALCULATOR_PROGRAM_ID: &str = "5tAg6PUJU3AcBGwCJotSbBkGzEm4yNLM9nUK22rPCukq";
+pub(crate) const SEND_PROGRAM_ID: &str = "Bj4vH3tVu1GjCHeU3peRfYyxJpAzooyZCTU6rRFR4AnY";
+pub(crate) const SWAP_PROGRAM_ID: &str = "7JFPcs97cBb6bgfWiLsmA5Qpiv87oVA4Ue3TLinzNhxj";
+/// Seed of the send program's program-wide stats PDA
+const GLOBAL_STATS_SEED: &[u8] = b"global_stats";
+
+fn setup_idl_loader(notes: &Notes) -> Result<IdlLoader> {
+    let mut loader = IdlLoader::new().with_notes(notes.clone());
+    
+    for (file, program_id, label) in [
+        ("hello_world.json", HELLO_WORLD_PROGRAM_ID, "Hello World"),
+        ("calculator.json", CALCULATOR_PROGRAM_ID, "Calculator"),
+        ("send_program.json", SEND_PROGRAM_ID, "Send Program"),
+        ("swap_program.json", SWAP_PROGRAM_ID, "Swap Program"),
+    ] {
+        match loader.load_from_file(file, program_id) {
+            Ok(()) => note!(notes, "✅ Loaded {} IDL", label),
+            // A missing file just means the program runs without its IDL
+            Err(e) if std::path::Path::new(file).exists() => note!(notes, "⚠️  Skipping {} IDL: {}", label, e),
+            Err(_) => {}
+        }
+    }
+    
+    Ok(loader)
+}
+
+/// Add the IDLs of `programs.d/` definitions to `loader`, reporting bad files by path. Returns the
+/// registry entries of the definitions that loaded.
+fn load_program_defs(notes: &Notes, loader: &mut IdlLoader) -> Vec<ProgramManifest> {
+    let (loaded, errors) = program_defs::load_dir(std::path::Path::new(program_defs::PROGRAM_DEFS_DIR));
+    for error in &errors {
+        note!(notes, "⚠️  Skipping program definition {}", error);
+    }
+    let mut manifests = Vec::new();
+    for program in loaded {
+        let program_id = program.def.program_id.clone();
+        if loader.has_idl(&program_id) {
+            note!(notes, "⚠️  Skipping program definition {}: program {} already has an IDL", program.path.display(), program_id);
+            continue;
+        }
+        match loader.add_idl(&program_id, program.idl) {
+            Ok(()) => {
+                note!(notes, "✅ Loaded {} from {}", program.def.name, program.path.display());
+                manifests.push(program.def.manifest(&program.path));
+            }
+            Err(e) => note!(notes, "⚠️  Skipping program definition {}: {}", program.path.display(), e),
+        }
+    }
+    manifests
+}
+
+/// Run one parsed command line: set up the RPC client, payer, and registry, then dispatch the command. Progress
+/// goes to `notes`; whatever they kept comes back in the outcome for the caller to render.
+pub async fn run(cli: Cli, notes: Notes) -> Result<CommandOutcome> {
+    // Only a send that landed, or still can, makes this run redundant; the check runs when the command first sends
+    let outcome = match execute(cli, &notes).await {
+        Err(e) => match e.downcast_ref::<idempotency::AlreadySent>() {
+            Some(sent) => {
+                let signature = match sent.prior {
+                    idempotency::PriorSend::Landed(signature) => {
+                        note!(notes, "♻️  Idempotency key '{}' already landed; not sending again", sent.key);
+                        note!(notes, "✅ Transaction signature: {}", signature);
+                        signature
+                    }
+                    idempotency::PriorSend::Pending(signature) => {
+                        note!(notes, "♻️  Idempotency key '{}' has a send still in flight ({}); not sending again", sent.key, signature);
+                        signature
+                    }
+                };
+                CommandOutcome::new().with_signature(Some(signature))
+            }
+            None => return Err(e),
+        },
+        Ok(outcome) => outcome,
+    };
+    Ok(outcome.with_notes(notes.take()))
+}
+
+/// The run-wide send settings the global flags describe; lookup tables, explorer links, and registry priority
+/// fees are filled in once the RPC endpoint and registry are known.
+fn send_settings(cli: &Cli, notes: &Notes) -> Result<SendSettings> {
+    let priority = if let Some(price) = cli.priority_fee {
+        Some(PrioritySetting::MicroLamports(price))
+    } else {
+        cli.priority.map(PrioritySetting::Tier)
+    };
+    let propose_to = cli.propose_to.as_ref()
+        .map(|multisig| Pubkey::from_str(multisig).map_err(|_| anyhow::anyhow!("Invalid --propose-to pubkey: {}", multisig)))
+        .transpose()?;
+    let return_accounts = cli.return_accounts.iter()
+        .map(|a| Pubkey::from_str(a).map_err(|_| anyhow::anyhow!("Invalid --return-accounts pubkey: {}", a)))
+        .collect::<Result<Vec<_>>>()?;
+    let mut settings = SendSettings::default();
+    settings.dry_run = cli.dry_run;
+    settings.show_logs = cli.confirm_and_show_logs;
+    settings.build_only = cli.build_only;
+    settings.sign_only = cli.sign_only;
+    settings.propose_to = propose_to;
+    settings.dump_instructions = cli.dump_instruction;
+    settings.show_blockhash_expiry = cli.build_only || cli.sign_only || cli.dump_instruction;
+    settings.send_retries = cli.send_retries;
+    settings.tx_version = cli.tx_version;
+    settings.max_fee_lamports = cli.max_fee_lamports;
+    settings.priority = PriorityFees { setting: priority, program_defaults: HashMap::new() };
+    settings.confirm_commitment = cli.confirm_commitment.config();
+    settings.return_accounts = return_accounts;
+    settings.fail_on_warning = cli.fail_on_warning;
+    settings.idempotency = cli.idempotency_key.clone()
+        .map(|key| idempotency::IdempotencyKey::new(key, cli.idempotency_window_secs, "./cache"));
+    settings.notes = notes.clone();
+    Ok(settings)
+}
+
+async fn execute(cli: Cli, notes: &Notes) -> Result<CommandOutcome> {
+    let mut settings = send_settings(&cli, notes)?;
+
+    if cli.offline && cli.command.requires_network() {
+        return Err(anyhow::anyhow!("This command requires network access; rerun without --offline"));
+    }
+
+    // Runs before the payer keypair is loaded, so it works without the Solana CLI set up
+    if let Commands::Keygen { outfile, force } = &cli.command {
+        return handle_keygen_command(notes, outfile, *force);
+    }
+    if let Commands::Util { action } = &cli.command {
+        return handle_util_command(notes, action, cli.output);
+    }
+        
+    let mut idl_loader = setup_idl_loader(notes)?.with_require_idl(cli.require_idl);
+    let program_defs = load_program_defs(notes, &mut idl_loader);
+    let encoder = BorshEncoder::new();
+
+    let payer = payer::load_payer(notes)?;
+
+    
+    let rpc_url = cli.url.clone()
+        .or_else(|| std::env::var("HELIUS_RPC_URL").ok())
+        .or_else(|| std::env::var("SOLANA_RPC_URL").ok())
+        .unwrap_or_else(|| "https://api.devnet.solana.com".to_string());
+    if cli.rpc_timeout_secs == 0 {
+        return Err(anyhow::anyhow!("--rpc-timeout-secs must be at least 1"));
+    }
+    let rpc_settings = RpcSettings::new(rpc_url, &cli.rpc_headers)?
+        .with_read_commitment(cli.read_commitment)
+        .with_timeout(std::time::Duration::from_secs(cli.rpc_timeout_secs));
+    
+    settings.explorer = ExplorerLinks::new(cli.explorer, &rpc_settings.url, cli.print_transaction_url);
+    // Startup banners would precede the document on stdout under --output json
+    let banners = !cli.output.is_json();
+    if banners {
+        note!(notes, "🌐 Using RPC endpoint: {}", rpc_settings.url);
+        if !rpc_settings.headers.is_empty() {
+            note!(notes, "🔐 Sending {} custom RPC header(s)", rpc_settings.headers.len());
+        }
+    }
+
+
+    let rpc_client = rpc_settings.client();
+
+    if !cli.alts.is_empty() {
+        if cli.tx_version != TxVersion::V0 {
+            return Err(anyhow::anyhow!("--alt requires --tx-version v0"));
+        }
+        let alts = cli.alts.iter()
+            .map(|a| Pubkey::from_str(a).map_err(|_| anyhow::anyhow!("Invalid --alt pubkey: {}", a)))
+            .collect::<Result<Vec<_>>>()?;
+        let tables = tx_sender::fetch_lookup_tables(&rpc_client, &alts)?;
+        for table in tables.iter().filter(|_| banners) {
+            note!(notes, "📚 Lookup table {}: {} addresses", table.key, table.addresses.len());
+        }
+        settings.lookup_tables = tables;
+    }
+
+    let fee_payer_keypair = match &cli.fee_payer {
+        Some(path) => {
+            let keypair = read_keypair_file(&*shellexpand::tilde(path))
+                .map_err(|e| anyhow::anyhow!("Failed to load fee payer keypair from {}: {}", path, e))?;
+            if cli.offline {
+                if banners {
+                    note!(notes, "💳 Fee payer: {} (balance not checked offline)", keypair.pubkey());
+                }
+            } else {
+                let balance = rpc_client.get_balance(&keypair.pubkey())?;
+                if balance < MIN_FEE_PAYER_LAMPORTS {
+                    return Err(anyhow::anyhow!(
+                        "Fee payer {} has {} lamports, need at least {} for fees",
+                        keypair.pubkey(), balance, MIN_FEE_PAYER_LAMPORTS
+                    ));
+                }
+                if banners {
+                    note!(notes, "💳 Fee payer: {} ({} SOL)", keypair.pubkey(), lamports_to_sol(balance));
+                }
+            }
+            Some(keypair)
+        }
+        None => None,
+    };
+    let fee_payer = fee_payer_keypair.as_ref().unwrap_or(&payer);
+
+    if banners {
+        note!(notes, "🔧 Initializing program registry...");
+    }
+    let mut program_registry = ProgramRegistry::load_or_create("./cache", notes.clone()).await?;
+    if cli.no_cache_write || cli.offline || cli.dry_run || cli.build_only || cli.sign_only {
+        program_registry = program_registry.without_cache_writes();
+    }
+    for program in program_defs {
+        if let Err(e) = program_registry.add_external(program) {
+            note!(notes, "⚠️  Program definition not added to the registry: {}", e);
+        }
+    }
+    if cli.offline {
+        if banners {
+            note!(notes, "📴 Offline mode: skipping registry validation and refresh");
+        }
+    } else {
+        // Once a refresh fails, don't hammer it again this run
+        let mut refresh_ok = true;
+        if let Err(e) = program_registry.validate() {
+            note!(notes, "⚠️  Registry validation failed: {}", e);
+            note!(notes, "🔄 Refreshing registry...");
+            refresh_ok = try_refresh_registry(notes, &mut program_registry).await;
+        }
+        if refresh_ok && program_registry.needs_refresh() {
+            note!(notes, "🔄 Registry needs refresh, updating...");
+            try_refresh_registry(notes, &mut program_registry).await;
+        }
+    }
+    settings.priority.program_defaults = program_registry.default_priority_fees();
+    let settings = Arc::new(settings);
+        
+    let rent_cache = Arc::new(RentCache::new());
+    let account_cache = Arc::new(AccountCache::new().with_sends(settings.sends.clone()));
+
+    let account_resolver = AccountResolver::new(
+        rpc_settings.client(), rpc_settings.read_commitment
+    ).with_rent_cache(rent_cache.clone()).with_account_cache(account_cache.clone())
+        .with_top_up_new_recipients(cli.top_up_new_recipient);
+
+        
+    let simulation_mode = if cli.strict_simulate { SimulationMode::Strict } else { SimulationMode::Lenient };
+    let simulator = TransactionSimulator::new(
+        rpc_settings.client()
+    ).with_mode(simulation_mode).with_idl_loader(idl_loader.clone())
+        .with_send_settings(settings.clone())
+        .with_retries(cli.simulate_retries);
+
+        
+    let jupiter_client = JupiterClient::new()
+        .with_priority_fee(cli.swap_priority_fee)
+        .with_freshness(QuoteFreshnessConfig::new(cli.max_quote_slot_drift, cli.max_quote_age_secs))
+        .with_send_settings(settings.clone());
+
+        
+    let ata_manager = AtaManager::new(rpc_settings.client(), rpc_settings.read_commitment)
+        .with_rent_cache(rent_cache.clone())
+        .with_account_cache(account_cache.clone())
+        .with_send_settings(settings.clone());
+
+    let stats = program_registry.get_stats();
+    if banners {
+        note!(notes, "📊 Registry stats: {} programs ({} enabled, {} disabled)",
+             stats.total_programs, stats.enabled_programs, stats.disabled_programs);
+    }
+
+    let program_commands = program_handlers::builtin();
+    if let Some(program_id) = cli.command.target_program(&program_commands)? {
+        program_registry.ensure_enabled(&program_id, cli.allow_disabled)?;
+    }
+    let program_context = CommandContext {
+        rpc_client: &rpc_client,
+        payer: &payer,
+        fee_payer,
+        idl_loader: &idl_loader,
+        simulator: &simulator,
+        settings: &settings,
+    };
+
+    let outcome = match cli.command {
+        Commands::HelloWorld { action } => {
+            program_commands.run(&program_context, "hello-world", &ProgramAction::from_subcommand(&action)?)?
+        }
+        Commands::Calculator { action } => {
+            program_commands.run(&program_context, "calculator", &ProgramAction::from_subcommand(&action)?)?
+        }
+        Commands::Send { action: SendActions::Batch { file, concurrency, simulate_before_batch, yes } } => {
+            let preview = simulate_before_batch.then_some(&simulator);
+            handle_send_batch(&rpc_settings, &rpc_client, &settings, &payer, fee_payer, &idl_loader, &encoder, &account_resolver, &program_registry, preview, yes, &file, concurrency, cli.abort_on_first_failure && !cli.continue_on_failure).await?
+        }
+        Commands::Send { action } => {
+            handle_send_command(&rpc_client, &settings, &payer, fee_payer, action, &idl_loader, &encoder, &account_resolver, &simulator, &jupiter_client, &ata_manager, &program_registry, cli.output).await?
+        }
+        Commands::Swap { action } => {
+            program_commands.run(&program_context, "swap", &ProgramAction::from_subcommand(&action)?)?
+        }
+        Commands::Registry { action } => {
+            handle_registry_command(notes, &mut program_registry, action, cli.output).await?
+        }
+        Commands::Account { action: AccountActions::Watch { address, field, ws_url } } => {
+            let ws_url = match ws_url {
+                Some(url) => url,
+                None => rpc_settings.ws_url()?,
+            };
+            handle_account_watch(notes, &rpc_client, &idl_loader, &address, field.as_deref(), &ws_url, rpc_settings.read_commitment).await?
+        }
+        Commands::Account { action } => {
+            handle_account_command(&rpc_client, &settings, &payer, fee_payer, &idl_loader, &account_resolver, &simulator, action).await?
+        }
+        Commands::Idl { action } => {
+            handle_idl_command(notes, &idl_loader, action, cli.output).await?
+        }
+        Commands::Balances { limit, min_balance } => {
+            handle_balances_command(notes, &payer, &account_resolver, &ata_manager, &jupiter_client, limit, min_balance, cli.output).await?
+        }
+        Commands::Jupiter { action } => {
+            handle_jupiter_command(notes, &jupiter_client, &ata_manager, action, cli.output).await?
+        }
+        Commands::Token { action } => {
+            handle_token_command(&rpc_client, &settings, &payer, fee_payer, &idl_loader, &ata_manager, action).await?
+        }
+        Commands::Keygen { .. } | Commands::Util { .. } => unreachable!("keygen and util return before the payer is loaded"),
+        Commands::Logs { program_id, commitment, ws_url } => {
+            let commitment = commitment.map(Commitment::config).unwrap_or(rpc_settings.read_commitment);
+            let ws_url = match ws_url {
+                Some(url) => url,
+                None => rpc_settings.ws_url()?,
+            };
+            handle_logs_command(notes, &idl_loader, &program_id, &ws_url, commitment).await?
+        }
+        Commands::Invoke { program_id, instruction, args, args_file, accounts, signers } => {
+            handle_invoke_command(&rpc_client, &settings, &payer, fee_payer, &idl_loader, &encoder, &program_id, &instruction, args, args_file, &accounts, &signers).await?
+        }
+        Commands::Bundle { file, signers } => {
+            handle_bundle_command(&rpc_client, &settings, &payer, fee_payer, &idl_loader, &encoder, &program_registry, cli.allow_disabled, &file, &signers).await?
+        }
+        Commands::Combine { parts } => {
+            handle_combine_command(&rpc_client, &settings, &parts)?
+        }
+    };
+    Ok(outcome)
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn handle_send_command(
+    rpc_client: &RpcClient,
+    settings: &SendSettings,
+    payer: &Keypair,
+    fee_payer: &Keypair,
+    action: SendActions,
+    idl_loader: &IdlLoader,
+    encoder: &BorshEncoder,
+    account_resolver: &AccountResolver,
+    simulator: &TransactionSimulator,
+    jupiter_client: &JupiterClient,
+    ata_manager: &AtaManager,
+    program_registry: &ProgramRegistry,
+    output: OutputFormat,
+) -> Result<CommandOutcome> {
+    let notes = &settings.notes;
+    let program_id = Pubkey::from_str(SEND_PROGRAM_ID)?;
+    let mut outcome = CommandOutcome::new();
+    
+    match action {
+        SendActions::Initialize { account_keypair } => {
+            let account_keypair = read_keypair_file(&account_keypair)
+                .map_err(|e| anyhow::anyhow!("Failed to read account keypair {} (create one with `keygen {}`): {}", account_keypair, account_keypair, e))?;
+            
+            note!(notes, "🚀 Initializing Send account...");
+            note!(notes, "📋 Program ID: {}", program_id);
+            note!(notes, "🔑 Account: {}", account_keypair.pubkey());
+            
+            
+            let args = HashMap::new(); // initialize has no arguments
+            let instruction_data = encoder.encode_instruction(
+                idl_loader, 
+                SEND_PROGRAM_ID, 
+                "initialize", 
+                args
+            )?;
+            
+            let instruction = Instruction {
+                program_id,
+                accounts: vec![
+                    AccountMeta::new(account_keypair.pubkey(), true), // send_account (writable, signer)
+                    AccountMeta::new(payer.pubkey(), true),           // user (writable, signer)
+                    AccountMeta::new_readonly(system_program::id(), false), // system_program
+                ],
+                data: instruction_data,
+            };
+
+            let signature = tx_sender::build_and_send(rpc_client, settings, vec![instruction], &signer_set(fee_payer, &[payer, &account_keypair]), SendOptions::with_idl(idl_loader))?;
+            outcome = outcome.with_address("send_account", account_keypair.pubkey()).with_signature(signature);
+            if signature.is_none() {
+                return Ok(outcome);
+            }
+            note!(notes, "🎉 Send account initialized successfully!");
+        }
+
+        SendActions::SendSol { account_pubkey, amount, recipient } => {
+            let account_pubkey = Pubkey::from_str(&account_pubkey)?;
+            let recipient_pubkey = Pubkey::from_str(&recipient)?;
+            
+            
+            let lamports = account_resolver.fund_new_recipient(&recipient_pubkey, sol_to_lamports(&amount)?)?;
+            generated::send_program::check_send_amount(lamports)?;
+            
+            note!(notes, "💰 Sending {} SOL ({} lamports) to {}...", lamports_to_sol(lamports), lamports, recipient_pubkey);
+            note!(notes, "📋 Program ID: {}", program_id);
+            note!(notes, "🔑 Send Account: {}", account_pubkey);
+            
+            
+            let mut args = HashMap::new();
+            args.insert("amount".to_string(), serde_json::to_value(lamports)?);
+            args.insert("recipient".to_string(), serde_json::to_value(recipient_pubkey.to_string())?);
+            
+            let instruction_data = encoder.encode_instruction(
+                idl_loader,
+                SEND_PROGRAM_ID,
+                "send_sol",
+                args
+            )?;
+            
+            let instruction = Instruction {
+                program_id,
+                accounts: vec![
+                    AccountMeta::new(account_pubkey, false),          // send_account (writable)
+                    AccountMeta::new(payer.pubkey(), true),           // sender (writable, signer)
+                    AccountMeta::new(recipient_pubkey, false),        // recipient (writable)
+                    AccountMeta::new_readonly(system_program::id(), false), // system_program
+                ],
+                data: instruction_data,
+            };
+
+            outcome = outcome.with_address("send_account", account_pubkey).with_address("recipient", recipient_pubkey);
+            let Some(signature) = tx_sender::build_and_send(rpc_client, settings, vec![instruction], &signer_set(fee_payer, &[payer]), SendOptions::with_idl(idl_loader))? else {
+                return Ok(outcome);
+            };
+            outcome = outcome.with_signature(Some(signature));
+            note!(notes, "💸 SOL sent successfully! Check logs for details.");
+            note!(notes, "🔍 Use: {}", settings.explorer.confirm_command(&signature));
+        }
+
+        SendActions::GetStats { account_pubkey } => {
+            let account_pubkey = Pubkey::from_str(&account_pubkey)?;
+            
+            note!(notes, "📊 Getting send statistics...");
+            note!(notes, "📋 Program ID: {}", program_id);
+            note!(notes, "🔑 Account: {}", account_pubkey);
+            fetch_owned_account(rpc_client, &account_pubkey, &program_id)?;
+            
+            // Use generic encoder for get_stats (no args)
+            let args = HashMap::new();
+            let instruction_data = encoder.encode_instruction(
+                idl_loader,
+                SEND_PROGRAM_ID,
+                "get_stats",
+                args
+            )?;
+            
+            let instruction = Instruction {
+                program_id,
+                accounts: vec![
+                    AccountMeta::new_readonly(account_pubkey, false), // send_account
+                ],
+                data: instruction_data,
+            };
+
+            let signature = tx_sender::build_and_send(rpc_client, settings, vec![instruction], &signer_set(fee_payer, &[payer]), SendOptions::with_idl(idl_loader).with_logs())?;
+            outcome = outcome.with_address("send_account", account_pubkey).with_signature(signature);
+        }
+
+        SendActions::SmartInit => {
+            note!(notes, "🧠 Smart Initialize - Deriving PDA for user...");
+            
+            // Resolve send account for this user
+            let resolution = account_resolver.smart_resolve(&payer.pubkey(), "send")?;
+            
+            match &resolution {
+                AccountResolution::Found { address, .. } => {
+                    note!(notes, "✅ Send account already exists at: {}", address);
+                    return Ok(outcome.with_address("send_account", *address));
+                }
+                AccountResolution::SuggestCreate { address, required_rent, .. } => {
+                    note!(notes, "📋 Program ID: {}", program_id);
+                    note!(notes, "🔑 Derived PDA: {}", address);
+                    note!(notes, "💰 Required rent: {} lamports ({} SOL)", required_rent, lamports_to_sol(*required_rent));
+                    
+            // Route to generated or dynamic per registry (demo: send program is generated)
+            let route = program_registry.resolve(&program_id);
+            let instruction = match route {
+                ProgramRoute::GeneratedClient(client_name) if client_name.starts_with("send_program") => {
+                    generated::send_program::initialize_instruction(
+                        *address, payer.pubkey(), system_program::id(),
+                    )?
+                }
+                _ => {
+                    // Fallback dynamic path (should not hit for send_program)
+                    let args = HashMap::new();
+                    let data = encoder.encode_instruction(idl_loader, SEND_PROGRAM_ID, "initialize", args)?;
+                    Instruction { program_id, accounts: vec![
+                        AccountMeta::new(*address, false),
+                        AccountMeta::new(payer.pubkey(), true),
+                        AccountMeta::new_readonly(system_program::id(), false),
+                    ], data }
+                }
+            };
+                    // Validate against IDL
+                    idl_loader.validate_account_metas(SEND_PROGRAM_ID, "initialize", &instruction.accounts)?;
+
+                    let signature = tx_sender::build_and_send(rpc_client, settings, vec![instruction], &signer_set(fee_payer, &[payer]), SendOptions::with_idl(idl_loader))?;
+                    outcome = outcome.with_address("send_account", *address).with_signature(signature);
+                    if signature.is_none() {
+                        return Ok(outcome);
+                    }
+                    note!(notes, "🎉 Smart Send account initialized at PDA!");
+                }
+            }
+        }
+
+        SendActions::SmartSend { amount, recipient } => {
+            note!(notes, "🧠 Smart Send - Using derived PDA...");
+            
+            let recipient_pubkey = Pubkey::from_str(&recipient)?;
+            let lamports = account_resolver.fund_new_recipient(&recipient_pubkey, sol_to_lamports(&amount)?)?;
+            generated::send_program::check_send_amount(lamports)?;
+            
+            // Resolve send account for this user
+            let resolution = account_resolver.smart_resolve(&payer.pubkey(), "send")?;
+            let send_account = resolution.address();
+            
+            if !resolution.exists() {
+                note!(notes, "❌ Send account doesn't exist. Run 'smart-init' first!");
+                return Ok(outcome);
+            }
+
+            check_send_account_owner(rpc_client, send_account, payer)?;
+            
+            note!(notes, "💰 Sending {} SOL ({} lamports) to {}...", lamports_to_sol(lamports), lamports, recipient_pubkey);
+            note!(notes, "📋 Program ID: {}", program_id);
+            note!(notes, "🔑 Send Account (PDA): {}", send_account);
+            
+            let instruction = send_sol_instruction(program_registry, encoder, idl_loader, send_account, &payer.pubkey(), &recipient_pubkey, lamports)?;
+
+            outcome = outcome.with_address("send_account", *send_account).with_address("recipient", recipient_pubkey);
+            let Some(signature) = tx_sender::build_and_send(rpc_client, settings, vec![instruction], &signer_set(fee_payer, &[payer]), SendOptions::with_idl(idl_loader))? else {
+                return Ok(outcome);
+            };
+            outcome = outcome.with_signature(Some(signature));
+            note!(notes, "💸 Smart SOL sent successfully!");
+            note!(notes, "🔍 Use: {}", settings.explorer.confirm_command(&signature));
+        }
+
+        SendActions::Batch { .. } => unreachable!("batch is dispatched from main"),
+
+        SendActions::History { address, since, until, limit } => {
+            let window = history::HistoryWindow::parse(since.as_deref(), until.as_deref())?;
+            let address = match address {
+                Some(address) => Pubkey::from_str(&address)
+                    .map_err(|_| anyhow::anyhow!("Invalid --address: {}", address))?,
+                None => *account_resolver.smart_resolve(&payer.pubkey(), "send")?.address(),
+            };
+            let limit = limit.or(if window.since.is_none() { Some(20) } else { None });
+
+            let entries = history::fetch_history(rpc_client, &address, window, limit)?;
+            if output.is_json() {
+                return outcome.with_state(&entries);
+            }
+
+            note!(notes, "📜 {} transaction(s) for {}", entries.len(), address);
+            if entries.iter().any(|entry| entry.block_time.is_none()) {
+                note!(notes, "ℹ️  Entries without a block time are still processing and are listed regardless of --since/--until");
+            }
+            let rows: Vec<Vec<String>> = entries.iter().map(|entry| vec![
+                entry.block_time.clone().unwrap_or_else(|| "⏳ processing".to_string()),
+                entry.signature.clone(),
+                entry.error.as_ref().map_or("✅".to_string(), |e| format!("❌ {}", e)),
+                entry.memo.clone().unwrap_or_default(),
+            ]).collect();
+            outcome = outcome.with_table(&["Time", "Signature", "Status", "Memo"], rows);
+        }
+
+        SendActions::SmartStats => {
+            note!(notes, "🧠 Smart Stats - Using derived PDA...");
+            
+            // Resolve send account for this user
+            let resolution = account_resolver.smart_resolve(&payer.pubkey(), "send")?;
+            let send_account = resolution.address();
+            
+            if !resolution.exists() {
+                note!(notes, "❌ Send account doesn't exist. Run 'smart-init' first!");
+                return Ok(outcome);
+            }
+            
+            note!(notes, "📊 Getting send statistics...");
+            note!(notes, "📋 Program ID: {}", program_id);
+            note!(notes, "🔑 Account (PDA): {}", send_account);
+            
+            let route = program_registry.resolve(&program_id);
+            let instruction = match route {
+                ProgramRoute::GeneratedClient(client_name) if client_name.starts_with("send_program") => {
+                    generated::send_program::get_stats_instruction(*send_account)?
+                }
+                _ => {
+                    let data = encoder.encode_instruction(idl_loader, SEND_PROGRAM_ID, "get_stats", HashMap::new())?;
+                    Instruction { program_id, accounts: vec![
+                        AccountMeta::new_readonly(*send_account, false),
+                    ], data }
+                }
+            };
+            idl_loader.validate_account_metas(SEND_PROGRAM_ID, "get_stats", &instruction.accounts)?;
+
+            outcome = outcome.with_address("send_account", *send_account);
+            let Some(signature) = tx_sender::build_and_send(rpc_client, settings, vec![instruction], &signer_set(fee_payer, &[payer]), SendOptions::with_idl(idl_loader))? else {
+                return Ok(outcome);
+            };
+            outcome = outcome.with_signature(Some(signature));
+            note!(notes, "📊 Check the transaction logs for smart send statistics!");
+            note!(notes, "🔍 Use: {}", settings.explorer.confirm_command(&signature));
+        }
+
+        SendActions::GlobalStats => {
+            let (global_stats, _bump) = account_resolver.derive_pda(&[GLOBAL_STATS_SEED], &program_id)?;
+            outcome = outcome.with_address("global_stats", global_stats);
+            note!(notes, "🌍 Global Stats - Using derived PDA...");
+            note!(notes, "📋 Program ID: {}", program_id);
+            note!(notes, "🔑 Account (PDA): {}", global_stats);
+
+            let Some(account) = rpc_client.get_account_with_commitment(&global_stats, rpc_client.commitment())?.value else {
+                note!(notes, "ℹ️  No global stats account: this send program doesn't keep program-wide totals");
+                return Ok(outcome);
+            };
+            if account.owner != program_id {
+                note!(notes, "ℹ️  {} is owned by {}, not the send program; no program-wide totals to show", global_stats, account.owner);
+                return Ok(outcome);
+            }
+
+            let Some((name, fields)) = idl_loader.decode_account(SEND_PROGRAM_ID, &account.data)? else {
+                note!(notes, "⚠️  The send IDL doesn't describe this account's layout ({} bytes):", account.data.len());
+                notes.text(account_inspector::hex_dump(&account.data, 0));
+                return Ok(outcome);
+            };
+            if output.is_json() {
+                let state: serde_json::Map<_, _> = fields.into_iter().collect();
+                return outcome.with_state(&state);
+            }
+            note!(notes, "📊 {}:", name);
+            for (field, value) in &fields {
+                note!(notes, "  {}: {}", field, value);
+            }
+        }
+
+        SendActions::CodegenStats => {
+            note!(notes, "🦀 Codegen Stats - Using Codama-generated client (DEMO)...");
+            note!(notes, "💡 This demonstrates type-safe, generated Rust client vs manual building");
+            
+            // Resolve send account for this user
+            let resolution = account_resolver.smart_resolve(&payer.pubkey(), "send")?;
+            let send_account = resolution.address();
+            
+            if !resolution.exists() {
+                note!(notes, "❌ Send account doesn't exist. Run 'smart-init' first!");
+                return Ok(outcome);
+            }
+            
+            note!(notes, "📊 Getting send statistics using Codama client...");
+            note!(notes, "📋 Program ID: {}", generated::send_program::PROGRAM_ID);
+            note!(notes, "🔑 Account (PDA): {}", send_account);
+            
+            // 🎯 USE CODAMA-GENERATED CLIENT (Type-safe!)
+            let instruction = generated::send_program::get_stats_instruction(*send_account)?;
+            
+            note!(notes, "✅ Instruction built with Codama-generated client:");
+            note!(notes, "  📦 Program ID: {}", instruction.program_id);
+            note!(notes, "  📝 Data length: {} bytes", instruction.data.len());
+            note!(notes, "  👥 Accounts: {}", instruction.accounts.len());
+            note!(notes, "  🔗 Discriminator: {:?}", &instruction.data[0..8]);
+
+            outcome = outcome.with_address("send_account", *send_account);
+            let Some(signature) = tx_sender::build_and_send(rpc_client, settings, vec![instruction], &signer_set(fee_payer, &[payer]), SendOptions::with_idl(idl_loader))? else {
+                return Ok(outcome);
+            };
+            outcome = outcome.with_signature(Some(signature));
+            note!(notes, "🎉 Codama stats completed successfully!");
+            note!(notes, "📊 Check the transaction logs for send statistics!");
+            note!(notes, "🔍 Use: {}", settings.explorer.confirm_command(&signature));
+            
+            note!(notes, "\n💭 COMPARISON:");
+            note!(notes, "  ❌ Manual: Encode discriminator, serialize args, build AccountMeta[]");
+            note!(notes, "  ✅ Codama: get_stats_instruction(send_account) - Type-safe!");
+        }
+
+        SendActions::Resolve => {
+            note!(notes, "🧠 Resolving accounts for user: {}", payer.pubkey());
+            
+            // Resolve send account
+            let send_resolution = account_resolver.smart_resolve(&payer.pubkey(), "send")?;
+            
+            match &send_resolution {
+                AccountResolution::Found { address, account_type } => {
+                    note!(notes, "✅ Send account ({}) found at: {}", account_type, address);
+                    let balance = account_resolver.get_balance(address)?;
+                    note!(notes, "💰 Account balance: {} lamports ({} SOL)", balance, lamports_to_sol(balance));
+                    outcome = outcome.with_address("send_account", *address);
+                }
+                AccountResolution::SuggestCreate { address, account_type, required_rent, creation_method } => {
+                    note!(notes, "💡 Send account ({}) not found", account_type);
+                    note!(notes, "🔑 Suggested address: {}", address);
+                    note!(notes, "💰 Required rent: {} lamports ({} SOL)", required_rent, lamports_to_sol(*required_rent));
+                    note!(notes, "🛠️  Creation method: {}", creation_method);
+                    note!(notes, "👉 Run 'smart-init' to create it");
+                    outcome = outcome.with_address("suggested_send_account", *address);
+                }
+            }
+            
+            // Check user's main balance
+            let user_balance = account_resolver.get_balance(&payer.pubkey())?;
+            note!(notes, "🏦 User balance: {} lamports ({} SOL)", user_balance, lamports_to_sol(user_balance));
+            outcome = outcome.with_address("user", payer.pubkey()).with_state(&serde_json::json!({ "user_balance": user_balance }))?;
+        }
+
+        SendActions::Simulate { amount, recipient } => {
+            note!(notes, "🧪 Simulating SOL send transaction...");
+            
+            let recipient_pubkey = Pubkey::from_str(&recipient)?;
+            let lamports = account_resolver.fund_new_recipient(&recipient_pubkey, sol_to_lamports(&amount)?)?;
+            generated::send_program::check_send_amount(lamports)?;
+            
+            // Resolve send account for this user
+            let resolution = account_resolver.smart_resolve(&payer.pubkey(), "send")?;
+            let send_account = resolution.address();
+            
+            if !resolution.exists() {
+                note!(notes, "❌ Send account doesn't exist. Run 'smart-init' first!");
+                return Ok(outcome);
+            }
+            
+            note!(notes, "💰 Simulating send of {} SOL ({} lamports) to {}...", lamports_to_sol(lamports), lamports, recipient_pubkey);
+            note!(notes, "🔑 Send Account (PDA): {}", send_account);
+            
+            // Build the transaction (same as smart-send)
+            let mut args = HashMap::new();
+            args.insert("amount".to_string(), serde_json::to_value(lamports)?);
+            args.insert("recipient".to_string(), serde_json::to_value(recipient_pubkey.to_string())?);
+            
+            let instruction_data = encoder.encode_instruction(
+                idl_loader,
+                SEND_PROGRAM_ID,
+                "send_sol",
+                args
+            )?;
+            
+            let instruction = Instruction {
+                program_id,
+                accounts: vec![
+                    AccountMeta::new(*send_account, false),          // send_account (writable)
+                    AccountMeta::new(payer.pubkey(), true),           // sender (writable, signer)
+                    AccountMeta::new(recipient_pubkey, false),        // recipient (writable)
+                    AccountMeta::new_readonly(system_program::id(), false), // system_program
+                ],
+                data: instruction_data,
+            };
+
+            let recent_blockhash = tx_sender::latest_blockhash(rpc_client, settings)?.blockhash;
+            let instructions = settings.priority.with_compute_budget(rpc_client, notes, vec![instruction])?;
+            if settings.dump_instructions {
+                instruction_dump::dump_instructions(notes, Some(idl_loader), &instructions);
+            }
+            let transaction = tx_sender::sign_transaction(settings, &instructions, &signer_set(fee_payer, &[payer]), recent_blockhash)?;
+
+            // Simulate the transaction
+            let preview = simulator.preview_transaction(&transaction)?;
+            outcome = outcome.with_address("send_account", *send_account).with_simulation(&preview);
+            
+            note!(notes, "\n🔍 SIMULATION RESULTS:");
+            note!(notes, "✅ Success: {}", if preview.will_succeed { "YES" } else { "NO" });
+            note!(notes, "💰 Estimated fee: {} lamports ({} SOL)", preview.estimated_fee, lamports_to_sol(preview.estimated_fee));
+            note!(notes, "⚡ Compute units: {}", preview.compute_units);
+            
+            if let Some(error) = &preview.error_summary {
+                note!(notes, "❌ Error: {}", error);
+            }
+            
+            if !preview.account_changes.is_empty() {
+                note!(notes, "📋 Account changes:");
+                for change in &preview.account_changes {
+                    note!(notes, "  📝 {}", change);
+                }
+            }
+            
+            if !preview.program_logs.is_empty() {
+                note!(notes, "📋 Expected program logs:");
+                for log in &preview.program_logs {
+                    note!(notes, "  📝 {}", log);
+                }
+            }
+            
+            note!(notes, "\n💡 This was a simulation only - no SOL was actually sent!");
+        }
+
+        SendActions::SafeSend { amount, recipient } => {
+            note!(notes, "🛡️  Safe Send - Simulating first, then sending...");
+            
+            let recipient_pubkey = Pubkey::from_str(&recipient)?;
+            let lamports = account_resolver.fund_new_recipient(&recipient_pubkey, sol_to_lamports(&amount)?)?;
+            generated::send_program::check_send_amount(lamports)?;
+            
+            // Resolve send account for this user
+            let resolution = account_resolver.smart_resolve(&payer.pubkey(), "send")?;
+            let send_account = resolution.address();
+            
+            if !resolution.exists() {
+                note!(notes, "❌ Send account doesn't exist. Run 'smart-init' first!");
+                return Ok(outcome);
+            }
+            
+            note!(notes, "💰 Preparing to send {} SOL ({} lamports) to {}...", lamports_to_sol(lamports), lamports, recipient_pubkey);
+            note!(notes, "🔑 Send Account (PDA): {}", send_account);
+            
+            // Build the transaction
+            let mut args = HashMap::new();
+            args.insert("amount".to_string(), serde_json::to_value(lamports)?);
+            args.insert("recipient".to_string(), serde_json::to_value(recipient_pubkey.to_string())?);
+            
+            let instruction_data = encoder.encode_instruction(
+                idl_loader,
+                SEND_PROGRAM_ID,
+                "send_sol",
+                args
+            )?;
+            
+            let instruction = Instruction {
+                program_id,
+                accounts: vec![
+                    AccountMeta::new(*send_account, false),          // send_account (writable)
+                    AccountMeta::new(payer.pubkey(), true),           // sender (writable, signer)
+                    AccountMeta::new(recipient_pubkey, false),        // recipient (writable)
+                    AccountMeta::new_readonly(system_program::id(), false), // system_program
+                ],
+                data: instruction_data,
+            };
+
+            let signers = signer_set(fee_payer, &[payer]);
+            let Some((_, transaction)) = tx_sender::build_signed(rpc_client, settings, vec![instruction], &signers, Some(idl_loader))? else {
+                return Ok(outcome.with_address("send_account", *send_account));
+            };
+
+            // Use safe send with automatic simulation
+            let result = simulator.safe_send_transaction(&transaction)?;
+            outcome = outcome.with_address("send_account", *send_account)
+                .with_signature(result.signature)
+                .with_simulation(&result.simulation);
+            
+            if result.sent {
+                note!(notes, "🎉 Safe send completed successfully!");
+                if let Some(signature) = result.signature {
+                    note!(notes, "🔍 Use: {}", settings.explorer.confirm_command(&signature));
+                }
+            } else if !result.validation_issues.is_empty() {
+                note!(notes, "❌ Safe send aborted due to validation issues:");
+                for issue in &result.validation_issues {
+                    note!(notes, "  🚨 {}", issue);
+                }
+            }
+        }
+
+        SendActions::JupiterQuote { input_mint, output_mint, amount, slippage_bps } => {
+            note!(notes, "🔍 Getting Jupiter quote for {} → {} swap...", input_mint, output_mint);
+            
+            // Convert token shortcuts
+            let input_mint = match input_mint.to_uppercase().as_str() {
+                "SOL" => jupiter_client::tokens::SOL.to_string(),
+                "USDC" => jupiter_client::tokens::USDC.to_string(),
+                "USDT" => jupiter_client::tokens::USDT.to_string(),
+                _ => input_mint,
+            };
+            
+            let output_mint = match output_mint.to_uppercase().as_str() {
+                "SOL" => jupiter_client::tokens::SOL.to_string(),
+                "USDC" => jupiter_client::tokens::USDC.to_string(),
+                "USDT" => jupiter_client::tokens::USDT.to_string(),
+                _ => output_mint,
+            };
+            
+            let amount_num: u64 = amount.parse()?;
+            
+            let quote_request = QuoteRequest {
+                input_mint: input_mint.clone(),
+                output_mint: output_mint.clone(),
+                amount: amount_num,
+                slippage_bps: Some(slippage_bps),
+                restrict_intermediate_tokens: Some(true),
+                only_direct_routes: Some(false),
+            };
+            
+            match jupiter_client.get_quote(quote_request).await {
+                Ok(quote) => {
+                    let input_decimals = Pubkey::from_str(&input_mint).ok().and_then(|m| ata_manager.mint_decimals(&m));
+                    let output_decimals = Pubkey::from_str(&output_mint).ok().and_then(|m| ata_manager.mint_decimals(&m));
+                    let summary = RouteSummary::from_quote(&quote).with_decimals(input_decimals, output_decimals);
+                    if output.is_json() {
+                        return outcome.with_state(&summary);
+                    }
+                    if output.is_table() {
+                        let (headers, rows) = summary.table_rows();
+                        return Ok(outcome.with_table(&headers, rows));
+                    }
+
+                    note!(notes, "✅ Quote received:");
+                    note!(notes, "📥 Input: {} ({})", display_amount(&quote.in_amount, input_decimals), input_mint);
+                    note!(notes, "📤 Output: {} ({})", display_amount(&quote.out_amount, output_decimals), output_mint);
+                    if input_decimals.is_none() || output_decimals.is_none() {
+                        note!(notes, "📝 Decimals unavailable for one or more mints; showing raw base units only");
+                    }
+                    note!(notes, "💸 Price impact: {}%", quote.price_impact_pct);
+                    note!(notes, "🎯 Slippage tolerance: {}% ({} bps)", slippage_bps as f64 / 100.0, slippage_bps);
+                    note!(notes, "⏱️  Quote time: {:.2}ms", quote.time_taken * 1000.0);
+                    note!(notes, "\n🛣️  Route plan ({} hops):", quote.route_plan.len());
+                    
+                    for line in summary.render_table().lines() {
+                        note!(notes, "  {}", line);
+                    }
+                    
+                    note!(notes, "\n💡 This was a quote only - no swap executed!");
+                    note!(notes, "💡 To execute: use 'jupiter-swap' with the same parameters");
+                }
+                Err(e) => {
+                    note!(notes, "❌ Failed to get Jupiter quote: {}", e);
+                }
+            }
+        }
+
+        SendActions::JupiterSwap { input_mint, output_mint, amount, slippage_bps, profile, max_price_impact, simulate_only, max_slippage_bps, allow_slippage_escalation, dynamic_slippage, dynamic_slippage_min_bps, dynamic_slippage_max_bps, decompose, cleanup_on_failure, congestion_retries, max_swap_priority_fee } => {
+            let simulate_only = simulate_only || settings.dry_run;
+            if settings.propose_to.is_some() {
+                return Err(anyhow::anyhow!("--propose-to is not supported for Jupiter swaps: routes expire before a multisig can approve them"));
+            }
+            
+            let profile = match &profile {
+                Some(name) => {
+                    let profile = program_registry.swap_profile(name)
+                        .ok_or_else(|| anyhow::anyhow!("Unknown swap profile '{}' (add it with `registry add-swap-profile`)", name))?;
+                    note!(notes, "📒 Using swap profile '{}'", name);
+                    Some(profile.clone())
+                }
+                None => None,
+            };
+            let input_mint = input_mint.or_else(|| profile.as_ref().map(|p| p.input.clone()))
+                .ok_or_else(|| anyhow::anyhow!("--input-mint is required"))?;
+            let output_mint = output_mint.or_else(|| profile.as_ref().map(|p| p.output.clone()))
+                .ok_or_else(|| anyhow::anyhow!("--output-mint is required"))?;
+            let slippage_bps = slippage_bps.or(profile.as_ref().map(|p| p.slippage_bps)).unwrap_or(50);
+            let max_price_impact = max_price_impact.or(profile.as_ref().and_then(|p| p.max_price_impact));
+            note!(notes, "🚀 Executing production Jupiter swap: {} → {}...", input_mint, output_mint);
+
+            // Convert token shortcuts to mint addresses
+            let input_mint_pubkey = CommonMints::from_name(&input_mint)?;
+            let output_mint_pubkey = CommonMints::from_name(&output_mint)?;
+            
+            let input_mint_str = input_mint_pubkey.to_string();
+            let output_mint_str = output_mint_pubkey.to_string();
+            let amount_num: u64 = amount.parse()?;
+            let slippage = SlippagePolicy {
+                requested_bps: slippage_bps,
+                max_bps: max_slippage_bps.unwrap_or(slippage_bps),
+                allow_escalation: allow_slippage_escalation,
+                max_price_impact_pct: max_price_impact,
+                dynamic: dynamic_slippage.then_some(DynamicSlippage { min_bps: dynamic_slippage_min_bps, max_bps: dynamic_slippage_max_bps }),
+            };
+            // Fail before any ATA setup if the cap is below the request
+            slippage.schedule()?;
+
+            // Catch typo'd-but-valid pubkeys before quoting or creating ATAs
+            let input_decimals = ata_manager.validate_mint(&input_mint_pubkey)
+                .map_err(|e| anyhow::anyhow!("Input mint {}: {}", input_mint, e))?;
+            let output_decimals = ata_manager.validate_mint(&output_mint_pubkey)
+                .map_err(|e| anyhow::anyhow!("Output mint {}: {}", output_mint, e))?;
+            jupiter_client.resolve_priority_fee(rpc_client)?;
+
+            if decompose {
+                let quote = jupiter_client.get_fresh_quote(QuoteRequest {
+                    input_mint: input_mint_str.clone(),
+                    output_mint: output_mint_str.clone(),
+                    amount: amount_num,
+                    slippage_bps: Some(slippage_bps),
+                    restrict_intermediate_tokens: Some(true),
+                    only_direct_routes: Some(false),
+                }, 3).await?;
+                let decomposed = jupiter_client.get_swap_instructions_decomposed(SwapRequest {
+                    user_public_key: payer.pubkey().to_string(),
+                    quote_response: quote,
+                    wrap_and_unwrap_sol: Some(true),
+                    dynamic_compute_unit_limit: Some(true),
+                    prioritization_fee_lamports: Some(jupiter_client.prioritization_fee()),
+                    dynamic_slippage: slippage.dynamic.map(DynamicSlippage::request_value),
+                }).await?;
+
+                if output.is_json() {
+                    return outcome.with_state(&decomposed);
+                }
+
+                note!(notes, "\n🧩 Jupiter swap instructions (not sent): {} total", decomposed.instructions()?.len());
+                let sections = [
+                    ("Compute budget", decomposed.compute_budget_instructions.clone()),
+                    ("Setup", decomposed.setup_instructions.clone()),
+                    ("Swap", vec![decomposed.swap_instruction.clone()]),
+                    ("Cleanup", decomposed.cleanup_instruction.clone().into_iter().collect()),
+                ];
+                for (name, instructions) in &sections {
+                    note!(notes, "\n📦 {} ({}):", name, instructions.len());
+                    for ix in instructions {
+                        let parsed = ix.to_instruction()?;
+                        note!(notes, "  🔧 {} ({}) - {} accounts, {} data bytes",
+                             parsed.program_id, program_label(&parsed.program_id), parsed.accounts.len(), parsed.data.len());
+                        for meta in &parsed.accounts {
+                            note!(notes, "    {} {}{}", meta.pubkey,
+                                 if meta.is_signer { "[signer]" } else { "" },
+                                 if meta.is_writable { "[writable]" } else { "" });
+                        }
+                    }
+                }
+                note!(notes, "\n📚 Address lookup tables ({}):", decomposed.address_lookup_table_addresses.len());
+                for alt in &decomposed.address_lookup_table_addresses {
+                    note!(notes, "  {}", alt);
+                }
+                return Ok(outcome);
+            }
+            
+            note!(notes, "📋 Swap details:");
+            note!(notes, "  🪙 From: {} ({})", display_amount(&amount, Some(input_decimals)), input_mint_str);
+            note!(notes, "  🎯 To: {} ({})", output_mint, output_mint_str);
+            match slippage.dynamic {
+                Some(dynamic) => note!(notes, "  📈 Slippage: dynamic, picked by Jupiter (request {}; quote at {}%)",
+                                      dynamic.request_value(), slippage_bps as f64 / 100.0),
+                None => note!(notes, "  📈 Slippage: {}%", slippage_bps as f64 / 100.0),
+            }
+            if let Some(max) = max_price_impact {
+                note!(notes, "  🧱 Max price impact: {}%", max);
+            }
+            if allow_slippage_escalation {
+                note!(notes, "  ⚠️  Escalation allowed up to {}%", slippage.max_bps as f64 / 100.0);
+            }
+            note!(notes, "  👤 User: {}", payer.pubkey());
+            if fee_payer.pubkey() != payer.pubkey() {
+                note!(notes, "  ⚠️  Jupiter builds the swap with the user as fee payer; --fee-payer only applies to ATA setup");
+            }
+            
+            let steps = notes.progress_bar(3, "Checking ATAs");
+
+            // Step 1: Auto-create ATAs if needed (production security)
+            note!(notes, "\n🔧 Checking/creating Associated Token Accounts...");
+            
+            // For swaps, we need ATAs for both input and output tokens (unless SOL)
+            let mut pre_instructions = Vec::new();
+            let mut missing_atas = Vec::new();
+            // Only ATAs this run actually created are ever closed on failure
+            let mut created_atas = Vec::new();
+
+            // Token-2022 transfer fees are withheld from what's transferred, so less than `amount` reaches the swap
+            let input_fee = ata_manager.transfer_fee(&input_mint_pubkey)?;
+            if let Some(fee) = input_fee {
+                let withheld = fee.fee_for(amount_num);
+                if withheld >= amount_num {
+                    return Err(anyhow::anyhow!("Amount {} is entirely consumed by the {} transfer fee", display_amount(&amount, Some(input_decimals)), input_mint));
+                }
+                note!(notes, "🧾 {} charges a {} bps transfer fee (max {}): ~{} of the {} sent is withheld, so ~{} is swapped",
+                     input_mint, fee.basis_points, format_token_amount(fee.maximum_fee, input_decimals),
+                     format_token_amount(withheld, input_decimals), format_token_amount(amount_num, input_decimals),
+                     format_token_amount(amount_num - withheld, input_decimals));
+            }
+            if let Some(fee) = ata_manager.transfer_fee(&output_mint_pubkey)? {
+                note!(notes, "🧾 {} charges a {} bps transfer fee (max {}); the amount you receive is net of it and below the quoted output",
+                     output_mint, fee.basis_points, format_token_amount(fee.maximum_fee, output_decimals));
+            }
+            
+            // Check input ATA (source of tokens)
+            if input_mint_pubkey != CommonMints::sol() {
+                note!(notes, "🔍 Checking input token ATA for {}...", input_mint);
+                let input_ata_info = ata_manager.check_ata(&payer.pubkey(), &input_mint_pubkey).await?;
+                if !input_ata_info.exists {
+                    note!(notes, "❌ Input ATA missing for {}! Creating...", input_mint);
+                    let create_ix = ata_manager.create_ata_instruction(&payer.pubkey(), &payer.pubkey(), &input_mint_pubkey)?;
+                    pre_instructions.push(create_ix);
+                    missing_atas.push(input_ata_info.address);
+                } else {
+                    if input_ata_info.frozen {
+                        return Err(anyhow::anyhow!("Input token account {} is frozen; the swap would fail", input_ata_info.address));
+                    }
+                    if let Some(blocker) = input_ata_info.extensions.outgoing_blocker() {
+                        return Err(anyhow::anyhow!("Input token account {} {}", input_ata_info.address, blocker));
+                    }
+                    note!(notes, "✅ Input ATA exists: {} (balance: {} {})", 
+                         input_ata_info.address, 
+                         format_token_amount(input_ata_info.balance.unwrap_or(0), input_decimals), input_mint);
+                    
+                    // Security check: ensure sufficient balance
+                    if let Some(balance) = input_ata_info.balance {
+                        if balance < amount_num {
+                            let fee_note = input_fee
+                                .map(|fee| format!(" ({} of it withheld as transfer fee)", format_token_amount(fee.fee_for(amount_num), input_decimals)))
+                                .unwrap_or_default();
+                            return Err(anyhow::anyhow!(
+                                "Insufficient token balance: need {}{}, have {}", 
+                                display_amount(&amount, Some(input_decimals)), fee_note,
+                                display_amount(&balance.to_string(), Some(input_decimals))
+                            ));
+                        }
+                    }
+                }
+            }
+            
+            // Check output ATA (destination for tokens)
+            if output_mint_pubkey != CommonMints::sol() {
+                note!(notes, "🔍 Checking output token ATA for {}...", output_mint);
+                let output_ata_info = ata_manager.check_ata(&payer.pubkey(), &output_mint_pubkey).await?;
+                if !output_ata_info.exists {
+                    note!(notes, "🔧 Output ATA missing for {}! Creating...", output_mint);
+                    let create_ix = ata_manager.create_ata_instruction(&payer.pubkey(), &payer.pubkey(), &output_mint_pubkey)?;
+                    pre_instructions.push(create_ix);
+                    missing_atas.push(output_ata_info.address);
+                } else {
+                    if output_ata_info.frozen {
+                        return Err(anyhow::anyhow!("Output token account {} is frozen; the swap would fail", output_ata_info.address));
+                    }
+                    if let Some(blocker) = output_ata_info.extensions.incoming_blocker() {
+                        return Err(anyhow::anyhow!("Output token account {} {}", output_ata_info.address, blocker));
+                    }
+                    note!(notes, "✅ Output ATA exists: {}", output_ata_info.address);
+                }
+            }
+            
+            // Execute ATA creation if needed (simulate + decode errors first)
+            if !pre_instructions.is_empty() {
+                note!(notes, "\n🔧 Creating {} missing ATA(s)...", pre_instructions.len());
+                // Simulate first to catch errors like insufficient funds or invalid mints
+                let ata_opts = SendOptions { dry_run: simulate_only, ..SendOptions::with_idl(idl_loader).with_preflight() };
+                match tx_sender::build_and_send(rpc_client, settings, pre_instructions.clone(), &signer_set(fee_payer, &[payer]), ata_opts)? {
+                    Some(ata_signature) => {
+                        note!(notes, "✅ ATA creation completed! Transaction: {}", ata_signature);
+                        settings.explorer.note_transaction_url(notes, &ata_signature);
+                        created_atas = missing_atas;
+                    }
+                    None if simulate_only => note!(notes, "🧪 Simulate-only: ATA creation not sent"),
+                    None => return Ok(outcome),
+                }
+            } else {
+                note!(notes, "✅ All required ATAs already exist");
+            }
+            
+            steps.inc(1);
+            steps.set_message("Building swap");
+
+            // Step 2: Execute Jupiter swap with fresh quote; congestion failures rebuild it at a higher priority fee
+            let mut congestion_attempts = 0;
+            loop {
+                note!(notes, "\n💱 Building Jupiter swap transaction...");
+                match jupiter_client.build_swap_transaction(
+                    &payer.pubkey(),
+                    &input_mint_str,
+                    &output_mint_str,
+                    amount_num,
+                    slippage,
+                    Some(input_decimals),
+                    Some(output_decimals),
+                ).await {
+                    Ok(transaction) => {
+                        note!(notes, "✅ Jupiter transaction built successfully!");
+                        note!(notes, "🔗 Contains {} instructions", transaction.message.instructions().len());
+
+                        // Quick quote sanity: versioned message must have 1+ instructions
+                        if transaction.message.instructions().is_empty() {
+                            note!(notes, "❌ Jupiter returned empty instruction set");
+                            cleanup_created_atas(rpc_client, settings, ata_manager, payer, fee_payer, &created_atas, cleanup_on_failure)?;
+                            return Ok(outcome);
+                        }
+                        
+                        // Security: Validate transaction before signing
+                        note!(notes, "🔍 Validating transaction structure...");
+                        if transaction.signatures.len() == 0 {
+                            return Err(anyhow::anyhow!("Invalid transaction: no signature slots"));
+                        }
+                        
+                        if settings.dump_instructions {
+                            instruction_dump::dump_versioned_message(notes, &transaction.message);
+                        }
+
+                        note!(notes, "🔏 Signing Jupiter transaction with user keypair...");
+                        let signed_transaction = VersionedTransaction::try_new(
+                            transaction.message.clone(),
+                            &[payer]
+                        ).map_err(|e| anyhow::anyhow!("Failed to sign transaction: {}", e))?;
+                        
+                        note!(notes, "✅ Transaction signed successfully!");
+                        note!(notes, "🔍 Signature: {}", signed_transaction.signatures[0]);
+                        steps.inc(1);
+
+                        if simulate_only {
+                            note!(notes, "\n🧪 Simulating Jupiter swap (simulate-only)...");
+                            // The ATAs weren't created, so simulate their creation in the same transaction
+                            let (simulation, simulated_message) = if pre_instructions.is_empty() {
+                                (simulator.simulate_versioned_transaction(&signed_transaction)?, signed_transaction.message.clone())
+                            } else {
+                                note!(notes, "🔧 Including creation of {} missing ATA(s) in the simulation", pre_instructions.len());
+                                let message = tx_sender::prepend_instructions(rpc_client, &signed_transaction.message, &pre_instructions)?;
+                                (simulator.simulate_message(&message)?, message)
+                            };
+                            outcome = outcome.with_simulation(&simulation);
+
+                            note!(notes, "\n🔍 SIMULATION RESULTS:");
+                            note!(notes, "✅ Success: {}", if simulation.success { "YES" } else { "NO" });
+                            note!(notes, "💰 Fee: {} ({} SOL)", simulation.fee_confidence.describe(simulation.fee_estimate), lamports_to_sol(simulation.fee_estimate));
+                            note!(notes, "⚡ Compute units: {}", simulation.compute_units_consumed);
+                            if let Some(error) = &simulation.error_message {
+                                note!(notes, "❌ Error: {}", error);
+                            }
+                            if !simulation.logs.is_empty() {
+                                note!(notes, "📋 Simulation logs:");
+                                for log in &simulation.logs {
+                                    note!(notes, "  📝 {}", log);
+                                }
+                            }
+                            notes.text(transaction_simulator::format_token_transfers(&simulation.logs, &simulated_message));
+                            notes.text(account_inspector::format_post_accounts(Some(idl_loader), &simulation.post_accounts));
+
+                            steps.finish_and_clear();
+                            note!(notes, "\n💡 This was a simulation only - no swap was executed!");
+                            return Ok(outcome);
+                        }
+                        
+                        // Step 3: Execute with production settings using safe-send guard
+                        steps.set_message("Sending swap");
+                        note!(notes, "\n🚀 Executing Jupiter swap on blockchain...");
+                        note!(notes, "🔒 Using safe-send guard with ALTs support and quote validation");
+                        
+                        tx_sender::check_max_fee(&rpc_client, settings, &signed_transaction.message)?;
+                        let mut result = match jupiter_client.safe_send_versioned_transaction(&signed_transaction, &rpc_client).await {
+                            Ok(result) => result,
+                            Err(e) => {
+                                steps.abandon();
+                                note!(notes, "❌ Jupiter swap failed: {}", e);
+                                cleanup_created_atas(rpc_client, settings, ata_manager, payer, fee_payer, &created_atas, cleanup_on_failure)?;
+                                return Err(e);
+                            }
+                        };
+                        let paid_fee = priority_fee::message_priority_fee(&signed_transaction.message);
+                        let failure = result.validation_issues.iter().cloned()
+                            .chain(result.simulation.error_message.clone())
+                            .collect::<Vec<_>>()
+                            .join("; ");
+                        if !failure.is_empty() && classify_swap_failure(&failure) == SwapFailure::Congestion && congestion_attempts < congestion_retries {
+                            // A fresh route gets a fresh blockhash; make sure this one can't land too
+                            let signature = signed_transaction.signatures[0];
+                            note!(notes, "⏳ Swap hit congestion ({}); waiting until {} can no longer land...", failure, signature);
+                            match rpc::wait_until_landed_or_expired(&rpc_client, &signature, signed_transaction.message.recent_blockhash())? {
+                                Some(Ok(())) => {
+                                    note!(notes, "✅ {} landed after all", signature);
+                                    result.sent = true;
+                                    result.signature = Some(signature);
+                                    result.validation_issues.clear();
+                                    result.simulation.success = true;
+                                    result.simulation.error_message = None;
+                                }
+                                Some(Err(err)) => result.validation_issues.push(format!("Landed with an error: {}", err)),
+                                None => match jupiter_client.escalate_priority_fee(paid_fee, max_swap_priority_fee) {
+                                    Some(next_fee) => {
+                                        congestion_attempts += 1;
+                                        note!(notes, "⛽ Congestion retry {}/{}: re-requesting the swap at {} lamports priority fee (was {})",
+                                             congestion_attempts, congestion_retries, next_fee, paid_fee);
+                                        steps.set_position(1);
+                                        continue;
+                                    }
+                                    None => note!(notes, "⚠️  Swap priority fee is already at the {} lamport cap; not retrying", max_swap_priority_fee),
+                                },
+                            }
+                        }
+                        steps.finish_and_clear();
+                        outcome = outcome.with_signature(result.signature).with_simulation(&result.simulation);
+                        
+                        note!(notes, "⛽ Swap priority fee paid: {} lamports", paid_fee);
+                        if result.sent && result.validation_issues.is_empty() {
+                            note!(notes, "\n🎉 Jupiter swap executed successfully!");
+                            if let Some(signature) = result.signature {
+                                note!(notes, "🔍 Transaction: {}", signature);
+                                if let Some(url) = settings.explorer.url_for(&signature) {
+                                    note!(notes, "🌐 View: {}", url);
+                                }
+                                
+                                // Post-swap ATA balances for confirmation
+                                note!(notes, "\n📊 Post-swap token balances:");
+                                if input_mint_pubkey != CommonMints::sol() {
+                                    if let Ok(input_ata_info) = ata_manager.check_ata(&payer.pubkey(), &input_mint_pubkey).await {
+                                        note!(notes, "  📥 {} balance: {}", input_mint, format_token_amount(input_ata_info.balance.unwrap_or(0), input_decimals));
+                                    }
+                                }
+                                if output_mint_pubkey != CommonMints::sol() {
+                                    if let Ok(output_ata_info) = ata_manager.check_ata(&payer.pubkey(), &output_mint_pubkey).await {
+                                        note!(notes, "  📤 {} balance: {}", output_mint, format_token_amount(output_ata_info.balance.unwrap_or(0), output_decimals));
+                                    }
+                                }
+                            }
+                        } else {
+                            note!(notes, "❌ Jupiter swap failed:");
+                            for issue in &result.validation_issues {
+                                note!(notes, "  🚨 {}", issue);
+                            }
+                            cleanup_created_atas(rpc_client, settings, ata_manager, payer, fee_payer, &created_atas, cleanup_on_failure)?;
+                        }
+                    }
+                    Err(e) => {
+                        steps.abandon();
+                        note!(notes, "❌ Failed to build Jupiter swap: {}", e);
+                        note!(notes, "💡 Tip: Check if the tokens exist and you have sufficient balance");
+                        cleanup_created_atas(rpc_client, settings, ata_manager, payer, fee_payer, &created_atas, cleanup_on_failure)?;
+                    }
+                }
+                break;
+            }
+        }
+    }
+
+    Ok(outcome)
+}
+
+/// Program error 6001 (Unauthorized) is guaranteed if the payer doesn't own the send account.
+fn check_send_account_owner(rpc_client: &RpcClient, send_account: &Pubkey, payer: &Keypair) -> Result<()> {
+    let account_data = rpc_client.get_account(send_account)?.data;
+    let decoded = generated::send_program::SendAccount::decode(&account_data)?;
+    if decoded.owner != payer.pubkey() {
+        return Err(anyhow::anyhow!(
+            "Send account {} is owned by {}, not the signer {}; the program would reject this with Unauthorized (6001)",
+            send_account, decoded.owner, payer.pubkey()
+        ));
+    }
+    Ok(())
+}
+
+/// `send_sol` from the user's send account, routed per the registry and checked against the IDL.
+fn send_sol_instruction(
+    program_registry: &ProgramRegistry,
+    encoder: &BorshEncoder,
+    idl_loader: &IdlLoader,
+    send_account: &Pubkey,
+    sender: &Pubkey,
+    recipient: &Pubkey,
+    lamports: u64,
+) -> Result<Instruction> {
+    let program_id = Pubkey::from_str(SEND_PROGRAM_ID)?;
+    let instruction = match program_registry.resolve(&program_id) {
+        ProgramRoute::GeneratedClient(client_name) if client_name.starts_with("send_program") => {
+            generated::send_program::send_sol_instruction(
+                lamports, *recipient, *send_account,
+                *sender, *recipient, system_program::id(),
+            )?
+        }
+        _ => {
+            let mut args = HashMap::new();
+            args.insert("amount".to_string(), serde_json::to_value(lamports)?);
+            args.insert("recipient".to_string(), serde_json::to_value(recipient.to_string())?);
+            let data = encoder.encode_instruction(idl_loader, SEND_PROGRAM_ID, "send_sol", args)?;
+            Instruction { program_id, accounts: vec![
+                AccountMeta::new(*send_account, false),
+                AccountMeta::new(*sender, true),
+                AccountMeta::new(*recipient, false),
+                AccountMeta::new_readonly(system_program::id(), false),
+            ], data }
+        }
+    };
+    idl_loader.validate_account_metas(SEND_PROGRAM_ID, "send_sol", &instruction.accounts)?;
+    Ok(instruction)
+}
+
+/// `send batch`: sign one smart-send per line up front, then submit `concurrency` at a time.
+/// With `preview_with`, every transaction is simulated first and the totals need confirmation unless `yes`.
+#[allow(clippy::too_many_arguments)]
+async fn handle_send_batch(
+    rpc_settings: &RpcSettings,
+    rpc_client: &RpcClient,
+    settings: &Arc<SendSettings>,
+    payer: &Keypair,
+    fee_payer: &Keypair,
+    idl_loader: &IdlLoader,
+    encoder: &BorshEncoder,
+    account_resolver: &AccountResolver,
+    program_registry: &ProgramRegistry,
+    preview_with: Option<&TransactionSimulator>,
+    yes: bool,
+    file: &str,
+    concurrency: usize,
+    abort: bool,
+) -> Result<CommandOutcome> {
+    let notes = &settings.notes;
+    if settings.idempotency.is_some() {
+        return Err(anyhow::anyhow!("--idempotency-key covers one transaction; a batch sends one per line"));
+    }
+    let contents = std::fs::read_to_string(file)
+        .map_err(|e| anyhow::anyhow!("Failed to read batch file {}: {}", file, e))?;
+    let mut entries = batch::parse_batch_file(&contents)?;
+    for entry in &mut entries {
+        entry.lamports = account_resolver.fund_new_recipient(&entry.recipient, entry.lamports)?;
+        generated::send_program::check_send_amount(entry.lamports)
+            .map_err(|e| anyhow::anyhow!("Line {}: {}", entry.line, e))?;
+    }
+
+    let resolution = account_resolver.smart_resolve(&payer.pubkey(), "send")?;
+    let send_account = resolution.address();
+    if !resolution.exists() {
+        note!(notes, "❌ Send account doesn't exist. Run 'smart-init' first!");
+        return Ok(CommandOutcome::new());
+    }
+    check_send_account_owner(rpc_client, send_account, payer)?;
+
+    let total: u64 = entries.iter().map(|entry| entry.lamports).sum();
+    note!(notes, "📦 Batch: {} transfer(s), {} SOL total, up to {} in flight", entries.len(), lamports_to_sol(total), concurrency);
+    note!(notes, "🔑 Send Account (PDA): {}", send_account);
+
+    if settings.propose_to.is_some() {
+        // One proposal carrying every transfer; the multisig executes them together
+        let instructions = entries.iter()
+            .map(|entry| send_sol_instruction(program_registry, encoder, idl_loader, send_account, &payer.pubkey(), &entry.recipient, entry.lamports))
+            .collect::<Result<Vec<_>>>()?;
+        proposal::propose_if_requested(notes, settings.propose_to, &instructions)?;
+        return Ok(CommandOutcome::new().with_address("send_account", *send_account));
+    }
+
+    // Unsigned messages are only checked and previewed; each send signs with a fresh blockhash, so a pause at
+    // the confirmation prompt can't expire the batch
+    let signers = signer_set(fee_payer, &[payer]);
+    let blockhash = tx_sender::latest_blockhash(rpc_client, settings)?.blockhash;
+    let mut instruction_sets = Vec::with_capacity(entries.len());
+    let mut messages = Vec::with_capacity(entries.len());
+    for entry in &entries {
+        let instruction = send_sol_instruction(program_registry, encoder, idl_loader, send_account, &payer.pubkey(), &entry.recipient, entry.lamports)?;
+        let instructions = settings.priority.with_compute_budget(rpc_client, notes, vec![instruction])?;
+        let message = tx_sender::compile_message(settings, &instructions, &fee_payer.pubkey(), blockhash)?;
+        tx_sender::check_max_fee(rpc_client, settings, &message)
+            .map_err(|e| anyhow::anyhow!("Line {}: {}", entry.line, e))?;
+        instruction_sets.push(instructions);
+        messages.push(message);
+    }
+
+    if let Some(simulator) = preview_with {
+        let bar = notes.progress_bar(messages.len() as u64, "Simulating batch");
+        let mut preview = batch::BatchPreview::default();
+        for (entry, message) in entries.iter().zip(&messages) {
+            let simulation = simulator.simulate_message(message)?;
+            let fee = tx_sender::message_fee(rpc_client, message).unwrap_or(simulation.fee_estimate);
+            let error = (!simulation.success).then(|| simulation.error_message.clone().unwrap_or_else(|| "Unknown error".to_string()));
+            preview.record(entry, fee, error);
+            bar.inc(1);
+        }
+        bar.finish_and_clear();
+        notes.text(preview.summary());
+        if preview.failures.len() == preview.transfers {
+            return Err(anyhow::anyhow!("Every transfer in the batch would fail; nothing sent"));
+        }
+        if !settings.dry_run && !yes && !batch::confirm(notes, "Send this batch?")? {
+            note!(notes, "🛑 Batch cancelled; nothing sent");
+            return Ok(CommandOutcome::new().with_address("send_account", *send_account));
+        }
+    }
+
+    if settings.dry_run {
+        note!(notes, "🧪 Dry run: {} transaction(s) built, not sending", messages.len());
+        return Ok(CommandOutcome::new().with_address("send_account", *send_account));
+    }
+
+    let client = Arc::new(rpc_settings.client_with_commitment(settings.confirm_commitment));
+    let bar = notes.progress_bar(instruction_sets.len() as u64, "Sending batch");
+    let owned_signers: Vec<Keypair> = signers.iter().map(|signer| signer.insecure_clone()).collect();
+    let signatures = Arc::new(std::sync::Mutex::new(std::collections::HashSet::new()));
+    let send_settings = settings.clone();
+    let results = batch::send_all(instruction_sets, concurrency, abort, &bar, move |instructions| {
+        let settings = &*send_settings;
+        // Signed just before sending so a long batch never outlives its blockhash; identical transfers
+        // get a newer blockhash so signatures stay distinct
+        let signers: Vec<&Keypair> = owned_signers.iter().collect();
+        let mut blockhash = tx_sender::latest_blockhash(&client, settings)?.blockhash;
+        let mut transaction = tx_sender::sign_transaction(settings, &instructions, &signers, blockhash)?;
+        while !signatures.lock().unwrap().insert(transaction.signatures[0]) {
+            blockhash = client.get_new_latest_blockhash(&blockhash)?;
+            transaction = tx_sender::sign_transaction(settings, &instructions, &signers, blockhash)?;
+        }
+        tx_sender::send_and_confirm_quiet(&client, settings, &transaction)
+    }).await;
+    bar.finish_and_clear();
+
+    for (entry, result) in entries.iter().zip(&results) {
+        match result {
+            BatchResult::Sent(signature) => {
+                note!(notes, "✅ Line {}: {} SOL → {}: {}", entry.line, lamports_to_sol(entry.lamports), entry.recipient, signature);
+                settings.explorer.note_transaction_url(notes, signature);
+            }
+            BatchResult::Skipped => note!(notes, "⏭️  Line {}: {} SOL → {}: skipped", entry.line, lamports_to_sol(entry.lamports), entry.recipient),
+            BatchResult::Failed(e) => note!(notes, "❌ Line {}: {} SOL → {}: {}", entry.line, lamports_to_sol(entry.lamports), entry.recipient, e),
+        }
+    }
+    let skipped = results.iter().filter(|result| matches!(result, BatchResult::Skipped)).count();
+    let failed = results.iter().filter(|result| matches!(result, BatchResult::Failed(_))).count();
+    note!(notes, "📊 Batch done: {} succeeded, {} failed, {} skipped", results.len() - failed - skipped, failed, skipped);
+    if abort && failed > 0 {
+        return Err(anyhow::anyhow!("Batch aborted after a failed transaction; rerun with the failed and skipped lines"));
+    }
+    let lines: Vec<serde_json::Value> = entries.iter().zip(&results).map(|(entry, result)| serde_json::json!({
+        "line": entry.line,
+        "recipient": entry.recipient.to_string(),
+        "lamports": entry.lamports,
+        "signature": result.signature().map(|signature| signature.to_string()),
+        "error": result.error(),
+    })).collect();
+    CommandOutcome::new().with_address("send_account", *send_account).with_state(&lines)
+}
+
+/// After a failed swap, close the still-empty ATAs this run created (`--cleanup-on-failure`) to reclaim rent.
+fn cleanup_created_atas(
+    rpc_client: &RpcClient,
+    settings: &SendSettings,
+    ata_manager: &AtaManager,
+    payer: &Keypair,
+    fee_payer: &Keypair,
+    created_atas: &[Pubkey],
+    cleanup: bool,
+) -> Result<()> {
+    let notes = &settings.notes;
+    if created_atas.is_empty() {
+        return Ok(());
+    }
+    if !cleanup {
+        note!(notes, "💡 This run created {} ATA(s) that now hold rent; pass --cleanup-on-failure to close them when a swap fails:", created_atas.len());
+        for ata in created_atas {
+            note!(notes, "  {}", ata);
+        }
+        return Ok(());
+    }
+
+    let closable = ata_manager.closable_atas(&payer.pubkey(), created_atas)?;
+    if closable.is_empty() {
+        note!(notes, "ℹ️  No empty ATAs from this run to close");
+        return Ok(());
+    }
+    note!(notes, "\n🧹 Closing {} empty ATA(s) created by this run...", closable.len());
+    let instructions = closable.iter()
+        .map(|(ata, mint, _)| ata_manager.close_ata_instruction(ata, mint, &payer.pubkey()))
+        .collect::<Result<Vec<_>>>()?;
+    if tx_sender::build_and_send(rpc_client, settings, instructions, &signer_set(fee_payer, &[payer]), SendOptions::default())?.is_some() {
+        let reclaimed: u64 = closable.iter().map(|(_, _, lamports)| lamports).sum();
+        note!(notes, "♻️  Reclaimed {} lamports ({} SOL)", reclaimed, lamports_to_sol(reclaimed));
+    }
+    Ok(())
+}
+
+async fn handle_registry_command(
+    notes: &Notes,
+    program_registry: &mut ProgramRegistry,
+    action: RegistryActions,
+    output: OutputFormat,
+) -> Result<CommandOutcome> {
+    let mut outcome = CommandOutcome::new();
+    match action {
+        RegistryActions::List if output.is_table() => {
+            let rows: Vec<Vec<String>> = program_registry.list_programs().iter().enumerate().map(|(i, program)| vec![
+                (i + 1).to_string(),
+                program.name.clone(),
+                program.program_id.clone(),
+                format!("{} v{}", program.client_type, program.client_version),
+                format!("{}/10", program.priority),
+                if program.enabled { "enabled" } else { "disabled" }.to_string(),
+            ]).collect();
+            outcome = outcome.with_table(&["#", "Name", "Program ID", "Client", "Priority", "Status"], rows);
+        }
+
+        RegistryActions::List if output.is_json() => {
+            outcome = outcome.with_state(&program_registry.list_programs())?;
+        }
+
+        RegistryActions::List => {
+            note!(notes, "📋 Program Registry - All Programs:");
+            note!(notes, "=====================================");
+            
+            let programs = program_registry.list_programs();
+            for (i, program) in programs.iter().enumerate() {
+                note!(notes, "{}. {} ({})", i + 1, program.name, program.program_id);
+                note!(notes, "   📝 Description: {}", program.description.as_deref().unwrap_or("None"));
+                note!(notes, "   🔗 IDL URL: {}", program.idl_url);
+                note!(notes, "   📦 Client: {} v{}", program.client_type, program.client_version);
+                note!(notes, "   ⭐ Priority: {}/10", program.priority);
+                if let Some(fee) = program.default_priority_fee {
+                    note!(notes, "   ⛽ Default priority fee: {} micro-lamports/CU", fee);
+                }
+                note!(notes, "   ✅ Status: {}", if program.enabled { "Enabled" } else { "Disabled" });
+                if let Some(metadata) = &program.metadata {
+                    if let Some(category) = metadata.get("category") {
+                        note!(notes, "   🏷️  Category: {}", category);
+                    }
+                }
+                notes.status("");
+            }
+
+            let profiles = program_registry.swap_profiles();
+            if !profiles.is_empty() {
+                note!(notes, "💱 Swap Profiles:");
+                for profile in profiles {
+                    note!(notes, "   {}: {} → {} at {} bps{}", profile.name, profile.input, profile.output, profile.slippage_bps,
+                         profile.max_price_impact.map(|max| format!(", max price impact {}%", max)).unwrap_or_default());
+                }
+            }
+        }
+        
+        RegistryActions::Stats => {
+            let stats = program_registry.get_stats();
+            note!(notes, "📊 Program Registry Statistics:");
+            note!(notes, "===============================");
+            note!(notes, "Total Programs: {}", stats.total_programs);
+            note!(notes, "Enabled: {}", stats.enabled_programs);
+            note!(notes, "Disabled: {}", stats.disabled_programs);
+            note!(notes, "Last Updated: {}", stats.last_updated);
+            note!(notes, "Cache TTL: {} seconds", stats.cache_ttl);
+            note!(notes, "Auto Refresh: {}", if stats.auto_refresh { "Yes" } else { "No" });
+        }
+        
+        RegistryActions::Refresh => {
+            note!(notes, "🔄 Refreshing program registry...");
+            program_registry.refresh().await?;
+            note!(notes, "✅ Registry refreshed successfully!");
+        }
+        
+        RegistryActions::Validate { deep } => {
+            note!(notes, "🔍 Validating program registry...");
+            match program_registry.validate() {
+                Ok(_) => note!(notes, "✅ Registry validation passed!"),
+                Err(e) => note!(notes, "❌ Registry validation failed: {}", e),
+            }
+            if deep {
+                if let Err(e) = program_registry.validate_deep().await {
+                    note!(notes, "❌ {}", e);
+                }
+            }
+        }
+        
+        RegistryActions::Add { program_id, name, idl_url, client_version, client_type, priority, default_priority_fee } => {
+            note!(notes, "➕ Adding program to registry...");
+            
+            // Validate program ID
+            let _: Pubkey = program_id.parse()
+                .map_err(|_| anyhow::anyhow!("Invalid program ID: {}", program_id))?;
+            
+            let program = ProgramManifest {
+                program_id: program_id.clone(),
+                name: name.clone(),
+                description: None,
+                idl_url: idl_url.clone(),
+                idl_hash: "".to_string(), // Will be calculated on refresh
+                client_version: client_version.clone(),
+                client_type: client_type.clone(),
+                generated_at: SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs(),
+                last_updated: SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs(),
+                priority,
+                enabled: true,
+                metadata: Some(HashMap::from([
+                    ("category".to_string(), "user".to_string()),
+                    ("maintainer".to_string(), "user".to_string()),
+                ])),
+                default_priority_fee,
+            };
+            
+            program_registry.add_program(program);
+            program_registry.save_to_cache().await?;
+            
+            note!(notes, "✅ Program '{}' added to registry!", name);
+        }
+        
+        RegistryActions::Update { program_id, idl_url, client_version, priority, default_priority_fee, clear_priority_fee } => {
+            note!(notes, "✏️  Updating program in registry...");
+
+            let Some(program) = program_registry.get_program(&program_id.parse()?) else {
+                note!(notes, "❌ Program '{}' not found in registry!", program_id);
+                return Ok(outcome);
+            };
+            let mut updated_program = program.clone();
+            if let Some(idl_url) = idl_url {
+                updated_program.idl_url = idl_url;
+                updated_program.idl_hash = String::new(); // Recalculated on refresh
+            }
+            if let Some(client_version) = client_version {
+                updated_program.client_version = client_version;
+            }
+            if let Some(priority) = priority {
+                updated_program.priority = priority;
+            }
+            if clear_priority_fee {
+                updated_program.default_priority_fee = None;
+            } else if default_priority_fee.is_some() {
+                updated_program.default_priority_fee = default_priority_fee;
+            }
+            updated_program.last_updated = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+            program_registry.add_program(updated_program);
+            program_registry.save_to_cache().await?;
+            note!(notes, "✅ Program '{}' updated!", program_id);
+        }
+
+        RegistryActions::Remove { program_id } => {
+            note!(notes, "🗑️  Removing program from registry...");
+            
+            if program_registry.remove_program(&program_id) {
+                program_registry.save_to_cache().await?;
+                note!(notes, "✅ Program '{}' removed from registry!", program_id);
+            } else {
+                note!(notes, "❌ Program '{}' not found in registry!", program_id);
+            }
+        }
+        
+        RegistryActions::AddSwapProfile { name, input, output, slippage_bps, max_price_impact } => {
+            program_registry.set_swap_profile(SwapProfile { name: name.clone(), input, output, slippage_bps, max_price_impact })?;
+            program_registry.save_to_cache().await?;
+            note!(notes, "✅ Swap profile '{}' saved!", name);
+        }
+
+        RegistryActions::RemoveSwapProfile { name } => {
+            if program_registry.remove_swap_profile(&name) {
+                program_registry.save_to_cache().await?;
+                note!(notes, "✅ Swap profile '{}' removed!", name);
+            } else {
+                note!(notes, "❌ Swap profile '{}' not found!", name);
+            }
+        }
+
+        RegistryActions::Enable { program_id } => {
+            note!(notes, "✅ Enabling program in registry...");
+            
+            if let Some(program) = program_registry.get_program(&program_id.parse()?) {
+                let mut updated_program = program.clone();
+                updated_program.enabled = true;
+                program_registry.add_program(updated_program);
+                program_registry.save_to_cache().await?;
+                note!(notes, "✅ Program '{}' enabled!", program_id);
+            } else {
+                note!(notes, "❌ Program '{}' not found in registry!", program_id);
+            }
+        }
+        
+        RegistryActions::Disable { program_id } => {
+            note!(notes, "❌ Disabling program in registry...");
+            
+            if let Some(program) = program_registry.get_program(&program_id.parse()?) {
+                let mut updated_program = program.clone();
+                updated_program.enabled = false;
+                program_registry.add_program(updated_program);
+                program_registry.save_to_cache().await?;
+                note!(notes, "✅ Program '{}' disabled!", program_id);
+            } else {
+                note!(notes, "❌ Program '{}' not found in registry!", program_id);
+            }
+        }
+    }
+
+    Ok(outcome)
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn handle_account_command(
+    rpc_client: &RpcClient,
+    settings: &SendSettings,
+    payer: &Keypair,
+    fee_payer: &Keypair,
+    idl_loader: &IdlLoader,
+    account_resolver: &AccountResolver,
+    simulator: &TransactionSimulator,
+    action: AccountActions,
+) -> Result<CommandOutcome> {
+    let notes = &settings.notes;
+    let mut outcome = CommandOutcome::new();
+    match action {
+        AccountActions::Create { owner_program, size, account_type, keypair } => {
+            if settings.propose_to.is_some() {
+                return Err(anyhow::anyhow!("--propose-to is not supported for account create: the new account's keypair must sign"));
+            }
+            let owner = Pubkey::from_str(&owner_program)
+                .map_err(|_| anyhow::anyhow!("Invalid owner program ID: {}", owner_program))?;
+            let size = match (size, account_type) {
+                (Some(size), _) => size,
+                (None, Some(account_type)) => {
+                    let size = idl_loader.account_size(&owner_program, &account_type)?;
+                    note!(notes, "📐 {} size from IDL: {} bytes", account_type, size);
+                    size
+                }
+                (None, None) => return Err(anyhow::anyhow!("Provide --size or --account-type")),
+            };
+
+            let account_keypair = match &keypair {
+                Some(path) if std::path::Path::new(&*shellexpand::tilde(path)).exists() => {
+                    read_keypair_file(&*shellexpand::tilde(path))
+                        .map_err(|e| anyhow::anyhow!("Failed to read account keypair {}: {}", path, e))?
+                }
+                _ => {
+                    let new_keypair = Keypair::new();
+                    let path = keypair.clone().unwrap_or_else(|| format!("{}.json", new_keypair.pubkey()));
+                    write_keypair_file(&new_keypair, &*shellexpand::tilde(&path))
+                        .map_err(|e| anyhow::anyhow!("Failed to write account keypair {}: {}", path, e))?;
+                    note!(notes, "🔑 Generated account keypair: {}", path);
+                    new_keypair
+                }
+            };
+
+            if account_resolver.account_exists(&account_keypair.pubkey())? {
+                return Err(anyhow::anyhow!("Account {} already exists", account_keypair.pubkey()));
+            }
+
+            let rent = account_resolver.get_minimum_rent(size)?;
+            note!(notes, "🏗️  Creating account {}...", account_keypair.pubkey());
+            note!(notes, "👤 Owner program: {} ({})", owner, program_label(&owner));
+            note!(notes, "📦 Size: {} bytes", size);
+            note!(notes, "💰 Rent-exempt minimum: {} lamports ({} SOL)", rent, lamports_to_sol(rent));
+
+            let instruction = solana_sdk::system_instruction::create_account(
+                &payer.pubkey(),
+                &account_keypair.pubkey(),
+                rent,
+                size as u64,
+                &owner,
+            );
+            let signers = signer_set(fee_payer, &[payer, &account_keypair]);
+            let Some((_, transaction)) = tx_sender::build_signed(rpc_client, settings, vec![instruction], &signers, Some(idl_loader))? else {
+                return Ok(outcome.with_address("account", account_keypair.pubkey()));
+            };
+
+            let result = simulator.safe_send_transaction(&transaction)?;
+            outcome = outcome.with_address("account", account_keypair.pubkey())
+                .with_signature(result.signature)
+                .with_simulation(&result.simulation);
+            if result.sent {
+                note!(notes, "🎉 Account created: {}", account_keypair.pubkey());
+            } else if !result.validation_issues.is_empty() {
+                note!(notes, "❌ Account creation aborted:");
+                for issue in &result.validation_issues {
+                    note!(notes, "  🚨 {}", issue);
+                }
+            }
+        }
+        AccountActions::Dump { address, offset, length } => {
+            let address = Pubkey::from_str(&address)?;
+
+            note!(notes, "🔍 Fetching account {}...", address);
+            let account = rpc_client.get_account(&address)
+                .map_err(|e| anyhow::anyhow!("Failed to fetch account {}: {}", address, e))?;
+
+            note!(notes, "👤 Owner: {} ({})", account.owner, program_label(&account.owner));
+            note!(notes, "💰 Lamports: {} ({} SOL)", account.lamports, lamports_to_sol(account.lamports));
+            note!(notes, "⚙️  Executable: {}", account.executable);
+            note!(notes, "🗓️  Rent epoch: {}", account.rent_epoch);
+            note!(notes, "📦 Data length: {} bytes", account.data.len());
+            outcome = outcome.with_address("account", address).with_state(&serde_json::json!({
+                "owner": account.owner.to_string(),
+                "lamports": account.lamports,
+                "executable": account.executable,
+                "rent_epoch": account.rent_epoch,
+                "data_len": account.data.len(),
+            }))?;
+
+            let data = account_inspector::slice_data(&account.data, offset, length)?;
+            if data.is_empty() {
+                note!(notes, "📭 No data in selected range");
+                return Ok(outcome);
+            }
+
+            note!(notes, "\n📄 Data [{}..{}]:", offset, offset + data.len());
+            notes.text(account_inspector::hex_dump(data, offset));
+        }
+        AccountActions::Watch { .. } => unreachable!("account watch is dispatched before the other account actions"),
+    }
+
+    Ok(outcome)
+}
+
+fn handle_keygen_command(notes: &Notes, outfile: &str, force: bool) -> Result<CommandOutcome> {
+    let path = shellexpand::tilde(outfile);
+    if std::path::Path::new(&*path).exists() && !force {
+        return Err(anyhow::anyhow!("{} already exists; pass --force to overwrite", outfile));
+    }
+
+    let keypair = Keypair::new();
+    write_keypair_file(&keypair, &*path)
+        .map_err(|e| anyhow::anyhow!("Failed to write keypair {}: {}", outfile, e))?;
+    note!(notes, "🔑 Wrote keypair to {}", outfile);
+    note!(notes, "📋 Pubkey: {}", keypair.pubkey());
+    Ok(CommandOutcome::new().with_address("pubkey", keypair.pubkey()))
+}
+
+fn handle_util_command(notes: &Notes, action: &UtilActions, output: OutputFormat) -> Result<CommandOutcome> {
+    match action {
+        UtilActions::Discriminator { name, namespace } => {
+            let discriminator = idl_loader::namespaced_discriminator(namespace, name);
+            let preimage = format!("{}:{}", namespace, name);
+            let hex = borsh_encoder::to_hex(&discriminator);
+            if output.is_json() {
+                return CommandOutcome::new().with_state(&serde_json::json!({
+                    "preimage": preimage,
+                    "bytes": discriminator,
+                    "hex": hex,
+                }));
+            }
+            note!(notes, "🔢 sha256(\"{}\")[..8]", preimage);
+            note!(notes, "  bytes: {:?}", discriminator);
+            note!(notes, "  hex:   {}", hex);
+        }
+    }
+    Ok(CommandOutcome::new())
+}
+
+async fn handle_logs_command(
+    notes: &Notes,
+    idl_loader: &IdlLoader,
+    program_id_str: &str,
+    ws_url: &str,
+    commitment: CommitmentConfig,
+) -> Result<CommandOutcome> {
+    let program_id = Pubkey::from_str(program_id_str)?;
+
+    let pubsub = PubsubClient::new(ws_url).await
+        .map_err(|e| anyhow::anyhow!("Failed to connect to {}: {}", ws_url, e))?;
+    let (mut stream, unsubscribe) = pubsub.logs_subscribe(
+        RpcTransactionLogsFilter::Mentions(vec![program_id.to_string()]),
+        RpcTransactionLogsConfig { commitment: Some(commitment) },
+    ).await.map_err(|e| anyhow::anyhow!("logsSubscribe failed: {}", e))?;
+
+    note!(notes, "📡 Streaming logs for {} ({}) at {:?} via {}", program_id, program_label(&program_id), commitment.commitment, ws_url);
+    note!(notes, "⏹️  Press Ctrl-C to stop");
+
+    loop {
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => {
+                note!(notes, "\n👋 Stopping log stream...");
+                break;
+            }
+            next = stream.next() => {
+                let Some(response) = next else {
+                    note!(notes, "⚠️  Subscription closed by the server");
+                    break;
+                };
+                let logs = response.value;
+                let status = if logs.err.is_some() { "❌" } else { "✅" };
+                note!(notes, "\n{} {} (slot {})", status, logs.signature, response.context.slot);
+                for line in &logs.logs {
+                    note!(notes, "  {}", line);
+                }
+                if logs.err.is_some() {
+                    error_decoder::note_decoded_error(notes, Some(idl_loader), &[program_id], None, &logs.logs);
+                }
+            }
+        }
+    }
+
+    drop(stream);
+    unsubscribe().await;
+    pubsub.shutdown().await
+        .map_err(|e| anyhow::anyhow!("Failed to close WebSocket: {}", e))?;
+    Ok(CommandOutcome::new())
+}
+
+/// One watched account state as printed: the `--field` value, or every decoded field.
+fn watched_state(idl_loader: &IdlLoader, account: &solana_sdk::account::Account, field: Option<&str>) -> Result<String> {
+    let decoded = idl_loader.decode_account(&account.owner.to_string(), &account.data)?;
+    match (decoded, field) {
+        (Some((account_type, fields)), Some(field)) => Ok(account_inspector::decoded_field(&account_type, &fields, field)?.to_string()),
+        (Some((account_type, fields)), None) => Ok(format!("{} {{ {} }}", account_type, fields.iter()
+            .map(|(name, value)| format!("{}: {}", name, value))
+            .collect::<Vec<_>>().join(", "))),
+        (None, Some(_)) => Err(anyhow::anyhow!(
+            "No IDL account layout matches this account (owner {}); --field needs the owner's IDL", account.owner
+        )),
+        (None, None) => Ok(format!("{} lamports, {} bytes (no IDL layout for owner {})", account.lamports, account.data.len(), account.owner)),
+    }
+}
+
+async fn handle_account_watch(
+    notes: &Notes,
+    rpc_client: &RpcClient,
+    idl_loader: &IdlLoader,
+    address: &str,
+    field: Option<&str>,
+    ws_url: &str,
+    commitment: CommitmentConfig,
+) -> Result<CommandOutcome> {
+    let address = Pubkey::from_str(address).map_err(|_| anyhow::anyhow!("Invalid account address: {}", address))?;
+    let account = rpc_client.get_account_with_commitment(&address, commitment)?.value
+        .ok_or_else(|| anyhow::anyhow!("Account {} not found", address))?;
+    // Checks --field against the layout before subscribing
+    let mut last = watched_state(idl_loader, &account, field)?;
+    let timestamp = || {
+        let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap_or_default();
+        chrono::DateTime::from_timestamp(now.as_secs() as i64, 0).map(|t| t.format("%H:%M:%SZ").to_string()).unwrap_or_default()
+    };
+
+    let pubsub = PubsubClient::new(ws_url).await
+        .map_err(|e| anyhow::anyhow!("Failed to connect to {}: {}", ws_url, e))?;
+    let config = RpcAccountInfoConfig {
+        encoding: Some(UiAccountEncoding::Base64),
+        commitment: Some(commitment),
+        ..RpcAccountInfoConfig::default()
+    };
+    let (mut stream, unsubscribe) = pubsub.account_subscribe(&address, Some(config)).await
+        .map_err(|e| anyhow::anyhow!("accountSubscribe failed: {}", e))?;
+
+    match field {
+        Some(field) => note!(notes, "👀 Watching {}.{} via {}", address, field, ws_url),
+        None => note!(notes, "👀 Watching {} ({}) via {}", address, program_label(&account.owner), ws_url),
+    }
+    note!(notes, "⏹️  Press Ctrl-C to stop");
+    note!(notes, "[{}] {}", timestamp(), last);
+
+    loop {
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => {
+                note!(notes, "\n👋 Stopping account watch...");
+                break;
+            }
+            next = stream.next() => {
+                let Some(response) = next else {
+                    note!(notes, "⚠️  Subscription closed by the server");
+                    break;
+                };
+                let Some(account) = response.value.decode::<solana_sdk::account::Account>() else {
+                    note!(notes, "[{}] ⚠️  Undecodable update at slot {}", timestamp(), response.context.slot);
+                    continue;
+                };
+                let state = match watched_state(idl_loader, &account, field) {
+                    Ok(state) => state,
+                    Err(e) => format!("⚠️  {}", e),
+                };
+                // A field is printed only when its value moves; full states on every update
+                if field.is_none() || state != last {
+                    note!(notes, "[{}] {} (slot {})", timestamp(), state, response.context.slot);
+                    last = state;
+                }
+            }
+        }
+    }
+
+    drop(stream);
+    unsubscribe().await;
+    pubsub.shutdown().await
+        .map_err(|e| anyhow::anyhow!("Failed to close WebSocket: {}", e))?;
+    Ok(CommandOutcome::new().with_address("account", address))
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn handle_invoke_command(
+    rpc_client: &RpcClient,
+    settings: &SendSettings,
+    payer: &Keypair,
+    fee_payer: &Keypair,
+    idl_loader: &IdlLoader,
+    encoder: &BorshEncoder,
+    program_id_str: &str,
+    instruction_name: &str,
+    args: Option<String>,
+    args_file: Option<String>,
+    accounts: &[String],
+    signer_paths: &[String],
+) -> Result<CommandOutcome> {
+    let notes = &settings.notes;
+    let program_id = Pubkey::from_str(program_id_str)?;
+
+    let args_value: serde_json::Value = match (args, args_file) {
+        (Some(inline), _) => serde_json::from_str(&inline)
+            .map_err(|e| anyhow::anyhow!("Invalid --args JSON: {}", e))?,
+        (None, Some(path)) => {
+            let contents = std::fs::read_to_string(&*shellexpand::tilde(&path))
+                .map_err(|e| anyhow::anyhow!("Failed to read args file {}: {}", path, e))?;
+            serde_json::from_str(&contents)
+                .map_err(|e| anyhow::anyhow!("Invalid JSON in args file {}: {}", path, e))?
+        }
+        (None, None) => serde_json::json!({}),
+    };
+    let instruction = bundle::build_instruction(idl_loader, encoder, program_id_str, instruction_name, args_value, accounts)?;
+    let extra_signers = read_signer_keypairs(signer_paths)?;
+    let mut authorities: Vec<&Keypair> = vec![payer];
+    authorities.extend(instruction_signers(&instruction, payer, &extra_signers, settings.sign_only)?);
+
+    note!(notes, "📞 Invoking {}::{}", program_label(&program_id), instruction_name);
+    note!(notes, "📋 Program ID: {}", program_id);
+
+    let opts = SendOptions::with_idl(idl_loader).with_preflight();
+    let signature = tx_sender::build_and_send(rpc_client, settings, vec![instruction], &signer_set(fee_payer, &authorities), opts)?;
+    if signature.is_some() {
+        note!(notes, "🎉 {} succeeded", instruction_name);
+    }
+    Ok(CommandOutcome::new()
+        .with_address("program", program_id)
+        .with_signature(signature))
+}
+
+/// Every program in the bundle is checked against the registry, since `target_program` names only one.
+#[allow(clippy::too_many_arguments)]
+async fn handle_bundle_command(
+    rpc_client: &RpcClient,
+    settings: &SendSettings,
+    payer: &Keypair,
+    fee_payer: &Keypair,
+    idl_loader: &IdlLoader,
+    encoder: &BorshEncoder,
+    program_registry: &ProgramRegistry,
+    allow_disabled: bool,
+    file: &str,
+    signer_paths: &[String],
+) -> Result<CommandOutcome> {
+    let notes = &settings.notes;
+    let contents = std::fs::read_to_string(&*shellexpand::tilde(file))
+        .map_err(|e| anyhow::anyhow!("Failed to read bundle file {}: {}", file, e))?;
+    let specs = bundle::parse_bundle(&contents)?;
+    let extra_signers = read_signer_keypairs(signer_paths)?;
+
+    note!(notes, "📦 Bundling {} instruction(s) into one transaction:", specs.len());
+    let mut instructions = Vec::with_capacity(specs.len());
+    let mut authorities: Vec<&Keypair> = vec![payer];
+    for (index, spec) in specs.iter().enumerate() {
+        let instruction = bundle::build_instruction(idl_loader, encoder, &spec.program, &spec.instruction, spec.args.clone(), &spec.accounts)
+            .map_err(|e| anyhow::anyhow!("Bundle instruction #{} ({}): {}", index, spec.instruction, e))?;
+        program_registry.ensure_enabled(&instruction.program_id, allow_disabled)?;
+        authorities.extend(instruction_signers(&instruction, payer, &extra_signers, settings.sign_only)
+            .map_err(|e| anyhow::anyhow!("Bundle instruction #{} ({}): {}", index, spec.instruction, e))?);
+        note!(notes, "  #{} {}::{}", index, program_label(&instruction.program_id), spec.instruction);
+        instructions.push(instruction);
+    }
+
+    let labels: Vec<String> = specs.iter().map(|spec| spec.instruction.clone()).collect();
+    let opts = SendOptions::with_idl(idl_loader).with_preflight().with_labels(&labels);
+    let signature = tx_sender::build_and_send(rpc_client, settings, instructions, &signer_set(fee_payer, &authorities), opts)?;
+    if signature.is_some() {
+        note!(notes, "🎉 All {} bundled instructions succeeded", specs.len());
+    }
+    Ok(CommandOutcome::new().with_signature(signature))
+}
+
+fn handle_combine_command(rpc_client: &RpcClient, settings: &SendSettings, paths: &[String]) -> Result<CommandOutcome> {
+    let notes = &settings.notes;
+    let parts = sign_only::read_parts(paths)?;
+    let transaction = sign_only::combine(&parts)?;
+    let blockhash = rpc::BlockhashWindow {
+        blockhash: *transaction.message.recent_blockhash(),
+        last_valid_block_height: parts[0].last_valid_block_height,
+    };
+    note!(notes, "🔗 Combined {} signature(s) from {} file(s)", transaction.signatures.len(), parts.len());
+    tx_sender::check_max_fee(rpc_client, settings, &transaction.message)?;
+    if settings.build_only {
+        note!(notes, "📦 Build only: signed transaction not sent; submit it before block height {}", blockhash.last_valid_block_height);
+        notes.document(&tx_sender::BuiltTransaction::new(&transaction, &blockhash)?)?;
+        return Ok(CommandOutcome::new());
+    }
+    let signature = tx_sender::send_signed(rpc_client, settings, &transaction, SendOptions::default())?;
+    Ok(CommandOutcome::new().with_signature(signature))
+}
+
+/// Load `--signer` keypair files.
+fn read_signer_keypairs(paths: &[String]) -> Result<Vec<Keypair>> {
+    paths.iter().map(|path| read_keypair_file(&*shellexpand::tilde(path))
+        .map_err(|e| anyhow::anyhow!("Failed to read signer keypair {}: {}", path, e)))
+        .collect()
+}
+
+/// Keypairs for the instruction's signer accounts, from the payer and the extra `--signer`s.
+fn instruction_signers<'a>(instruction: &Instruction, payer: &'a Keypair, extra_signers: &'a [Keypair], sign_only: bool) -> Result<Vec<&'a Keypair>> {
+    instruction.accounts.iter().filter(|meta| meta.is_signer).filter_map(|meta| {
+        let keypair = std::iter::once(payer).chain(extra_signers.iter()).find(|k| k.pubkey() == meta.pubkey);
+        match keypair {
+            Some(keypair) => Some(Ok(keypair)),
+            // Under --sign-only, signers without a local keypair sign on their own machines
+            None if sign_only => None,
+            None => Some(Err(anyhow::anyhow!("Account {} must sign; pass its keypair with --signer (or use --sign-only)", meta.pubkey))),
+        }
+    }).collect()
+}
+
+async fn handle_idl_command(
+    notes: &Notes,
+    idl_loader: &IdlLoader,
+    action: IdlActions,
+    output: OutputFormat,
+) -> Result<CommandOutcome> {
+    match action {
+        IdlActions::Errors { program_id, code } => {
+            let _: Pubkey = program_id.parse()
+                .map_err(|_| anyhow::anyhow!("Invalid program ID: {}", program_id))?;
+
+            // (code, name, msg, source)
+            let mut entries: Vec<(u32, String, String, &str)> = Vec::new();
+            match idl_loader.get_errors(&program_id) {
+                Ok(errors) => {
+                    for error in errors {
+                        entries.push((error.code, error.name.clone(), error.msg.clone(), "idl"));
+                    }
+                }
+                Err(e) => {
+                    if !output.is_json() {
+                        note!(notes, "⚠️  {}", e);
+                    }
+                }
+            }
+            if program_id == generated::send_program::PROGRAM_ID {
+                for (code, name, msg) in generated::send_program::ERRORS {
+                    entries.push((*code, name.to_string(), msg.to_string(), "generated"));
+                }
+            }
+            entries.sort_by_key(|(code, _, _, source)| (*code, *source));
+
+            if let Some(code) = code {
+                entries.retain(|(c, _, _, _)| *c == code);
+            }
+
+            if output.is_json() {
+                let json: Vec<serde_json::Value> = entries.iter().map(|(code, name, msg, source)| serde_json::json!({
+                    "code": code,
+                    "name": name,
+                    "msg": msg,
+                    "source": source,
+                })).collect();
+                return CommandOutcome::new().with_state(&json);
+            }
+
+            match code {
+                Some(code) => {
+                    note!(notes, "🔎 Error code {} (0x{:x}) for {}:", code, code, program_id);
+                    if entries.is_empty() {
+                        note!(notes, "❌ No mapping found for code {}", code);
+                    }
+                    for (_, name, msg, source) in &entries {
+                        note!(notes, "  ✅ [{}] {}: {}", source, name, msg);
+                    }
+                    if code >= 6000 {
+                        note!(notes, "💡 Anchor custom error index: {} (code - 6000)", code - 6000);
+                    } else {
+                        note!(notes, "💡 Codes below 6000 are not Anchor custom errors; as an index this would be code {}", code + 6000);
+                    }
+                }
+                None => {
+                    note!(notes, "📋 Errors for {} ({} total):", program_id, entries.len());
+                    let rows: Vec<Vec<String>> = entries.iter().map(|(code, name, msg, source)| vec![
+                        code.to_string(),
+                        name.clone(),
+                        source.to_string(),
+                        msg.clone(),
+                    ]).collect();
+                    notes.text(output::render_table(&["Code", "Name", "Source", "Message"], &rows));
+                }
+            }
+        }
+        IdlActions::AccountLayout { program_id, account_name } => {
+            let (discriminator, fields) = idl_loader.account_layout(&program_id, &account_name)?;
+            if output.is_json() {
+                return CommandOutcome::new().with_state(&serde_json::json!({
+                    "account": account_name,
+                    "discriminator": discriminator,
+                    "fields": fields,
+                }));
+            }
+
+            let unknown = || "?".to_string();
+            let rows: Vec<Vec<String>> = std::iter::once(vec![
+                "(discriminator)".to_string(), "[u8; 8]".to_string(), "0".to_string(), ACCOUNT_DISCRIMINATOR_LEN.to_string(),
+            ]).chain(fields.iter().map(|field| vec![
+                field.name.clone(),
+                field.ty.clone(),
+                field.offset.map(|o| o.to_string()).unwrap_or_else(unknown),
+                field.size.map(|s| s.to_string()).unwrap_or_else(unknown),
+            ])).collect();
+            note!(notes, "📐 {} layout for {} (discriminator {}):", account_name, program_id, borsh_encoder::to_hex(&discriminator));
+            notes.text(output::render_table(&["Field", "Type", "Offset", "Size"], &rows));
+            if fields.iter().any(|field| field.size.is_none()) {
+                note!(notes, "💡 ? = variable-length; later offsets depend on the data");
+            }
+        }
+    }
+
+    Ok(CommandOutcome::new())
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn handle_balances_command(
+    notes: &Notes,
+    payer: &Keypair,
+    account_resolver: &AccountResolver,
+    ata_manager: &AtaManager,
+    jupiter_client: &JupiterClient,
+    limit: Option<usize>,
+    min_balance: Option<f64>,
+    output: OutputFormat,
+) -> Result<CommandOutcome> {
+    let owner = payer.pubkey();
+    let sol_balance = account_resolver.get_balance(&owner)?;
+    let (token_balances, hidden) = ata_manager::filter_balances(ata_manager.list_token_accounts(&owner)?, min_balance, limit);
+
+    let token_list = match jupiter_client.get_token_list().await {
+        Ok(list) => list,
+        Err(e) => {
+            if !output.is_json() {
+                note!(notes, "⚠️  Could not load Jupiter token list, symbols unavailable: {}", e);
+            }
+            HashMap::new()
+        }
+    };
+
+    let send_account = account_resolver.resolve_send_account(&owner)?;
+    let swap_pool = account_resolver.resolve_swap_pool(&owner)?;
+    let mut pdas = Vec::new();
+    for (label, address, exists) in [
+        ("send_account", send_account.address, send_account.exists),
+        ("swap_pool", swap_pool.address, swap_pool.exists),
+    ] {
+        let lamports = if exists { account_resolver.get_balance(&address)? } else { 0 };
+        pdas.push((label, address, exists, lamports));
+    }
+
+    let tokens: Vec<(String, &ata_manager::TokenBalance, Option<String>)> = token_balances.iter()
+        .map(|balance| {
+            let symbol = token_list.get(&balance.mint.to_string())
+                .map(|t| t.symbol.clone())
+                .unwrap_or_else(|| "?".to_string());
+            let ui_amount = balance.decimals.map(|d| format_token_amount(balance.amount, d));
+            (symbol, balance, ui_amount)
+        })
+        .collect();
+
+    if output.is_json() {
+        return CommandOutcome::new().with_state(&serde_json::json!({
+            "owner": owner.to_string(),
+            "sol": {
+                "lamports": sol_balance,
+                "ui_amount": lamports_to_sol(sol_balance),
+            },
+            "tokens": tokens.iter().map(|(symbol, balance, ui_amount)| serde_json::json!({
+                "account": balance.address.to_string(),
+                "mint": balance.mint.to_string(),
+                "symbol": symbol,
+                "amount": balance.amount,
+                "decimals": balance.decimals,
+                "ui_amount": ui_amount,
+            })).collect::<Vec<_>>(),
+            "hidden_tokens": hidden,
+            "pdas": pdas.iter().map(|(label, address, exists, lamports)| serde_json::json!({
+                "name": label,
+                "address": address.to_string(),
+                "exists": exists,
+                "lamports": lamports,
+            })).collect::<Vec<_>>(),
+        }));
+    }
+
+    if output.is_table() {
+        let mut rows = vec![vec!["sol".to_string(), "SOL".to_string(), lamports_to_sol(sol_balance), owner.to_string()]];
+        for (symbol, balance, ui_amount) in &tokens {
+            rows.push(vec![
+                "token".to_string(),
+                symbol.clone(),
+                ui_amount.clone().unwrap_or_else(|| format!("{} (raw)", balance.amount)),
+                balance.address.to_string(),
+            ]);
+        }
+        for (label, address, exists, lamports) in &pdas {
+            let amount = if *exists { lamports_to_sol(*lamports) } else { "not created".to_string() };
+            rows.push(vec!["pda".to_string(), label.to_string(), amount, address.to_string()]);
+        }
+        return Ok(CommandOutcome::new().with_table(&["Kind", "Name", "Amount", "Address"], rows));
+    }
+
+    note!(notes, "👤 Owner: {}", owner);
+    note!(notes, "💰 SOL: {} ({} lamports)", lamports_to_sol(sol_balance), sol_balance);
+
+    notes.status("");
+    if tokens.is_empty() {
+        note!(notes, "🪙 No token accounts");
+    } else {
+        note!(notes, "🪙 Token accounts:");
+        let rows: Vec<Vec<String>> = tokens.iter().map(|(symbol, balance, ui_amount)| vec![
+            symbol.clone(),
+            ui_amount.clone().unwrap_or_else(|| format!("{} (raw)", balance.amount)),
+            balance.mint.to_string(),
+            balance.address.to_string(),
+        ]).collect();
+        notes.text(output::render_table(&["Symbol", "Amount", "Mint", "Account"], &rows));
+    }
+    if hidden > 0 {
+        note!(notes, "🙈 {} more token account(s) hidden by --limit/--min-balance", hidden);
+    }
+
+    notes.status("");
+    note!(notes, "📍 Program PDAs:");
+    for (label, address, exists, lamports) in &pdas {
+        if *exists {
+            note!(notes, "  ✅ {}: {} ({} SOL)", label, address, lamports_to_sol(*lamports));
+        } else {
+            note!(notes, "  ❌ {}: {} (not created)", label, address);
+        }
+    }
+
+    Ok(CommandOutcome::new())
+}
+
+async fn handle_token_command(
+    rpc_client: &RpcClient,
+    settings: &SendSettings,
+    payer: &Keypair,
+    fee_payer: &Keypair,
+    idl_loader: &IdlLoader,
+    ata_manager: &AtaManager,
+    action: TokenActions,
+) -> Result<CommandOutcome> {
+    let notes = &settings.notes;
+    let mut outcome = CommandOutcome::new();
+    match action {
+        TokenActions::Transfer { mint, amount, recipient } => {
+            let mint_pubkey = CommonMints::from_name(&mint)?;
+            if mint_pubkey == CommonMints::sol() {
+                return Err(anyhow::anyhow!("Native SOL isn't a token transfer; wrap it first or use a system transfer"));
+            }
+            let recipient_pubkey = Pubkey::from_str(&recipient)
+                .map_err(|_| anyhow::anyhow!("Invalid recipient pubkey: {}", recipient))?;
+            let decimals = ata_manager.validate_mint(&mint_pubkey)?;
+            let amount_raw = parse_token_amount(&amount, decimals)?;
+            if amount_raw == 0 {
+                return Err(anyhow::anyhow!("Transfer amount must be greater than zero"));
+            }
+
+            let source = ata_manager.check_ata(&payer.pubkey(), &mint_pubkey).await?;
+            if !source.exists {
+                return Err(anyhow::anyhow!("{} has no token account for {} ({} does not exist)", payer.pubkey(), mint_pubkey, source.address));
+            }
+            if source.frozen {
+                return Err(anyhow::anyhow!("Source token account {} is frozen", source.address));
+            }
+            let balance = source.balance.unwrap_or(0);
+            if balance < amount_raw {
+                return Err(anyhow::anyhow!(
+                    "Insufficient token balance: need {}, have {}",
+                    format_token_amount(amount_raw, decimals), format_token_amount(balance, decimals)
+                ));
+            }
+            let destination = ata_manager.check_ata(&recipient_pubkey, &mint_pubkey).await?;
+            if destination.exists {
+                if destination.frozen {
+                    return Err(anyhow::anyhow!("Recipient token account {} is frozen", destination.address));
+                }
+                if let Some(blocker) = destination.extensions.incoming_blocker() {
+                    return Err(anyhow::anyhow!("Recipient token account {} {}", destination.address, blocker));
+                }
+            } else {
+                note!(notes, "🔧 Recipient ATA {} will be created ({} SOL rent, paid by the fee payer)",
+                     destination.address, lamports_to_sol(destination.rent_exemption_required));
+            }
+
+            note!(notes, "🪙 Transferring {} of {} to {}", format_token_amount(amount_raw, decimals), mint_pubkey, recipient_pubkey);
+            let instructions = ata_manager.transfer_checked_instructions(
+                &fee_payer.pubkey(), &payer.pubkey(), &recipient_pubkey, &mint_pubkey, amount_raw, decimals,
+            )?;
+            outcome = outcome.with_address("source_ata", source.address).with_address("recipient_ata", destination.address);
+            let opts = SendOptions::with_idl(idl_loader).with_preflight();
+            let Some(signature) = tx_sender::build_and_send(rpc_client, settings, instructions, &signer_set(fee_payer, &[payer]), opts)? else {
+                return Ok(outcome);
+            };
+            outcome = outcome.with_signature(Some(signature));
+            note!(notes, "✅ Transfer confirmed: {}", signature);
+            settings.explorer.note_transaction_url(notes, &signature);
+        }
+    }
+    Ok(outcome)
+}
+
+async fn handle_jupiter_command(
+    notes: &Notes,
+    jupiter_client: &JupiterClient,
+    ata_manager: &AtaManager,
+    action: JupiterActions,
+    output: OutputFormat,
+) -> Result<CommandOutcome> {
+    let mut outcome = CommandOutcome::new();
+    match action {
+        JupiterActions::Compare { input_mint, output_mint, amount, slippage_bps } => {
+            let input_mint_pubkey = CommonMints::from_name(&input_mint)?;
+            let output_mint_pubkey = CommonMints::from_name(&output_mint)?;
+            let input_decimals = ata_manager.mint_decimals(&input_mint_pubkey);
+            let output_decimals = ata_manager.mint_decimals(&output_mint_pubkey);
+
+            if !output.is_json() {
+                note!(notes, "🔍 Comparing {} quotes for {} {} → {}...",
+                     slippage_bps.len(), display_amount(&amount.to_string(), input_decimals), input_mint, output_mint);
+            }
+            let comparisons = jupiter_client.compare_slippage(
+                &input_mint_pubkey.to_string(),
+                &output_mint_pubkey.to_string(),
+                amount,
+                &slippage_bps,
+            ).await;
+
+            if output.is_json() {
+                return outcome.with_state(&comparisons);
+            }
+
+            let rows: Vec<Vec<String>> = comparisons.iter().map(|c| match &c.error {
+                None => vec![
+                    c.slippage_bps.to_string(),
+                    display_amount(c.out_amount.as_deref().unwrap_or_default(), output_decimals),
+                    display_amount(c.other_amount_threshold.as_deref().unwrap_or_default(), output_decimals),
+                    format!("{}%", c.price_impact_pct.as_deref().unwrap_or_default()),
+                ],
+                Some(e) => vec![c.slippage_bps.to_string(), format!("❌ {}", e), String::new(), String::new()],
+            }).collect();
+            let headers = ["Slippage (bps)", "Out amount", "Min out (threshold)", "Price impact"];
+            if output.is_table() {
+                outcome = outcome.with_table(&headers, rows);
+            } else {
+                notes.text(output::render_table(&headers, &rows));
+                note!(notes, "\n💡 Min out is the guaranteed output at that slippage; higher slippage lowers it");
+            }
+        }
+        JupiterActions::Price { base, quote } => {
+            let base_mint = CommonMints::from_name(&base)?;
+            let quote_mint = CommonMints::from_name(&quote)?;
+            let base_decimals = ata_manager.mint_decimals(&base_mint)
+                .ok_or_else(|| anyhow::anyhow!("Can't read decimals of base mint {}", base))?;
+            let quote_decimals = ata_manager.mint_decimals(&quote_mint)
+                .ok_or_else(|| anyhow::anyhow!("Can't read decimals of quote mint {}", quote))?;
+
+            // get_fresh_quote applies the freshness checks and retries a stale quote
+            let response = jupiter_client.get_fresh_quote(QuoteRequest {
+                input_mint: base_mint.to_string(),
+                output_mint: quote_mint.to_string(),
+                amount: 10u64.checked_pow(base_decimals as u32)
+                    .ok_or_else(|| anyhow::anyhow!("Base mint decimals too large: {}", base_decimals))?,
+                slippage_bps: None,
+                restrict_intermediate_tokens: Some(true),
+                only_direct_routes: Some(false),
+            }, 3).await?;
+            let price = jupiter_client::implied_price(&response, base_decimals, quote_decimals)?;
+            // Jupiter doesn't always stamp quotes; fall back to when we received it
+            let timestamp = response.timestamp
+                .unwrap_or_else(|| SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs());
+            let as_of = chrono::DateTime::from_timestamp(timestamp as i64, 0).map(|t| t.to_rfc3339());
+            let slot = response.slot.unwrap_or(response.context_slot);
+
+            if output.is_json() {
+                return outcome.with_state(&serde_json::json!({
+                    "base": base_mint.to_string(),
+                    "quote": quote_mint.to_string(),
+                    "price": price,
+                    "price_impact_pct": response.price_impact_pct,
+                    "timestamp": as_of,
+                    "slot": slot,
+                }));
+            }
+            note!(notes, "💱 1 {} = {} {}", base, price, quote);
+            note!(notes, "🕒 As of {} (slot {})", as_of.as_deref().unwrap_or("unknown time"), slot);
+        }
+    }
+
+    Ok(outcome)
+}
@@ -0,0 +1,192 @@
+use solana_sdk::instruction::InstructionError;
+use solana_sdk::message::VersionedMessage;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::transaction::TransactionError;
+use crate::generated;
+use crate::idl_loader::IdlLoader;
+use crate::outcome::{note, Notes};
+
+const TOKEN_PROGRAM_ID: &str = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA";
+const TOKEN_2022_PROGRAM_ID: &str = "TokenzQdBNbLqU2YPbVmjYVBRhCF9dDid1i9QpZ5dKQ";
+const ATA_PROGRAM_ID: &str = "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL";
+
+/// `spl_token::error::TokenError`, indexed by code (Token-2022 shares these codes).
+const TOKEN_ERRORS: &[&str] = &[
+    "NotRentExempt: Lamport balance below rent-exempt threshold",
+    "InsufficientFunds: Insufficient funds",
+    "InvalidMint: Invalid Mint",
+    "MintMismatch: Account not associated with this Mint",
+    "OwnerMismatch: Owner does not match",
+    "FixedSupply: Fixed supply",
+    "AlreadyInUse: Already in use",
+    "InvalidNumberOfProvidedSigners: Invalid number of provided signers",
+    "InvalidNumberOfRequiredSigners: Invalid number of required signers",
+    "UninitializedState: State is uninitialized",
+    "NativeNotSupported: Instruction does not support native tokens",
+    "NonNativeHasBalance: Non-native account can only be closed if its balance is zero",
+    "InvalidInstruction: Invalid instruction",
+    "InvalidState: State is invalid for requested operation",
+    "Overflow: Operation overflowed",
+    "AuthorityTypeNotSupported: Account does not support specified authority type",
+    "MintCannotFreeze: This token mint cannot freeze accounts",
+    "AccountFrozen: Account is frozen",
+    "MintDecimalsMismatch: The provided decimals value different from the Mint decimals",
+    "NonNativeNotSupported: Instruction does not support non-native tokens",
+];
+
+/// `spl_associated_token_account::error::AssociatedTokenAccountError`, indexed by code.
+const ATA_ERRORS: &[&str] = &[
+    "InvalidOwner: Associated token account owner does not match address derivation",
+];
+
+/// A custom program error and the program that raised it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodedError {
+    pub program_id: String,
+    pub code: u32,
+    /// `None` when no generated map, SPL table, or IDL knows the code
+    pub message: Option<String>,
+}
+
+impl std::fmt::Display for DecodedError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.message {
+            Some(message) => write!(f, "{} (code {} from {})", message, self.code, self.program_id),
+            None => write!(f, "code {} from {} (no mapping found)", self.code, self.program_id),
+        }
+    }
+}
+
+/// First `Program <id> failed: custom program error: 0x..` line; the innermost failing program logs first.
+fn custom_error_from_logs(logs: &[String]) -> Option<(Option<String>, u32)> {
+    logs.iter().find_map(|line| {
+        let pos = line.find("custom program error: 0x")?;
+        let hex = &line[pos + "custom program error: 0x".len()..];
+        let end = hex.find(|c: char| !c.is_ascii_hexdigit()).unwrap_or(hex.len());
+        let code = u32::from_str_radix(&hex[..end], 16).ok()?;
+        let program_id = line.strip_prefix("Program ")
+            .and_then(|rest| rest.split_once(' '))
+            .filter(|(_, reason)| reason.starts_with("failed:"))
+            .map(|(program_id, _)| program_id.to_string());
+        Some((program_id, code))
+    })
+}
+
+/// Look `code` up for one program: generated maps, then the SPL Token/ATA tables, then the loaded IDL.
+pub fn decode_program_error(idl_loader: Option<&IdlLoader>, program_id: &str, code: u32) -> Option<String> {
+    let builtin = match program_id {
+        generated::send_program::PROGRAM_ID => generated::send_program::decode_error(code),
+        generated::swap_program::PROGRAM_ID => generated::swap_program::decode_error(code),
+        TOKEN_PROGRAM_ID | TOKEN_2022_PROGRAM_ID => TOKEN_ERRORS.get(code as usize).copied(),
+        ATA_PROGRAM_ID => ATA_ERRORS.get(code as usize).copied(),
+        _ => None,
+    };
+    builtin.map(str::to_string)
+        .or_else(|| idl_loader.and_then(|loader| loader.decode_error(program_id, code)))
+}
+
+/// Decode a failed transaction's custom error against the program that raised it.
+///
+/// `program_ids` are the transaction's instruction programs, in order. The failing program comes from
+/// the logs when present (this also catches CPIs), else from the `InstructionError` index; if neither
+/// names one, the first candidate that knows the code wins.
+pub fn decode_error_for_programs(
+    idl_loader: Option<&IdlLoader>,
+    program_ids: &[Pubkey],
+    err: Option<&TransactionError>,
+    logs: &[String],
+) -> Option<DecodedError> {
+    let (logged_program, logged_code) = match custom_error_from_logs(logs) {
+        Some((program, code)) => (program, Some(code)),
+        None => (None, None),
+    };
+    let (indexed_program, err_code) = match err {
+        Some(TransactionError::InstructionError(index, InstructionError::Custom(code))) => {
+            (program_ids.get(*index as usize).map(|p| p.to_string()), Some(*code))
+        }
+        Some(TransactionError::InstructionError(index, _)) => (program_ids.get(*index as usize).map(|p| p.to_string()), None),
+        _ => (None, None),
+    };
+    let code = logged_code.or(err_code)?;
+
+    let program_id = logged_program.or(indexed_program).or_else(|| {
+        program_ids.iter()
+            .map(|p| p.to_string())
+            .find(|p| decode_program_error(idl_loader, p, code).is_some())
+    })?;
+    let message = decode_program_error(idl_loader, &program_id, code);
+    Some(DecodedError { program_id, code, message })
+}
+
+/// Instruction program IDs of a compiled message, in instruction order.
+pub fn message_program_ids(message: &VersionedMessage) -> Vec<Pubkey> {
+    let keys = message.static_account_keys();
+    message.instructions().iter()
+        .filter_map(|ix| keys.get(ix.program_id_index as usize).copied())
+        .collect()
+}
+
+/// `err` as a simulation error message, with the decoded custom error appended when there is one.
+pub fn describe_failure(
+    idl_loader: Option<&IdlLoader>,
+    program_ids: &[Pubkey],
+    err: &TransactionError,
+    logs: &[String],
+) -> String {
+    match decode_error_for_programs(idl_loader, program_ids, Some(err), logs) {
+        Some(decoded) => format!("{:?} - {}", err, decoded),
+        None => format!("{:?}", err),
+    }
+}
+
+/// Note the decoded error line, if the failure was a custom program error.
+pub fn note_decoded_error(
+    notes: &Notes,
+    idl_loader: Option<&IdlLoader>,
+    program_ids: &[Pubkey],
+    err: Option<&TransactionError>,
+    logs: &[String],
+) {
+    if let Some(decoded) = decode_error_for_programs(idl_loader, program_ids, err, logs) {
+        note!(notes, "🔎 Decoded program error: {}", decoded);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_decode_error_for_programs_picks_failing_program() {
+        let token = Pubkey::from_str(TOKEN_PROGRAM_ID).unwrap();
+        let ata = Pubkey::from_str(ATA_PROGRAM_ID).unwrap();
+        let programs = [ata, token];
+
+        // No logs: the InstructionError index picks the program
+        let err = TransactionError::InstructionError(1, InstructionError::Custom(4));
+        let decoded = decode_error_for_programs(None, &programs, Some(&err), &[]).unwrap();
+        assert_eq!(decoded.program_id, TOKEN_PROGRAM_ID);
+        assert_eq!(decoded.message.as_deref(), Some("OwnerMismatch: Owner does not match"));
+
+        // A CPI failure: the logs name the inner program even though the outer instruction failed
+        let logs = vec![
+            format!("Program {} invoke [1]", ATA_PROGRAM_ID),
+            format!("Program {} invoke [2]", TOKEN_PROGRAM_ID),
+            format!("Program {} failed: custom program error: 0x1", TOKEN_PROGRAM_ID),
+            format!("Program {} failed: custom program error: 0x1", ATA_PROGRAM_ID),
+        ];
+        let err = TransactionError::InstructionError(0, InstructionError::Custom(1));
+        let decoded = decode_error_for_programs(None, &programs, Some(&err), &logs).unwrap();
+        assert_eq!(decoded.program_id, TOKEN_PROGRAM_ID);
+        assert_eq!(decoded.message.as_deref(), Some("InsufficientFunds: Insufficient funds"));
+
+        let decoded = decode_error_for_programs(None, &programs, None, &["custom program error: 0x0".to_string()]).unwrap();
+        assert_eq!(decoded.program_id, ATA_PROGRAM_ID);
+
+        let err = TransactionError::InstructionError(0, InstructionError::Custom(6001));
+        let unknown = decode_error_for_programs(None, &[Pubkey::new_unique()], Some(&err), &[]).unwrap();
+        assert!(unknown.message.is_none());
+        assert!(decode_error_for_programs(None, &programs, Some(&TransactionError::AccountNotFound), &[]).is_none());
+    }
+}
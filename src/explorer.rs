@@ -0,0 +1,131 @@
+use clap::ValueEnum;
+use solana_sdk::signature::Signature;
+use crate::outcome::{note, Notes};
+
+/// Block explorer for transaction links (`--explorer`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum Explorer {
+    #[default]
+    Solscan,
+    Solanafm,
+    /// explorer.solana.com
+    Explorer,
+}
+
+/// Cluster an RPC endpoint serves, guessed from its URL.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cluster {
+    Mainnet,
+    Devnet,
+    Testnet,
+    Localnet,
+}
+
+impl Cluster {
+    /// `devnet`/`testnet`/localhost are recognized by name; any other endpoint is taken to be mainnet.
+    pub fn from_rpc_url(url: &str) -> Self {
+        let url = url.to_ascii_lowercase();
+        if url.contains("devnet") {
+            Cluster::Devnet
+        } else if url.contains("testnet") {
+            Cluster::Testnet
+        } else if url.contains("localhost") || url.contains("127.0.0.1") || url.contains("0.0.0.0") {
+            Cluster::Localnet
+        } else {
+            Cluster::Mainnet
+        }
+    }
+
+    /// The `solana --url` moniker for this cluster.
+    pub fn moniker(self) -> &'static str {
+        match self {
+            Cluster::Mainnet => "mainnet-beta",
+            Cluster::Devnet => "devnet",
+            Cluster::Testnet => "testnet",
+            Cluster::Localnet => "localhost",
+        }
+    }
+}
+
+/// Link to `signature` on `explorer`. Only explorer.solana.com can show a local validator's transactions.
+pub fn transaction_url(explorer: Explorer, cluster: Cluster, signature: &Signature) -> Option<String> {
+    let query = match (explorer, cluster) {
+        (_, Cluster::Mainnet) => "",
+        (Explorer::Solscan | Explorer::Explorer, Cluster::Devnet) => "?cluster=devnet",
+        (Explorer::Solscan | Explorer::Explorer, Cluster::Testnet) => "?cluster=testnet",
+        (Explorer::Solanafm, Cluster::Devnet) => "?cluster=devnet-solana",
+        (Explorer::Solanafm, Cluster::Testnet) => "?cluster=testnet-solana",
+        (Explorer::Explorer, Cluster::Localnet) => "?cluster=custom&customUrl=http%3A%2F%2Flocalhost%3A8899",
+        (Explorer::Solscan | Explorer::Solanafm, Cluster::Localnet) => return None,
+    };
+    let base = match explorer {
+        Explorer::Solscan => "https://solscan.io/tx",
+        Explorer::Solanafm => "https://solana.fm/tx",
+        Explorer::Explorer => "https://explorer.solana.com/tx",
+    };
+    Some(format!("{}/{}{}", base, signature, query))
+}
+
+/// Where links to sent transactions point (`--explorer`, the RPC endpoint's cluster), and whether every sent
+/// signature gets one (`--print-transaction-url`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExplorerLinks {
+    pub explorer: Explorer,
+    pub cluster: Cluster,
+    pub print_urls: bool,
+}
+
+impl Default for ExplorerLinks {
+    /// Devnet, the default endpoint
+    fn default() -> Self {
+        Self { explorer: Explorer::default(), cluster: Cluster::Devnet, print_urls: false }
+    }
+}
+
+impl ExplorerLinks {
+    pub fn new(explorer: Explorer, rpc_url: &str, print_urls: bool) -> Self {
+        Self { explorer, cluster: Cluster::from_rpc_url(rpc_url), print_urls }
+    }
+
+    /// Link to `signature` on this explorer and cluster.
+    pub fn url_for(&self, signature: &Signature) -> Option<String> {
+        transaction_url(self.explorer, self.cluster, signature)
+    }
+
+    /// `solana confirm` command for `signature` on this cluster.
+    pub fn confirm_command(&self, signature: &Signature) -> String {
+        format!("solana confirm -v {} --url {}", signature, self.cluster.moniker())
+    }
+
+    /// Note the explorer link for a sent transaction under `--print-transaction-url`.
+    pub fn note_transaction_url(&self, notes: &Notes, signature: &Signature) {
+        if self.print_urls {
+            if let Some(url) = self.url_for(signature) {
+                note!(notes, "🌐 {}", url);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transaction_url_per_cluster() {
+        assert_eq!(Cluster::from_rpc_url("https://api.devnet.solana.com"), Cluster::Devnet);
+        assert_eq!(Cluster::from_rpc_url("https://devnet.helius-rpc.com/?api-key=x"), Cluster::Devnet);
+        assert_eq!(Cluster::from_rpc_url("http://127.0.0.1:8899"), Cluster::Localnet);
+        assert_eq!(Cluster::from_rpc_url("https://mainnet.helius-rpc.com/?api-key=x"), Cluster::Mainnet);
+
+        let signature = Signature::default();
+        assert_eq!(transaction_url(Explorer::Solscan, Cluster::Mainnet, &signature).unwrap(),
+                   format!("https://solscan.io/tx/{}", signature));
+        assert_eq!(transaction_url(Explorer::Solscan, Cluster::Devnet, &signature).unwrap(),
+                   format!("https://solscan.io/tx/{}?cluster=devnet", signature));
+        assert_eq!(transaction_url(Explorer::Solanafm, Cluster::Testnet, &signature).unwrap(),
+                   format!("https://solana.fm/tx/{}?cluster=testnet-solana", signature));
+        assert!(transaction_url(Explorer::Explorer, Cluster::Localnet, &signature).unwrap().contains("customUrl="));
+        assert_eq!(transaction_url(Explorer::Solscan, Cluster::Localnet, &signature), None);
+    }
+}
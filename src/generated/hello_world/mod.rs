@@ -17,6 +17,27 @@ pub fn program_id() -> Pubkey {
 }
 
 
+/// Bytes ahead of the message in a HelloWorld account: the 8-byte account discriminator and the
+/// 4-byte Borsh string length.
+pub const ACCOUNT_HEADER_LEN: usize = 8 + 4;
+
+/// Message bytes an account of `data_len` bytes can hold; its space is fixed when `initialize` creates it.
+pub fn message_capacity(data_len: usize) -> usize {
+    data_len.saturating_sub(ACCOUNT_HEADER_LEN)
+}
+
+/// Rejects a message over `max_len` bytes; counts UTF-8 bytes, not chars. `limit` names what sets the bound.
+pub fn check_message(message: &str, max_len: usize, limit: &str) -> Result<()> {
+    if message.len() > max_len {
+        return Err(anyhow::anyhow!(
+            "Message is {} bytes ({} chars); {} holds at most {} bytes",
+            message.len(), message.chars().count(), limit, max_len
+        ));
+    }
+    Ok(())
+}
+
+
 pub const GET_MESSAGE_DISCRIMINATOR: [u8; 8] = [159, 69, 186, 171, 244, 131, 99, 223];
 
 pub fn get_message_instruction(
@@ -99,3 +120,20 @@ pub fn update_message_instruction(
 pub struct HelloWorldAccount {
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_message_counts_bytes() {
+        let max_len = message_capacity(ACCOUNT_HEADER_LEN + 200);
+        assert_eq!(max_len, 200);
+        assert!(check_message(&"a".repeat(max_len), max_len, "the account").is_ok());
+        assert!(check_message(&"a".repeat(max_len + 1), max_len, "the account").is_err());
+
+        // 'é' is 2 bytes in UTF-8, so 101 of them overflow 200 bytes despite being 101 chars
+        let err = check_message(&"é".repeat(101), max_len, "the account").unwrap_err();
+        assert!(err.to_string().contains("202 bytes (101 chars); the account holds at most 200 bytes"));
+        assert_eq!(message_capacity(4), 0);
+    }
+}
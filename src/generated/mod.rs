@@ -1,2 +1,3 @@
 pub mod send_program;
 pub mod hello_world;
+pub mod swap_program;
@@ -15,12 +15,27 @@ pub fn program_id() -> Pubkey {
 }
 
 
+pub const ERRORS: &[(u32, &str, &str)] = &[
+    (6000, "AmountTooSmall", "Amount must be at least 0.001 SOL (1,000,000 lamports)"),
+    (6001, "Unauthorized", "Unauthorized: sender does not own the send account"),
+];
+
 pub fn decode_error(code: u32) -> Option<&'static str> {
-    match code {
-        6000 => Some("Amount must be at least 0.001 SOL (1,000,000 lamports)"),
-        6001 => Some("Unauthorized: sender does not own the send account"),
-        _ => None,
+    ERRORS.iter()
+        .find(|(c, _, _)| *c == code)
+        .map(|(_, _, msg)| *msg)
+}
+
+/// Smallest amount `send_sol` accepts on-chain (error 6000 below this).
+pub const MIN_SEND_LAMPORTS: u64 = 1_000_000;
+
+/// Client-side mirror of the program's AmountTooSmall check.
+pub fn check_send_amount(lamports: u64) -> Result<()> {
+    if lamports < MIN_SEND_LAMPORTS {
+        let msg = decode_error(6000).unwrap_or("Amount below program minimum");
+        return Err(anyhow::anyhow!("{} - got {} lamports", msg, lamports));
     }
+    Ok(())
 }
 
 
@@ -100,6 +115,8 @@ pub fn send_sol_instruction(
 }
 
 
+pub const SEND_ACCOUNT_DISCRIMINATOR: [u8; 8] = [167, 114, 146, 182, 4, 151, 134, 228];
+
 #[derive(Debug, Clone)]
 pub struct SendAccount {
     pub owner: Pubkey,
@@ -107,3 +124,45 @@ pub struct SendAccount {
     pub transactions_count: u64,
 }
 
+impl SendAccount {
+    /// discriminator + owner + total_sent + transactions_count
+    pub const LEN: usize = 8 + 32 + 8 + 8;
+
+    pub fn decode(data: &[u8]) -> Result<Self> {
+        if data.len() < Self::LEN {
+            return Err(anyhow::anyhow!("SendAccount data too short: {} bytes, expected {}", data.len(), Self::LEN));
+        }
+        if data[..8] != SEND_ACCOUNT_DISCRIMINATOR {
+            return Err(anyhow::anyhow!("Account is not a SendAccount (discriminator mismatch)"));
+        }
+        Ok(Self {
+            owner: Pubkey::try_from(&data[8..40]).map_err(|_| anyhow::anyhow!("Invalid owner pubkey"))?,
+            total_sent: u64::from_le_bytes(data[40..48].try_into()?),
+            transactions_count: u64::from_le_bytes(data[48..56].try_into()?),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_send_account_decode() {
+        let owner = Pubkey::new_unique();
+        let mut data = SEND_ACCOUNT_DISCRIMINATOR.to_vec();
+        data.extend_from_slice(owner.as_ref());
+        data.extend_from_slice(&5_000_000u64.to_le_bytes());
+        data.extend_from_slice(&3u64.to_le_bytes());
+
+        let account = SendAccount::decode(&data).unwrap();
+        assert_eq!(account.owner, owner);
+        assert_eq!(account.total_sent, 5_000_000);
+        assert_eq!(account.transactions_count, 3);
+
+        data[0] ^= 0xff;
+        assert!(SendAccount::decode(&data).is_err());
+        assert!(SendAccount::decode(&data[..20]).is_err());
+    }
+}
+
@@ -0,0 +1,192 @@
+use anyhow::{anyhow, Result};
+use chrono::DateTime;
+use serde::Serialize;
+use solana_client::rpc_client::{GetConfirmedSignaturesForAddress2Config, RpcClient};
+use solana_client::rpc_request::RpcRequest;
+use solana_client::rpc_response::RpcConfirmedTransactionStatusWithSignature;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_sdk::transaction::VersionedTransaction;
+use std::str::FromStr;
+
+const PAGE_SIZE: usize = 1_000;
+
+/// `--since`/`--until` bounds on a signature's `block_time` (unix seconds, inclusive).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct HistoryWindow {
+    pub since: Option<i64>,
+    pub until: Option<i64>,
+}
+
+impl HistoryWindow {
+    pub fn parse(since: Option<&str>, until: Option<&str>) -> Result<Self> {
+        let parse = |flag: &str, value: Option<&str>| {
+            value.map(|v| DateTime::parse_from_rfc3339(v)
+                .map(|t| t.timestamp())
+                .map_err(|e| anyhow!("Invalid {} '{}' (expected RFC 3339, e.g. 2025-01-31T00:00:00Z): {}", flag, v, e)))
+                .transpose()
+        };
+        let window = Self { since: parse("--since", since)?, until: parse("--until", until)? };
+        if let (Some(since), Some(until)) = (window.since, window.until) {
+            if since > until {
+                return Err(anyhow!("--since must not be after --until"));
+            }
+        }
+        Ok(window)
+    }
+
+    /// Whether a signature belongs in the window; ones without a block time (still processing) always do.
+    pub fn contains(&self, block_time: Option<i64>) -> bool {
+        let Some(time) = block_time else { return true };
+        self.since.is_none_or(|since| time >= since) && self.until.is_none_or(|until| time <= until)
+    }
+
+    /// Older than `--since`. Signatures come newest first, so nothing after this one can match.
+    fn is_past(&self, block_time: Option<i64>) -> bool {
+        matches!((self.since, block_time), (Some(since), Some(time)) if time < since)
+    }
+}
+
+/// One signature of an address's history.
+#[derive(Debug, Serialize)]
+pub struct HistoryEntry {
+    pub signature: String,
+    pub slot: u64,
+    /// RFC 3339, or `None` while the transaction is still processing
+    pub block_time: Option<String>,
+    pub error: Option<String>,
+    pub memo: Option<String>,
+}
+
+impl From<&RpcConfirmedTransactionStatusWithSignature> for HistoryEntry {
+    fn from(status: &RpcConfirmedTransactionStatusWithSignature) -> Self {
+        Self {
+            signature: status.signature.clone(),
+            slot: status.slot,
+            block_time: format_block_time(status.block_time),
+            error: status.err.as_ref().map(|e| format!("{:?}", e)),
+            memo: status.memo.clone(),
+        }
+    }
+}
+
+pub fn format_block_time(block_time: Option<i64>) -> Option<String> {
+    block_time.and_then(|t| DateTime::from_timestamp(t, 0)).map(|t| t.to_rfc3339())
+}
+
+/// Page through `address`'s signatures, newest first, until `limit` fall inside `window` or the window is covered.
+pub fn fetch_history(
+    rpc_client: &RpcClient,
+    address: &Pubkey,
+    window: HistoryWindow,
+    limit: Option<usize>,
+) -> Result<Vec<HistoryEntry>> {
+    let limit = limit.unwrap_or(usize::MAX);
+    let mut entries = Vec::new();
+    let mut before = None;
+    loop {
+        let page = rpc_client.get_signatures_for_address_with_config(address, GetConfirmedSignaturesForAddress2Config {
+            before,
+            until: None,
+            limit: Some(PAGE_SIZE),
+            commitment: Some(rpc_client.commitment()),
+        })?;
+
+        for status in &page {
+            if window.is_past(status.block_time) {
+                return Ok(entries);
+            }
+            if window.contains(status.block_time) {
+                entries.push(HistoryEntry::from(status));
+                if entries.len() >= limit {
+                    return Ok(entries);
+                }
+            }
+        }
+
+        match page.last() {
+            Some(last) if page.len() == PAGE_SIZE => before = Some(Signature::from_str(&last.signature)?),
+            _ => return Ok(entries),
+        }
+    }
+}
+
+/// A landed transaction, decoded, with its outcome.
+#[derive(Debug)]
+pub struct FetchedTransaction {
+    pub transaction: VersionedTransaction,
+    pub error: Option<String>,
+}
+
+/// `getTransaction` for `signature` in base64, so its instructions can be decoded.
+pub fn fetch_transaction(rpc_client: &RpcClient, signature: &str) -> Result<FetchedTransaction> {
+    let response: serde_json::Value = rpc_client.send(RpcRequest::GetTransaction, serde_json::json!([
+        signature,
+        { "encoding": "base64", "commitment": "confirmed", "maxSupportedTransactionVersion": 0 },
+    ]))?;
+    parse_transaction_response(&response)
+        .map_err(|e| anyhow!("Transaction {}: {}", signature, e))
+}
+
+fn parse_transaction_response(response: &serde_json::Value) -> Result<FetchedTransaction> {
+    use base64::Engine;
+    if response.is_null() {
+        return Err(anyhow!("not found"));
+    }
+    let encoded = response["transaction"][0].as_str()
+        .ok_or_else(|| anyhow!("response has no base64 transaction"))?;
+    let bytes = base64::engine::general_purpose::STANDARD.decode(encoded)?;
+    let (transaction, _): (VersionedTransaction, usize) = bincode::serde::decode_from_slice(&bytes, bincode::config::legacy())?;
+    let error = match &response["meta"]["err"] {
+        serde_json::Value::Null => None,
+        err => Some(err.to_string()),
+    };
+    Ok(FetchedTransaction { transaction, error })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_history_window() {
+        let window = HistoryWindow::parse(Some("2025-01-01T00:00:00Z"), Some("2025-01-31T23:59:59+00:00")).unwrap();
+        assert_eq!(window.since, Some(1_735_689_600));
+
+        assert!(window.contains(Some(1_736_000_000)));
+        assert!(!window.contains(Some(1_735_689_599)));
+        assert!(!window.contains(Some(1_738_368_000)));
+        assert!(window.contains(None));
+
+        assert!(window.is_past(Some(1_735_689_599)));
+        assert!(!window.is_past(Some(1_738_368_000)));
+        assert!(!window.is_past(None));
+
+        assert!(HistoryWindow::parse(Some("yesterday"), None).is_err());
+        assert!(HistoryWindow::parse(Some("2025-02-01T00:00:00Z"), Some("2025-01-01T00:00:00Z")).is_err());
+        assert_eq!(format_block_time(Some(1_735_689_600)).as_deref(), Some("2025-01-01T00:00:00+00:00"));
+    }
+
+    #[test]
+    fn test_parse_transaction_response() {
+        use base64::Engine;
+        use solana_sdk::signature::Keypair;
+        use solana_sdk::signer::Signer;
+        let payer = Keypair::new();
+        let transfer = solana_sdk::system_instruction::transfer(&payer.pubkey(), &Pubkey::new_unique(), 1);
+        let transaction = VersionedTransaction::from(solana_sdk::transaction::Transaction::new_signed_with_payer(
+            &[transfer], Some(&payer.pubkey()), &[&payer], solana_sdk::hash::Hash::default(),
+        ));
+        let bytes = bincode::serde::encode_to_vec(&transaction, bincode::config::legacy()).unwrap();
+        let encoded = base64::engine::general_purpose::STANDARD.encode(bytes);
+
+        let response = serde_json::json!({ "transaction": [encoded, "base64"], "meta": { "err": null } });
+        let fetched = parse_transaction_response(&response).unwrap();
+        assert_eq!(fetched.transaction, transaction);
+        assert_eq!(fetched.error, None);
+
+        let failed = serde_json::json!({ "transaction": [encoded, "base64"], "meta": { "err": { "InstructionError": [0, { "Custom": 1 }] } } });
+        assert!(parse_transaction_response(&failed).unwrap().error.unwrap().contains("Custom"));
+        assert!(parse_transaction_response(&serde_json::Value::Null).is_err());
+    }
+}
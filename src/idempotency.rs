@@ -0,0 +1,241 @@
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::hash::Hash;
+use solana_sdk::signature::Signature;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use crate::outcome::{note, Notes};
+
+/// Default `--idempotency-window-secs`.
+pub const DEFAULT_WINDOW_SECS: u64 = 600;
+
+/// The one transaction sent under an `--idempotency-key`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IdempotencyEntry {
+    pub key: String,
+    pub signature: String,
+    /// Its recent blockhash: an unseen send can still land while this is valid
+    pub blockhash: String,
+    /// Unix seconds of the send
+    pub recorded_at: u64,
+}
+
+/// The idempotency log (`idempotency.json` in the cache directory).
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct IdempotencyLog {
+    entries: Vec<IdempotencyEntry>,
+}
+
+impl IdempotencyLog {
+    /// A missing file is an empty log.
+    pub fn load(path: &Path) -> Result<Self> {
+        match std::fs::read_to_string(path) {
+            Ok(content) => serde_json::from_str(&content)
+                .map_err(|e| anyhow!("Invalid idempotency log {}: {}", path.display(), e)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(anyhow!("Cannot read idempotency log {}: {}", path.display(), e)),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// The entry for `key` if it was sent within `window_secs` of `now`.
+    pub fn find(&self, key: &str, now: u64, window_secs: u64) -> Option<&IdempotencyEntry> {
+        self.entries.iter().find(|e| e.key == key && now.saturating_sub(e.recorded_at) <= window_secs)
+    }
+
+    /// Set `key`'s send to `signature`, replacing any earlier one and dropping entries older than the window.
+    pub fn record(&mut self, key: &str, signature: &Signature, blockhash: &Hash, now: u64, window_secs: u64) {
+        self.entries.retain(|e| e.key != key && now.saturating_sub(e.recorded_at) <= window_secs);
+        self.entries.push(IdempotencyEntry {
+            key: key.to_string(),
+            signature: signature.to_string(),
+            blockhash: blockhash.to_string(),
+            recorded_at: now,
+        });
+    }
+
+    /// Drop `key`, so its next send starts a fresh entry.
+    pub fn forget(&mut self, key: &str) {
+        self.entries.retain(|e| e.key != key);
+    }
+}
+
+/// A prior send that makes this run redundant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PriorSend {
+    /// Landed without error
+    Landed(Signature),
+    /// Not seen yet, but its blockhash is still valid
+    Pending(Signature),
+}
+
+/// Returned by `check` when the key's transaction already went out in an earlier run, so this one must not send.
+#[derive(Debug)]
+pub struct AlreadySent {
+    pub key: String,
+    pub prior: PriorSend,
+}
+
+impl std::fmt::Display for AlreadySent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.prior {
+            PriorSend::Landed(signature) => write!(f, "Idempotency key '{}' already landed as {}", self.key, signature),
+            PriorSend::Pending(signature) => write!(f, "Idempotency key '{}' has {} still in flight", self.key, signature),
+        }
+    }
+}
+
+impl std::error::Error for AlreadySent {}
+
+/// Whether the recorded send still counts: `landed` is `Some(ok)` once the cluster has seen it, `None` before.
+/// Failed and dropped sends don't count.
+fn standing(signature: Signature, landed: Option<bool>, blockhash_valid: impl FnOnce() -> Result<bool>) -> Result<Option<PriorSend>> {
+    Ok(match landed {
+        Some(true) => Some(PriorSend::Landed(signature)),
+        Some(false) => None,
+        None if blockhash_valid()? => Some(PriorSend::Pending(signature)),
+        None => None,
+    })
+}
+
+/// `--idempotency-key`: the key, its window, and the log it is recorded in.
+#[derive(Debug)]
+pub struct IdempotencyKey {
+    key: String,
+    window_secs: u64,
+    path: PathBuf,
+    lock: Mutex<()>,
+    /// Set once this run records a send, so a second transaction under the key is refused
+    sent_this_run: AtomicBool,
+}
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs()
+}
+
+impl IdempotencyKey {
+    /// Record the transaction this run submits under `key`, in `idempotency.json` in `cache_dir`.
+    pub fn new(key: String, window_secs: u64, cache_dir: &str) -> Self {
+        Self {
+            key,
+            window_secs,
+            path: Path::new(cache_dir).join("idempotency.json"),
+            lock: Mutex::new(()),
+            sent_this_run: AtomicBool::new(false),
+        }
+    }
+
+    /// Call before sending `signature` under this key. Errors with `AlreadySent` when an earlier run's send
+    /// landed or can still land, and refuses a second transaction in this run: a key covers one transaction.
+    /// Resubmitting the recorded transaction itself is fine; a failed or dropped one is cleared so this replaces it.
+    pub fn check(&self, rpc_client: &RpcClient, signature: &Signature, notes: &Notes) -> Result<()> {
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        let mut log = IdempotencyLog::load(&self.path)?;
+        let Some(entry) = log.find(&self.key, now(), self.window_secs).cloned() else {
+            return Ok(());
+        };
+        if entry.signature == signature.to_string() {
+            return Ok(());
+        }
+        let signature = Signature::from_str(&entry.signature)
+            .map_err(|_| anyhow!("Invalid signature in idempotency log: {}", entry.signature))?;
+        let status = rpc_client.get_signature_statuses_with_history(&[signature])
+            .map_err(|e| anyhow!("Cannot check prior send {} under idempotency key '{}': {}", signature, self.key, e))?
+            .value.into_iter().next().flatten();
+        let landed = status.map(|status| status.err.is_none());
+        let prior = standing(signature, landed, || {
+            let blockhash = Hash::from_str(&entry.blockhash)
+                .map_err(|_| anyhow!("Invalid blockhash in idempotency log: {}", entry.blockhash))?;
+            Ok(rpc_client.is_blockhash_valid(&blockhash, CommitmentConfig::processed())?)
+        })?;
+        let sent_this_run = self.sent_this_run.load(Ordering::Relaxed);
+        match prior {
+            Some(_) if sent_this_run => {
+                return Err(anyhow!(
+                    "--idempotency-key '{}' covers one transaction, but this command sends more than one ({} already went out)",
+                    self.key, signature
+                ));
+            }
+            Some(prior) => return Err(AlreadySent { key: self.key.clone(), prior }.into()),
+            None if sent_this_run => {}
+            None if landed == Some(false) => note!(notes, "⚠️  Prior send {} under '{}' failed on-chain; sending again", signature, self.key),
+            None => note!(notes, "⚠️  Prior send {} under '{}' was dropped; sending again", signature, self.key),
+        }
+        log.forget(&self.key);
+        log.save(&self.path)
+    }
+
+    /// Note a submitted transaction under this key. The transaction is already out, so a log that can't be
+    /// written is a warning.
+    pub fn record(&self, signature: &Signature, blockhash: &Hash, notes: &Notes) {
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        self.sent_this_run.store(true, Ordering::Relaxed);
+        let result = IdempotencyLog::load(&self.path).and_then(|mut log| {
+            log.record(&self.key, signature, blockhash, now(), self.window_secs);
+            log.save(&self.path)
+        });
+        if let Err(e) = result {
+            note!(notes, "⚠️  Could not record {} under idempotency key '{}': {}", signature, self.key, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_idempotency_log_window() {
+        let mut log = IdempotencyLog::default();
+        let first = Signature::new_unique();
+        let second = Signature::new_unique();
+        let blockhash = Hash::new_unique();
+        log.record("payroll-42", &first, &blockhash, 1_000, 600);
+        log.record("payroll-42", &second, &blockhash, 1_100, 600);
+
+        // One transaction per key: a retry's signature replaces the dropped one
+        let entry = log.find("payroll-42", 1_600, 600).unwrap();
+        assert_eq!(entry.signature, second.to_string());
+        assert_eq!(entry.blockhash, blockhash.to_string());
+        assert_eq!(entry.recorded_at, 1_100);
+        assert!(log.find("payroll-42", 1_701, 600).is_none());
+        assert!(log.find("other", 1_100, 600).is_none());
+
+        // An expired key starts over
+        let third = Signature::new_unique();
+        log.record("payroll-42", &third, &blockhash, 2_000, 600);
+        assert_eq!(log.find("payroll-42", 2_000, 600).unwrap().signature, third.to_string());
+        assert_eq!(log.entries.len(), 1);
+
+        let path = std::env::temp_dir().join(format!("idempotency_test_{}.json", std::process::id()));
+        log.save(&path).unwrap();
+        let loaded = IdempotencyLog::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.entries, log.entries);
+        assert!(IdempotencyLog::load(&path).unwrap().entries.is_empty());
+
+        log.forget("payroll-42");
+        assert!(log.find("payroll-42", 2_000, 600).is_none());
+    }
+
+    #[test]
+    fn test_prior_send_standing() {
+        let signature = Signature::new_unique();
+        assert_eq!(standing(signature, Some(true), || unreachable!()).unwrap(), Some(PriorSend::Landed(signature)));
+        assert_eq!(standing(signature, Some(false), || unreachable!()).unwrap(), None);
+        assert_eq!(standing(signature, None, || Ok(true)).unwrap(), Some(PriorSend::Pending(signature)));
+        assert_eq!(standing(signature, None, || Ok(false)).unwrap(), None);
+    }
+}
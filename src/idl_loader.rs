@@ -1,8 +1,12 @@
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use solana_sdk::instruction::{AccountMeta, Instruction};
 use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
+use crate::borsh_encoder::BorshEncoder;
+use crate::outcome::{note, Notes};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct IdlInstruction {
@@ -21,6 +25,9 @@ pub struct IdlAccount {
     pub signer: bool,
     #[serde(default)]
     pub optional: bool,
+    /// Fixed address (e.g. the System Program); filled in when the caller leaves the account out
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -37,32 +44,126 @@ pub struct IdlError {
     pub msg: String,
 }
 
+/// Anchor account discriminator prefix on every program-owned account.
+pub const ACCOUNT_DISCRIMINATOR_LEN: usize = 8;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IdlAccountDef {
+    pub name: String,
+    pub discriminator: [u8; 8],
+    /// Inline layout (older IDLs); newer IDLs put it in `types` under the account's name
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub ty: Option<IdlTypeDefTy>,
+}
+
+/// One field of an account layout. Offsets include the discriminator; past a variable-length field they
+/// depend on the data, so they're `None`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FieldLayout {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub ty: String,
+    pub offset: Option<usize>,
+    pub size: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IdlTypeDef {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub ty: IdlTypeDefTy,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IdlTypeDefTy {
+    pub kind: String,
+    #[serde(default)]
+    pub fields: Vec<serde_json::Value>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProgramIdl {
     pub address: String,
     pub instructions: Vec<IdlInstruction>,
     pub errors: Option<Vec<IdlError>>,
+    #[serde(default)]
+    pub accounts: Vec<IdlAccountDef>,
+    #[serde(default)]
+    pub types: Vec<IdlTypeDef>,
 }
 
+#[derive(Clone)]
 pub struct IdlLoader {
     idls: HashMap<String, ProgramIdl>,
+    require_idl: bool,
+    notes: Notes,
 }
 
 impl IdlLoader {
     pub fn new() -> Self {
         Self {
             idls: HashMap::new(),
+            require_idl: false,
+            notes: Notes::default(),
         }
     }
 
+    /// Where IDL warnings go as files load.
+    pub fn with_notes(mut self, notes: Notes) -> Self {
+        self.notes = notes;
+        self
+    }
+
+    /// `--require-idl`: refuse to send any program instruction a loaded IDL can't validate.
+    pub fn with_require_idl(mut self, require_idl: bool) -> Self {
+        self.require_idl = require_idl;
+        self
+    }
+
+    /// Build a loader from already-parsed IDLs keyed by program ID, without touching disk.
+    #[cfg(test)]
+    pub fn from_idls(idls: HashMap<String, ProgramIdl>) -> Self {
+        Self { idls, require_idl: false, notes: Notes::default() }
+    }
+
+    /// Build a loader holding a single IDL given as JSON.
+    #[cfg(test)]
+    pub fn from_value(program_id: &str, idl: serde_json::Value) -> Result<Self> {
+        let idl: ProgramIdl = serde_json::from_value(idl)?;
+        Ok(Self::from_idls(HashMap::from([(program_id.to_string(), idl)])))
+    }
+
    
+    /// Load an IDL for `program_id`; errors if the IDL's own `address` names a different program.
     pub fn load_from_file<P: AsRef<Path>>(&mut self, path: P, program_id: &str) -> Result<()> {
+        let path = path.as_ref();
         let content = fs::read_to_string(path)?;
         let idl: ProgramIdl = serde_json::from_str(&content)?;
+        if idl.address != program_id {
+            return Err(anyhow::anyhow!(
+                "IDL {} declares address {}, but was loaded for program {}",
+                path.display(), idl.address, program_id
+            ));
+        }
+        for warning in check_discriminators(&idl)? {
+            note!(self.notes, "⚠️  {}: {}", program_id, warning);
+        }
+        self.idls.insert(program_id.to_string(), idl);
+        Ok(())
+    }
+
+    /// Add an IDL built in memory (e.g. from a `programs.d/` definition). Discriminators must be
+    /// distinct but needn't follow Anchor's scheme.
+    pub fn add_idl(&mut self, program_id: &str, idl: ProgramIdl) -> Result<()> {
+        check_discriminators(&idl)?;
         self.idls.insert(program_id.to_string(), idl);
         Ok(())
     }
 
+    pub fn has_idl(&self, program_id: &str) -> bool {
+        self.idls.contains_key(program_id)
+    }
+
    
     pub fn get_instruction(&self, program_id: &str, instruction_name: &str) -> Result<&IdlInstruction> {
         let idl = self.idls.get(program_id)
@@ -74,6 +175,100 @@ impl IdlLoader {
     }
 
    
+    /// Check provided metas against the IDL account list. Trailing optional accounts may be
+    /// omitted; every account that is provided must satisfy its signer/writable flags.
+    pub fn validate_account_metas(&self, program_id: &str, instruction_name: &str, metas: &[AccountMeta]) -> Result<()> {
+        let spec = self.get_instruction(program_id, instruction_name)?;
+        let required = spec.accounts.iter()
+            .rposition(|acc| !acc.optional)
+            .map(|i| i + 1)
+            .unwrap_or(0);
+
+        if metas.len() < required || metas.len() > spec.accounts.len() {
+            let expected = if required == spec.accounts.len() {
+                format!("{}", required)
+            } else {
+                format!("{}-{}", required, spec.accounts.len())
+            };
+            return Err(anyhow::anyhow!("Account count mismatch: IDL expects {}, provided {}", expected, metas.len()));
+        }
+
+        for (i, (idl_acc, meta)) in spec.accounts.iter().zip(metas.iter()).enumerate() {
+           
+            if idl_acc.signer && !meta.is_signer {
+                return Err(anyhow::anyhow!("Account #{} ('{}') must be signer", i, idl_acc.name));
+            }
+           
+            if idl_acc.writable && !meta.is_writable {
+                return Err(anyhow::anyhow!("Account #{} ('{}') must be writable", i, idl_acc.name));
+            }
+        }
+        Ok(())
+    }
+
+    /// Check a JSON args value is an object holding every argument the instruction declares.
+    pub fn check_args(&self, program_id: &str, instruction_name: &str, args: serde_json::Value) -> Result<HashMap<String, serde_json::Value>> {
+        let spec = self.get_instruction(program_id, instruction_name)?;
+        let serde_json::Value::Object(args) = args else {
+            return Err(anyhow::anyhow!("Instruction args must be a JSON object, got: {}", args));
+        };
+
+        let missing: Vec<&str> = spec.args.iter()
+            .filter(|arg| !args.contains_key(&arg.name))
+            .map(|arg| arg.name.as_str())
+            .collect();
+        if !missing.is_empty() {
+            return Err(anyhow::anyhow!("Missing required argument(s) for '{}': {}", instruction_name, missing.join(", ")));
+        }
+        Ok(args.into_iter().collect())
+    }
+
+    /// Match a built instruction to its IDL instruction by discriminator, then check its accounts
+    /// and that its data decodes as exactly the IDL's args.
+    pub fn validate_instruction(&self, instruction: &Instruction) -> Result<&IdlInstruction> {
+        let program_id = instruction.program_id.to_string();
+        if !self.idls.contains_key(&program_id) {
+            return Err(anyhow::anyhow!("No IDL loaded for program {}", program_id));
+        }
+        let spec = self.find_instruction_by_discriminator(&program_id, &instruction.data)
+            .ok_or_else(|| anyhow::anyhow!("Instruction data for {} matches no IDL discriminator", program_id))?;
+        self.validate_account_metas(&program_id, &spec.name, &instruction.accounts)
+            .map_err(|e| anyhow::anyhow!("'{}': {}", spec.name, e))?;
+
+        let encoder = BorshEncoder::new();
+        let mut offset = 8;
+        for arg in &spec.args {
+            let (_, used) = encoder.decode_value(&instruction.data[offset..], &arg.field_type)
+                .map_err(|e| anyhow::anyhow!("'{}': argument '{}' does not decode as {}: {}", spec.name, arg.name, arg.field_type, e))?;
+            offset += used;
+        }
+        if offset != instruction.data.len() {
+            return Err(anyhow::anyhow!("'{}': {} bytes of data past the IDL args", spec.name, instruction.data.len() - offset));
+        }
+        Ok(spec)
+    }
+
+    /// Under `--require-idl`, validate every instruction before it is sent.
+    pub fn check_required(&self, instructions: &[Instruction]) -> Result<()> {
+        if !self.require_idl {
+            return Ok(());
+        }
+        for instruction in instructions {
+            self.validate_instruction(instruction)
+                .map_err(|e| anyhow::anyhow!("--require-idl: {}", e))?;
+        }
+        Ok(())
+    }
+
+    /// Match instruction data's leading 8 bytes against the program's IDL discriminators.
+    pub fn find_instruction_by_discriminator(&self, program_id: &str, data: &[u8]) -> Option<&IdlInstruction> {
+        let discriminator = data.get(..8)?;
+        self.idls.get(program_id)?
+            .instructions.iter()
+            .find(|inst| inst.discriminator[..] == *discriminator)
+    }
+
+   
     pub fn get_discriminator(&self, program_id: &str, instruction_name: &str) -> Result<[u8; 8]> {
         let instruction = self.get_instruction(program_id, instruction_name)?;
         Ok(instruction.discriminator)
@@ -87,6 +282,13 @@ impl IdlLoader {
     }
 
    
+    pub fn get_errors(&self, program_id: &str) -> Result<&[IdlError]> {
+        let idl = self.idls.get(program_id)
+            .ok_or_else(|| anyhow::anyhow!("IDL not found for program: {}", program_id))?;
+        Ok(idl.errors.as_deref().unwrap_or(&[]))
+    }
+
+   
     pub fn decode_error(&self, program_id: &str, error_code: u32) -> Option<String> {
         if let Some(idl) = self.idls.get(program_id) {
             if let Some(errors) = &idl.errors {
@@ -100,12 +302,199 @@ impl IdlLoader {
         None
     }
 
+    /// On-chain size (discriminator + fields) of an IDL account with only fixed-size fields.
+    pub fn account_size(&self, program_id: &str, account_name: &str) -> Result<usize> {
+        self.account_size_with_max_lens(program_id, account_name, &HashMap::new())
+    }
+
+    /// Like `account_size`, with the max element/byte count for each variable-length
+    /// (string, bytes, vec) top-level field given by field name.
+    pub fn account_size_with_max_lens(
+        &self,
+        program_id: &str,
+        account_name: &str,
+        max_lens: &HashMap<String, usize>,
+    ) -> Result<usize> {
+        let idl = self.idls.get(program_id)
+            .ok_or_else(|| anyhow::anyhow!("IDL not found for program: {}", program_id))?;
+        if !idl.accounts.iter().any(|acc| acc.name == account_name) {
+            return Err(anyhow::anyhow!("Account '{}' not found in IDL", account_name));
+        }
+        let fields_size = Self::struct_size(idl, account_name, max_lens)?;
+        Ok(ACCOUNT_DISCRIMINATOR_LEN + fields_size)
+    }
+
+    /// Field names, types, offsets, and sizes of an IDL account, plus its discriminator.
+    pub fn account_layout(&self, program_id: &str, account_name: &str) -> Result<([u8; 8], Vec<FieldLayout>)> {
+        let idl = self.idls.get(program_id)
+            .ok_or_else(|| anyhow::anyhow!("IDL not found for program: {}", program_id))?;
+        let account = idl.accounts.iter().find(|acc| acc.name == account_name)
+            .ok_or_else(|| anyhow::anyhow!("Account '{}' not found in IDL", account_name))?;
+        let type_def = Self::type_def(idl, account_name)?;
+
+        let mut offset = Some(ACCOUNT_DISCRIMINATOR_LEN);
+        let mut fields = Vec::with_capacity(type_def.fields.len());
+        for field in &type_def.fields {
+            let name = field.get("name").and_then(|n| n.as_str()).unwrap_or("<unnamed>");
+            let ty = field.get("type")
+                .ok_or_else(|| anyhow::anyhow!("Field '{}' of '{}' has no type", name, account_name))?;
+            let size = Self::type_size(idl, ty, None).ok();
+            fields.push(FieldLayout { name: name.to_string(), ty: type_name(ty), offset, size });
+            offset = offset.zip(size).map(|(offset, size)| offset + size);
+        }
+        Ok((account.discriminator, fields))
+    }
+
+    /// Struct or enum definition named `name`: from `types`, else inline on an account.
+    fn type_def<'a>(idl: &'a ProgramIdl, name: &str) -> Result<&'a IdlTypeDefTy> {
+        idl.types.iter()
+            .find(|t| t.name == name)
+            .map(|t| &t.ty)
+            .or_else(|| idl.accounts.iter().find(|acc| acc.name == name).and_then(|acc| acc.ty.as_ref()))
+            .ok_or_else(|| anyhow::anyhow!("Type '{}' not found in IDL", name))
+    }
+
+    /// Decode program-owned account data by matching its discriminator against the IDL's accounts.
+    /// `Ok(None)` when there's no IDL or no account type with that discriminator.
+    pub fn decode_account(&self, program_id: &str, data: &[u8]) -> Result<Option<(String, Vec<(String, serde_json::Value)>)>> {
+        let Some(idl) = self.idls.get(program_id) else { return Ok(None) };
+        let Some(discriminator) = data.get(..ACCOUNT_DISCRIMINATOR_LEN) else { return Ok(None) };
+        let Some(account) = idl.accounts.iter().find(|acc| acc.discriminator[..] == *discriminator) else {
+            return Ok(None);
+        };
+        let type_def = Self::type_def(idl, &account.name)?;
+
+        let encoder = crate::borsh_encoder::BorshEncoder::new();
+        let mut offset = ACCOUNT_DISCRIMINATOR_LEN;
+        let mut fields = Vec::with_capacity(type_def.fields.len());
+        for field in &type_def.fields {
+            let name = field.get("name").and_then(|n| n.as_str()).unwrap_or("<unnamed>");
+            let ty = field.get("type").and_then(|t| t.as_str())
+                .ok_or_else(|| anyhow::anyhow!("Field '{}' of '{}' has a non-primitive type", name, account.name))?;
+            let (value, used) = encoder.decode_value(&data[offset..], ty)
+                .map_err(|e| anyhow::anyhow!("Field '{}' of '{}': {}", name, account.name, e))?;
+            fields.push((name.to_string(), value));
+            offset += used;
+        }
+        Ok(Some((account.name.clone(), fields)))
+    }
+
+    fn struct_size(idl: &ProgramIdl, type_name: &str, max_lens: &HashMap<String, usize>) -> Result<usize> {
+        let type_def = Self::type_def(idl, type_name)?;
+        if type_def.kind != "struct" {
+            return Err(anyhow::anyhow!("Type '{}' is a {}, only structs have a fixed layout", type_name, type_def.kind));
+        }
+
+        let mut size = 0;
+        for field in &type_def.fields {
+            let name = field.get("name").and_then(|n| n.as_str()).unwrap_or("<unnamed>");
+            let ty = field.get("type")
+                .ok_or_else(|| anyhow::anyhow!("Field '{}' of '{}' has no type", name, type_name))?;
+            size += Self::type_size(idl, ty, max_lens.get(name).copied())
+                .map_err(|e| anyhow::anyhow!("Field '{}' of '{}': {}", name, type_name, e))?;
+        }
+        Ok(size)
+    }
+
+    fn type_size(idl: &ProgramIdl, ty: &serde_json::Value, max_len: Option<usize>) -> Result<usize> {
+        let no_max = || anyhow::anyhow!("variable-length type needs an explicit max length");
+        if let Some(primitive) = ty.as_str() {
+            return match primitive {
+                "bool" | "u8" | "i8" => Ok(1),
+                "u16" | "i16" => Ok(2),
+                "u32" | "i32" | "f32" => Ok(4),
+                "u64" | "i64" | "f64" => Ok(8),
+                "u128" | "i128" => Ok(16),
+                "pubkey" | "publicKey" => Ok(32),
+                "string" | "bytes" => Ok(4 + max_len.ok_or_else(no_max)?),
+                other => Err(anyhow::anyhow!("unsupported type '{}'", other)),
+            };
+        }
+
+        if let Some(inner) = ty.get("option") {
+            return Ok(1 + Self::type_size(idl, inner, max_len)?);
+        }
+        if let Some(inner) = ty.get("vec") {
+            let max_len = max_len.ok_or_else(no_max)?;
+            return Ok(4 + max_len * Self::type_size(idl, inner, None)?);
+        }
+        if let Some(array) = ty.get("array").and_then(|a| a.as_array()) {
+            let len = array.get(1).and_then(|n| n.as_u64())
+                .ok_or_else(|| anyhow::anyhow!("array type needs a literal length"))?;
+            return Ok(len as usize * Self::type_size(idl, &array[0], None)?);
+        }
+        if let Some(defined) = ty.get("defined") {
+            let name = defined.as_str()
+                .or_else(|| defined.get("name").and_then(|n| n.as_str()))
+                .ok_or_else(|| anyhow::anyhow!("malformed defined type"))?;
+            return Self::struct_size(idl, name, &HashMap::new());
+        }
+        Err(anyhow::anyhow!("unsupported type {}", ty))
+    }
+
     
     pub fn list_programs(&self) -> Vec<&String> {
         self.idls.keys().collect()
     }
 }
 
+/// Readable IDL type: `u64`, `vec<u8>`, `option<pubkey>`, `[u8; 32]`, or a defined type's name.
+pub fn type_name(ty: &serde_json::Value) -> String {
+    if let Some(primitive) = ty.as_str() {
+        return primitive.to_string();
+    }
+    if let Some(inner) = ty.get("option") {
+        return format!("option<{}>", type_name(inner));
+    }
+    if let Some(inner) = ty.get("vec") {
+        return format!("vec<{}>", type_name(inner));
+    }
+    if let Some(array) = ty.get("array").and_then(|a| a.as_array()) {
+        if let (Some(inner), Some(len)) = (array.first(), array.get(1)) {
+            return format!("[{}; {}]", type_name(inner), len);
+        }
+    }
+    if let Some(defined) = ty.get("defined") {
+        if let Some(name) = defined.as_str().or_else(|| defined.get("name").and_then(|n| n.as_str())) {
+            return name.to_string();
+        }
+    }
+    ty.to_string()
+}
+
+/// Anchor's instruction discriminator: `sha256("global:<name>")[..8]`.
+pub fn anchor_discriminator(instruction_name: &str) -> [u8; 8] {
+    namespaced_discriminator("global", instruction_name)
+}
+
+/// `sha256("<namespace>:<name>")[..8]`, e.g. `account:<Type>` for Anchor account discriminators.
+pub fn namespaced_discriminator(namespace: &str, name: &str) -> [u8; 8] {
+    let hash = Sha256::digest(format!("{}:{}", namespace, name).as_bytes());
+    let mut discriminator = [0u8; 8];
+    discriminator.copy_from_slice(&hash[..8]);
+    discriminator
+}
+
+/// Error on instructions sharing a discriminator; return warnings for non-Anchor-standard ones (often a stale IDL).
+fn check_discriminators(idl: &ProgramIdl) -> Result<Vec<String>> {
+    let mut seen: HashMap<[u8; 8], &str> = HashMap::new();
+    let mut warnings = Vec::new();
+    for inst in &idl.instructions {
+        if let Some(other) = seen.insert(inst.discriminator, &inst.name) {
+            return Err(anyhow::anyhow!(
+                "Instructions '{}' and '{}' share discriminator {:?}", other, inst.name, inst.discriminator
+            ));
+        }
+        if inst.discriminator != anchor_discriminator(&inst.name) {
+            warnings.push(format!(
+                "discriminator for '{}' doesn't match sha256(\"global:{}\")[..8]; the IDL may be stale",
+                inst.name, inst.name
+            ));
+        }
+    }
+    Ok(warnings)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -117,4 +506,221 @@ mod tests {
        
         assert_eq!(loader.list_programs().len(), 0);
     }
+
+    #[test]
+    fn test_validate_instruction() {
+        let program_id = solana_sdk::pubkey::Pubkey::new_unique();
+        let loader = IdlLoader::from_value(&program_id.to_string(), serde_json::json!({
+            "address": program_id.to_string(),
+            "instructions": [{
+                "name": "deposit",
+                "discriminator": [1, 2, 3, 4, 5, 6, 7, 8],
+                "accounts": [{ "name": "user", "writable": true, "signer": true }],
+                "args": [{ "name": "amount", "type": "u64" }]
+            }],
+            "errors": null
+        })).unwrap();
+        let user = solana_sdk::pubkey::Pubkey::new_unique();
+        let mut data = vec![1, 2, 3, 4, 5, 6, 7, 8];
+        data.extend_from_slice(&5u64.to_le_bytes());
+        let ix = Instruction { program_id, accounts: vec![AccountMeta::new(user, true)], data: data.clone() };
+        assert_eq!(loader.validate_instruction(&ix).unwrap().name, "deposit");
+
+        let mut long = ix.clone();
+        long.data.push(0);
+        assert!(loader.validate_instruction(&long).unwrap_err().to_string().contains("1 bytes of data past"));
+        let short = Instruction { data: data[..12].to_vec(), ..ix.clone() };
+        assert!(loader.validate_instruction(&short).is_err());
+        let unsigned = Instruction { accounts: vec![AccountMeta::new(user, false)], ..ix.clone() };
+        assert!(loader.validate_instruction(&unsigned).is_err());
+        let wrong_discriminator = Instruction { data: vec![0; 16], ..ix.clone() };
+        assert!(loader.validate_instruction(&wrong_discriminator).is_err());
+        let unknown = Instruction { program_id: solana_sdk::pubkey::Pubkey::new_unique(), ..ix };
+        assert!(loader.validate_instruction(&unknown).unwrap_err().to_string().contains("No IDL loaded"));
+    }
+
+    #[test]
+    fn test_get_errors() {
+        let mut loader = IdlLoader::new();
+        let program_id = "Bj4vH3tVu1GjCHeU3peRfYyxJpAzooyZCTU6rRFR4AnY";
+        loader.load_from_file(concat!(env!("CARGO_MANIFEST_DIR"), "/send_program.json"), program_id).unwrap();
+
+        let errors = loader.get_errors(program_id).unwrap();
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].code, 6000);
+        assert_eq!(errors[0].name, "AmountTooSmall");
+
+        assert!(loader.get_errors("11111111111111111111111111111111").is_err());
+    }
+
+    #[test]
+    fn test_load_rejects_mismatched_address() {
+        let mut loader = IdlLoader::new();
+        let hello_world_id = "5PiuXarsz2F7Q6NpSCtdBbK6vroQWiGSdJZW3fPkjWHw";
+        let err = loader.load_from_file(concat!(env!("CARGO_MANIFEST_DIR"), "/send_program.json"), hello_world_id).unwrap_err();
+        assert!(err.to_string().contains("declares address Bj4vH3tVu1GjCHeU3peRfYyxJpAzooyZCTU6rRFR4AnY"));
+        assert!(loader.get_instructions(hello_world_id).is_err());
+    }
+
+    #[test]
+    fn test_validate_optional_trailing_account() {
+        let program_id = "Bj4vH3tVu1GjCHeU3peRfYyxJpAzooyZCTU6rRFR4AnY";
+        let loader = IdlLoader::from_value(program_id, serde_json::json!({
+            "address": program_id,
+            "instructions": [{
+                "name": "transfer",
+                "discriminator": [0, 0, 0, 0, 0, 0, 0, 1],
+                "accounts": [
+                    { "name": "authority", "signer": true },
+                    { "name": "vault", "writable": true },
+                    { "name": "referrer", "writable": true, "optional": true }
+                ],
+                "args": []
+            }],
+            "errors": null
+        })).unwrap();
+
+        let authority = solana_sdk::pubkey::Pubkey::new_unique();
+        let vault = solana_sdk::pubkey::Pubkey::new_unique();
+        let referrer = solana_sdk::pubkey::Pubkey::new_unique();
+
+        let without_optional = vec![
+            AccountMeta::new_readonly(authority, true),
+            AccountMeta::new(vault, false),
+        ];
+        assert!(loader.validate_account_metas(program_id, "transfer", &without_optional).is_ok());
+
+        let mut with_optional = without_optional.clone();
+        with_optional.push(AccountMeta::new(referrer, false));
+        assert!(loader.validate_account_metas(program_id, "transfer", &with_optional).is_ok());
+
+        // Optional accounts that are provided still have their flags enforced
+        with_optional[2] = AccountMeta::new_readonly(referrer, false);
+        assert!(loader.validate_account_metas(program_id, "transfer", &with_optional).is_err());
+
+        assert!(loader.validate_account_metas(program_id, "transfer", &without_optional[..1]).is_err());
+    }
+
+    #[test]
+    fn test_namespaced_discriminator() {
+        assert_eq!(namespaced_discriminator("global", "initialize"), [175, 175, 109, 31, 13, 152, 155, 237]);
+        assert_eq!(anchor_discriminator("initialize"), namespaced_discriminator("global", "initialize"));
+        assert_ne!(namespaced_discriminator("account", "initialize"), anchor_discriminator("initialize"));
+    }
+
+    #[test]
+    fn test_discriminator_checks() {
+        let program_id = "Bj4vH3tVu1GjCHeU3peRfYyxJpAzooyZCTU6rRFR4AnY";
+        let nanos = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_nanos();
+        let path = std::env::temp_dir().join(format!("colliding_idl_test_{}_{}.json", std::process::id(), nanos));
+        fs::write(&path, serde_json::json!({
+            "address": program_id,
+            "instructions": [
+                { "name": "deposit", "discriminator": [1, 2, 3, 4, 5, 6, 7, 8], "accounts": [], "args": [] },
+                { "name": "withdraw", "discriminator": [1, 2, 3, 4, 5, 6, 7, 8], "accounts": [], "args": [] }
+            ],
+            "errors": null
+        }).to_string()).unwrap();
+
+        let err = IdlLoader::new().load_from_file(&path, program_id).unwrap_err();
+        assert!(err.to_string().contains("'deposit' and 'withdraw'"));
+        fs::remove_file(&path).unwrap();
+
+        // send_program.json was generated by Anchor, so it passes without warnings
+        let idl: ProgramIdl = serde_json::from_str(
+            &fs::read_to_string(concat!(env!("CARGO_MANIFEST_DIR"), "/send_program.json")).unwrap()
+        ).unwrap();
+        assert!(check_discriminators(&idl).unwrap().is_empty());
+
+        // A renamed instruction keeps its old discriminator: loads, with a stale-IDL warning
+        let mut stale = idl.clone();
+        stale.instructions[0].name.push_str("_v2");
+        let warnings = check_discriminators(&stale).unwrap();
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains(&stale.instructions[0].name) && warnings[0].contains("may be stale"));
+    }
+
+    #[test]
+    fn test_decode_account() {
+        let program_id = "Bj4vH3tVu1GjCHeU3peRfYyxJpAzooyZCTU6rRFR4AnY";
+        let mut loader = IdlLoader::new();
+        loader.load_from_file(concat!(env!("CARGO_MANIFEST_DIR"), "/send_program.json"), program_id).unwrap();
+
+        let owner = solana_sdk::pubkey::Pubkey::new_unique();
+        let mut data = crate::generated::send_program::SEND_ACCOUNT_DISCRIMINATOR.to_vec();
+        data.extend_from_slice(owner.as_ref());
+        data.extend_from_slice(&7u64.to_le_bytes());
+        data.extend_from_slice(&2u64.to_le_bytes());
+
+        let (name, fields) = loader.decode_account(program_id, &data).unwrap().unwrap();
+        assert_eq!(name, "SendAccount");
+        assert_eq!(fields[0], ("owner".to_string(), serde_json::json!(owner.to_string())));
+        assert_eq!(fields[1].1, 7);
+        assert_eq!(fields[2].1, 2);
+
+        assert!(loader.decode_account(program_id, &[0u8; 56]).unwrap().is_none());
+        assert!(loader.decode_account(program_id, &data[..20]).is_err());
+    }
+
+    #[test]
+    fn test_check_args() {
+        let program_id = "Bj4vH3tVu1GjCHeU3peRfYyxJpAzooyZCTU6rRFR4AnY";
+        let mut loader = IdlLoader::new();
+        loader.load_from_file(concat!(env!("CARGO_MANIFEST_DIR"), "/send_program.json"), program_id).unwrap();
+
+        let args = loader.check_args(program_id, "send_sol", serde_json::json!({
+            "amount": 1_000_000,
+            "recipient": program_id,
+        })).unwrap();
+        assert_eq!(args["amount"], 1_000_000);
+
+        let missing = loader.check_args(program_id, "send_sol", serde_json::json!({ "amount": 1 })).unwrap_err();
+        assert!(missing.to_string().contains("recipient"));
+        assert!(loader.check_args(program_id, "send_sol", serde_json::json!([1, 2])).is_err());
+    }
+
+    #[test]
+    fn test_send_account_layout() {
+        let mut loader = IdlLoader::new();
+        let program_id = "Bj4vH3tVu1GjCHeU3peRfYyxJpAzooyZCTU6rRFR4AnY";
+        loader.load_from_file(concat!(env!("CARGO_MANIFEST_DIR"), "/send_program.json"), program_id).unwrap();
+
+        let (discriminator, fields) = loader.account_layout(program_id, "SendAccount").unwrap();
+        assert_eq!(discriminator, namespaced_discriminator("account", "SendAccount"));
+        let layout: Vec<(&str, &str, Option<usize>, Option<usize>)> = fields.iter()
+            .map(|f| (f.name.as_str(), f.ty.as_str(), f.offset, f.size))
+            .collect();
+        assert_eq!(layout, vec![
+            ("owner", "pubkey", Some(8), Some(32)),
+            ("total_sent", "u64", Some(40), Some(8)),
+            ("transactions_count", "u64", Some(48), Some(8)),
+        ]);
+        assert!(loader.account_layout(program_id, "Missing").is_err());
+
+        // Offsets after a variable-length field depend on the data
+        let idl = loader.idls.get_mut(program_id).unwrap();
+        idl.types[0].ty.fields.insert(1, serde_json::json!({ "name": "memo", "type": { "vec": "u8" } }));
+        let (_, fields) = loader.account_layout(program_id, "SendAccount").unwrap();
+        assert_eq!((fields[1].ty.as_str(), fields[1].offset, fields[1].size), ("vec<u8>", Some(40), None));
+        assert_eq!(fields[2].offset, None);
+    }
+
+    #[test]
+    fn test_account_size_from_layout() {
+        let mut loader = IdlLoader::new();
+        let program_id = "Bj4vH3tVu1GjCHeU3peRfYyxJpAzooyZCTU6rRFR4AnY";
+        loader.load_from_file(concat!(env!("CARGO_MANIFEST_DIR"), "/send_program.json"), program_id).unwrap();
+
+        // owner (32) + total_sent (8) + transactions_count (8)
+        let size = loader.account_size(program_id, "SendAccount").unwrap();
+        assert_eq!(size - ACCOUNT_DISCRIMINATOR_LEN, 48);
+        assert_eq!(size, 56);
+        assert!(loader.account_size(program_id, "Missing").is_err());
+
+        loader.idls.get_mut(program_id).unwrap().types[0].ty.fields
+            .push(serde_json::json!({ "name": "memo", "type": "string" }));
+        assert!(loader.account_size(program_id, "SendAccount").is_err());
+        let max_lens = HashMap::from([("memo".to_string(), 32)]);
+        assert_eq!(loader.account_size_with_max_lens(program_id, "SendAccount", &max_lens).unwrap(), 56 + 4 + 32);
+    }
 }
@@ -0,0 +1,107 @@
+use solana_sdk::instruction::Instruction;
+use solana_sdk::message::VersionedMessage;
+use crate::borsh_encoder::{to_hex, BorshEncoder};
+use crate::idl_loader::IdlLoader;
+use crate::outcome::{note, Notes};
+
+/// Note instructions before they are signed (`--dump-instruction`).
+pub fn dump_instructions(notes: &Notes, idl_loader: Option<&IdlLoader>, instructions: &[Instruction]) {
+    notes.text(format_instructions(idl_loader, instructions));
+}
+
+/// Note a compiled (e.g. Jupiter) message; accounts loaded from lookup tables show by index only.
+pub fn dump_versioned_message(notes: &Notes, message: &VersionedMessage) {
+    let keys = message.static_account_keys();
+    for (i, ix) in message.instructions().iter().enumerate() {
+        let program = keys.get(ix.program_id_index as usize)
+            .map(|k| k.to_string())
+            .unwrap_or_else(|| format!("<lookup #{}>", ix.program_id_index));
+        note!(notes, "🧬 Instruction #{}: {}", i, program);
+        note!(notes, "  accounts ({}):", ix.accounts.len());
+        for (j, index) in ix.accounts.iter().enumerate() {
+            let index = *index as usize;
+            let key = keys.get(index)
+                .map(|k| k.to_string())
+                .unwrap_or_else(|| format!("<lookup #{}>", index));
+            let flags = if index < keys.len() { account_flags(message.is_signer(index), message.is_maybe_writable(index, None)) } else { String::new() };
+            note!(notes, "    {}: {}{}", j, key, flags);
+        }
+        note!(notes, "  data ({} bytes): {}", ix.data.len(), to_hex(&ix.data));
+    }
+}
+
+fn account_flags(is_signer: bool, is_writable: bool) -> String {
+    match (is_signer, is_writable) {
+        (true, true) => " [signer, writable]".to_string(),
+        (true, false) => " [signer]".to_string(),
+        (false, true) => " [writable]".to_string(),
+        (false, false) => String::new(),
+    }
+}
+
+pub fn format_instructions(idl_loader: Option<&IdlLoader>, instructions: &[Instruction]) -> String {
+    let mut out = String::new();
+    for (i, ix) in instructions.iter().enumerate() {
+        out.push_str(&format!("🧬 Instruction #{}: {}\n", i, ix.program_id));
+        out.push_str(&format!("  accounts ({}):\n", ix.accounts.len()));
+        for (j, meta) in ix.accounts.iter().enumerate() {
+            out.push_str(&format!("    {}: {}{}\n", j, meta.pubkey, account_flags(meta.is_signer, meta.is_writable)));
+        }
+        out.push_str(&format!("  data ({} bytes): {}\n", ix.data.len(), to_hex(&ix.data)));
+
+        let program_id = ix.program_id.to_string();
+        let Some(idl_ix) = idl_loader.and_then(|l| l.find_instruction_by_discriminator(&program_id, &ix.data)) else {
+            continue;
+        };
+        out.push_str(&format!("  discriminator: {} ({})\n", to_hex(&ix.data[..8]), idl_ix.name));
+
+        let encoder = BorshEncoder::new();
+        let mut offset = 8;
+        for arg in &idl_ix.args {
+            match encoder.decode_value(&ix.data[offset..], &arg.field_type) {
+                Ok((value, used)) => {
+                    out.push_str(&format!("  {}: {} = {}\n", arg.name, arg.field_type, value));
+                    offset += used;
+                }
+                Err(e) => {
+                    out.push_str(&format!("  {}: {} = <undecodable: {}>\n", arg.name, arg.field_type, e));
+                    break;
+                }
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::instruction::AccountMeta;
+    use solana_sdk::pubkey::Pubkey;
+
+    #[test]
+    fn test_format_instructions_decodes_args() {
+        let program_id = Pubkey::new_unique();
+        let loader = IdlLoader::from_value(&program_id.to_string(), serde_json::json!({
+            "address": program_id.to_string(),
+            "instructions": [{
+                "name": "deposit",
+                "discriminator": [1, 2, 3, 4, 5, 6, 7, 8],
+                "accounts": [],
+                "args": [{ "name": "amount", "type": "u64" }]
+            }],
+            "errors": null
+        })).unwrap();
+
+        let user = Pubkey::new_unique();
+        let mut data = vec![1, 2, 3, 4, 5, 6, 7, 8];
+        data.extend_from_slice(&5u64.to_le_bytes());
+        let ix = Instruction { program_id, accounts: vec![AccountMeta::new(user, true)], data };
+
+        let dump = format_instructions(Some(&loader), &[ix]);
+        assert!(dump.contains(&format!("0: {} [signer, writable]", user)));
+        assert!(dump.contains("data (16 bytes): 01020304050607080500000000000000"));
+        assert!(dump.contains("discriminator: 0102030405060708 (deposit)"));
+        assert!(dump.contains("amount: u64 = 5"));
+    }
+}
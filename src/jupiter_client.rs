@@ -2,16 +2,68 @@ use anyhow::{anyhow, Result};
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
     pubkey::Pubkey,
     transaction::VersionedTransaction,
 };
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use crate::error_decoder;
+use crate::lamports::format_token_amount;
+use crate::transaction_simulator::{estimate_fee, format_token_transfers, FeeConfidence};
+use crate::tx_sender::{self, SendSettings};
+use crate::outcome::{note, Notes};
 
-
+const QUOTE_CACHE_TTL: Duration = Duration::from_secs(5);
+const DEFAULT_SLIPPAGE_BPS: u16 = 50;
+const SLIPPAGE_ESCALATION_STEPS_BPS: [u16; 3] = [100, 150, 200];
+const TOKEN_LIST_URL: &str = "https://token.jup.ag/strict";
+/// Compute units assumed when turning a per-CU percentile price into Jupiter's total-lamports fee
+const SWAP_COMPUTE_UNIT_ESTIMATE: u64 = 300_000;
 
 
 pub struct JupiterClient {
     client: Client,
     base_url: String,
+    quote_cache: Mutex<HashMap<QuoteCacheKey, CachedQuote>>,
+    token_list: Mutex<Option<HashMap<String, TokenInfo>>>,
+    priority_fee: Mutex<SwapPriorityFee>,
+    freshness: QuoteFreshnessConfig,
+    settings: Arc<SendSettings>,
+}
+
+/// Entry from Jupiter's token list.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenInfo {
+    pub address: String,
+    pub symbol: String,
+    pub name: String,
+    pub decimals: u8,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct QuoteCacheKey {
+    input_mint: String,
+    output_mint: String,
+    amount: u64,
+    slippage_bps: Option<u16>,
+}
+
+impl QuoteCacheKey {
+    fn from_request(request: &QuoteRequest) -> Self {
+        Self {
+            input_mint: request.input_mint.clone(),
+            output_mint: request.output_mint.clone(),
+            amount: request.amount,
+            slippage_bps: request.slippage_bps,
+        }
+    }
+}
+
+struct CachedQuote {
+    quote: QuoteResponse,
+    fetched_at: Instant,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -29,7 +81,7 @@ pub struct QuoteRequest {
     pub only_direct_routes: Option<bool>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct QuoteResponse {
     #[serde(rename = "inputMint")]
     pub input_mint: String,
@@ -61,14 +113,14 @@ pub struct QuoteResponse {
     pub timestamp: Option<u64>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RoutePlan {
     #[serde(rename = "swapInfo")]
     pub swap_info: SwapInfo,
     pub percent: u8,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SwapInfo {
     #[serde(rename = "ammKey")]
     pub amm_key: String,
@@ -87,6 +139,111 @@ pub struct SwapInfo {
     pub fee_mint: String,
 }
 
+#[derive(Debug, Serialize)]
+pub struct RouteHop {
+    pub label: String,
+    pub amm_key: String,
+    pub percent: u8,
+    pub fee_amount: String,
+    pub fee_mint: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RouteSummary {
+    pub input_mint: String,
+    pub output_mint: String,
+    pub in_amount: String,
+    pub out_amount: String,
+    pub price_impact_pct: String,
+    pub slippage_bps: u16,
+    pub hops: Vec<RouteHop>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub in_ui_amount: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub out_ui_amount: Option<String>,
+}
+
+/// Show a raw base-unit amount alongside its UI value when the mint's decimals are known.
+pub fn display_amount(raw: &str, decimals: Option<u8>) -> String {
+    match (raw.parse::<u64>(), decimals) {
+        (Ok(amount), Some(decimals)) => format!("{} ({} raw)", format_token_amount(amount, decimals), raw),
+        _ => format!("{} raw (decimals unknown)", raw),
+    }
+}
+
+/// Price implied by a quote: output UI units per input UI unit.
+pub fn implied_price(quote: &QuoteResponse, input_decimals: u8, output_decimals: u8) -> Result<f64> {
+    let in_amount: u64 = quote.in_amount.parse()
+        .map_err(|_| anyhow::anyhow!("Invalid inAmount in quote: {}", quote.in_amount))?;
+    let out_amount: u64 = quote.out_amount.parse()
+        .map_err(|_| anyhow::anyhow!("Invalid outAmount in quote: {}", quote.out_amount))?;
+    if in_amount == 0 {
+        return Err(anyhow::anyhow!("Quote has a zero input amount"));
+    }
+    let input = in_amount as f64 / 10f64.powi(input_decimals as i32);
+    let output = out_amount as f64 / 10f64.powi(output_decimals as i32);
+    Ok(output / input)
+}
+
+fn ui_amount(raw: &str, decimals: Option<u8>) -> Option<String> {
+    let amount = raw.parse::<u64>().ok()?;
+    Some(format_token_amount(amount, decimals?))
+}
+
+impl RouteSummary {
+    pub fn from_quote(quote: &QuoteResponse) -> Self {
+        Self {
+            input_mint: quote.input_mint.clone(),
+            output_mint: quote.output_mint.clone(),
+            in_amount: quote.in_amount.clone(),
+            out_amount: quote.out_amount.clone(),
+            price_impact_pct: quote.price_impact_pct.clone(),
+            slippage_bps: quote.slippage_bps,
+            hops: quote.route_plan.iter().map(|route| RouteHop {
+                label: route.swap_info.label.clone(),
+                amm_key: route.swap_info.amm_key.clone(),
+                percent: route.percent,
+                fee_amount: route.swap_info.fee_amount.clone(),
+                fee_mint: route.swap_info.fee_mint.clone(),
+            }).collect(),
+            in_ui_amount: None,
+            out_ui_amount: None,
+        }
+    }
+
+    pub fn with_decimals(mut self, input_decimals: Option<u8>, output_decimals: Option<u8>) -> Self {
+        self.in_ui_amount = ui_amount(&self.in_amount, input_decimals);
+        self.out_ui_amount = ui_amount(&self.out_amount, output_decimals);
+        self
+    }
+
+    pub fn table_rows(&self) -> ([&'static str; 5], Vec<Vec<String>>) {
+        let rows = self.hops.iter().enumerate().map(|(i, hop)| vec![
+            (i + 1).to_string(),
+            hop.label.clone(),
+            hop.amm_key.clone(),
+            format!("{}%", hop.percent),
+            format!("{} {}", hop.fee_amount, hop.fee_mint),
+        ]).collect();
+        (["#", "Label", "AMM", "Percent", "Fee"], rows)
+    }
+
+    pub fn render_table(&self) -> String {
+        let (headers, rows) = self.table_rows();
+        crate::output::render_table(&headers, &rows)
+    }
+}
+
+/// One row of a slippage comparison; `error` is set when that quote failed.
+#[derive(Debug, Serialize)]
+pub struct SlippageComparison {
+    pub slippage_bps: u16,
+    pub out_amount: Option<String>,
+    pub other_amount_threshold: Option<String>,
+    pub price_impact_pct: Option<String>,
+    pub error: Option<String>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SwapRequest {
     #[serde(rename = "userPublicKey")]
@@ -98,7 +255,10 @@ pub struct SwapRequest {
     #[serde(rename = "dynamicComputeUnitLimit")]
     pub dynamic_compute_unit_limit: Option<bool>,
     #[serde(rename = "prioritizationFeeLamports")]
-    pub prioritization_fee_lamports: Option<String>,
+    pub prioritization_fee_lamports: Option<serde_json::Value>,
+    /// `true` or `{minBps, maxBps}`; see [`DynamicSlippage::request_value`]
+    #[serde(rename = "dynamicSlippage", skip_serializing_if = "Option::is_none")]
+    pub dynamic_slippage: Option<serde_json::Value>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -107,6 +267,446 @@ pub struct SwapResponse {
     pub swap_transaction: String, // Base64 encoded
     #[serde(rename = "lastValidBlockHeight")]
     pub last_valid_block_height: Option<u64>,
+    /// Present when the request asked for dynamic slippage
+    #[serde(rename = "dynamicSlippageReport", default)]
+    pub dynamic_slippage_report: Option<DynamicSlippageReport>,
+}
+
+/// Server-side slippage Jupiter picks per route (`--dynamic-slippage`), optionally bounded.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DynamicSlippage {
+    pub min_bps: Option<u16>,
+    pub max_bps: Option<u16>,
+}
+
+impl DynamicSlippage {
+    /// `dynamicSlippage` for a swap request: `true` when unbounded, else the given bounds.
+    pub fn request_value(self) -> serde_json::Value {
+        if self.min_bps.is_none() && self.max_bps.is_none() {
+            return serde_json::json!(true);
+        }
+        let mut bounds = serde_json::Map::new();
+        if let Some(min_bps) = self.min_bps {
+            bounds.insert("minBps".to_string(), min_bps.into());
+        }
+        if let Some(max_bps) = self.max_bps {
+            bounds.insert("maxBps".to_string(), max_bps.into());
+        }
+        serde_json::Value::Object(bounds)
+    }
+}
+
+/// What Jupiter applied under dynamic slippage.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DynamicSlippageReport {
+    #[serde(rename = "slippageBps")]
+    pub slippage_bps: Option<u16>,
+    #[serde(rename = "simulatedIncurredSlippageBps")]
+    pub simulated_incurred_slippage_bps: Option<i32>,
+    #[serde(rename = "categoryName")]
+    pub category_name: Option<String>,
+}
+
+impl DynamicSlippageReport {
+    pub fn describe(&self) -> String {
+        let mut out = match self.slippage_bps {
+            Some(bps) => format!("Jupiter applied {} bps ({}%) dynamic slippage", bps, bps as f64 / 100.0),
+            None => "Jupiter didn't report the dynamic slippage it applied".to_string(),
+        };
+        if let Some(category) = &self.category_name {
+            out.push_str(&format!(" for a {} pair", category));
+        }
+        if let Some(incurred) = self.simulated_incurred_slippage_bps {
+            out.push_str(&format!("; {} bps incurred in its simulation", incurred));
+        }
+        out
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JupiterAccountMeta {
+    pub pubkey: String,
+    #[serde(rename = "isSigner")]
+    pub is_signer: bool,
+    #[serde(rename = "isWritable")]
+    pub is_writable: bool,
+}
+
+/// Instruction as returned by `/swap-instructions`, with base64 data.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JupiterInstruction {
+    #[serde(rename = "programId")]
+    pub program_id: String,
+    pub accounts: Vec<JupiterAccountMeta>,
+    pub data: String,
+}
+
+impl JupiterInstruction {
+    pub fn to_instruction(&self) -> Result<Instruction> {
+        use base64::Engine;
+        let accounts = self.accounts.iter().map(|meta| {
+            let pubkey = meta.pubkey.parse::<Pubkey>()
+                .map_err(|_| anyhow!("Invalid account pubkey in Jupiter instruction: {}", meta.pubkey))?;
+            Ok(if meta.is_writable {
+                AccountMeta::new(pubkey, meta.is_signer)
+            } else {
+                AccountMeta::new_readonly(pubkey, meta.is_signer)
+            })
+        }).collect::<Result<Vec<_>>>()?;
+
+        Ok(Instruction {
+            program_id: self.program_id.parse()
+                .map_err(|_| anyhow!("Invalid program id in Jupiter instruction: {}", self.program_id))?,
+            accounts,
+            data: base64::engine::general_purpose::STANDARD.decode(&self.data)?,
+        })
+    }
+}
+
+/// Component instructions of a Jupiter swap, for composing into a caller-built transaction.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DecomposedSwap {
+    #[serde(rename = "computeBudgetInstructions", default)]
+    pub compute_budget_instructions: Vec<JupiterInstruction>,
+    #[serde(rename = "setupInstructions", default)]
+    pub setup_instructions: Vec<JupiterInstruction>,
+    #[serde(rename = "swapInstruction")]
+    pub swap_instruction: JupiterInstruction,
+    #[serde(rename = "cleanupInstruction")]
+    pub cleanup_instruction: Option<JupiterInstruction>,
+    #[serde(rename = "addressLookupTableAddresses", default)]
+    pub address_lookup_table_addresses: Vec<String>,
+}
+
+impl DecomposedSwap {
+    /// All instructions in execution order: compute budget, setup, swap, cleanup.
+    pub fn instructions(&self) -> Result<Vec<Instruction>> {
+        self.compute_budget_instructions.iter()
+            .chain(self.setup_instructions.iter())
+            .chain(std::iter::once(&self.swap_instruction))
+            .chain(self.cleanup_instruction.iter())
+            .map(|ix| ix.to_instruction())
+            .collect()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct JupiterErrorBody {
+    error: Option<String>,
+    #[serde(rename = "errorCode")]
+    error_code: Option<String>,
+}
+
+/// A non-success response from the Jupiter API, with the error code parsed when present.
+#[derive(Debug)]
+pub struct JupiterApiError {
+    pub context: &'static str,
+    pub status: u16,
+    pub code: Option<String>,
+    pub message: String,
+    pub retry_after: Option<Duration>,
+}
+
+impl JupiterApiError {
+    pub fn from_parts(context: &'static str, status: u16, body: &str, retry_after: Option<Duration>) -> Self {
+        let (code, message) = match serde_json::from_str::<JupiterErrorBody>(body) {
+            Ok(parsed) => (parsed.error_code, parsed.error.unwrap_or_else(|| body.to_string())),
+            Err(_) => (None, body.trim().to_string()),
+        };
+        Self { context, status, code, message, retry_after }
+    }
+
+    async fn from_response(context: &'static str, response: reqwest::Response) -> Self {
+        let status = response.status().as_u16();
+        let retry_after = response.headers()
+            .get(reqwest::header::RETRY_AFTER)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.trim().parse::<u64>().ok())
+            .map(Duration::from_secs);
+        let body = response.text().await.unwrap_or_default();
+        Self::from_parts(context, status, &body, retry_after)
+    }
+
+    pub fn is_rate_limited(&self) -> bool {
+        self.status == 429
+    }
+
+    pub fn is_no_route(&self) -> bool {
+        matches!(self.code.as_deref(), Some("COULD_NOT_FIND_ANY_ROUTE") | Some("NO_ROUTES_FOUND"))
+            || self.message.to_lowercase().contains("no routes found")
+            || self.message.to_lowercase().contains("could not find any route")
+    }
+
+    pub fn is_amount_too_small(&self) -> bool {
+        let message = self.message.to_lowercase();
+        matches!(self.code.as_deref(), Some("AMOUNT_TOO_SMALL") | Some("ROUTE_PLAN_DOES_NOT_CONSUME_ALL_THE_AMOUNT"))
+            || (message.contains("amount") && message.contains("too small"))
+    }
+
+    pub fn suggestion(&self) -> Option<&'static str> {
+        if self.is_rate_limited() {
+            Some("Jupiter is rate limiting requests; wait a moment and retry")
+        } else if self.is_no_route() {
+            Some("No route exists for this pair/amount; check the mints or try a larger amount")
+        } else if self.is_amount_too_small() {
+            Some("The amount is too small to route; increase it (amounts are in base units)")
+        } else {
+            None
+        }
+    }
+}
+
+impl std::fmt::Display for JupiterApiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let summary = if self.is_rate_limited() {
+            "Rate limited by Jupiter API".to_string()
+        } else if self.is_no_route() {
+            "No routes found".to_string()
+        } else if self.is_amount_too_small() {
+            "Amount too small".to_string()
+        } else {
+            self.message.clone()
+        };
+
+        write!(f, "Jupiter {} failed (HTTP {}", self.context, self.status)?;
+        if let Some(code) = &self.code {
+            write!(f, ", {}", code)?;
+        }
+        write!(f, "): {}", summary)?;
+        if let Some(suggestion) = self.suggestion() {
+            write!(f, " - {}", suggestion)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for JupiterApiError {}
+
+/// Jupiter has no route for a pair at an amount, whether it answered with a no-route error or an empty route plan.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NoRouteError {
+    pub input_mint: String,
+    pub output_mint: String,
+    pub amount: u64,
+}
+
+impl NoRouteError {
+    pub fn for_request(request: &QuoteRequest) -> Self {
+        Self { input_mint: request.input_mint.clone(), output_mint: request.output_mint.clone(), amount: request.amount }
+    }
+}
+
+impl std::fmt::Display for NoRouteError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "No swap route found for {}→{} at amount {} (base units); try a larger amount or a different pair",
+               self.input_mint, self.output_mint, self.amount)
+    }
+}
+
+impl std::error::Error for NoRouteError {}
+
+/// Map a `/quote` outcome for `request`, turning both no-route forms into [`NoRouteError`].
+fn quote_result(request: &QuoteRequest, response: std::result::Result<QuoteResponse, JupiterApiError>) -> Result<QuoteResponse> {
+    match response {
+        Ok(quote) if quote.route_plan.is_empty() => Err(NoRouteError::for_request(request).into()),
+        Ok(quote) => Ok(quote),
+        Err(e) if e.is_no_route() => Err(NoRouteError::for_request(request).into()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Swap priority fee (`--swap-priority-fee`): Jupiter's `auto`, a fixed lamport amount, or a percentile of
+/// recent prioritization fees.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SwapPriorityFee {
+    #[default]
+    Auto,
+    Lamports(u64),
+    Percentile(u8),
+}
+
+impl std::str::FromStr for SwapPriorityFee {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        if s == "auto" {
+            return Ok(SwapPriorityFee::Auto);
+        }
+        if let Some(percentile) = s.strip_prefix("percentile:") {
+            let percentile: u8 = percentile.parse()
+                .map_err(|_| anyhow!("Invalid percentile '{}'", percentile))?;
+            if percentile > 100 {
+                return Err(anyhow!("Percentile {} is above 100", percentile));
+            }
+            return Ok(SwapPriorityFee::Percentile(percentile));
+        }
+        s.parse().map(SwapPriorityFee::Lamports)
+            .map_err(|_| anyhow!("Expected auto, a lamport amount, or percentile:N, got '{}'", s))
+    }
+}
+
+impl SwapPriorityFee {
+    /// Turn a percentile into lamports from recent prioritization fees (per-CU prices scaled to a typical swap's
+    /// compute units). Notes any non-`auto` result.
+    pub fn resolve(self, rpc_client: &solana_client::rpc_client::RpcClient, notes: &Notes) -> Result<Self> {
+        let percentile = match self {
+            SwapPriorityFee::Auto => return Ok(self),
+            SwapPriorityFee::Lamports(lamports) => {
+                note!(notes, "⛽ Swap priority fee: {} lamports", lamports);
+                return Ok(self);
+            }
+            SwapPriorityFee::Percentile(percentile) => percentile,
+        };
+        let fees: Vec<u64> = rpc_client.get_recent_prioritization_fees(&[])?
+            .iter()
+            .map(|f| f.prioritization_fee)
+            .collect();
+        let price = crate::priority_fee::fee_at_percentile(&fees, percentile);
+        let lamports = (price as u128 * SWAP_COMPUTE_UNIT_ESTIMATE as u128).div_ceil(1_000_000) as u64;
+        note!(notes, "⛽ Swap priority fee: p{} of {} recent slots = {} micro-lamports/CU, ~{} lamports at {} CU",
+             percentile, fees.len(), price, lamports, SWAP_COMPUTE_UNIT_ESTIMATE);
+        Ok(SwapPriorityFee::Lamports(lamports))
+    }
+
+    /// Jupiter's `prioritizationFeeLamports`: `"auto"` or a lamport amount.
+    pub fn request_value(self) -> serde_json::Value {
+        match self {
+            SwapPriorityFee::Auto => serde_json::json!("auto"),
+            SwapPriorityFee::Lamports(lamports) => serde_json::json!(lamports),
+            // Unresolved: let Jupiter pick rather than guess
+            SwapPriorityFee::Percentile(_) => serde_json::json!("auto"),
+        }
+    }
+}
+
+/// Starting point for escalation when the failed swap paid no priority fee
+const MIN_ESCALATED_PRIORITY_FEE: u64 = 10_000;
+
+impl SwapPriorityFee {
+    /// Next fee after a congestion failure: double what the last attempt paid (or its fixed amount, whichever
+    /// is higher), at most `cap_lamports`. `None` once the cap leaves no room to raise it.
+    pub fn escalate(self, paid_lamports: u64, cap_lamports: u64) -> Option<u64> {
+        let current = match self {
+            SwapPriorityFee::Lamports(lamports) => lamports.max(paid_lamports),
+            _ => paid_lamports,
+        };
+        let next = current.saturating_mul(2).max(MIN_ESCALATED_PRIORITY_FEE).min(cap_lamports);
+        (next > current).then_some(next)
+    }
+}
+
+/// Why a swap send failed, as far as a higher priority fee is concerned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SwapFailure {
+    /// Dropped, expired, or timed out: a higher priority fee may land it
+    Congestion,
+    /// The route moved past the slippage limit; paying more won't help
+    Slippage,
+    InsufficientFunds,
+    Other,
+}
+
+/// Classify a send/confirmation error message. Slippage and funds errors win over congestion markers, since
+/// a transaction that landed and failed also "did not confirm".
+pub fn classify_swap_failure(message: &str) -> SwapFailure {
+    let message = message.to_lowercase();
+    // 0x1771 (6001) is Jupiter's SlippageToleranceExceeded
+    if message.contains("slippage") || message.contains("0x1771") {
+        return SwapFailure::Slippage;
+    }
+    if ["insufficient funds", "insufficient lamports", "insufficientfunds"].iter().any(|m| message.contains(m)) {
+        return SwapFailure::InsufficientFunds;
+    }
+    let congestion = ["blockhash not found", "blockhashnotfound", "block height exceeded", "blockhash expired",
+                      "was dropped", "timed out", "timeout", "not confirmed"];
+    if congestion.iter().any(|m| message.contains(m)) {
+        return SwapFailure::Congestion;
+    }
+    SwapFailure::Other
+}
+
+/// How far swap building may widen slippage past the requested value when a build fails.
+#[derive(Debug, Clone, Copy)]
+pub struct SlippagePolicy {
+    pub requested_bps: u16,
+    pub max_bps: u16,
+    pub allow_escalation: bool,
+    /// Abort instead of building when a quote's price impact (percent) is above this
+    pub max_price_impact_pct: Option<f64>,
+    /// Let Jupiter choose slippage per route; `requested_bps` then only applies to the quote
+    pub dynamic: Option<DynamicSlippage>,
+}
+
+impl SlippagePolicy {
+    /// Exactly the requested slippage, never escalated.
+    pub fn fixed(requested_bps: u16) -> Self {
+        Self { requested_bps, max_bps: requested_bps, allow_escalation: false, max_price_impact_pct: None, dynamic: None }
+    }
+
+    pub fn with_dynamic(mut self, dynamic: Option<DynamicSlippage>) -> Self {
+        self.dynamic = dynamic;
+        self
+    }
+
+    pub fn with_max_price_impact(mut self, max_price_impact_pct: Option<f64>) -> Self {
+        self.max_price_impact_pct = max_price_impact_pct;
+        self
+    }
+
+    /// Slippage values to try in order: the request, then escalation steps up to `max_bps` if allowed.
+    pub fn schedule(&self) -> Result<Vec<u16>> {
+        if self.requested_bps > self.max_bps {
+            return Err(anyhow!(
+                "Requested slippage {} bps exceeds --max-slippage-bps {}",
+                self.requested_bps, self.max_bps
+            ));
+        }
+        if let Some(DynamicSlippage { min_bps: Some(min), max_bps: Some(max) }) = self.dynamic {
+            if min > max {
+                return Err(anyhow!("Dynamic slippage min {} bps is above max {} bps", min, max));
+            }
+        }
+        let mut schedule = vec![self.requested_bps];
+        if self.allow_escalation {
+            schedule.extend(SLIPPAGE_ESCALATION_STEPS_BPS.iter()
+                .copied()
+                .filter(|bps| *bps > self.requested_bps && *bps <= self.max_bps));
+        }
+        Ok(schedule)
+    }
+}
+
+impl Default for SlippagePolicy {
+    fn default() -> Self {
+        Self::fixed(DEFAULT_SLIPPAGE_BPS)
+    }
+}
+
+/// How stale a quote may be before it's rejected (`--max-quote-slot-drift`, `--max-quote-age-secs`).
+/// Quotes past a third of either limit pass with a warning.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuoteFreshnessConfig {
+    pub max_slot_drift: u64,
+    pub warn_slot_drift: u64,
+    pub max_age_secs: u64,
+    pub warn_age_secs: u64,
+}
+
+impl QuoteFreshnessConfig {
+    pub fn new(max_slot_drift: u64, max_age_secs: u64) -> Self {
+        Self {
+            max_slot_drift,
+            warn_slot_drift: max_slot_drift / 3,
+            max_age_secs,
+            warn_age_secs: max_age_secs / 3,
+        }
+    }
+}
+
+impl Default for QuoteFreshnessConfig {
+    fn default() -> Self {
+        Self::new(150, 30)
+    }
 }
 
 #[derive(Debug)]
@@ -133,6 +733,7 @@ pub struct SimulationResult {
     pub error_message: Option<String>,
     pub compute_units_consumed: u64,
     pub fee_estimate: u64,
+    pub fee_confidence: FeeConfidence,
     pub logs: Vec<String>,
     pub account_changes: std::collections::HashMap<String, String>,
     pub warnings: Vec<String>,
@@ -143,34 +744,110 @@ impl JupiterClient {
         Self {
             client: Client::new(),
             base_url: "https://quote-api.jup.ag/v6".to_string(),
+            quote_cache: Mutex::new(HashMap::new()),
+            token_list: Mutex::new(None),
+            priority_fee: Mutex::new(SwapPriorityFee::Auto),
+            freshness: QuoteFreshnessConfig::default(),
+            settings: Arc::new(SendSettings::default()),
         }
     }
 
+    /// Run-wide send settings for swap sends and `--fail-on-warning`
+    pub fn with_send_settings(mut self, settings: Arc<SendSettings>) -> Self {
+        self.settings = settings;
+        self
+    }
+
+    pub fn with_freshness(mut self, freshness: QuoteFreshnessConfig) -> Self {
+        self.freshness = freshness;
+        self
+    }
+
+    /// Priority fee sent with every swap request; call `resolve_priority_fee` before building a swap.
+    pub fn with_priority_fee(mut self, priority_fee: SwapPriorityFee) -> Self {
+        *self.priority_fee.get_mut().unwrap() = priority_fee;
+        self
+    }
+
+    /// Resolve a percentile fee (see `SwapPriorityFee::resolve`). Only swaps need it, so other commands
+    /// never pay for the RPC call.
+    pub fn resolve_priority_fee(&self, rpc_client: &solana_client::rpc_client::RpcClient) -> Result<()> {
+        let current = *self.priority_fee.lock().unwrap();
+        let resolved = current.resolve(rpc_client, &self.settings.notes)?;
+        *self.priority_fee.lock().unwrap() = resolved;
+        Ok(())
+    }
+
+    /// `prioritizationFeeLamports` for a swap request.
+    pub fn prioritization_fee(&self) -> serde_json::Value {
+        self.priority_fee.lock().unwrap().request_value()
+    }
+
+    /// Raise the swap priority fee for the next build after a congestion failure (see
+    /// `SwapPriorityFee::escalate`). Returns the new fee, or `None` at the cap.
+    pub fn escalate_priority_fee(&self, paid_lamports: u64, cap_lamports: u64) -> Option<u64> {
+        let mut priority_fee = self.priority_fee.lock().unwrap();
+        let next = priority_fee.escalate(paid_lamports, cap_lamports)?;
+        *priority_fee = SwapPriorityFee::Lamports(next);
+        Some(next)
+    }
+
+    /// Return a cached quote if it is within the TTL and still passes freshness validation.
+    fn cached_quote(&self, request: &QuoteRequest, current_slot: u64) -> Option<QuoteResponse> {
+        let key = QuoteCacheKey::from_request(request);
+        let mut cache = self.quote_cache.lock().unwrap();
+        let entry = cache.get(&key)?;
+
+        let still_valid = entry.fetched_at.elapsed() < QUOTE_CACHE_TTL
+            && self.validate_quote_freshness(&entry.quote, current_slot)
+                .map(|v| v.is_fresh)
+                .unwrap_or(false);
+
+        if still_valid {
+            Some(entry.quote.clone())
+        } else {
+            cache.remove(&key);
+            None
+        }
+    }
+
+    fn cache_quote(&self, request: &QuoteRequest, quote: &QuoteResponse) {
+        self.quote_cache.lock().unwrap().insert(
+            QuoteCacheKey::from_request(request),
+            CachedQuote { quote: quote.clone(), fetched_at: Instant::now() },
+        );
+    }
+
 
     pub fn validate_quote_freshness(&self, quote: &QuoteResponse, current_slot: u64) -> Result<QuoteValidation> {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        self.validate_quote_freshness_at(quote, current_slot, now)
+    }
+
+    fn validate_quote_freshness_at(&self, quote: &QuoteResponse, current_slot: u64, now: u64) -> Result<QuoteValidation> {
+        let limits = self.freshness;
         let mut issues = Vec::new();
         let mut warnings = Vec::new();
 
 
         if let Some(quote_slot) = quote.slot {
             let slot_drift = current_slot.saturating_sub(quote_slot);
-            if slot_drift > 150 {
-                issues.push(format!("Quote too stale: {} slots behind current (max 150)", slot_drift));
-            } else if slot_drift > 50 {
+            if slot_drift > limits.max_slot_drift {
+                issues.push(format!("Quote too stale: {} slots behind current (max {})", slot_drift, limits.max_slot_drift));
+            } else if slot_drift > limits.warn_slot_drift {
                 warnings.push(format!("Quote aging: {} slots behind current", slot_drift));
             }
         }
 
 
         if let Some(timestamp) = quote.timestamp {
-            let now = std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap_or_default()
-                .as_secs();
             let age_seconds = now.saturating_sub(timestamp);
-            if age_seconds > 30 {
-                issues.push(format!("Quote too old: {} seconds (max 30)", age_seconds));
-            } else if age_seconds > 10 {
+            if age_seconds > limits.max_age_secs {
+                issues.push(format!("Quote too old: {} seconds (max {})", age_seconds, limits.max_age_secs));
+            } else if age_seconds > limits.warn_age_secs {
                 warnings.push(format!("Quote aging: {} seconds old", age_seconds));
             }
         }
@@ -199,44 +876,48 @@ impl JupiterClient {
             issues,
             warnings,
             slot_drift: quote.slot.map(|s| current_slot.saturating_sub(s)).unwrap_or(0),
-            age_seconds: quote.timestamp.map(|t| {
-                std::time::SystemTime::now()
-                    .duration_since(std::time::UNIX_EPOCH)
-                    .unwrap_or_default()
-                    .as_secs()
-                    .saturating_sub(t)
-            }).unwrap_or(0),
+            age_seconds: quote.timestamp.map(|t| now.saturating_sub(t)).unwrap_or(0),
         })
     }
 
     pub async fn get_fresh_quote(&self, request: QuoteRequest, max_retries: usize) -> Result<QuoteResponse> {
         let mut last_error = None;
+
+        let current_slot = self.get_current_slot().await.unwrap_or(0);
+        if let Some(quote) = self.cached_quote(&request, current_slot) {
+            note!(self.settings.notes, "♻️  Using cached Jupiter quote");
+            return Ok(quote);
+        }
         
         for attempt in 1..=max_retries {
-            println!("🔍 Getting fresh Jupiter quote (attempt {}/{}...", attempt, max_retries);
+            note!(self.settings.notes, "🔍 Getting fresh Jupiter quote (attempt {}/{}...", attempt, max_retries);
             
             match self.get_quote(request.clone()).await {
                 Ok(quote) => {
                     let current_slot = self.get_current_slot().await.unwrap_or(0);
-                    let validation = self.validate_quote_freshness(&quote, current_slot)?;
+                    let mut validation = self.validate_quote_freshness(&quote, current_slot)?;
+                    if self.settings.promote_warnings(&mut validation.issues, &mut validation.warnings) {
+                        validation.is_fresh = false;
+                    }
                     
                     if validation.is_fresh {
-                        println!("✅ Fresh quote obtained!");
+                        note!(self.settings.notes, "✅ Fresh quote obtained!");
+                        self.cache_quote(&request, &quote);
                         if !validation.warnings.is_empty() {
-                            println!("⚠️  Quote warnings:");
+                            note!(self.settings.notes, "⚠️  Quote warnings:");
                             for warning in &validation.warnings {
-                                println!("  ⚠️  {}", warning);
+                                note!(self.settings.notes, "  ⚠️  {}", warning);
                             }
                         }
                         return Ok(quote);
                     } else {
-                        println!("⚠️  Quote validation failed:");
+                        note!(self.settings.notes, "⚠️  Quote validation failed:");
                         for issue in &validation.issues {
-                            println!("  🚨 {}", issue);
+                            note!(self.settings.notes, "  🚨 {}", issue);
                         }
                         
                         if attempt < max_retries {
-                            println!("🔄 Retrying with fresh quote...");
+                            note!(self.settings.notes, "🔄 Retrying with fresh quote...");
                             tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
                             continue;
                         } else {
@@ -244,11 +925,15 @@ impl JupiterClient {
                         }
                     }
                 }
+                Err(e) if e.downcast_ref::<NoRouteError>().is_some() => return Err(e),
                 Err(e) => {
+                    let retry_delay = e.downcast_ref::<JupiterApiError>()
+                        .and_then(|api_err| api_err.retry_after)
+                        .unwrap_or(Duration::from_millis(1000));
                     last_error = Some(e);
                     if attempt < max_retries {
-                        println!("⚠️  Quote attempt {} failed: {}, retrying...", attempt, last_error.as_ref().unwrap());
-                        tokio::time::sleep(tokio::time::Duration::from_millis(1000)).await;
+                        note!(self.settings.notes, "⚠️  Quote attempt {} failed: {}, retrying in {:.1}s...", attempt, last_error.as_ref().unwrap(), retry_delay.as_secs_f64());
+                        tokio::time::sleep(retry_delay).await;
                     }
                 }
             }
@@ -268,7 +953,7 @@ impl JupiterClient {
         transaction: &VersionedTransaction,
         rpc_client: &solana_client::rpc_client::RpcClient,
     ) -> Result<SafeSendResult> {
-        println!("🔍 Safe-send guard: Validating versioned transaction...");
+        note!(self.settings.notes, "🔍 Safe-send guard: Validating versioned transaction...");
         
        
         let uses_alts = match &transaction.message {
@@ -277,16 +962,16 @@ impl JupiterClient {
         };
 
         if uses_alts {
-            println!("📋 Transaction uses Address Lookup Tables (ALTs)");
-            println!("⚠️  ALTs require mainnet RPC - simulation may fail on devnet");
+            note!(self.settings.notes, "📋 Transaction uses Address Lookup Tables (ALTs)");
+            note!(self.settings.notes, "⚠️  ALTs require mainnet RPC - simulation may fail on devnet");
             
        
             match self.simulate_versioned_transaction(transaction, rpc_client).await {
                 Ok(simulation) => {
                     if !simulation.success {
-                        println!("❌ Simulation failed - transaction would fail:");
+                        note!(self.settings.notes, "❌ Simulation failed - transaction would fail:");
                         if let Some(error) = &simulation.error_message {
-                            println!("  🚨 {}", error);
+                            note!(self.settings.notes, "  🚨 {}", error);
                         }
                         return Ok(SafeSendResult {
                             sent: false,
@@ -295,24 +980,25 @@ impl JupiterClient {
                             simulation,
                         });
                     } else {
-                        println!("✅ Simulation successful - transaction safe to send");
-                        println!("💰 Estimated fee: {} lamports", simulation.fee_estimate);
-                        println!("⚡ Compute units: {}", simulation.compute_units_consumed);
+                        note!(self.settings.notes, "✅ Simulation successful - transaction safe to send");
+                        note!(self.settings.notes, "💰 Fee: {}", simulation.fee_confidence.describe(simulation.fee_estimate));
+                        note!(self.settings.notes, "⚡ Compute units: {}", simulation.compute_units_consumed);
+                        self.settings.notes.text(format_token_transfers(&simulation.logs, &transaction.message));
                     }
                 }
                 Err(e) => {
-                    println!("⚠️  Simulation failed (likely due to ALTs on devnet): {}", e);
-                    println!("🚀 Proceeding with direct send (production mode)");
+                    note!(self.settings.notes, "⚠️  Simulation failed (likely due to ALTs on devnet): {}", e);
+                    note!(self.settings.notes, "🚀 Proceeding with direct send (production mode)");
                 }
             }
         } else {
-            println!("📋 Standard transaction - running full simulation");
+            note!(self.settings.notes, "📋 Standard transaction - running full simulation");
             match self.simulate_versioned_transaction(transaction, rpc_client).await {
                 Ok(simulation) => {
                     if !simulation.success {
-                        println!("❌ Simulation failed - transaction would fail:");
+                        note!(self.settings.notes, "❌ Simulation failed - transaction would fail:");
                         if let Some(error) = &simulation.error_message {
-                            println!("  🚨 {}", error);
+                            note!(self.settings.notes, "  🚨 {}", error);
                         }
                         return Ok(SafeSendResult {
                             sent: false,
@@ -321,13 +1007,14 @@ impl JupiterClient {
                             simulation,
                         });
                     } else {
-                        println!("✅ Simulation successful - transaction safe to send");
-                        println!("💰 Estimated fee: {} lamports", simulation.fee_estimate);
-                        println!("⚡ Compute units: {}", simulation.compute_units_consumed);
+                        note!(self.settings.notes, "✅ Simulation successful - transaction safe to send");
+                        note!(self.settings.notes, "💰 Fee: {}", simulation.fee_confidence.describe(simulation.fee_estimate));
+                        note!(self.settings.notes, "⚡ Compute units: {}", simulation.compute_units_consumed);
+                        self.settings.notes.text(format_token_transfers(&simulation.logs, &transaction.message));
                     }
                 }
                 Err(e) => {
-                    println!("❌ Simulation failed: {}", e);
+                    note!(self.settings.notes, "❌ Simulation failed: {}", e);
                     return Ok(SafeSendResult {
                         sent: false,
                         signature: None,
@@ -337,6 +1024,7 @@ impl JupiterClient {
                             error_message: Some(e.to_string()),
                             compute_units_consumed: 0,
                             fee_estimate: 0,
+                            fee_confidence: FeeConfidence::Estimated,
                             logs: vec![],
                             account_changes: std::collections::HashMap::new(),
                             warnings: vec![],
@@ -347,22 +1035,27 @@ impl JupiterClient {
         }
 
         // Send the transaction
-        println!("🚀 Sending versioned transaction to blockchain...");
-        match rpc_client.send_transaction_with_config(
+        note!(self.settings.notes, "🚀 Sending versioned transaction to blockchain...");
+        // Refuse up front; a failed submit below is reported, not returned
+        tx_sender::ensure_sending_allowed(rpc_client, &self.settings, &transaction.signatures[0])?;
+        match tx_sender::submit(
+            rpc_client,
+            &self.settings,
             transaction,
-            solana_client::rpc_config::RpcSendTransactionConfig {
+            Some(solana_client::rpc_config::RpcSendTransactionConfig {
                 skip_preflight: false,
                 max_retries: Some(3),
                 ..Default::default()
-            }
+            }),
         ) {
             Ok(signature) => {
-                println!("📤 Transaction submitted: {}", signature);
-                println!("⏳ Waiting for confirmation...");
+                note!(self.settings.notes, "📤 Transaction submitted: {}", signature);
+                note!(self.settings.notes, "⏳ Waiting for confirmation...");
                 
-                match rpc_client.confirm_transaction(&signature) {
+                match tx_sender::confirm_sent(rpc_client, &self.settings, transaction) {
                     Ok(_) => {
-                        println!("✅ Transaction confirmed: {}", signature);
+                        note!(self.settings.notes, "✅ Transaction confirmed: {}", signature);
+                        self.settings.explorer.note_transaction_url(&self.settings.notes, &signature);
                         Ok(SafeSendResult {
                             sent: true,
                             signature: Some(signature),
@@ -372,6 +1065,7 @@ impl JupiterClient {
                                 logs: vec!["Transaction sent and confirmed successfully".to_string()],
                                 compute_units_consumed: 0,
                                 fee_estimate: 0,
+                                fee_confidence: FeeConfidence::Estimated,
                                 error_message: None,
                                 account_changes: std::collections::HashMap::new(),
                                 warnings: vec![],
@@ -379,7 +1073,7 @@ impl JupiterClient {
                         })
                     }
                     Err(confirm_err) => {
-                        println!("⚠️  Transaction sent but confirmation failed: {}", confirm_err);
+                        note!(self.settings.notes, "⚠️  Transaction sent but confirmation failed: {}", confirm_err);
                         Ok(SafeSendResult {
                             sent: true,
                             signature: Some(signature),
@@ -389,6 +1083,7 @@ impl JupiterClient {
                                 logs: vec!["Transaction sent but confirmation failed".to_string()],
                                 compute_units_consumed: 0,
                                 fee_estimate: 0,
+                                fee_confidence: FeeConfidence::Estimated,
                                 error_message: Some(confirm_err.to_string()),
                                 account_changes: std::collections::HashMap::new(),
                                 warnings: vec![],
@@ -398,7 +1093,7 @@ impl JupiterClient {
                 }
             }
             Err(e) => {
-                println!("❌ Transaction failed to send: {}", e);
+                note!(self.settings.notes, "❌ Transaction failed to send: {}", e);
                 Ok(SafeSendResult {
                     sent: false,
                     signature: None,
@@ -408,6 +1103,7 @@ impl JupiterClient {
                         logs: vec!["Transaction send failed".to_string()],
                         compute_units_consumed: 0,
                         fee_estimate: 0,
+                        fee_confidence: FeeConfidence::Estimated,
                         error_message: Some(e.to_string()),
                         account_changes: std::collections::HashMap::new(),
                         warnings: vec![],
@@ -440,14 +1136,16 @@ impl JupiterClient {
             error_message: None,
             compute_units_consumed: 0,
             fee_estimate: 0,
+            fee_confidence: FeeConfidence::Estimated,
             logs: response.value.logs.unwrap_or_default(),
             account_changes: std::collections::HashMap::new(),
             warnings: vec![],
         };
 
       
-        if let Some(err) = response.value.err {
-            result.error_message = Some(format!("{:?}", err));
+        if let Some(err) = &response.value.err {
+            let program_ids = error_decoder::message_program_ids(&transaction.message);
+            result.error_message = Some(error_decoder::describe_failure(None, &program_ids, err, &result.logs));
         }
 
   
@@ -456,9 +1154,7 @@ impl JupiterClient {
         }
 
    
-        let signature_fee = transaction.signatures.len() as u64 * 5000;
-        let compute_fee = (result.compute_units_consumed / 1000) * 100; 
-        result.fee_estimate = signature_fee + compute_fee;
+        (result.fee_estimate, result.fee_confidence) = estimate_fee(rpc_client, &transaction.message, result.compute_units_consumed);
 
         Ok(result)
     }
@@ -481,12 +1177,11 @@ impl JupiterClient {
             .await?;
 
         if !response.status().is_success() {
-            let error_text = response.text().await?;
-            return Err(anyhow!("Jupiter quote failed: {}", error_text));
+            return quote_result(&request, Err(JupiterApiError::from_response("quote", response).await));
         }
 
         let quote: QuoteResponse = response.json().await?;
-        Ok(quote)
+        quote_result(&request, Ok(quote))
     }
 
    
@@ -500,14 +1195,66 @@ impl JupiterClient {
             .await?;
 
         if !response.status().is_success() {
-            let error_text = response.text().await?;
-            return Err(anyhow!("Jupiter swap failed: {}", error_text));
+            return Err(JupiterApiError::from_response("swap", response).await.into());
         }
 
         let swap: SwapResponse = response.json().await?;
         Ok(swap)
     }
 
+    /// Quote the same swap at each slippage setting concurrently, preserving input order.
+    pub async fn compare_slippage(
+        &self,
+        input_mint: &str,
+        output_mint: &str,
+        amount: u64,
+        slippages_bps: &[u16],
+    ) -> Vec<SlippageComparison> {
+        let quotes = futures::future::join_all(slippages_bps.iter().map(|bps| self.get_quote(QuoteRequest {
+            input_mint: input_mint.to_string(),
+            output_mint: output_mint.to_string(),
+            amount,
+            slippage_bps: Some(*bps),
+            restrict_intermediate_tokens: Some(true),
+            only_direct_routes: Some(false),
+        }))).await;
+
+        slippages_bps.iter().zip(quotes).map(|(bps, quote)| match quote {
+            Ok(quote) => SlippageComparison {
+                slippage_bps: *bps,
+                out_amount: Some(quote.out_amount),
+                other_amount_threshold: Some(quote.other_amount_threshold),
+                price_impact_pct: Some(quote.price_impact_pct),
+                error: None,
+            },
+            Err(e) => SlippageComparison {
+                slippage_bps: *bps,
+                out_amount: None,
+                other_amount_threshold: None,
+                price_impact_pct: None,
+                error: Some(e.to_string()),
+            },
+        }).collect()
+    }
+
+    /// Call `/swap-instructions` for the component instructions instead of a serialized transaction.
+    pub async fn get_swap_instructions_decomposed(&self, request: SwapRequest) -> Result<DecomposedSwap> {
+        let url = format!("{}/swap-instructions", self.base_url);
+
+        let response = self.client
+            .post(&url)
+            .json(&request)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(JupiterApiError::from_response("swap-instructions", response).await.into());
+        }
+
+        let decomposed: DecomposedSwap = response.json().await?;
+        Ok(decomposed)
+    }
+
    
     pub async fn build_swap_transaction(
         &self,
@@ -515,17 +1262,25 @@ impl JupiterClient {
         input_mint: &str,
         output_mint: &str,
         amount: u64,
-        slippage_bps: Option<u16>,
+        slippage: SlippagePolicy,
+        input_decimals: Option<u8>,
+        output_decimals: Option<u8>,
     ) -> Result<VersionedTransaction> {
-        let mut slippage_candidates: Vec<u16> = match slippage_bps {
-            Some(s) => vec![s, 100, 150, 200],
-            None => vec![50, 100, 150, 200],
-        };
-       
-        slippage_candidates.dedup();
+        let slippage_candidates = slippage.schedule()?;
+        if slippage.dynamic.is_some() {
+            note!(self.settings.notes, "🎚️  Dynamic slippage: Jupiter picks per route; quoting at {} bps", slippage.requested_bps);
+        }
+        note!(self.settings.notes, "🎚️  Slippage schedule: {} bps (cap {} bps{})",
+             slippage_candidates.iter().map(|s| s.to_string()).collect::<Vec<_>>().join(" → "),
+             slippage.max_bps,
+             if slippage.allow_escalation { "" } else { ", escalation disabled" });
 
         for (idx, s) in slippage_candidates.iter().enumerate() {
-            println!("🔍 Getting Jupiter quote (attempt {} with {} bps)...", idx + 1, s);
+            if *s > slippage.requested_bps {
+                note!(self.settings.notes, "⚠️  Escalating slippage to {} bps ({}%) above requested {} bps",
+                     s, *s as f64 / 100.0, slippage.requested_bps);
+            }
+            note!(self.settings.notes, "🔍 Getting Jupiter quote (attempt {} with {} bps)...", idx + 1, s);
 
         let quote_request = QuoteRequest {
             input_mint: input_mint.to_string(),
@@ -539,18 +1294,23 @@ impl JupiterClient {
        
             let quote = self.get_fresh_quote(quote_request, 3).await?;
         
-            println!("💱 Fresh quote received:");
-        println!("  📥 Input: {} {} tokens", quote.in_amount, input_mint);
-        println!("  📤 Output: {} {} tokens", quote.out_amount, output_mint);
-        println!("  💸 Price impact: {}%", quote.price_impact_pct);
-        println!("  🛣️  Route uses {} DEXs:", quote.route_plan.len());
+            note!(self.settings.notes, "💱 Fresh quote received:");
+        note!(self.settings.notes, "  📥 Input: {} {}", display_amount(&quote.in_amount, input_decimals), input_mint);
+        note!(self.settings.notes, "  📤 Output: {} {}", display_amount(&quote.out_amount, output_decimals), output_mint);
+        note!(self.settings.notes, "  💸 Price impact: {}%", quote.price_impact_pct);
+        if let (Some(max), Ok(impact)) = (slippage.max_price_impact_pct, quote.price_impact_pct.parse::<f64>()) {
+            if impact > max {
+                return Err(anyhow!("Price impact {}% exceeds the {}% limit", impact, max));
+            }
+        }
+        note!(self.settings.notes, "  🛣️  Route uses {} DEXs:", quote.route_plan.len());
         for (i, route) in quote.route_plan.iter().enumerate() {
-            println!("    {}. {} ({}%)", i + 1, route.swap_info.label, route.percent);
+            note!(self.settings.notes, "    {}. {} ({}%)", i + 1, route.swap_info.label, route.percent);
         }
 
            
             if let Some(slot) = quote.slot {
-                println!("  📊 Quote slot: {}", slot);
+                note!(self.settings.notes, "  📊 Quote slot: {}", slot);
             }
             if let Some(timestamp) = quote.timestamp {
                 let age = std::time::SystemTime::now()
@@ -558,23 +1318,27 @@ impl JupiterClient {
                     .unwrap_or_default()
                     .as_secs()
                     .saturating_sub(timestamp);
-                println!("  ⏰ Quote age: {} seconds", age);
+                note!(self.settings.notes, "  ⏰ Quote age: {} seconds", age);
             }
             if let Some(hash) = &quote.quote_hash {
-                println!("  🔐 Quote hash: {}...", &hash[..std::cmp::min(8, hash.len())]);
+                note!(self.settings.notes, "  🔐 Quote hash: {}...", &hash[..std::cmp::min(8, hash.len())]);
             }
 
-        println!("\n🔧 Building swap transaction...");
+        note!(self.settings.notes, "\n🔧 Building swap transaction...");
         let swap_request = SwapRequest {
             user_public_key: user_pubkey.to_string(),
             quote_response: quote,
             wrap_and_unwrap_sol: Some(true),
             dynamic_compute_unit_limit: Some(true),
-            prioritization_fee_lamports: Some("auto".to_string()),
+            prioritization_fee_lamports: Some(self.prioritization_fee()),
+            dynamic_slippage: slippage.dynamic.map(DynamicSlippage::request_value),
         };
 
             match self.get_swap_instructions(swap_request).await {
                 Ok(swap_response) => {
+                    if let Some(report) = &swap_response.dynamic_slippage_report {
+                        note!(self.settings.notes, "🎚️  {}", report.describe());
+                    }
         use base64::Engine;
                     let transaction_bytes = base64::engine::general_purpose::STANDARD
                         .decode(&swap_response.swap_transaction)?;
@@ -583,19 +1347,23 @@ impl JupiterClient {
                         bincode::config::standard(),
                     )?
                     .0;
-                    println!("✅ Jupiter transaction built successfully with {} bps!", s);
-                    println!("🔗 Contains {} instructions", transaction.message.instructions().len());
+                    note!(self.settings.notes, "✅ Jupiter transaction built successfully with {} bps!", s);
+                    note!(self.settings.notes, "🔗 Contains {} instructions", transaction.message.instructions().len());
                     return Ok(transaction);
                 }
                 Err(e) => {
-                    println!("⚠️  Build failed at {} bps: {}", s, e);
+                    note!(self.settings.notes, "⚠️  Build failed at {} bps: {}", s, e);
                    
                     continue;
                 }
             }
         }
 
-        Err(anyhow!("Failed to build swap after adaptive slippage attempts"))
+        Err(anyhow!(
+            "Failed to build swap at {} bps{}",
+            slippage_candidates.last().copied().unwrap_or(slippage.requested_bps),
+            if slippage.allow_escalation { " (max escalation reached)" } else { "; pass --allow-slippage-escalation with --max-slippage-bps to retry wider" }
+        ))
     }
 
         
@@ -616,6 +1384,30 @@ impl JupiterClient {
         Ok(tokens)
     }
 
+    /// Jupiter's token list keyed by mint address, fetched once per client.
+    pub async fn get_token_list(&self) -> Result<HashMap<String, TokenInfo>> {
+        if let Some(list) = self.token_list.lock().unwrap().as_ref() {
+            return Ok(list.clone());
+        }
+
+        let response = self.client
+            .get(TOKEN_LIST_URL)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await?;
+            return Err(anyhow!("Failed to fetch token list: {}", error_text));
+        }
+
+        let tokens: Vec<TokenInfo> = response.json().await?;
+        let list: HashMap<String, TokenInfo> = tokens.into_iter()
+            .map(|t| (t.address.clone(), t))
+            .collect();
+        *self.token_list.lock().unwrap() = Some(list.clone());
+        Ok(list)
+    }
+
    
     pub async fn get_program_labels(&self) -> Result<std::collections::HashMap<String, String>> {
         let url = "https://quote-api.jup.ag/v6/program-id-to-label".to_string();
@@ -640,9 +1432,6 @@ pub mod tokens {
     pub const SOL: &str = "So11111111111111111111111111111111111111112";
     pub const USDC: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
     pub const USDT: &str = "Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB";
-    pub const RAY: &str = "4k3Dyjzvzp8eMZWUXbBCjEvwSkkk59S5iCNLY3QrkX6R";
-    pub const SRM: &str = "SRMuApVNdxXokk5GT7XD5cUUgXMBCoAz2LHeuAoKWRt";
-    pub const BONK: &str = "DezXAZ8z7PnrnRJjz3wXBoRgixCa6xjnB7YaB1pPB263";
 }
 
 #[cfg(test)]
@@ -669,4 +1458,249 @@ mod tests {
         assert!(!quote.out_amount.is_empty(), "Should return output amount");
         assert!(!quote.route_plan.is_empty(), "Should have at least one route");
     }
+
+    fn sample_quote() -> QuoteResponse {
+        serde_json::from_value(serde_json::json!({
+            "inputMint": tokens::SOL,
+            "inAmount": "1000000000",
+            "outputMint": tokens::USDC,
+            "outAmount": "150000000",
+            "otherAmountThreshold": "149250000",
+            "swapMode": "ExactIn",
+            "slippageBps": 50,
+            "priceImpactPct": "0.01",
+            "routePlan": [
+                { "swapInfo": { "ammKey": "AmmA", "label": "Raydium", "inputMint": tokens::SOL, "outputMint": tokens::USDC,
+                    "inAmount": "600000000", "outAmount": "90000000", "feeAmount": "1500", "feeMint": tokens::SOL }, "percent": 60 },
+                { "swapInfo": { "ammKey": "AmmB", "label": "Orca", "inputMint": tokens::SOL, "outputMint": tokens::USDC,
+                    "inAmount": "400000000", "outAmount": "60000000", "feeAmount": "1000", "feeMint": tokens::SOL }, "percent": 40 }
+            ],
+            "contextSlot": 1,
+            "timeTaken": 0.01
+        })).unwrap()
+    }
+
+    fn sample_request() -> QuoteRequest {
+        QuoteRequest {
+            input_mint: tokens::SOL.to_string(),
+            output_mint: tokens::USDC.to_string(),
+            amount: 1_000_000_000,
+            slippage_bps: Some(50),
+            restrict_intermediate_tokens: Some(true),
+            only_direct_routes: Some(false),
+        }
+    }
+
+    #[test]
+    fn test_swap_priority_fee_parse() {
+        assert_eq!("auto".parse::<SwapPriorityFee>().unwrap(), SwapPriorityFee::Auto);
+        assert_eq!("5000".parse::<SwapPriorityFee>().unwrap(), SwapPriorityFee::Lamports(5_000));
+        assert_eq!("percentile:75".parse::<SwapPriorityFee>().unwrap(), SwapPriorityFee::Percentile(75));
+        assert!("percentile:101".parse::<SwapPriorityFee>().is_err());
+        assert!("fast".parse::<SwapPriorityFee>().is_err());
+
+        assert_eq!(SwapPriorityFee::Auto.request_value(), serde_json::json!("auto"));
+        assert_eq!(SwapPriorityFee::Lamports(5_000).request_value(), serde_json::json!(5_000));
+    }
+
+    #[test]
+    fn test_swap_failure_and_fee_escalation() {
+        assert_eq!(classify_swap_failure("Transaction 5x was dropped: its blockhash expired before it landed (2 rebroadcast(s))"), SwapFailure::Congestion);
+        assert_eq!(classify_swap_failure("Send failed: operation timed out"), SwapFailure::Congestion);
+        assert_eq!(classify_swap_failure("Error processing Instruction 3: custom program error: 0x1771"), SwapFailure::Slippage);
+        assert_eq!(classify_swap_failure("Transfer: insufficient lamports 10, need 20"), SwapFailure::InsufficientFunds);
+        assert_eq!(classify_swap_failure("Error processing Instruction 2: invalid account data"), SwapFailure::Other);
+
+        let cap = 100_000;
+        assert_eq!(SwapPriorityFee::Auto.escalate(30_000, cap), Some(60_000));
+        assert_eq!(SwapPriorityFee::Auto.escalate(0, cap), Some(MIN_ESCALATED_PRIORITY_FEE));
+        assert_eq!(SwapPriorityFee::Lamports(60_000).escalate(0, cap), Some(cap));
+        assert_eq!(SwapPriorityFee::Lamports(cap).escalate(cap, cap), None);
+
+        let client = JupiterClient::new().with_priority_fee(SwapPriorityFee::Lamports(20_000));
+        assert_eq!(client.escalate_priority_fee(20_000, cap), Some(40_000));
+        assert_eq!(client.prioritization_fee(), serde_json::json!(40_000));
+    }
+
+    #[test]
+    fn test_route_summary_from_quote() {
+        let quote = sample_quote();
+        let summary = RouteSummary::from_quote(&quote);
+        assert_eq!(summary.hops.len(), 2);
+        assert_eq!(summary.hops[1].label, "Orca");
+        assert_eq!(summary.hops[0].percent, 60);
+
+        let json = serde_json::to_value(&summary).unwrap();
+        assert_eq!(json["hops"][0]["amm_key"], "AmmA");
+
+        let table = summary.render_table();
+        assert_eq!(table.lines().count(), 3);
+        assert!(table.lines().nth(2).unwrap().starts_with("2  Orca     AmmB  40%"));
+    }
+
+    #[test]
+    fn test_display_amounts_with_decimals() {
+        let summary = RouteSummary::from_quote(&sample_quote()).with_decimals(Some(9), None);
+        assert_eq!(summary.in_ui_amount.as_deref(), Some("1"));
+        assert_eq!(summary.out_ui_amount, None);
+
+        assert_eq!(display_amount("150000000", Some(6)), "150 (150000000 raw)");
+        assert_eq!(display_amount("150000000", None), "150000000 raw (decimals unknown)");
+    }
+
+    #[test]
+    fn test_quote_freshness_thresholds() {
+        let client = JupiterClient::new().with_freshness(QuoteFreshnessConfig::new(30, 9));
+        let check = |slot_drift: u64, age: u64| {
+            let quote = QuoteResponse { slot: Some(1_000), timestamp: Some(5_000), ..sample_quote() };
+            let v = client.validate_quote_freshness_at(&quote, 1_000 + slot_drift, 5_000 + age).unwrap();
+            (v.issues.len(), v.warnings.len())
+        };
+        assert_eq!(check(10, 3), (0, 0));
+        assert_eq!(check(11, 3), (0, 1));
+        assert_eq!(check(30, 3), (0, 1));
+        assert_eq!(check(31, 3), (1, 0));
+        assert_eq!(check(0, 4), (0, 1));
+        assert_eq!(check(0, 9), (0, 1));
+        assert_eq!(check(0, 10), (1, 0));
+
+        // Defaults keep the original 150/50 slot and 30/10 second limits
+        assert_eq!(QuoteFreshnessConfig::default(),
+                   QuoteFreshnessConfig { max_slot_drift: 150, warn_slot_drift: 50, max_age_secs: 30, warn_age_secs: 10 });
+    }
+
+    #[test]
+    fn test_implied_price() {
+        let quote = sample_quote();
+        assert_eq!(implied_price(&quote, 9, 6).unwrap(), 150.0);
+        assert!(implied_price(&QuoteResponse { in_amount: "0".to_string(), ..quote }, 9, 6).is_err());
+    }
+
+    #[test]
+    fn test_decomposed_swap_instructions() {
+        let decomposed: DecomposedSwap = serde_json::from_value(serde_json::json!({
+            "computeBudgetInstructions": [{
+                "programId": "ComputeBudget111111111111111111111111111111",
+                "accounts": [],
+                "data": "AsBcAAA="
+            }],
+            "setupInstructions": [],
+            "swapInstruction": {
+                "programId": "JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4",
+                "accounts": [{
+                    "pubkey": "So11111111111111111111111111111111111111112",
+                    "isSigner": false,
+                    "isWritable": true
+                }],
+                "data": "AQID"
+            },
+            "cleanupInstruction": null,
+            "otherInstructions": [],
+            "addressLookupTableAddresses": ["GxS6FiQ3mNnAar9HGQ6mxP7t6FcwmHkU7peSeQDUHmpN"]
+        })).unwrap();
+
+        let instructions = decomposed.instructions().unwrap();
+        assert_eq!(instructions.len(), 2);
+        assert_eq!(instructions[1].data, vec![1, 2, 3]);
+        assert!(instructions[1].accounts[0].is_writable);
+        assert!(!instructions[1].accounts[0].is_signer);
+        assert_eq!(decomposed.address_lookup_table_addresses.len(), 1);
+    }
+
+    #[test]
+    fn test_slippage_schedule_respects_cap() {
+        assert_eq!(SlippagePolicy::fixed(50).schedule().unwrap(), vec![50]);
+
+        let capped = SlippagePolicy { requested_bps: 50, max_bps: 150, allow_escalation: true, max_price_impact_pct: None, dynamic: None };
+        assert_eq!(capped.schedule().unwrap(), vec![50, 100, 150]);
+
+        // Escalation never applies without the opt-in, regardless of the cap
+        let no_opt_in = SlippagePolicy { requested_bps: 50, max_bps: 200, allow_escalation: false, max_price_impact_pct: None, dynamic: None };
+        assert_eq!(no_opt_in.schedule().unwrap(), vec![50]);
+
+        let above_steps = SlippagePolicy { requested_bps: 175, max_bps: 300, allow_escalation: true, max_price_impact_pct: None, dynamic: None };
+        assert_eq!(above_steps.schedule().unwrap(), vec![175, 200]);
+
+        assert!(SlippagePolicy { requested_bps: 300, max_bps: 100, allow_escalation: false, max_price_impact_pct: None, dynamic: None }.schedule().is_err());
+    }
+
+    #[test]
+    fn test_dynamic_slippage_request_and_report() {
+        assert_eq!(DynamicSlippage::default().request_value(), serde_json::json!(true));
+        let bounded = DynamicSlippage { min_bps: Some(30), max_bps: Some(300) };
+        assert_eq!(bounded.request_value(), serde_json::json!({"minBps": 30, "maxBps": 300}));
+        assert!(SlippagePolicy::fixed(50).with_dynamic(Some(DynamicSlippage { min_bps: Some(300), max_bps: Some(30) }))
+            .schedule().is_err());
+
+        let response: SwapResponse = serde_json::from_value(serde_json::json!({
+            "swapTransaction": "AA==",
+            "lastValidBlockHeight": 1,
+            "dynamicSlippageReport": {"slippageBps": 74, "otherAmount": null, "simulatedIncurredSlippageBps": -8,
+                                      "amplificationRatio": "1.5", "categoryName": "stable"}
+        })).unwrap();
+        assert_eq!(response.dynamic_slippage_report.unwrap().describe(),
+                   "Jupiter applied 74 bps (0.74%) dynamic slippage for a stable pair; -8 bps incurred in its simulation");
+    }
+
+    #[test]
+    fn test_quote_cache_hit_and_expiry() {
+        let client = JupiterClient::new();
+        let request = sample_request();
+        assert!(client.cached_quote(&request, 0).is_none());
+
+        client.cache_quote(&request, &sample_quote());
+        assert_eq!(client.cached_quote(&request, 0).unwrap().out_amount, "150000000");
+
+        let mut other = sample_request();
+        other.slippage_bps = Some(100);
+        assert!(client.cached_quote(&other, 0).is_none());
+
+        // Entries older than the TTL are evicted on lookup
+        client.quote_cache.lock().unwrap()
+            .get_mut(&QuoteCacheKey::from_request(&request)).unwrap()
+            .fetched_at = Instant::now() - QUOTE_CACHE_TTL;
+        assert!(client.cached_quote(&request, 0).is_none());
+        assert!(client.quote_cache.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_jupiter_error_mapping() {
+        let no_route = JupiterApiError::from_parts(
+            "quote",
+            400,
+            r#"{"error":"Could not find any route","errorCode":"COULD_NOT_FIND_ANY_ROUTE"}"#,
+            None,
+        );
+        assert_eq!(no_route.code.as_deref(), Some("COULD_NOT_FIND_ANY_ROUTE"));
+        assert_eq!(no_route.message, "Could not find any route");
+        assert!(no_route.is_no_route());
+        assert!(no_route.to_string().contains("No routes found"));
+
+        let rate_limited = JupiterApiError::from_parts("quote", 429, "Too Many Requests", Some(Duration::from_secs(2)));
+        assert!(rate_limited.is_rate_limited());
+        assert_eq!(rate_limited.retry_after, Some(Duration::from_secs(2)));
+        assert!(rate_limited.suggestion().is_some());
+
+        let err: anyhow::Error = rate_limited.into();
+        assert!(err.downcast_ref::<JupiterApiError>().is_some());
+    }
+
+    #[test]
+    fn test_no_route_error() {
+        let request = sample_request();
+        let no_route_body = r#"{"error":"Could not find any route","errorCode":"COULD_NOT_FIND_ANY_ROUTE"}"#;
+        let err = quote_result(&request, Err(JupiterApiError::from_parts("quote", 400, no_route_body, None))).unwrap_err();
+        assert_eq!(err.downcast_ref::<NoRouteError>(), Some(&NoRouteError::for_request(&request)));
+        assert_eq!(err.to_string(), format!(
+            "No swap route found for {}→{} at amount 1000000000 (base units); try a larger amount or a different pair",
+            tokens::SOL, tokens::USDC));
+
+        // A 200 with an empty route plan is the same condition
+        let empty = QuoteResponse { route_plan: Vec::new(), ..sample_quote() };
+        assert!(quote_result(&request, Ok(empty)).unwrap_err().downcast_ref::<NoRouteError>().is_some());
+
+        let rate_limited = JupiterApiError::from_parts("quote", 429, "Too Many Requests", None);
+        assert!(quote_result(&request, Err(rate_limited)).unwrap_err().downcast_ref::<JupiterApiError>().is_some());
+        assert!(quote_result(&request, Ok(sample_quote())).is_ok());
+    }
 }
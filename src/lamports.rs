@@ -0,0 +1,124 @@
+use anyhow::{anyhow, Result};
+
+const SOL_DECIMALS: usize = 9;
+
+/// Parse a decimal SOL amount (e.g. "1.5") into lamports using integer math.
+pub fn sol_to_lamports(amount: &str) -> Result<u64> {
+    parse_decimal_amount(amount, SOL_DECIMALS, "SOL")
+}
+
+/// Parse a UI token amount (e.g. "2.5") into raw units for a mint with `decimals` places.
+pub fn parse_token_amount(amount: &str, decimals: u8) -> Result<u64> {
+    parse_decimal_amount(amount, decimals as usize, "Token")
+}
+
+fn parse_decimal_amount(amount: &str, decimals: usize, unit: &str) -> Result<u64> {
+    let amount = amount.trim();
+    if amount.is_empty() {
+        return Err(anyhow!("Empty {} amount", unit));
+    }
+    if amount.starts_with('-') {
+        return Err(anyhow!("{} amount cannot be negative: {}", unit, amount));
+    }
+    let amount = amount.strip_prefix('+').unwrap_or(amount);
+
+    let (whole, fraction) = match amount.split_once('.') {
+        Some((w, f)) => (w, f),
+        None => (amount, ""),
+    };
+    if whole.is_empty() && fraction.is_empty() {
+        return Err(anyhow!("Invalid {} amount: {}", unit, amount));
+    }
+    if !whole.chars().all(|c| c.is_ascii_digit()) || !fraction.chars().all(|c| c.is_ascii_digit()) {
+        return Err(anyhow!("Invalid {} amount: {}", unit, amount));
+    }
+    if fraction.len() > decimals {
+        return Err(anyhow!("{} amount has more than {} decimal places: {}", unit, decimals, amount));
+    }
+
+    // u128 so mints with up to 38 decimals scale without overflowing before the final range check
+    let too_large = || anyhow!("{} amount too large: {}", unit, amount);
+    let scale = 10u128.checked_pow(decimals as u32).ok_or_else(too_large)?;
+    let whole_units = if whole.is_empty() {
+        0
+    } else {
+        whole.parse::<u128>().map_err(|_| too_large())?.checked_mul(scale).ok_or_else(too_large)?
+    };
+
+    let fraction_units = if fraction.is_empty() {
+        0
+    } else {
+        let padded = format!("{:0<width$}", fraction, width = decimals);
+        padded.parse::<u128>().map_err(|_| anyhow!("Invalid {} amount: {}", unit, amount))?
+    };
+
+    u64::try_from(whole_units + fraction_units).map_err(|_| too_large())
+}
+
+/// Render lamports as an exact decimal SOL string (trailing zeros trimmed).
+pub fn lamports_to_sol(lamports: u64) -> String {
+    format_token_amount(lamports, SOL_DECIMALS as u8)
+}
+
+/// Render a raw token amount in UI units for a mint with `decimals` places.
+pub fn format_token_amount(amount: u64, decimals: u8) -> String {
+    // u64 can't exceed 10^20, so an overflowing scale just means everything is fractional
+    let scale = 10u128.checked_pow(decimals as u32).unwrap_or(u128::MAX);
+    let whole = amount as u128 / scale;
+    let fraction = amount as u128 % scale;
+    if fraction == 0 {
+        return whole.to_string();
+    }
+    let fraction = format!("{:0width$}", fraction, width = decimals as usize);
+    format!("{}.{}", whole, fraction.trim_end_matches('0'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LAMPORTS_PER_SOL: u64 = 1_000_000_000;
+
+    #[test]
+    fn test_sol_to_lamports() {
+        assert_eq!(sol_to_lamports("0.000000001").unwrap(), 1);
+        assert_eq!(sol_to_lamports("1").unwrap(), LAMPORTS_PER_SOL);
+        assert_eq!(sol_to_lamports("0.01").unwrap(), 10_000_000);
+        assert_eq!(sol_to_lamports(".5").unwrap(), 500_000_000);
+
+        // f64 math would drift here; integer math must not
+        assert_eq!(sol_to_lamports("18446744073.709551615").unwrap(), u64::MAX);
+        assert_eq!(sol_to_lamports("9007199.254740993").unwrap(), 9_007_199_254_740_993);
+
+        assert!(sol_to_lamports("18446744073.709551616").is_err());
+        assert!(sol_to_lamports("0.0000000001").is_err());
+        assert!(sol_to_lamports("-1").is_err());
+        assert!(sol_to_lamports("abc").is_err());
+        assert!(sol_to_lamports(".").is_err());
+    }
+
+    #[test]
+    fn test_lamports_to_sol() {
+        assert_eq!(lamports_to_sol(1), "0.000000001");
+        assert_eq!(lamports_to_sol(1_500_000_000), "1.5");
+        assert_eq!(lamports_to_sol(2 * LAMPORTS_PER_SOL), "2");
+        assert_eq!(lamports_to_sol(u64::MAX), "18446744073.709551615");
+    }
+
+    #[test]
+    fn test_format_token_amount() {
+        assert_eq!(format_token_amount(1_500_000, 6), "1.5");
+        assert_eq!(format_token_amount(42, 0), "42");
+        assert_eq!(format_token_amount(5, 2), "0.05");
+        assert_eq!(format_token_amount(u64::MAX, 20), "0.18446744073709551615");
+    }
+
+    #[test]
+    fn test_parse_token_amount() {
+        assert_eq!(parse_token_amount("2.5", 6).unwrap(), 2_500_000);
+        assert_eq!(parse_token_amount("42", 0).unwrap(), 42);
+        assert_eq!(parse_token_amount("0.18446744073709551615", 20).unwrap(), u64::MAX);
+        assert!(parse_token_amount("0.1", 0).is_err());
+        assert!(parse_token_amount("18446744073709.551616", 6).is_err());
+    }
+}
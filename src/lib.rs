@@ -0,0 +1,72 @@
+//! Building blocks behind the `solana-program-cli` binary: IDL loading and Borsh encoding, account
+//! resolution, simulation and sending, the program registry, ATAs, and the Jupiter client.
+
+mod account_cache;
+mod account_inspector;
+mod account_resolver;
+mod ata_manager;
+mod batch;
+mod borsh_encoder;
+mod bundle;
+mod cli;
+mod commands;
+mod error_decoder;
+mod explorer;
+// Generated clients keep every instruction builder, used or not
+#[allow(dead_code)]
+mod generated;
+mod history;
+mod idempotency;
+mod idl_loader;
+mod instruction_dump;
+mod jupiter_client;
+mod lamports;
+mod outcome;
+mod output;
+mod payer;
+mod priority_fee;
+mod program_command;
+mod program_defs;
+mod program_handlers;
+mod program_registry;
+mod proposal;
+mod progress;
+mod rent_cache;
+mod rpc;
+mod sign_only;
+mod transaction_simulator;
+mod tx_sender;
+
+pub use cli::Cli;
+pub use commands::run;
+pub use outcome::{CommandOutcome, Note, Notes};
+pub use output::{render_table, render_tsv, to_json_string, OutputFormat};
+
+pub use account_resolver::AccountResolver;
+pub use ata_manager::AtaManager;
+pub use borsh_encoder::BorshEncoder;
+pub use idl_loader::IdlLoader;
+pub use jupiter_client::JupiterClient;
+pub use program_registry::ProgramRegistry;
+pub use transaction_simulator::TransactionSimulator;
+
+// What the types above take and return
+pub use account_cache::AccountCache;
+pub use account_resolver::{AccountResolution, SendAccountInfo, SwapPoolInfo};
+pub use ata_manager::{AtaInfo, CommonMints, TokenBalance, TransferFee};
+pub use explorer::{Explorer, ExplorerLinks};
+pub use idempotency::IdempotencyKey;
+pub use idl_loader::{IdlError, IdlInstruction, ProgramIdl};
+pub use jupiter_client::{
+    DecomposedSwap, QuoteFreshnessConfig, QuoteRequest, QuoteResponse, QuoteValidation, SlippageComparison,
+    SlippagePolicy, SwapPriorityFee, SwapRequest, SwapResponse, TokenInfo,
+};
+pub use jupiter_client::{SafeSendResult as SwapSendResult, SimulationResult as SwapSimulation};
+pub use priority_fee::{PriorityFees, PrioritySetting, PriorityTier};
+pub use program_registry::{ProgramManifest, ProgramRoute, RegistryStats, SwapProfile};
+pub use rent_cache::RentCache;
+pub use rpc::{Commitment, SendCounter};
+pub use transaction_simulator::{
+    FeeConfidence, SafeSendResult, SimulationMode, SimulationResult, TransactionPreview, ValidationResult,
+};
+pub use tx_sender::{SendSettings, TxVersion};
@@ -0,0 +1,271 @@
+use anyhow::Result;
+use indicatif::ProgressBar;
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use std::sync::{Arc, Mutex};
+use crate::progress;
+use crate::transaction_simulator::{FeeConfidence, SimulationResult, TransactionPreview};
+
+/// Something a command reports while it runs, for the renderer to show.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Note {
+    /// A human-readable status line ("📤 Sending...", "✅ Transaction signature: ...")
+    Status(String),
+    /// A document a command hands back instead of sending: a `--build-only`/`--sign-only` transaction or a proposal
+    Document(Value),
+}
+
+type Listener = Arc<dyn Fn(&Note) + Send + Sync>;
+
+/// Where library code reports progress; it never prints. Notes are kept for the command's outcome, or passed
+/// straight to a listener so a long send or `logs`/`watch` shows them as they happen.
+#[derive(Clone, Default)]
+pub struct Notes {
+    kept: Arc<Mutex<Vec<Note>>>,
+    listener: Option<Listener>,
+    progress: bool,
+}
+
+impl std::fmt::Debug for Notes {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Notes").field("streaming", &self.listener.is_some()).field("progress", &self.progress).finish()
+    }
+}
+
+impl Notes {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Notes that go to `listener` as they are made instead of being kept.
+    pub fn streaming(listener: impl Fn(&Note) + Send + Sync + 'static) -> Self {
+        Self { listener: Some(Arc::new(listener)), ..Self::default() }
+    }
+
+    /// Draw progress bars on a terminal's stderr while notes stream.
+    pub fn with_progress(mut self, progress: bool) -> Self {
+        self.progress = progress;
+        self
+    }
+
+    fn push(&self, note: Note) {
+        match &self.listener {
+            Some(listener) => listener(&note),
+            None => self.kept.lock().unwrap().push(note),
+        }
+    }
+
+    /// Report a status line; multi-line text is kept as one note.
+    pub fn status(&self, line: impl Into<String>) {
+        let line = line.into();
+        self.push(Note::Status(line.trim_end_matches('\n').to_string()));
+    }
+
+    /// Report pre-formatted lines (a table, a dump); nothing when `text` is empty.
+    pub fn text(&self, text: impl Into<String>) {
+        let text = text.into();
+        if !text.is_empty() {
+            self.status(text);
+        }
+    }
+
+    pub fn document(&self, document: &impl Serialize) -> Result<()> {
+        self.push(Note::Document(serde_json::to_value(document)?));
+        Ok(())
+    }
+
+    /// A step/count bar, hidden unless progress is on.
+    pub fn progress_bar(&self, len: u64, message: &str) -> ProgressBar {
+        if self.progress { progress::progress_bar(len, message) } else { ProgressBar::hidden() }
+    }
+
+    /// Notes kept so far, oldest first.
+    pub fn take(&self) -> Vec<Note> {
+        std::mem::take(&mut *self.kept.lock().unwrap())
+    }
+}
+
+/// `format!` a status line onto `Notes`.
+macro_rules! note {
+    ($notes:expr, $($arg:tt)*) => {
+        $notes.status(format!($($arg)*))
+    };
+}
+pub(crate) use note;
+
+/// The parts of a simulation worth reporting after the fact.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SimulationSummary {
+    pub success: bool,
+    pub compute_units: u64,
+    pub fee_estimate: u64,
+    /// `exact` from the RPC, or `estimated` by the offline heuristic (a rough lower bound)
+    pub fee_confidence: FeeConfidence,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl From<&SimulationResult> for SimulationSummary {
+    fn from(simulation: &SimulationResult) -> Self {
+        Self {
+            success: simulation.success,
+            compute_units: simulation.compute_units_consumed,
+            fee_estimate: simulation.fee_estimate,
+            fee_confidence: simulation.fee_confidence,
+            error: simulation.error_message.clone(),
+        }
+    }
+}
+
+impl From<&crate::jupiter_client::SimulationResult> for SimulationSummary {
+    fn from(simulation: &crate::jupiter_client::SimulationResult) -> Self {
+        Self {
+            success: simulation.success,
+            compute_units: simulation.compute_units_consumed,
+            fee_estimate: simulation.fee_estimate,
+            fee_confidence: simulation.fee_confidence,
+            error: simulation.error_message.clone(),
+        }
+    }
+}
+
+impl From<&TransactionPreview> for SimulationSummary {
+    fn from(preview: &TransactionPreview) -> Self {
+        Self {
+            success: preview.will_succeed,
+            compute_units: preview.compute_units,
+            fee_estimate: preview.estimated_fee,
+            fee_confidence: preview.fee_confidence,
+            error: preview.error_summary.clone(),
+        }
+    }
+}
+
+/// What a command produced. Handlers return it; `main` renders it per `--output`, and library callers read it directly.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct CommandOutcome {
+    /// Signature of the transaction the command sent, if it sent one
+    pub signature: Option<Signature>,
+    /// Accounts the command created or acted on, labeled (`send_account`, `ata`, ...)
+    pub addresses: Vec<(String, Pubkey)>,
+    pub simulation: Option<SimulationSummary>,
+    /// Decoded account state or query result
+    pub state: Option<Value>,
+    /// Rows for `--output table`
+    pub table: Option<(Vec<String>, Vec<Vec<String>>)>,
+    /// Status lines and documents the command reported, when its `Notes` kept them
+    pub notes: Vec<Note>,
+}
+
+impl CommandOutcome {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_signature(mut self, signature: Option<Signature>) -> Self {
+        self.signature = signature;
+        self
+    }
+
+    pub fn with_address(mut self, label: &str, address: Pubkey) -> Self {
+        self.addresses.push((label.to_string(), address));
+        self
+    }
+
+    pub fn with_simulation(mut self, simulation: impl Into<SimulationSummary>) -> Self {
+        self.simulation = Some(simulation.into());
+        self
+    }
+
+    pub fn with_state(mut self, state: &impl Serialize) -> Result<Self> {
+        self.state = Some(serde_json::to_value(state)?);
+        Ok(self)
+    }
+
+    pub fn with_table(mut self, headers: &[&str], rows: Vec<Vec<String>>) -> Self {
+        self.table = Some((headers.iter().map(|h| h.to_string()).collect(), rows));
+        self
+    }
+
+    pub fn with_notes(mut self, notes: Vec<Note>) -> Self {
+        self.notes.extend(notes);
+        self
+    }
+
+    /// JSON form. An outcome that is only a query result is that result as-is, so `--output json` shapes don't
+    /// change with the wrapping; `None` when there is nothing to report.
+    pub fn to_json(&self) -> Option<Value> {
+        let only_state = self.signature.is_none() && self.addresses.is_empty() && self.simulation.is_none();
+        if only_state {
+            return self.state.clone();
+        }
+
+        let mut out = Map::new();
+        if let Some(signature) = &self.signature {
+            out.insert("signature".to_string(), json!(signature.to_string()));
+        }
+        if !self.addresses.is_empty() {
+            let addresses: Map<String, Value> = self.addresses.iter()
+                .map(|(label, address)| (label.clone(), json!(address.to_string())))
+                .collect();
+            out.insert("addresses".to_string(), Value::Object(addresses));
+        }
+        if let Some(simulation) = &self.simulation {
+            out.insert("simulation".to_string(), json!(simulation));
+        }
+        if let Some(state) = &self.state {
+            out.insert("state".to_string(), state.clone());
+        }
+        Some(Value::Object(out))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_outcome_to_json() {
+        assert_eq!(CommandOutcome::new().to_json(), None);
+
+        let query = CommandOutcome::new().with_state(&json!([{ "code": 6000 }])).unwrap();
+        assert_eq!(query.to_json(), Some(json!([{ "code": 6000 }])));
+
+        let account = Pubkey::new_unique();
+        let sent = CommandOutcome::new()
+            .with_signature(Some(Signature::default()))
+            .with_address("send_account", account)
+            .with_state(&json!({ "balance": 1 })).unwrap();
+        let value = sent.to_json().unwrap();
+        assert_eq!(value["signature"], Signature::default().to_string());
+        assert_eq!(value["addresses"]["send_account"], account.to_string());
+        assert_eq!(value["state"]["balance"], 1);
+        assert!(value.get("simulation").is_none());
+    }
+
+    #[test]
+    fn test_notes_kept_or_streamed() {
+        let notes = Notes::new();
+        note!(notes, "📤 Sending {} transaction(s)", 2);
+        notes.text("📦 Built\n");
+        notes.text("");
+        notes.document(&json!({ "transaction": "abc" })).unwrap();
+        assert_eq!(notes.take(), vec![
+            Note::Status("📤 Sending 2 transaction(s)".to_string()),
+            Note::Status("📦 Built".to_string()),
+            Note::Document(json!({ "transaction": "abc" })),
+        ]);
+        assert!(notes.take().is_empty());
+
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let streamed = Notes::streaming({
+            let seen = seen.clone();
+            move |note| seen.lock().unwrap().push(note.clone())
+        });
+        streamed.status("✅ Done");
+        assert!(streamed.take().is_empty());
+        assert_eq!(*seen.lock().unwrap(), vec![Note::Status("✅ Done".to_string())]);
+    }
+}
@@ -0,0 +1,103 @@
+use anyhow::Result;
+use clap::ValueEnum;
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    Human,
+    Json,
+    Table,
+}
+
+impl OutputFormat {
+    pub fn is_json(&self) -> bool {
+        matches!(self, OutputFormat::Json)
+    }
+
+    pub fn is_table(&self) -> bool {
+        matches!(self, OutputFormat::Table)
+    }
+}
+
+/// `value` as JSON, pretty-printed unless `compact`.
+pub fn to_json_string<T: Serialize>(value: &T, compact: bool) -> Result<String> {
+    Ok(if compact { serde_json::to_string(value)? } else { serde_json::to_string_pretty(value)? })
+}
+
+/// Tab-separated rows, for a table piped to another program.
+pub fn render_tsv(headers: &[&str], rows: &[Vec<String>]) -> String {
+    let mut out = headers.join("\t");
+    out.push('\n');
+    for row in rows {
+        out.push_str(&row.join("\t"));
+        out.push('\n');
+    }
+    out
+}
+
+/// Render rows as a left-aligned, space-padded table with a header row.
+pub fn render_table(headers: &[&str], rows: &[Vec<String>]) -> String {
+    let mut widths: Vec<usize> = headers.iter().map(|h| h.chars().count()).collect();
+    for row in rows {
+        for (i, cell) in row.iter().enumerate() {
+            if i < widths.len() {
+                widths[i] = widths[i].max(cell.chars().count());
+            }
+        }
+    }
+
+    let format_row = |cells: Vec<&str>| -> String {
+        let mut line = String::new();
+        for (i, cell) in cells.iter().enumerate() {
+            let width = widths.get(i).copied().unwrap_or(0);
+            if i + 1 == cells.len() {
+                line.push_str(cell);
+            } else {
+                line.push_str(&format!("{:<width$}  ", cell, width = width));
+            }
+        }
+        line
+    };
+
+    let mut out = String::new();
+    out.push_str(&format_row(headers.to_vec()));
+    out.push('\n');
+    for row in rows {
+        out.push_str(&format_row(row.iter().map(|s| s.as_str()).collect()));
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_table_aligns_columns() {
+        let table = render_table(
+            &["#", "Label", "Percent"],
+            &[
+                vec!["1".to_string(), "Raydium".to_string(), "60%".to_string()],
+                vec!["2".to_string(), "Orca".to_string(), "40%".to_string()],
+            ],
+        );
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines[0], "#  Label    Percent");
+        assert_eq!(lines[1], "1  Raydium  60%");
+        assert_eq!(lines[2], "2  Orca     40%");
+    }
+
+    #[test]
+    fn test_render_tsv_for_pipes() {
+        let tsv = render_tsv(&["Name", "Amount"], &[vec!["SOL".to_string(), "1.5".to_string()]]);
+        assert_eq!(tsv, "Name\tAmount\nSOL\t1.5\n");
+    }
+
+    #[test]
+    fn test_json_compact_or_pretty() {
+        let value = serde_json::json!({ "signature": "abc", "slot": 1 });
+        assert_eq!(to_json_string(&value, true).unwrap(), r#"{"signature":"abc","slot":1}"#);
+        assert_eq!(to_json_string(&value, false).unwrap().lines().count(), 4);
+    }
+}
@@ -0,0 +1,49 @@
+use anyhow::{anyhow, Result};
+use solana_sdk::signature::{read_keypair_file, Keypair};
+use crate::outcome::{note, Notes};
+
+/// Env var holding the payer's secret key, for CI and containers without a keypair file.
+pub const PRIVATE_KEY_ENV: &str = "SOLANA_PRIVATE_KEY";
+const DEFAULT_KEYPAIR_PATH: &str = "~/.config/solana/id.json";
+
+/// Payer from `SOLANA_PRIVATE_KEY` when set, otherwise `~/.config/solana/id.json`.
+pub fn load_payer(notes: &Notes) -> Result<Keypair> {
+    if let Ok(secret) = std::env::var(PRIVATE_KEY_ENV) {
+        let keypair = keypair_from_secret(&secret)
+            .map_err(|e| anyhow!("Failed to load keypair from {}: {}", PRIVATE_KEY_ENV, e))?;
+        note!(notes, "⚠️  Using payer from {}; the secret key is visible to anything that can read this environment", PRIVATE_KEY_ENV);
+        return Ok(keypair);
+    }
+    read_keypair_file(&*shellexpand::tilde(DEFAULT_KEYPAIR_PATH))
+        .map_err(|e| anyhow!("Failed to load keypair from {} (or set {}): {}", DEFAULT_KEYPAIR_PATH, PRIVATE_KEY_ENV, e))
+}
+
+/// Parse a 64-byte secret key given as base58 or a JSON byte array (the solana-keygen file format).
+pub fn keypair_from_secret(secret: &str) -> Result<Keypair> {
+    let secret = secret.trim();
+    let bytes: Vec<u8> = if secret.starts_with('[') {
+        serde_json::from_str(secret).map_err(|e| anyhow!("Invalid JSON byte array: {}", e))?
+    } else {
+        bs58::decode(secret).into_vec().map_err(|e| anyhow!("Invalid base58: {}", e))?
+    };
+    Keypair::try_from(bytes.as_slice())
+        .map_err(|_| anyhow!("Expected a 64-byte secret key, got {} bytes", bytes.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::signer::Signer;
+
+    #[test]
+    fn test_keypair_from_secret() {
+        let keypair = Keypair::new();
+        let base58 = keypair.to_base58_string();
+        let json = serde_json::to_string(&keypair.to_bytes().to_vec()).unwrap();
+
+        assert_eq!(keypair_from_secret(&base58).unwrap().pubkey(), keypair.pubkey());
+        assert_eq!(keypair_from_secret(&format!(" {}\n", json)).unwrap().pubkey(), keypair.pubkey());
+        assert!(keypair_from_secret("[1, 2, 3]").unwrap_err().to_string().contains("64-byte"));
+        assert!(keypair_from_secret("not-base58!").is_err());
+    }
+}
@@ -0,0 +1,203 @@
+use anyhow::Result;
+use clap::ValueEnum;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::compute_budget::ComputeBudgetInstruction;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::message::VersionedMessage;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashMap;
+use crate::outcome::{note, Notes};
+
+const BASE_FEE_LAMPORTS_PER_SIGNATURE: u64 = 5_000;
+const DEFAULT_COMPUTE_UNITS_PER_INSTRUCTION: u64 = 200_000;
+const MAX_COMPUTE_UNITS: u64 = 1_400_000;
+
+/// Named priority levels, mapped to percentiles of recent prioritization fees.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum PriorityTier {
+    Low,
+    Medium,
+    High,
+}
+
+impl PriorityTier {
+    pub fn percentile(&self) -> u8 {
+        match self {
+            PriorityTier::Low => 25,
+            PriorityTier::Medium => 50,
+            PriorityTier::High => 90,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum PrioritySetting {
+    /// Explicit compute unit price in micro-lamports (`--priority-fee`)
+    MicroLamports(u64),
+    /// Resolved per transaction from recent fees on its writable accounts (`--priority`)
+    Tier(PriorityTier),
+}
+
+/// Priority fee for every transaction a run builds.
+#[derive(Debug, Clone, Default)]
+pub struct PriorityFees {
+    /// `--priority-fee` or `--priority`
+    pub setting: Option<PrioritySetting>,
+    /// Registry `default_priority_fee`s, used when no fee was configured
+    pub program_defaults: HashMap<Pubkey, u64>,
+}
+
+/// Highest registry default among the programs these instructions invoke.
+fn program_default(defaults: &HashMap<Pubkey, u64>, instructions: &[Instruction]) -> Option<(Pubkey, u64)> {
+    instructions.iter()
+        .filter_map(|ix| defaults.get(&ix.program_id).map(|price| (ix.program_id, *price)))
+        .max_by_key(|(_, price)| *price)
+}
+
+/// Fee at `percentile` (nearest-rank) of the observed per-slot fees; 0 when there are none.
+pub fn fee_at_percentile(fees: &[u64], percentile: u8) -> u64 {
+    if fees.is_empty() {
+        return 0;
+    }
+    let mut sorted = fees.to_vec();
+    sorted.sort_unstable();
+    let rank = (percentile as usize * sorted.len()).div_ceil(100).max(1);
+    sorted[rank.min(sorted.len()) - 1]
+}
+
+/// Rough total cost: base signature fees plus price × default compute units per instruction.
+pub fn estimate_total_fee(micro_lamports: u64, instruction_count: usize, signature_count: usize) -> u64 {
+    let compute_units = (DEFAULT_COMPUTE_UNITS_PER_INSTRUCTION * instruction_count as u64).min(MAX_COMPUTE_UNITS);
+    let priority_lamports = (micro_lamports as u128 * compute_units as u128).div_ceil(1_000_000) as u64;
+    BASE_FEE_LAMPORTS_PER_SIGNATURE * signature_count as u64 + priority_lamports
+}
+
+fn resolve(rpc_client: &RpcClient, notes: &Notes, setting: PrioritySetting, instructions: &[Instruction]) -> Result<u64> {
+    let tier = match setting {
+        PrioritySetting::MicroLamports(price) => return Ok(price),
+        PrioritySetting::Tier(tier) => tier,
+    };
+
+    let mut writable: Vec<Pubkey> = instructions.iter()
+        .flat_map(|ix| ix.accounts.iter())
+        .filter(|meta| meta.is_writable)
+        .map(|meta| meta.pubkey)
+        .collect();
+    writable.sort();
+    writable.dedup();
+
+    let fees: Vec<u64> = rpc_client.get_recent_prioritization_fees(&writable)?
+        .iter()
+        .map(|f| f.prioritization_fee)
+        .collect();
+    let price = fee_at_percentile(&fees, tier.percentile());
+    note!(notes, "⛽ Priority {:?} (p{} of {} recent slots): {} micro-lamports/CU",
+         tier, tier.percentile(), fees.len(), price);
+    Ok(price)
+}
+
+impl PriorityFees {
+    /// Prepend a compute unit price instruction when a priority fee is configured.
+    pub fn with_compute_budget(&self, rpc_client: &RpcClient, notes: &Notes, instructions: Vec<Instruction>) -> Result<Vec<Instruction>> {
+        let setting = match self.setting {
+            Some(setting) => setting,
+            None => {
+                let Some((program_id, price)) = program_default(&self.program_defaults, &instructions) else {
+                    return Ok(instructions);
+                };
+                note!(notes, "⛽ Registry default priority fee for {}: {} micro-lamports/CU", program_id, price);
+                PrioritySetting::MicroLamports(price)
+            }
+        };
+
+        let price = resolve(rpc_client, notes, setting, &instructions)?;
+        let signers = instructions.iter()
+            .flat_map(|ix| ix.accounts.iter())
+            .filter(|meta| meta.is_signer)
+            .map(|meta| meta.pubkey)
+            .collect::<std::collections::HashSet<_>>()
+            .len()
+            .max(1);
+        note!(notes, "💸 Estimated total fee: ~{} lamports ({} micro-lamports/CU)",
+             estimate_total_fee(price, instructions.len(), signers), price);
+
+        let mut with_budget = vec![ComputeBudgetInstruction::set_compute_unit_price(price)];
+        with_budget.extend(instructions);
+        Ok(with_budget)
+    }
+}
+
+/// Priority fee in lamports a built message pays: its compute unit price × limit (when it sets none, the
+/// default limit per instruction, not counting compute budget instructions, as the runtime does).
+pub fn message_priority_fee(message: &VersionedMessage) -> u64 {
+    let keys = message.static_account_keys();
+    let mut price = 0u64;
+    let mut limit = None;
+    let mut counted_instructions = 0u64;
+    for ix in message.instructions() {
+        if *ix.program_id(keys) != solana_sdk::compute_budget::id() {
+            counted_instructions += 1;
+            continue;
+        }
+        match ix.data.split_first() {
+            Some((2, rest)) if rest.len() >= 4 => limit = Some(u32::from_le_bytes(rest[..4].try_into().unwrap()) as u64),
+            Some((3, rest)) if rest.len() >= 8 => price = u64::from_le_bytes(rest[..8].try_into().unwrap()),
+            _ => {}
+        }
+    }
+    let limit = limit.unwrap_or_else(|| {
+        (DEFAULT_COMPUTE_UNITS_PER_INSTRUCTION * counted_instructions).min(MAX_COMPUTE_UNITS)
+    });
+    (price as u128 * limit as u128).div_ceil(1_000_000) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fee_at_percentile() {
+        let fees = [0, 10, 20, 30, 40, 50, 60, 70, 80, 1_000];
+        assert_eq!(fee_at_percentile(&fees, PriorityTier::Low.percentile()), 20);
+        assert_eq!(fee_at_percentile(&fees, PriorityTier::Medium.percentile()), 40);
+        assert_eq!(fee_at_percentile(&fees, PriorityTier::High.percentile()), 80);
+        assert_eq!(fee_at_percentile(&[], 50), 0);
+        assert_eq!(fee_at_percentile(&[7], 90), 7);
+    }
+
+    #[test]
+    fn test_estimate_total_fee() {
+        // 1 signature + 200k CU at 1,000 micro-lamports = 5,000 + 200
+        assert_eq!(estimate_total_fee(1_000, 1, 1), 5_200);
+        assert_eq!(estimate_total_fee(0, 3, 2), 10_000);
+    }
+
+    #[test]
+    fn test_program_default() {
+        let (swap, hello, other) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let defaults = HashMap::from([(swap, 50_000), (hello, 1_000)]);
+        let ix = |program_id| Instruction::new_with_bytes(program_id, &[], vec![]);
+
+        assert_eq!(program_default(&defaults, &[ix(hello), ix(swap)]), Some((swap, 50_000)));
+        assert_eq!(program_default(&defaults, &[ix(hello), ix(other)]), Some((hello, 1_000)));
+        assert_eq!(program_default(&defaults, &[ix(other)]), None);
+    }
+
+    #[test]
+    fn test_message_priority_fee() {
+        let payer = Pubkey::new_unique();
+        let transfer = solana_sdk::system_instruction::transfer(&payer, &Pubkey::new_unique(), 1);
+        let message = |ixs: &[Instruction]| VersionedMessage::Legacy(solana_sdk::message::Message::new(ixs, Some(&payer)));
+
+        let budgeted = message(&[
+            ComputeBudgetInstruction::set_compute_unit_limit(300_000),
+            ComputeBudgetInstruction::set_compute_unit_price(50_000),
+            transfer.clone(),
+        ]);
+        assert_eq!(message_priority_fee(&budgeted), 15_000);
+        // No limit: 200k CU per instruction, not counting the compute budget one
+        assert_eq!(message_priority_fee(&message(&[ComputeBudgetInstruction::set_compute_unit_price(1_000), transfer.clone()])), 200);
+        assert_eq!(message_priority_fee(&message(&[ComputeBudgetInstruction::set_compute_unit_price(1_000), transfer.clone(), transfer.clone()])), 400);
+        assert_eq!(message_priority_fee(&message(&[transfer])), 0);
+    }
+}
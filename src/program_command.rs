@@ -0,0 +1,198 @@
+use anyhow::{anyhow, Result};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Keypair;
+use std::collections::HashMap;
+use crate::idl_loader::IdlLoader;
+use crate::outcome::CommandOutcome;
+use crate::transaction_simulator::TransactionSimulator;
+use crate::tx_sender::{self, SendOptions, SendSettings};
+use crate::outcome::note;
+
+/// Shared state every program handler builds against.
+pub struct CommandContext<'a> {
+    pub rpc_client: &'a RpcClient,
+    pub payer: &'a Keypair,
+    pub fee_payer: &'a Keypair,
+    pub idl_loader: &'a IdlLoader,
+    pub simulator: &'a TransactionSimulator,
+    pub settings: &'a SendSettings,
+}
+
+/// A named action (`initialize`, `update-message`, ...) and its arguments.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProgramAction {
+    pub name: String,
+    pub args: Map<String, Value>,
+}
+
+impl ProgramAction {
+    /// From a CLI action enum serialized with `#[serde(tag = "action", rename_all = "kebab-case")]`.
+    pub fn from_subcommand(action: &impl Serialize) -> Result<Self> {
+        let Value::Object(mut args) = serde_json::to_value(action)? else {
+            return Err(anyhow!("Program action must serialize to an object"));
+        };
+        match args.remove("action") {
+            Some(Value::String(name)) => Ok(Self { name, args }),
+            _ => Err(anyhow!("Program action is missing its 'action' tag")),
+        }
+    }
+
+    /// Back into a handler's typed action enum.
+    pub fn parse<T: DeserializeOwned>(&self) -> Result<T> {
+        let mut value = self.args.clone();
+        value.insert("action".to_string(), Value::String(self.name.clone()));
+        serde_json::from_value(Value::Object(value))
+            .map_err(|e| anyhow!("Invalid arguments for '{}': {}", self.name, e))
+    }
+}
+
+/// Instructions an action sends, plus the signers it needs besides the payer.
+pub struct ProgramTransaction<'a> {
+    pub instructions: Vec<Instruction>,
+    pub signers: Vec<Keypair>,
+    pub options: SendOptions<'a>,
+    /// Printed once the transaction lands
+    pub success_message: Option<String>,
+    /// Follow the success message with a `solana confirm` hint
+    pub confirm_hint: bool,
+    /// Accounts the action touches, reported in the outcome whether or not it was sent
+    pub addresses: Vec<(String, Pubkey)>,
+}
+
+impl<'a> ProgramTransaction<'a> {
+    pub fn new(instructions: Vec<Instruction>) -> Self {
+        Self {
+            instructions,
+            signers: Vec::new(),
+            options: SendOptions::default(),
+            success_message: None,
+            confirm_hint: false,
+            addresses: Vec::new(),
+        }
+    }
+
+    pub fn with_signer(mut self, signer: Keypair) -> Self {
+        self.signers.push(signer);
+        self
+    }
+
+    pub fn with_options(mut self, options: SendOptions<'a>) -> Self {
+        self.options = options;
+        self
+    }
+
+    pub fn on_success(mut self, message: impl Into<String>) -> Self {
+        self.success_message = Some(message.into());
+        self
+    }
+
+    pub fn with_confirm_hint(mut self) -> Self {
+        self.confirm_hint = true;
+        self
+    }
+
+    pub fn with_address(mut self, label: &str, address: Pubkey) -> Self {
+        self.addresses.push((label.to_string(), address));
+        self
+    }
+}
+
+pub enum ActionOutcome<'a> {
+    /// Sign and send these instructions
+    Send(ProgramTransaction<'a>),
+    /// Read-only action; its result is already in the outcome
+    Done(CommandOutcome),
+}
+
+/// One program's CLI actions.
+pub trait ProgramCommand {
+    fn program_id(&self) -> Pubkey;
+
+    /// Build the transaction for `action`; read-only actions do their work here and return `Done`.
+    fn build_action<'a>(&self, ctx: &CommandContext<'a>, action: &ProgramAction) -> Result<ActionOutcome<'a>>;
+}
+
+/// Program handlers keyed by program name (the CLI subcommand, e.g. `hello-world`).
+#[derive(Default)]
+pub struct ProgramCommands {
+    handlers: HashMap<&'static str, Box<dyn ProgramCommand>>,
+}
+
+impl ProgramCommands {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register(&mut self, name: &'static str, handler: impl ProgramCommand + 'static) {
+        self.handlers.insert(name, Box::new(handler));
+    }
+
+    pub fn get(&self, name: &str) -> Option<&dyn ProgramCommand> {
+        self.handlers.get(name).map(|handler| handler.as_ref())
+    }
+
+    /// Build `action` with the `program` handler, then sign and send whatever it returns.
+    pub fn run(&self, ctx: &CommandContext, program: &str, action: &ProgramAction) -> Result<CommandOutcome> {
+        let handler = self.get(program)
+            .ok_or_else(|| anyhow!("No handler registered for program '{}'", program))?;
+        let transaction = match handler.build_action(ctx, action)? {
+            ActionOutcome::Send(transaction) => transaction,
+            ActionOutcome::Done(outcome) => return Ok(outcome),
+        };
+        ctx.idl_loader.check_required(&transaction.instructions)?;
+
+        let authorities: Vec<&Keypair> = std::iter::once(ctx.payer).chain(&transaction.signers).collect();
+        let signers = tx_sender::signer_set(ctx.fee_payer, &authorities);
+        let outcome = transaction.addresses.iter()
+            .fold(CommandOutcome::new(), |outcome, (label, address)| outcome.with_address(label, *address));
+        let Some(signature) = tx_sender::build_and_send(ctx.rpc_client, ctx.settings, transaction.instructions, &signers, transaction.options)? else {
+            return Ok(outcome);
+        };
+        if let Some(message) = &transaction.success_message {
+            note!(ctx.settings.notes, "{}", message);
+        }
+        if transaction.confirm_hint {
+            note!(ctx.settings.notes, "🔍 Use: {}", ctx.settings.explorer.confirm_command(&signature));
+        }
+        Ok(outcome.with_signature(Some(signature)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    #[serde(tag = "action", rename_all = "kebab-case")]
+    enum TestActions {
+        UpdateMessage { account_pubkey: String, new_message: String },
+        Add { a: i64, b: i64 },
+    }
+
+    #[test]
+    fn test_program_action_round_trip() {
+        let action = ProgramAction::from_subcommand(&TestActions::UpdateMessage {
+            account_pubkey: "acct".to_string(),
+            new_message: "gm".to_string(),
+        }).unwrap();
+        assert_eq!(action.name, "update-message");
+        assert_eq!(action.args["new_message"], "gm");
+        assert!(!action.args.contains_key("action"));
+
+        assert_eq!(action.parse::<TestActions>().unwrap(), TestActions::UpdateMessage {
+            account_pubkey: "acct".to_string(),
+            new_message: "gm".to_string(),
+        });
+
+        let mut args = Map::new();
+        args.insert("a".to_string(), Value::from(2));
+        let err = ProgramAction { name: "add".to_string(), args }.parse::<TestActions>().unwrap_err();
+        assert!(err.to_string().contains("Invalid arguments for 'add'"));
+    }
+}
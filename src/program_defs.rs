@@ -0,0 +1,215 @@
+use anyhow::{anyhow, Result};
+use serde::Deserialize;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use crate::borsh_encoder::SUPPORTED_TYPES;
+use crate::idl_loader::{anchor_discriminator, IdlAccount, IdlField, IdlInstruction, ProgramIdl};
+use crate::program_registry::ProgramManifest;
+
+/// Directory of `*.toml` program definitions, merged into the registry and IDL loader at startup.
+pub const PROGRAM_DEFS_DIR: &str = "./programs.d";
+
+/// A program declared in TOML: instruction shortcuts for `invoke` without writing a JSON IDL.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProgramDef {
+    pub program_id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default, rename = "instruction")]
+    pub instructions: Vec<InstructionDef>,
+}
+
+/// One `[[instruction]]` table. Accounts are in instruction order; an account with an `address`
+/// is its default layout and can be left out of `invoke --accounts`.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct InstructionDef {
+    pub name: String,
+    /// 8 bytes; defaults to the Anchor discriminator of `name`
+    #[serde(default)]
+    pub discriminator: Option<Vec<u8>>,
+    #[serde(default)]
+    pub args: Vec<IdlField>,
+    #[serde(default)]
+    pub accounts: Vec<IdlAccount>,
+}
+
+impl ProgramDef {
+    pub fn parse(toml: &str) -> Result<Self> {
+        toml::from_str(toml).map_err(|e| anyhow!("Invalid TOML: {}", e))
+    }
+
+    /// Check the definition and build the IDL `invoke` encodes against.
+    pub fn to_idl(&self) -> Result<ProgramIdl> {
+        Pubkey::try_from(self.program_id.as_str())
+            .map_err(|_| anyhow!("Invalid program_id: {}", self.program_id))?;
+        if self.name.is_empty() {
+            return Err(anyhow!("Program name is empty"));
+        }
+        if self.instructions.is_empty() {
+            return Err(anyhow!("No [[instruction]] tables"));
+        }
+
+        let mut names = HashSet::new();
+        let mut instructions = Vec::with_capacity(self.instructions.len());
+        for def in &self.instructions {
+            if !names.insert(def.name.as_str()) {
+                return Err(anyhow!("Instruction '{}' is defined twice", def.name));
+            }
+            let discriminator = match &def.discriminator {
+                Some(bytes) => <[u8; 8]>::try_from(bytes.as_slice())
+                    .map_err(|_| anyhow!("Instruction '{}': discriminator must be 8 bytes, got {}", def.name, bytes.len()))?,
+                None => anchor_discriminator(&def.name),
+            };
+            for arg in &def.args {
+                if !SUPPORTED_TYPES.contains(&arg.field_type.as_str()) {
+                    return Err(anyhow!(
+                        "Instruction '{}': argument '{}' has unsupported type '{}' (expected one of {})",
+                        def.name, arg.name, arg.field_type, SUPPORTED_TYPES.join(", ")
+                    ));
+                }
+            }
+            for account in &def.accounts {
+                if let Some(address) = &account.address {
+                    Pubkey::try_from(address.as_str()).map_err(|_| anyhow!(
+                        "Instruction '{}': account '{}' has invalid address {}", def.name, account.name, address
+                    ))?;
+                }
+            }
+            instructions.push(IdlInstruction {
+                name: def.name.clone(),
+                discriminator,
+                accounts: def.accounts.clone(),
+                args: def.args.clone(),
+            });
+        }
+
+        Ok(ProgramIdl {
+            address: self.program_id.clone(),
+            instructions,
+            errors: None,
+            accounts: Vec::new(),
+            types: Vec::new(),
+        })
+    }
+
+    /// Registry entry for this definition; `path` stands in for the IDL URL.
+    pub fn manifest(&self, path: &Path) -> ProgramManifest {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+        ProgramManifest {
+            program_id: self.program_id.clone(),
+            name: self.name.clone(),
+            description: self.description.clone(),
+            idl_url: format!("file://{}", path.display()),
+            idl_hash: String::new(),
+            client_version: "1.0.0".to_string(),
+            client_type: "toml".to_string(),
+            generated_at: now,
+            last_updated: now,
+            priority: 5,
+            enabled: true,
+            metadata: Some(HashMap::from([("source".to_string(), "programs.d".to_string())])),
+            default_priority_fee: None,
+        }
+    }
+}
+
+/// A definition file that parsed and validated.
+pub struct LoadedProgramDef {
+    pub path: PathBuf,
+    pub def: ProgramDef,
+    pub idl: ProgramIdl,
+}
+
+fn load_file(path: &Path) -> Result<LoadedProgramDef> {
+    let content = std::fs::read_to_string(path)?;
+    let def = ProgramDef::parse(&content)?;
+    let idl = def.to_idl()?;
+    Ok(LoadedProgramDef { path: path.to_path_buf(), def, idl })
+}
+
+/// Load every `*.toml` in `dir` in file name order. A missing directory holds no definitions; a bad
+/// file is reported as `"<path>: <error>"` and skipped.
+pub fn load_dir(dir: &Path) -> (Vec<LoadedProgramDef>, Vec<String>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return (Vec::new(), Vec::new());
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "toml"))
+        .collect();
+    paths.sort();
+
+    let mut loaded = Vec::new();
+    let mut errors = Vec::new();
+    let mut seen: HashMap<String, PathBuf> = HashMap::new();
+    for path in paths {
+        match load_file(&path) {
+            Ok(program) => match seen.get(&program.def.program_id) {
+                Some(first) => errors.push(format!(
+                    "{}: program {} is already defined in {}", path.display(), program.def.program_id, first.display()
+                )),
+                None => {
+                    seen.insert(program.def.program_id.clone(), path);
+                    loaded.push(program);
+                }
+            },
+            Err(e) => errors.push(format!("{}: {}", path.display(), e)),
+        }
+    }
+    (loaded, errors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VAULT_DEF: &str = r#"
+program_id = "Bj4vH3tVu1GjCHeU3peRfYyxJpAzooyZCTU6rRFR4AnY"
+name = "vault"
+
+[[instruction]]
+name = "deposit"
+args = [{ name = "amount", type = "u64" }]
+accounts = [
+    { name = "vault", writable = true },
+    { name = "user", writable = true, signer = true },
+    { name = "system_program", address = "11111111111111111111111111111111" },
+]
+
+[[instruction]]
+name = "ping"
+discriminator = [1, 0, 0, 0, 0, 0, 0, 0]
+"#;
+
+    #[test]
+    fn test_program_def_to_idl() {
+        let def = ProgramDef::parse(VAULT_DEF).unwrap();
+        let idl = def.to_idl().unwrap();
+        assert_eq!(idl.instructions.len(), 2);
+        assert_eq!(idl.instructions[0].discriminator, anchor_discriminator("deposit"));
+        assert_eq!(idl.instructions[0].accounts[2].address.as_deref(), Some("11111111111111111111111111111111"));
+        assert_eq!(idl.instructions[1].discriminator, [1, 0, 0, 0, 0, 0, 0, 0]);
+
+        let bad_type = VAULT_DEF.replace("type = \"u64\"", "type = \"u128\"");
+        assert!(ProgramDef::parse(&bad_type).unwrap().to_idl().unwrap_err().to_string().contains("u128"));
+        let short_discriminator = VAULT_DEF.replace("[1, 0, 0, 0, 0, 0, 0, 0]", "[1]");
+        assert!(ProgramDef::parse(&short_discriminator).unwrap().to_idl().is_err());
+        assert!(ProgramDef::parse("program_id = \"x\"\nname = \"a\"\ntypo = 1").is_err());
+
+        let dir = std::env::temp_dir().join(format!("program_defs_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("a_vault.toml"), VAULT_DEF).unwrap();
+        std::fs::write(dir.join("b_broken.toml"), "name = ").unwrap();
+        std::fs::write(dir.join("notes.txt"), "ignored").unwrap();
+        let (loaded, errors) = load_dir(&dir);
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("b_broken.toml"));
+    }
+}
@@ -0,0 +1,701 @@
+use anyhow::{anyhow, Result};
+use serde_json::json;
+use solana_client::rpc_client::RpcClient;
+use serde::Serialize;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::message::Message;
+use solana_sdk::packet::PACKET_DATA_SIZE;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{read_keypair_file, Keypair};
+use solana_sdk::signer::Signer;
+use solana_sdk::system_program;
+use solana_sdk::transaction::{Transaction, VersionedTransaction};
+use std::str::FromStr;
+use crate::account_resolver::{check_owner, fetch_owned_account};
+use crate::borsh_encoder::BorshEncoder;
+use crate::generated;
+use crate::history;
+use crate::idl_loader::IdlLoader;
+use crate::lamports::{lamports_to_sol, sol_to_lamports};
+use crate::outcome::CommandOutcome;
+use crate::program_command::{ActionOutcome, CommandContext, ProgramAction, ProgramCommand, ProgramCommands, ProgramTransaction};
+use crate::tx_sender::{signer_set, SendOptions};
+use crate::{instruction_dump, tx_sender};
+use crate::cli::{CalculatorActions, HelloWorldActions, SwapActions};
+use crate::commands::{CALCULATOR_PROGRAM_ID, HELLO_WORLD_PROGRAM_ID, SWAP_PROGRAM_ID};
+use crate::outcome::note;
+
+/// The handlers for the built-in demo programs, keyed by their CLI subcommand.
+pub fn builtin() -> ProgramCommands {
+    let mut commands = ProgramCommands::new();
+    commands.register("hello-world", HelloWorldProgram);
+    commands.register("calculator", CalculatorProgram);
+    commands.register("swap", SwapProgram);
+    commands
+}
+
+/// Serialized size of an unsigned legacy transaction carrying just `instruction`.
+fn transaction_len(instruction: &Instruction, fee_payer: &Pubkey) -> Result<usize> {
+    let transaction = Transaction::new_unsigned(Message::new(std::slice::from_ref(instruction), Some(fee_payer)));
+    Ok(bincode::serde::encode_to_vec(&transaction, bincode::config::legacy())?.len())
+}
+
+fn read_account_keypair(path: &str) -> Result<Keypair> {
+    read_keypair_file(path)
+        .map_err(|e| anyhow!("Failed to read account keypair {} (create one with `keygen {}`): {}", path, path, e))
+}
+
+pub struct HelloWorldProgram;
+
+impl ProgramCommand for HelloWorldProgram {
+    fn program_id(&self) -> Pubkey {
+        Pubkey::from_str(HELLO_WORLD_PROGRAM_ID).expect("valid program id")
+    }
+
+    fn build_action<'a>(&self, ctx: &CommandContext<'a>, action: &ProgramAction) -> Result<ActionOutcome<'a>> {
+        let program_id = self.program_id();
+
+        let transaction = match action.parse::<HelloWorldActions>()? {
+            HelloWorldActions::Initialize { message, account_keypair } => {
+                let account_keypair = read_account_keypair(&account_keypair)?;
+
+                note!(ctx.settings.notes, "🚀 Initializing Hello World account...");
+                note!(ctx.settings.notes, "📋 Program ID: {}", program_id);
+                note!(ctx.settings.notes, "🔑 Account: {}", account_keypair.pubkey());
+                note!(ctx.settings.notes, "💬 Message: '{}'", message);
+
+                let mut instruction_data = vec![175, 175, 109, 31, 13, 152, 155, 237]; // initialize discriminator
+                instruction_data.extend_from_slice(&(message.len() as u32).to_le_bytes());
+                instruction_data.extend_from_slice(message.as_bytes());
+
+                let instruction = Instruction {
+                    program_id,
+                    accounts: vec![
+                        AccountMeta::new(account_keypair.pubkey(), true), // hello_world_account (writable, signer)
+                        AccountMeta::new(ctx.payer.pubkey(), true),       // user (writable, signer)
+                        AccountMeta::new_readonly(system_program::id(), false), // system_program
+                    ],
+                    data: instruction_data,
+                };
+                // The account's space is the program's choice and not known here; the hard bound is one packet
+                let max_len = (message.len() + PACKET_DATA_SIZE).saturating_sub(transaction_len(&instruction, &ctx.fee_payer.pubkey())?);
+                generated::hello_world::check_message(&message, max_len, "an initialize transaction")?;
+
+                ProgramTransaction::new(vec![instruction])
+                    .with_address("hello_world_account", account_keypair.pubkey())
+                    .with_signer(account_keypair)
+                    .on_success("🎉 Hello World account initialized successfully!")
+            }
+            HelloWorldActions::UpdateMessage { account_pubkey, new_message } => {
+                let account_pubkey = Pubkey::from_str(&account_pubkey)?;
+                let account = fetch_owned_account(ctx.rpc_client, &account_pubkey, &program_id)?;
+                let max_len = generated::hello_world::message_capacity(account.data.len());
+                generated::hello_world::check_message(&new_message, max_len, "the account")?;
+
+                note!(ctx.settings.notes, "🔄 Updating message in Hello World account...");
+                note!(ctx.settings.notes, "📋 Program ID: {}", program_id);
+                note!(ctx.settings.notes, "🔑 Account: {}", account_pubkey);
+                note!(ctx.settings.notes, "💬 New message: '{}'", new_message);
+
+                let mut instruction_data = vec![23, 135, 34, 211, 96, 120, 107, 9]; // update_message discriminator
+                instruction_data.extend_from_slice(&(new_message.len() as u32).to_le_bytes());
+                instruction_data.extend_from_slice(new_message.as_bytes());
+
+                let instruction = Instruction {
+                    program_id,
+                    accounts: vec![
+                        AccountMeta::new(account_pubkey, false),             // hello_world_account (writable)
+                        AccountMeta::new_readonly(ctx.payer.pubkey(), true), // user (signer)
+                    ],
+                    data: instruction_data,
+                };
+
+                ProgramTransaction::new(vec![instruction])
+                    .with_address("hello_world_account", account_pubkey)
+                    .with_options(SendOptions::default().with_preflight())
+                    .on_success("🎉 Message updated successfully!")
+            }
+            HelloWorldActions::GetMessage { account_pubkey } => {
+                let account_pubkey = Pubkey::from_str(&account_pubkey)?;
+
+                note!(ctx.settings.notes, "📖 Getting message from Hello World account...");
+                note!(ctx.settings.notes, "📋 Program ID: {}", program_id);
+                note!(ctx.settings.notes, "🔑 Account: {}", account_pubkey);
+                fetch_owned_account(ctx.rpc_client, &account_pubkey, &program_id)?;
+
+                let instruction_data = vec![159, 69, 186, 171, 244, 131, 99, 223]; // get_message discriminator
+
+                let instruction = Instruction {
+                    program_id,
+                    accounts: vec![
+                        AccountMeta::new_readonly(account_pubkey, false), // hello_world_account
+                    ],
+                    data: instruction_data,
+                };
+
+                ProgramTransaction::new(vec![instruction])
+                    .with_address("hello_world_account", account_pubkey)
+                    .with_options(SendOptions::default().with_preflight().with_logs())
+            }
+        };
+        Ok(ActionOutcome::Send(transaction))
+    }
+}
+
+pub struct CalculatorProgram;
+
+impl CalculatorProgram {
+    fn add_instruction(&self, account: Pubkey, user: Pubkey, a: i64, b: i64) -> Instruction {
+        let mut instruction_data = vec![41, 249, 249, 146, 197, 111, 56, 181]; // add discriminator
+        instruction_data.extend_from_slice(&a.to_le_bytes());
+        instruction_data.extend_from_slice(&b.to_le_bytes());
+
+        Instruction {
+            program_id: self.program_id(),
+            accounts: vec![
+                AccountMeta::new(account, false),      // calculator_account (writable)
+                AccountMeta::new_readonly(user, true), // user (signer)
+            ],
+            data: instruction_data,
+        }
+    }
+
+    /// Stored state when a Calculator IDL describes the account, otherwise the value `get_result` logs in simulation.
+    fn read_result(&self, ctx: &CommandContext, account_pubkey: Pubkey) -> Result<CommandOutcome> {
+        let program_id = self.program_id();
+        let outcome = CommandOutcome::new().with_address("calculator_account", account_pubkey);
+        let Some(account) = ctx.rpc_client.get_account_with_commitment(&account_pubkey, ctx.rpc_client.commitment())?.value else {
+            note!(ctx.settings.notes, "❌ Calculator account not found; create it with `calculator initialize`");
+            return Ok(outcome);
+        };
+        check_owner(&account_pubkey, &account, &program_id)?;
+        if account.data.get(..8).is_none_or(|discriminator| discriminator == [0u8; 8]) {
+            note!(ctx.settings.notes, "⚠️  Calculator account is not initialized; run `calculator initialize` first");
+            return Ok(outcome);
+        }
+
+        // Stored state, when a Calculator IDL (calculator.json) describes the account layout
+        if let Some((name, fields)) = ctx.idl_loader.decode_account(CALCULATOR_PROGRAM_ID, &account.data)? {
+            note!(ctx.settings.notes, "🧾 {}:", name);
+            for (field, value) in &fields {
+                note!(ctx.settings.notes, "  {}: {}", field, value);
+            }
+            if let Some((_, result)) = fields.iter().find(|(field, _)| field == "result") {
+                note!(ctx.settings.notes, "🧮 Result: {}", result);
+            }
+            let state: serde_json::Map<_, _> = fields.into_iter().collect();
+            return outcome.with_state(&state);
+        }
+
+        // Otherwise ask the program: simulate get_result and read the value it logs
+        let instruction_data = vec![57, 144, 166, 101, 148, 52, 100, 135]; // get_result discriminator
+
+        let instruction = Instruction {
+            program_id,
+            accounts: vec![
+                AccountMeta::new_readonly(account_pubkey, false), // calculator_account
+            ],
+            data: instruction_data,
+        };
+
+        let recent_blockhash = tx_sender::latest_blockhash(ctx.rpc_client, ctx.settings)?.blockhash;
+        let transaction = tx_sender::sign_transaction(ctx.settings, &[instruction], &signer_set(ctx.fee_payer, &[ctx.payer]), recent_blockhash)?;
+        let preview = ctx.simulator.preview_transaction(&transaction)?;
+        let outcome = outcome.with_simulation(&preview);
+        if let Some(error) = &preview.error_summary {
+            note!(ctx.settings.notes, "❌ get_result failed in simulation: {}", error);
+            return Ok(outcome);
+        }
+        match preview.logged_result() {
+            Some(result) => {
+                note!(ctx.settings.notes, "🧮 Result: {}", result);
+                return outcome.with_state(&json!({ "result": result }));
+            }
+            None => {
+                note!(ctx.settings.notes, "ℹ️  get_result logged no numeric result:");
+                for log in &preview.program_logs {
+                    note!(ctx.settings.notes, "  📝 {}", log);
+                }
+            }
+        }
+        Ok(outcome)
+    }
+
+    fn simulate_add(&self, ctx: &CommandContext, account_pubkey: Pubkey, a: i64, b: i64) -> Result<CommandOutcome> {
+        let instruction = self.add_instruction(account_pubkey, ctx.payer.pubkey(), a, b);
+
+        let recent_blockhash = tx_sender::latest_blockhash(ctx.rpc_client, ctx.settings)?.blockhash;
+        let instructions = ctx.settings.priority.with_compute_budget(ctx.rpc_client, &ctx.settings.notes, vec![instruction])?;
+        if ctx.settings.dump_instructions {
+            instruction_dump::dump_instructions(&ctx.settings.notes, None, &instructions);
+        }
+        let transaction = tx_sender::sign_transaction(ctx.settings, &instructions, &signer_set(ctx.fee_payer, &[ctx.payer]), recent_blockhash)?;
+
+        let preview = ctx.simulator.preview_transaction(&transaction)?;
+
+        note!(ctx.settings.notes, "\n🔍 SIMULATION RESULTS:");
+        note!(ctx.settings.notes, "✅ Success: {}", if preview.will_succeed { "YES" } else { "NO" });
+        note!(ctx.settings.notes, "💰 Fee: {} ({} SOL)", preview.fee_confidence.describe(preview.estimated_fee), lamports_to_sol(preview.estimated_fee));
+        note!(ctx.settings.notes, "⚡ Compute units: {}", preview.compute_units);
+        if let Some(error) = &preview.error_summary {
+            note!(ctx.settings.notes, "❌ Error: {}", error);
+        }
+        if !preview.program_logs.is_empty() {
+            note!(ctx.settings.notes, "📋 Expected program logs:");
+            for log in &preview.program_logs {
+                note!(ctx.settings.notes, "  📝 {}", log);
+            }
+        }
+
+        match (preview.logged_result(), a.checked_add(b)) {
+            (Some(result), Some(expected)) if result == expected => note!(ctx.settings.notes, "🧮 Expected result: {}", result),
+            (Some(result), Some(expected)) => note!(ctx.settings.notes, "⚠️  Program would log {}, but {} + {} = {}", result, a, b, expected),
+            (Some(result), None) => note!(ctx.settings.notes, "🧮 Expected result: {} ({} + {} overflows i64)", result, a, b),
+            (None, _) if preview.will_succeed => note!(ctx.settings.notes, "ℹ️  No result found in the program logs"),
+            (None, _) => {}
+        }
+
+        note!(ctx.settings.notes, "\n💡 This was a simulation only - nothing was sent!");
+        let outcome = CommandOutcome::new()
+            .with_address("calculator_account", account_pubkey)
+            .with_simulation(&preview);
+        match preview.logged_result() {
+            Some(result) => outcome.with_state(&json!({ "result": result })),
+            None => Ok(outcome),
+        }
+    }
+}
+
+impl ProgramCommand for CalculatorProgram {
+    fn program_id(&self) -> Pubkey {
+        Pubkey::from_str(CALCULATOR_PROGRAM_ID).expect("valid program id")
+    }
+
+    fn build_action<'a>(&self, ctx: &CommandContext<'a>, action: &ProgramAction) -> Result<ActionOutcome<'a>> {
+        let program_id = self.program_id();
+
+        let transaction = match action.parse::<CalculatorActions>()? {
+            CalculatorActions::Initialize { account_keypair } => {
+                let account_keypair = read_account_keypair(&account_keypair)?;
+
+                note!(ctx.settings.notes, "🚀 Initializing Calculator account...");
+                note!(ctx.settings.notes, "📋 Program ID: {}", program_id);
+                note!(ctx.settings.notes, "🔑 Account: {}", account_keypair.pubkey());
+
+                let instruction_data = vec![175, 175, 109, 31, 13, 152, 155, 237]; // initialize discriminator
+
+                let instruction = Instruction {
+                    program_id,
+                    accounts: vec![
+                        AccountMeta::new(account_keypair.pubkey(), true), // calculator_account (writable, signer)
+                        AccountMeta::new(ctx.payer.pubkey(), true),       // user (writable, signer)
+                        AccountMeta::new_readonly(system_program::id(), false), // system_program
+                    ],
+                    data: instruction_data,
+                };
+
+                ProgramTransaction::new(vec![instruction])
+                    .with_address("calculator_account", account_keypair.pubkey())
+                    .with_signer(account_keypair)
+                    .on_success("🎉 Calculator account initialized successfully!")
+            }
+            CalculatorActions::Add { account_pubkey, a, b } => {
+                let account_pubkey = Pubkey::from_str(&account_pubkey)?;
+
+                note!(ctx.settings.notes, "➕ Adding {} + {} using Calculator...", a, b);
+                note!(ctx.settings.notes, "📋 Program ID: {}", program_id);
+                note!(ctx.settings.notes, "🔑 Account: {}", account_pubkey);
+
+                ProgramTransaction::new(vec![self.add_instruction(account_pubkey, ctx.payer.pubkey(), a, b)])
+                    .with_address("calculator_account", account_pubkey)
+                    .with_options(SendOptions::default().with_preflight())
+                    .on_success("🎉 Addition completed! Check logs for result.")
+                    .with_confirm_hint()
+            }
+            CalculatorActions::Ping { account_pubkey, message } => {
+                let account_pubkey = Pubkey::from_str(&account_pubkey)?;
+
+                note!(ctx.settings.notes, "🏓 Sending ping '{}' to Calculator...", message);
+                note!(ctx.settings.notes, "📋 Program ID: {}", program_id);
+                note!(ctx.settings.notes, "🔑 Account: {}", account_pubkey);
+
+                let mut instruction_data = vec![173, 0, 94, 236, 73, 133, 225, 153]; // ping discriminator
+                instruction_data.extend_from_slice(&(message.len() as u32).to_le_bytes());
+                instruction_data.extend_from_slice(message.as_bytes());
+
+                let instruction = Instruction {
+                    program_id,
+                    accounts: vec![
+                        AccountMeta::new(account_pubkey, false),             // calculator_account (writable)
+                        AccountMeta::new_readonly(ctx.payer.pubkey(), true), // user (signer)
+                    ],
+                    data: instruction_data,
+                };
+
+                ProgramTransaction::new(vec![instruction])
+                    .with_address("calculator_account", account_pubkey)
+                    .on_success("🏓 Ping sent! Check logs for pong response.")
+                    .with_confirm_hint()
+            }
+            CalculatorActions::GetResult { account_pubkey } => {
+                let account_pubkey = Pubkey::from_str(&account_pubkey)?;
+
+                note!(ctx.settings.notes, "📊 Getting result from Calculator...");
+                note!(ctx.settings.notes, "📋 Program ID: {}", program_id);
+                note!(ctx.settings.notes, "🔑 Account: {}", account_pubkey);
+
+                return Ok(ActionOutcome::Done(self.read_result(ctx, account_pubkey)?));
+            }
+            CalculatorActions::Simulate { account_pubkey, a, b } => {
+                let account_pubkey = Pubkey::from_str(&account_pubkey)?;
+
+                note!(ctx.settings.notes, "🧪 Simulating {} + {} using Calculator...", a, b);
+                note!(ctx.settings.notes, "📋 Program ID: {}", program_id);
+                note!(ctx.settings.notes, "🔑 Account: {}", account_pubkey);
+
+                return Ok(ActionOutcome::Done(self.simulate_add(ctx, account_pubkey, a, b)?));
+            }
+        };
+        Ok(ActionOutcome::Send(transaction))
+    }
+}
+
+pub struct SwapProgram;
+
+/// One swap reconstructed from a pool transaction.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct SwapRecord {
+    pub signature: String,
+    pub block_time: Option<String>,
+    /// `sol_for_tokens` or `tokens_for_sol`
+    pub direction: String,
+    /// Lamports in for `sol_for_tokens`, tokens in for `tokens_for_sol`
+    pub amount: u64,
+    pub error: Option<String>,
+}
+
+/// Direction and input amount of the first top-level swap instruction against `pool`, decoded via the IDL.
+pub fn decode_swap(idl_loader: &IdlLoader, pool: &Pubkey, transaction: &VersionedTransaction) -> Option<(String, u64)> {
+    let keys = transaction.message.static_account_keys();
+    let encoder = BorshEncoder::new();
+    transaction.message.instructions().iter().find_map(|ix| {
+        if keys.get(ix.program_id_index as usize)?.to_string() != SWAP_PROGRAM_ID
+            || keys.get(*ix.accounts.first()? as usize)? != pool {
+            return None;
+        }
+        let spec = idl_loader.find_instruction_by_discriminator(SWAP_PROGRAM_ID, &ix.data)?;
+        let direction = spec.name.strip_prefix("swap_")?;
+        let (amount, _) = encoder.decode_value(&ix.data[8..], &spec.args.first()?.field_type).ok()?;
+        Some((direction.to_string(), amount.as_u64()?))
+    })
+}
+
+impl SwapProgram {
+    fn fetch_pool(&self, rpc_client: &RpcClient, pool: &Pubkey) -> Result<generated::swap_program::SwapPool> {
+        let account = fetch_owned_account(rpc_client, pool, &generated::swap_program::program_id())
+            .map_err(|e| anyhow!("Swap pool: {}", e))?;
+        generated::swap_program::SwapPool::try_deserialize(&account.data)
+    }
+}
+
+impl ProgramCommand for SwapProgram {
+    fn program_id(&self) -> Pubkey {
+        Pubkey::from_str(SWAP_PROGRAM_ID).expect("valid program id")
+    }
+
+    fn build_action<'a>(&self, ctx: &CommandContext<'a>, action: &ProgramAction) -> Result<ActionOutcome<'a>> {
+        let program_id = self.program_id();
+
+        let transaction = match action.parse::<SwapActions>()? {
+            SwapActions::Initialize { account_keypair, initial_sol_pool, initial_token_pool } => {
+                let account_keypair = read_account_keypair(&account_keypair)?;
+
+                // Convert values
+                let sol_lamports = sol_to_lamports(&initial_sol_pool)?;
+                let token_amount: u64 = initial_token_pool.parse()?;
+
+                note!(ctx.settings.notes, "🚀 Initializing Swap pool...");
+                note!(ctx.settings.notes, "📋 Program ID: {}", program_id);
+                note!(ctx.settings.notes, "🔑 Account: {}", account_keypair.pubkey());
+                note!(ctx.settings.notes, "💰 Initial SOL pool: {} SOL ({} lamports)", lamports_to_sol(sol_lamports), sol_lamports);
+                note!(ctx.settings.notes, "🪙 Initial token pool: {} tokens", token_amount);
+
+                // Create instruction data: discriminator + initial_sol_pool + initial_token_pool
+                let mut instruction_data = vec![175, 175, 109, 31, 13, 152, 155, 237]; // initialize discriminator
+                instruction_data.extend_from_slice(&sol_lamports.to_le_bytes());
+                instruction_data.extend_from_slice(&token_amount.to_le_bytes());
+
+                let instruction = Instruction {
+                    program_id,
+                    accounts: vec![
+                        AccountMeta::new(account_keypair.pubkey(), true), // swap_pool (writable, signer)
+                        AccountMeta::new(ctx.payer.pubkey(), true),       // user (writable, signer)
+                        AccountMeta::new_readonly(system_program::id(), false), // system_program
+                    ],
+                    data: instruction_data,
+                };
+
+                ProgramTransaction::new(vec![instruction])
+                    .with_address("pool", account_keypair.pubkey())
+                    .with_signer(account_keypair)
+                    .with_options(SendOptions::with_idl(ctx.idl_loader))
+                    .on_success("🎉 Swap pool initialized successfully!")
+            }
+            SwapActions::SwapSolForTokens { account_pubkey, sol_amount } => {
+                let account_pubkey = Pubkey::from_str(&account_pubkey)?;
+
+                // Convert SOL to lamports
+                let lamports = sol_to_lamports(&sol_amount)?;
+
+                note!(ctx.settings.notes, "🔄 Swapping {} SOL ({} lamports) for tokens...", lamports_to_sol(lamports), lamports);
+                note!(ctx.settings.notes, "📋 Program ID: {}", program_id);
+                note!(ctx.settings.notes, "🔑 Pool Account: {}", account_pubkey);
+
+                // Create instruction data: discriminator + sol_amount
+                let mut instruction_data = vec![1, 171, 24, 135, 201, 236, 210, 219];
+                instruction_data.extend_from_slice(&lamports.to_le_bytes());
+
+                let instruction = Instruction {
+                    program_id,
+                    accounts: vec![
+                        AccountMeta::new(account_pubkey, false),
+                        AccountMeta::new(ctx.payer.pubkey(), true),
+                    ],
+                    data: instruction_data,
+                };
+
+                ProgramTransaction::new(vec![instruction])
+                    .with_address("pool", account_pubkey)
+                    .with_options(SendOptions::with_idl(ctx.idl_loader))
+                    .on_success("🔄 Swap completed! Check logs for details.")
+                    .with_confirm_hint()
+            }
+            SwapActions::SwapTokensForSol { account_pubkey, token_amount } => {
+                let account_pubkey = Pubkey::from_str(&account_pubkey)?;
+                let tokens: u64 = token_amount.parse()?;
+
+                note!(ctx.settings.notes, "🔄 Swapping {} tokens for SOL...", tokens);
+                note!(ctx.settings.notes, "📋 Program ID: {}", program_id);
+                note!(ctx.settings.notes, "🔑 Pool Account: {}", account_pubkey);
+
+                // Create instruction data: discriminator + token_amount
+                let mut instruction_data = vec![188, 116, 108, 23, 68, 33, 204, 220];
+                instruction_data.extend_from_slice(&tokens.to_le_bytes());
+
+                let instruction = Instruction {
+                    program_id,
+                    accounts: vec![
+                        AccountMeta::new(account_pubkey, false),
+                        AccountMeta::new(ctx.payer.pubkey(), true),
+                    ],
+                    data: instruction_data,
+                };
+
+                ProgramTransaction::new(vec![instruction])
+                    .with_address("pool", account_pubkey)
+                    .with_options(SendOptions::with_idl(ctx.idl_loader))
+                    .on_success("🔄 Swap completed! Check logs for details.")
+                    .with_confirm_hint()
+            }
+            SwapActions::GetPoolInfo { account_pubkey } => {
+                let account_pubkey = Pubkey::from_str(&account_pubkey)?;
+
+                note!(ctx.settings.notes, "📊 Getting pool information...");
+                note!(ctx.settings.notes, "📋 Program ID: {}", program_id);
+                note!(ctx.settings.notes, "🔑 Pool Account: {}", account_pubkey);
+
+                let pool = self.fetch_pool(ctx.rpc_client, &account_pubkey)?;
+                note!(ctx.settings.notes, "👤 Authority: {}", pool.authority);
+                note!(ctx.settings.notes, "💰 SOL reserve: {} SOL ({} lamports)", lamports_to_sol(pool.sol_reserve), pool.sol_reserve);
+                note!(ctx.settings.notes, "🪙 Token reserve: {}", pool.token_reserve);
+                note!(ctx.settings.notes, "🔁 Total swaps: {}", pool.total_swaps);
+                note!(ctx.settings.notes, "💸 Fee rate: {} bps", pool.fee_rate);
+                let outcome = CommandOutcome::new().with_address("pool", account_pubkey).with_state(&json!({
+                    "authority": pool.authority.to_string(),
+                    "sol_reserve": pool.sol_reserve,
+                    "token_reserve": pool.token_reserve,
+                    "total_swaps": pool.total_swaps,
+                    "fee_rate": pool.fee_rate,
+                }))?;
+                return Ok(ActionOutcome::Done(outcome));
+            }
+            SwapActions::Quote { account_pubkey, sol_amount, token_amount } => {
+                let account_pubkey = Pubkey::from_str(&account_pubkey)?;
+                let pool = self.fetch_pool(ctx.rpc_client, &account_pubkey)?;
+
+                let quote = match (sol_amount, token_amount) {
+                    (Some(sol_amount), _) => {
+                        let lamports = sol_to_lamports(&sol_amount)?;
+                        let tokens = pool.quote(lamports, true);
+                        note!(ctx.settings.notes, "💱 {} SOL → ~{} tokens (fee {} bps)", lamports_to_sol(lamports), tokens, pool.fee_rate);
+                        json!({ "sol_in_lamports": lamports, "tokens_out": tokens, "fee_rate": pool.fee_rate })
+                    }
+                    (None, Some(tokens)) => {
+                        let lamports = pool.quote(tokens, false);
+                        note!(ctx.settings.notes, "💱 {} tokens → ~{} SOL (fee {} bps)", tokens, lamports_to_sol(lamports), pool.fee_rate);
+                        json!({ "tokens_in": tokens, "sol_out_lamports": lamports, "fee_rate": pool.fee_rate })
+                    }
+                    (None, None) => return Err(anyhow!("Provide --sol-amount or --token-amount")),
+                };
+                note!(ctx.settings.notes, "ℹ️  Estimated from current reserves; the on-chain result may differ if the pool moves");
+                return Ok(ActionOutcome::Done(CommandOutcome::new().with_address("pool", account_pubkey).with_state(&quote)?));
+            }
+            SwapActions::History { account_pubkey, limit } => {
+                let account_pubkey = Pubkey::from_str(&account_pubkey)?;
+                if ctx.idl_loader.get_instructions(SWAP_PROGRAM_ID).is_err() {
+                    return Err(anyhow!("swap history decodes instructions with the swap program IDL; add swap_program.json"));
+                }
+                self.fetch_pool(ctx.rpc_client, &account_pubkey)?;
+
+                note!(ctx.settings.notes, "📜 Scanning the last {} transaction(s) of pool {}...", limit, account_pubkey);
+                let entries = history::fetch_history(ctx.rpc_client, &account_pubkey, history::HistoryWindow::default(), Some(limit))?;
+                let mut swaps = Vec::new();
+                for entry in entries {
+                    let fetched = match history::fetch_transaction(ctx.rpc_client, &entry.signature) {
+                        Ok(fetched) => fetched,
+                        Err(e) => {
+                            note!(ctx.settings.notes, "⚠️  Skipping {}", e);
+                            continue;
+                        }
+                    };
+                    if let Some((direction, amount)) = decode_swap(ctx.idl_loader, &account_pubkey, &fetched.transaction) {
+                        swaps.push(SwapRecord { signature: entry.signature, block_time: entry.block_time, direction, amount, error: fetched.error });
+                    }
+                }
+
+                note!(ctx.settings.notes, "🔁 {} swap(s) found", swaps.len());
+                let rows: Vec<Vec<String>> = swaps.iter().map(|swap| vec![
+                    swap.block_time.clone().unwrap_or_else(|| "⏳ processing".to_string()),
+                    match swap.direction.as_str() {
+                        "sol_for_tokens" => "SOL → tokens".to_string(),
+                        "tokens_for_sol" => "tokens → SOL".to_string(),
+                        other => other.to_string(),
+                    },
+                    match swap.direction.as_str() {
+                        "sol_for_tokens" => format!("{} SOL", lamports_to_sol(swap.amount)),
+                        _ => format!("{} tokens", swap.amount),
+                    },
+                    swap.error.as_ref().map_or("✅".to_string(), |e| format!("❌ {}", e)),
+                    swap.signature.clone(),
+                ]).collect();
+                let outcome = CommandOutcome::new()
+                    .with_address("pool", account_pubkey)
+                    .with_table(&["Time", "Direction", "Amount", "Outcome", "Signature"], rows)
+                    .with_state(&swaps)?;
+                return Ok(ActionOutcome::Done(outcome));
+            }
+            SwapActions::Ping { account_pubkey, message } => {
+                let account_pubkey = Pubkey::from_str(&account_pubkey)?;
+
+                note!(ctx.settings.notes, "🏓 Sending ping '{}' to Swap pool...", message);
+                note!(ctx.settings.notes, "📋 Program ID: {}", program_id);
+                note!(ctx.settings.notes, "🔑 Pool Account: {}", account_pubkey);
+                fetch_owned_account(ctx.rpc_client, &account_pubkey, &program_id)?;
+
+                // Create instruction data: discriminator + message
+                let mut instruction_data = vec![228, 87, 187, 161, 115, 241, 73, 35];
+                instruction_data.extend_from_slice(&(message.len() as u32).to_le_bytes());
+                instruction_data.extend_from_slice(message.as_bytes());
+
+                let instruction = Instruction {
+                    program_id,
+                    accounts: vec![
+                        AccountMeta::new_readonly(account_pubkey, false),
+                    ],
+                    data: instruction_data,
+                };
+
+                ProgramTransaction::new(vec![instruction])
+                    .with_address("pool", account_pubkey)
+                    .with_options(SendOptions::with_idl(ctx.idl_loader))
+                    .on_success("🏓 Ping sent! Check logs for pong response.")
+                    .with_confirm_hint()
+            }
+        };
+        Ok(ActionOutcome::Send(transaction))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::idl_loader::IdlLoader;
+    use crate::transaction_simulator::TransactionSimulator;
+    use crate::tx_sender::SendSettings;
+
+    #[test]
+    fn test_decode_swap() {
+        let mut idl_loader = IdlLoader::new();
+        idl_loader.load_from_file(concat!(env!("CARGO_MANIFEST_DIR"), "/swap_program.json"), SWAP_PROGRAM_ID).unwrap();
+        let payer = Keypair::new();
+        let pool = Pubkey::new_unique();
+        let swap_ix = |discriminator: [u8; 8], pool: Pubkey, amount: u64| {
+            let mut data = discriminator.to_vec();
+            data.extend_from_slice(&amount.to_le_bytes());
+            Instruction {
+                program_id: SwapProgram.program_id(),
+                accounts: vec![AccountMeta::new(pool, false), AccountMeta::new(payer.pubkey(), true)],
+                data,
+            }
+        };
+        let sign = |ix: Instruction| VersionedTransaction::from(Transaction::new_signed_with_payer(
+            &[ix], Some(&payer.pubkey()), &[&payer], solana_sdk::hash::Hash::default(),
+        ));
+
+        let sol_in = sign(swap_ix([1, 171, 24, 135, 201, 236, 210, 219], pool, 500_000_000));
+        assert_eq!(decode_swap(&idl_loader, &pool, &sol_in), Some(("sol_for_tokens".to_string(), 500_000_000)));
+        let tokens_in = sign(swap_ix([188, 116, 108, 23, 68, 33, 204, 220], pool, 42));
+        assert_eq!(decode_swap(&idl_loader, &pool, &tokens_in), Some(("tokens_for_sol".to_string(), 42)));
+
+        let other_pool = sign(swap_ix([1, 171, 24, 135, 201, 236, 210, 219], Pubkey::new_unique(), 1));
+        assert_eq!(decode_swap(&idl_loader, &pool, &other_pool), None);
+        let initialize = sign(swap_ix([175, 175, 109, 31, 13, 152, 155, 237], pool, 1));
+        assert_eq!(decode_swap(&idl_loader, &pool, &initialize), None);
+    }
+
+    #[test]
+    fn test_builtin_builds_uniformly() {
+        let payer = Keypair::new();
+        let rpc_client = RpcClient::new("http://localhost:8899");
+        let idl_loader = IdlLoader::new();
+        let simulator = TransactionSimulator::new(RpcClient::new("http://localhost:8899"));
+        let settings = SendSettings::default();
+        let ctx = CommandContext {
+            rpc_client: &rpc_client,
+            payer: &payer,
+            fee_payer: &payer,
+            idl_loader: &idl_loader,
+            simulator: &simulator,
+            settings: &settings,
+        };
+        let commands = builtin();
+        let account = Pubkey::new_unique();
+
+        let action = ProgramAction::from_subcommand(&CalculatorActions::Add { account_pubkey: account.to_string(), a: 2, b: 40 }).unwrap();
+        let ActionOutcome::Send(transaction) = commands.get("calculator").unwrap().build_action(&ctx, &action).unwrap() else {
+            panic!("add should send");
+        };
+        let instruction = &transaction.instructions[0];
+        assert_eq!(instruction.program_id, CalculatorProgram.program_id());
+        assert_eq!(&instruction.data[8..16], &2i64.to_le_bytes());
+        assert_eq!(instruction.accounts[1].pubkey, payer.pubkey());
+        assert!(transaction.confirm_hint);
+
+        // update-message reads the live account, so initialize stands in for hello-world offline
+        let account_keypair = Keypair::new();
+        let keypair_path = std::env::temp_dir().join(format!("hello-world-{}.json", account_keypair.pubkey()));
+        solana_sdk::signature::write_keypair_file(&account_keypair, &keypair_path).unwrap();
+        let action = ProgramAction::from_subcommand(&HelloWorldActions::Initialize { message: "gm".to_string(), account_keypair: keypair_path.display().to_string() }).unwrap();
+        let outcome = commands.get("hello-world").unwrap().build_action(&ctx, &action);
+        std::fs::remove_file(&keypair_path).unwrap();
+        let ActionOutcome::Send(transaction) = outcome.unwrap() else {
+            panic!("initialize should send");
+        };
+        assert_eq!(transaction.instructions[0].accounts[0].pubkey, account_keypair.pubkey());
+        assert_eq!(&transaction.instructions[0].data[8..], &[2, 0, 0, 0, b'g', b'm']);
+
+        let bogus = ProgramAction { name: "add".to_string(), args: Default::default() };
+        assert!(commands.get("swap").unwrap().build_action(&ctx, &bogus).is_err());
+        assert!(commands.get("token").is_none());
+    }
+}
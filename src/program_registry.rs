@@ -3,9 +3,12 @@ use serde::{Deserialize, Serialize};
 use solana_sdk::pubkey::Pubkey;
 use std::collections::HashMap;
 use std::path::Path;
+use std::str::FromStr;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use tokio::fs;
 use sha2::{Sha256, Digest};
+use crate::ata_manager::CommonMints;
+use crate::outcome::{note, Notes};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ProgramRoute {
@@ -27,6 +30,41 @@ pub struct ProgramManifest {
     pub priority: u8, // 1-10, higher = more important
     pub enabled: bool,
     pub metadata: Option<HashMap<String, String>>,
+    /// Compute unit price (micro-lamports) used when neither `--priority-fee` nor `--priority` is given
+    #[serde(default)]
+    pub default_priority_fee: Option<u64>,
+}
+
+/// Remembered settings for a recurring swap, loaded by `send jupiter-swap --profile <name>`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SwapProfile {
+    pub name: String,
+    /// Input mint: a pubkey or a common token name (SOL, USDC, USDT)
+    pub input: String,
+    pub output: String,
+    pub slippage_bps: u16,
+    /// Abort when the quote's price impact (percent) is above this
+    #[serde(default)]
+    pub max_price_impact: Option<f64>,
+}
+
+impl SwapProfile {
+    /// Both mints resolve and differ, and the price impact cap is a sane percentage.
+    pub fn validate(&self) -> Result<()> {
+        let input = CommonMints::from_name(&self.input)
+            .map_err(|e| anyhow!("Swap profile '{}' input: {}", self.name, e))?;
+        let output = CommonMints::from_name(&self.output)
+            .map_err(|e| anyhow!("Swap profile '{}' output: {}", self.name, e))?;
+        if input == output {
+            return Err(anyhow!("Swap profile '{}' swaps {} to itself", self.name, self.input));
+        }
+        if let Some(max) = self.max_price_impact {
+            if !(0.0..=100.0).contains(&max) {
+                return Err(anyhow!("Swap profile '{}' max price impact {}% is not between 0 and 100", self.name, max));
+            }
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -38,6 +76,50 @@ pub struct RegistryManifest {
     pub programs: Vec<ProgramManifest>,
     pub cache_ttl: u64, // Cache time-to-live in seconds
     pub auto_refresh: bool,
+    #[serde(default)]
+    pub swap_profiles: Vec<SwapProfile>,
+}
+
+/// Schema version written to `program_registry.json`; a cache with another major version is migrated on load.
+pub const REGISTRY_SCHEMA_VERSION: &str = "1.0.0";
+
+fn schema_major(version: &str) -> Option<u64> {
+    version.split('.').next()?.parse().ok()
+}
+
+/// A cached program entry in the current shape: fields the old entry lacks (or that changed type) take their
+/// defaults. `None` without a valid `program_id`.
+fn migrate_program(entry: &serde_json::Value) -> Option<ProgramManifest> {
+    let program_id = entry.get("program_id")?.as_str()?;
+    Pubkey::from_str(program_id).ok()?;
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+    let defaults = ProgramManifest {
+        program_id: program_id.to_string(),
+        name: program_id.to_string(),
+        description: None,
+        idl_url: String::new(),
+        idl_hash: String::new(),
+        client_version: "1.0.0".to_string(),
+        client_type: "rust".to_string(),
+        generated_at: now,
+        last_updated: now,
+        priority: 5,
+        enabled: true,
+        metadata: None,
+        default_priority_fee: None,
+    };
+    let mut migrated = serde_json::to_value(defaults).ok()?;
+    for (key, field) in entry.as_object()? {
+        if migrated.get(key).is_none() {
+            continue;
+        }
+        let mut candidate = migrated.clone();
+        candidate[key] = field.clone();
+        if serde_json::from_value::<ProgramManifest>(candidate.clone()).is_ok() {
+            migrated = candidate;
+        }
+    }
+    serde_json::from_value(migrated).ok()
 }
 
 pub struct ProgramRegistry {
@@ -45,13 +127,18 @@ pub struct ProgramRegistry {
     cache_path: String,
     last_refresh: SystemTime,
     programs: HashMap<String, ProgramManifest>,
+    /// `--no-cache-write`: keep changes in memory only
+    cache_writes: bool,
+    /// Programs from `programs.d/` definitions; looked up like any other but never cached
+    external: Vec<ProgramManifest>,
+    notes: Notes,
 }
 
 impl ProgramRegistry {
-    /// Create a new program registry with default manifest
-    pub fn new(cache_path: &str) -> Self {
-        let default_manifest = RegistryManifest {
-            version: "1.0.0".to_string(),
+    /// The manifest a fresh registry starts from: the built-in programs at the current schema version.
+    fn default_manifest() -> RegistryManifest {
+        RegistryManifest {
+            version: REGISTRY_SCHEMA_VERSION.to_string(),
             created_at: SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs(),
             last_updated: SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs(),
             signature: None,
@@ -73,6 +160,7 @@ impl ProgramRegistry {
                         ("category".to_string(), "core".to_string()),
                         ("maintainer".to_string(), "solana-program-cli".to_string()),
                     ])),
+                    default_priority_fee: None,
                 },
                 ProgramManifest {
                     program_id: "5PiuXarsz2F7Q6NpSCtdBbK6vroQWiGSdJZW3fPkjWHw".to_string(),
@@ -90,17 +178,25 @@ impl ProgramRegistry {
                         ("category".to_string(), "example".to_string()),
                         ("maintainer".to_string(), "solana-program-cli".to_string()),
                     ])),
+                    default_priority_fee: None,
                 },
             ],
             cache_ttl: 3600, // 1 hour
             auto_refresh: true,
-        };
+            swap_profiles: Vec::new(),
+        }
+    }
 
+    /// Create a new program registry with default manifest
+    pub fn new(cache_path: &str) -> Self {
         let mut registry = Self {
-            manifest: default_manifest,
+            manifest: Self::default_manifest(),
             cache_path: cache_path.to_string(),
             last_refresh: SystemTime::now(),
             programs: HashMap::new(),
+            cache_writes: true,
+            external: Vec::new(),
+            notes: Notes::default(),
         };
 
         // Build program lookup map
@@ -111,36 +207,46 @@ impl ProgramRegistry {
         registry
     }
 
-    /// Load registry from cache or create new one
-    pub async fn load_or_create(cache_path: &str) -> Result<Self> {
+    /// Never write the cache file; refreshes and edits apply to this run only.
+    pub fn without_cache_writes(mut self) -> Self {
+        self.cache_writes = false;
+        self
+    }
+
+    /// Load registry from cache or create new one; the registry reports to `notes` from then on
+    pub async fn load_or_create(cache_path: &str, notes: Notes) -> Result<Self> {
         let cache_file = format!("{}/program_registry.json", cache_path);
         
         if Path::new(&cache_file).exists() {
-            match Self::load_from_cache(&cache_file).await {
-                Ok(registry) => {
-                    println!("✅ Loaded program registry from cache");
+            match Self::load_from_cache(&cache_file, &notes).await {
+                Ok(mut registry) => {
+                    note!(notes, "✅ Loaded program registry from cache");
+                    registry.notes = notes;
                     return Ok(registry);
                 }
                 Err(e) => {
-                    println!("⚠️  Failed to load cache, creating new registry: {}", e);
+                    note!(notes, "⚠️  Failed to load cache, creating new registry: {}", e);
                 }
             }
         }
 
-        println!("🔧 Creating new program registry");
-        Ok(Self::new(cache_path))
+        note!(notes, "🔧 Creating new program registry");
+        Ok(Self { notes, ..Self::new(cache_path) })
     }
 
     /// Load registry from cache file
-    async fn load_from_cache(cache_file: &str) -> Result<Self> {
+    async fn load_from_cache(cache_file: &str, notes: &Notes) -> Result<Self> {
         let content = fs::read_to_string(cache_file).await?;
-        let manifest: RegistryManifest = serde_json::from_str(&content)?;
+        let manifest = Self::manifest_from_json(&content, notes)?;
         
         let mut registry = Self {
             manifest,
             cache_path: Path::new(cache_file).parent().unwrap().to_string_lossy().to_string(),
             last_refresh: SystemTime::now(),
             programs: HashMap::new(),
+            cache_writes: true,
+            external: Vec::new(),
+            notes: Notes::default(),
         };
 
         // Build program lookup map
@@ -151,12 +257,50 @@ impl ProgramRegistry {
         Ok(registry)
     }
 
+    /// Parse a cached manifest. One from another major schema version is migrated instead: the built-in
+    /// programs are recreated and user-added programs and swap profiles are kept, field by field where the
+    /// old value still fits.
+    fn manifest_from_json(content: &str, notes: &Notes) -> Result<RegistryManifest> {
+        let value: serde_json::Value = serde_json::from_str(content)?;
+        let version = value.get("version").and_then(|v| v.as_str()).unwrap_or("0.0.0");
+        if schema_major(version).is_some() && schema_major(version) == schema_major(REGISTRY_SCHEMA_VERSION) {
+            return Ok(serde_json::from_value(value)?);
+        }
+
+        let mut manifest = Self::default_manifest();
+        let entries = value.get("programs").and_then(|p| p.as_array()).cloned().unwrap_or_default();
+        let mut kept = 0;
+        let mut dropped = 0;
+        for entry in &entries {
+            match migrate_program(entry) {
+                Some(program) if manifest.programs.iter().any(|p| p.program_id == program.program_id) => {}
+                Some(program) => {
+                    manifest.programs.push(program);
+                    kept += 1;
+                }
+                None => dropped += 1,
+            }
+        }
+        manifest.swap_profiles = value.get("swap_profiles").and_then(|p| p.as_array()).into_iter().flatten()
+            .filter_map(|profile| serde_json::from_value(profile.clone()).ok())
+            .collect();
+
+        let direction = if schema_major(version) > schema_major(REGISTRY_SCHEMA_VERSION) { " (written by a newer version)" } else { "" };
+        note!(notes, "🔄 Migrated registry cache from schema {}{} to {}: kept {} user program(s), dropped {} unreadable entr{}",
+             version, direction, REGISTRY_SCHEMA_VERSION, kept, dropped, if dropped == 1 { "y" } else { "ies" });
+        Ok(manifest)
+    }
+
     /// Save registry to cache
     pub async fn save_to_cache(&self) -> Result<()> {
+        if !self.cache_writes {
+            note!(self.notes, "📝 Program registry cache not written (cache writes disabled)");
+            return Ok(());
+        }
         let cache_file = format!("{}/program_registry.json", self.cache_path);
         let content = serde_json::to_string_pretty(&self.manifest)?;
         fs::write(cache_file, content).await?;
-        println!("💾 Program registry saved to cache");
+        note!(self.notes, "💾 Program registry saved to cache");
         Ok(())
     }
 
@@ -173,6 +317,21 @@ impl ProgramRegistry {
         ProgramRoute::Dynamic
     }
 
+    /// Refuse to run against a program disabled in the registry unless `allow_disabled` (`--allow-disabled`) is set.
+    pub fn ensure_enabled(&self, program_id: &Pubkey, allow_disabled: bool) -> Result<()> {
+        match self.get_program(program_id) {
+            Some(program) if !program.enabled && allow_disabled => {
+                note!(self.notes, "⚠️  Program {} ({}) is disabled in the registry; continuing because of --allow-disabled", program.name, program_id);
+                Ok(())
+            }
+            Some(program) if !program.enabled => Err(anyhow!(
+                "Program {} ({}) is disabled in the registry; enable it with `registry enable --program-id {}` or pass --allow-disabled",
+                program.name, program_id, program_id
+            )),
+            _ => Ok(()),
+        }
+    }
+
     /// Get program manifest by ID
     pub fn get_program(&self, program_id: &Pubkey) -> Option<&ProgramManifest> {
         self.programs.get(&program_id.to_string())
@@ -186,6 +345,24 @@ impl ProgramRegistry {
         self.manifest.last_updated = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
     }
 
+    /// Merge a program defined outside the cache (a `programs.d/` file). Fails if the ID is taken.
+    pub fn add_external(&mut self, program: ProgramManifest) -> Result<()> {
+        if let Some(existing) = self.programs.get(&program.program_id) {
+            return Err(anyhow!("Program {} is already registered as '{}'", program.program_id, existing.name));
+        }
+        self.programs.insert(program.program_id.clone(), program.clone());
+        self.external.push(program);
+        Ok(())
+    }
+
+    /// Per-program default priority fees of enabled programs, for `PriorityFees::program_defaults`
+    pub fn default_priority_fees(&self) -> HashMap<Pubkey, u64> {
+        self.programs.values()
+            .filter(|p| p.enabled)
+            .filter_map(|p| Some((p.program_id.parse().ok()?, p.default_priority_fee?)))
+            .collect()
+    }
+
     /// Remove a program from the registry
     pub fn remove_program(&mut self, program_id: &str) -> bool {
         if self.programs.remove(program_id).is_some() {
@@ -197,6 +374,29 @@ impl ProgramRegistry {
         }
     }
 
+    pub fn swap_profile(&self, name: &str) -> Option<&SwapProfile> {
+        self.manifest.swap_profiles.iter().find(|p| p.name == name)
+    }
+
+    pub fn swap_profiles(&self) -> &[SwapProfile] {
+        &self.manifest.swap_profiles
+    }
+
+    /// Add or replace a swap profile after validating it
+    pub fn set_swap_profile(&mut self, profile: SwapProfile) -> Result<()> {
+        profile.validate()?;
+        self.manifest.swap_profiles.retain(|p| p.name != profile.name);
+        self.manifest.swap_profiles.push(profile);
+        self.manifest.last_updated = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+        Ok(())
+    }
+
+    pub fn remove_swap_profile(&mut self, name: &str) -> bool {
+        let before = self.manifest.swap_profiles.len();
+        self.manifest.swap_profiles.retain(|p| p.name != name);
+        before != self.manifest.swap_profiles.len()
+    }
+
     /// Check if registry needs refresh
     pub fn needs_refresh(&self) -> bool {
         if !self.manifest.auto_refresh {
@@ -210,7 +410,7 @@ impl ProgramRegistry {
 
     /// Refresh registry from remote sources
     pub async fn refresh(&mut self) -> Result<()> {
-        println!("🔄 Refreshing program registry...");
+        note!(self.notes, "🔄 Refreshing program registry...");
         
         // In a real implementation, this would fetch from remote sources
         // For now, we'll just update the timestamp and validate existing programs
@@ -236,14 +436,14 @@ impl ProgramRegistry {
         
         // Rebuild program lookup map
         self.programs.clear();
-        for program in &self.manifest.programs {
+        for program in self.manifest.programs.iter().chain(&self.external) {
             self.programs.insert(program.program_id.clone(), program.clone());
         }
 
         // Save updated registry
         self.save_to_cache().await?;
         
-        println!("✅ Program registry refreshed successfully");
+        note!(self.notes, "✅ Program registry refreshed successfully");
         Ok(())
     }
 
@@ -261,7 +461,7 @@ impl ProgramRegistry {
 
     /// Validate registry integrity
     pub fn validate(&self) -> Result<()> {
-        println!("🔍 Validating program registry integrity...");
+        note!(self.notes, "🔍 Validating program registry integrity...");
         
         let mut issues = Vec::new();
         
@@ -290,18 +490,76 @@ impl ProgramRegistry {
             }
         }
 
+        // Hand-edited caches can hold swap profiles with bad mints
+        for profile in &self.manifest.swap_profiles {
+            if let Err(e) = profile.validate() {
+                issues.push(e.to_string());
+            }
+        }
+
         if issues.is_empty() {
-            println!("✅ Registry validation passed");
+            note!(self.notes, "✅ Registry validation passed");
             Ok(())
         } else {
-            println!("❌ Registry validation failed:");
+            note!(self.notes, "❌ Registry validation failed:");
             for issue in &issues {
-                println!("  🚨 {}", issue);
+                note!(self.notes, "  🚨 {}", issue);
             }
             Err(anyhow!("Registry validation failed with {} issues", issues.len()))
         }
     }
 
+    /// Load, parse, and address-check every program's IDL concurrently, reporting all failures.
+    pub async fn validate_deep(&self) -> Result<()> {
+        note!(self.notes, "🔍 Deep-validating {} program IDLs...", self.manifest.programs.len());
+
+        let results = futures::future::join_all(
+            self.manifest.programs.iter().map(Self::check_program_idl)
+        ).await;
+
+        let issues: Vec<String> = self.manifest.programs.iter()
+            .zip(results)
+            .filter_map(|(program, result)| result.err()
+                .map(|e| format!("{} ({}): {}", program.name, program.program_id, e)))
+            .collect();
+
+        if issues.is_empty() {
+            note!(self.notes, "✅ All program IDLs load and match their program IDs");
+            Ok(())
+        } else {
+            note!(self.notes, "❌ Deep validation failed:");
+            for issue in &issues {
+                note!(self.notes, "  🚨 {}", issue);
+            }
+            Err(anyhow!("Deep validation failed with {} issues", issues.len()))
+        }
+    }
+
+    async fn check_program_idl(program: &ProgramManifest) -> Result<()> {
+        let content = Self::fetch_idl(&program.idl_url).await?;
+        let idl: crate::idl_loader::ProgramIdl = serde_json::from_str(&content)
+            .map_err(|e| anyhow!("IDL does not parse: {}", e))?;
+        if idl.address != program.program_id {
+            return Err(anyhow!("IDL address {} does not match program ID", idl.address));
+        }
+        Ok(())
+    }
+
+    async fn fetch_idl(idl_url: &str) -> Result<String> {
+        if let Some(file_path) = idl_url.strip_prefix("file://") {
+            fs::read_to_string(file_path).await
+                .map_err(|e| anyhow!("Cannot read {}: {}", file_path, e))
+        } else if idl_url.starts_with("http://") || idl_url.starts_with("https://") {
+            let response = reqwest::get(idl_url).await?;
+            if !response.status().is_success() {
+                return Err(anyhow!("Fetching {} returned HTTP {}", idl_url, response.status()));
+            }
+            Ok(response.text().await?)
+        } else {
+            Err(anyhow!("Unsupported IDL URL scheme: {}", idl_url))
+        }
+    }
+
     /// Get registry statistics
     pub fn get_stats(&self) -> RegistryStats {
         let enabled_count = self.manifest.programs.iter().filter(|p| p.enabled).count();
@@ -319,7 +577,7 @@ impl ProgramRegistry {
 
     /// List all programs in the registry
     pub fn list_programs(&self) -> Vec<&ProgramManifest> {
-        let mut programs: Vec<&ProgramManifest> = self.manifest.programs.iter().collect();
+        let mut programs: Vec<&ProgramManifest> = self.manifest.programs.iter().chain(&self.external).collect();
         programs.sort_by(|a, b| b.priority.cmp(&a.priority));
         programs
     }
@@ -341,4 +599,124 @@ impl Default for ProgramRegistry {
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manifest_for(program_id: &str, idl_url: String) -> ProgramManifest {
+        ProgramManifest {
+            program_id: program_id.to_string(),
+            name: "test_program".to_string(),
+            description: None,
+            idl_url,
+            idl_hash: String::new(),
+            client_version: "1.0.0".to_string(),
+            client_type: "rust".to_string(),
+            generated_at: 0,
+            last_updated: 0,
+            priority: 1,
+            enabled: true,
+            metadata: None,
+            default_priority_fee: None,
+        }
+    }
+
+    #[test]
+    fn test_ensure_enabled() {
+        let program_id = Pubkey::new_unique();
+        let mut registry = ProgramRegistry::new("./cache").without_cache_writes();
+        assert!(registry.ensure_enabled(&program_id, false).is_ok());
+
+        registry.add_program(ProgramManifest { enabled: false, ..manifest_for(&program_id.to_string(), String::new()) });
+        let err = registry.ensure_enabled(&program_id, false).unwrap_err().to_string();
+        assert!(err.contains("disabled in the registry") && err.contains("--allow-disabled"));
+        assert!(registry.ensure_enabled(&program_id, true).is_ok());
+    }
+
+    #[test]
+    fn test_swap_profiles() {
+        let mut registry = ProgramRegistry::new("./cache").without_cache_writes();
+        let profile = SwapProfile {
+            name: "dca".to_string(),
+            input: "USDC".to_string(),
+            output: "SOL".to_string(),
+            slippage_bps: 30,
+            max_price_impact: Some(1.0),
+        };
+        registry.set_swap_profile(profile.clone()).unwrap();
+        registry.set_swap_profile(SwapProfile { slippage_bps: 40, ..profile.clone() }).unwrap();
+        assert_eq!(registry.swap_profiles().len(), 1);
+        assert_eq!(registry.swap_profile("dca").unwrap().slippage_bps, 40);
+
+        assert!(registry.set_swap_profile(SwapProfile { output: "NOTAMINT".to_string(), ..profile.clone() }).is_err());
+        assert!(registry.set_swap_profile(SwapProfile { output: "USDC".to_string(), ..profile.clone() }).is_err());
+        assert!(registry.remove_swap_profile("dca"));
+        assert!(registry.swap_profile("dca").is_none());
+    }
+
+    #[test]
+    fn test_migrate_old_schema_keeps_user_programs() {
+        let user_program = Pubkey::new_unique().to_string();
+        let v0 = format!(r#"{{
+            "version": "0.3.0",
+            "created_at": 1,
+            "programs": [
+                {{"program_id": "Bj4vH3tVu1GjCHeU3peRfYyxJpAzooyZCTU6rRFR4AnY", "name": "old_send", "idl_url": "file://./old.json"}},
+                {{"program_id": "{}", "name": "my_vault", "idl_url": "https://example.com/vault.json", "priority": 8, "enabled": "yes"}},
+                {{"name": "no_id"}}
+            ],
+            "swap_profiles": [{{"name": "dca", "input": "USDC", "output": "SOL", "slippage_bps": 30}}]
+        }}"#, user_program);
+
+        let manifest = ProgramRegistry::manifest_from_json(&v0, &Notes::new()).unwrap();
+        assert_eq!(manifest.version, REGISTRY_SCHEMA_VERSION);
+        let user = manifest.programs.iter().find(|p| p.program_id == user_program).unwrap();
+        assert_eq!(user.name, "my_vault");
+        assert_eq!(user.idl_url, "https://example.com/vault.json");
+        assert_eq!(user.priority, 8);
+        // A field whose type changed falls back to its default
+        assert!(user.enabled);
+        // Built-in programs are recreated, not taken from the old cache
+        let send = manifest.programs.iter().find(|p| p.program_id == "Bj4vH3tVu1GjCHeU3peRfYyxJpAzooyZCTU6rRFR4AnY").unwrap();
+        assert_eq!(send.name, "send_program");
+        assert_eq!(manifest.programs.len(), ProgramRegistry::default_manifest().programs.len() + 1);
+        assert_eq!(manifest.swap_profiles.len(), 1);
+
+        // The current schema loads as-is
+        let current = serde_json::to_string(&manifest).unwrap();
+        assert_eq!(ProgramRegistry::manifest_from_json(&current, &Notes::new()).unwrap().programs.len(), manifest.programs.len());
+    }
+
+    #[tokio::test]
+    async fn test_without_cache_writes_leaves_disk_untouched() {
+        let cache_dir = std::env::temp_dir().join("registry_no_cache_write_test");
+        let _ = std::fs::remove_dir_all(&cache_dir);
+        std::fs::create_dir_all(&cache_dir).unwrap();
+
+        let mut registry = ProgramRegistry::new(&cache_dir.to_string_lossy()).without_cache_writes();
+        registry.refresh().await.unwrap();
+        registry.save_to_cache().await.unwrap();
+        assert!(!cache_dir.join("program_registry.json").exists());
+
+        let registry = ProgramRegistry::new(&cache_dir.to_string_lossy());
+        registry.save_to_cache().await.unwrap();
+        assert!(cache_dir.join("program_registry.json").exists());
+        std::fs::remove_dir_all(&cache_dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_validate_deep_checks_idl_address() {
+        let program_id = "Bj4vH3tVu1GjCHeU3peRfYyxJpAzooyZCTU6rRFR4AnY";
+        let idl_url = format!("file://{}", concat!(env!("CARGO_MANIFEST_DIR"), "/send_program.json"));
 
+        let mut registry = ProgramRegistry::new(&std::env::temp_dir().to_string_lossy());
+        registry.manifest.programs = vec![manifest_for(program_id, idl_url.clone())];
+        assert!(registry.validate_deep().await.is_ok());
+
+        // Same IDL claimed by a different program, plus a missing file
+        registry.manifest.programs.push(manifest_for("11111111111111111111111111111111", idl_url));
+        registry.manifest.programs.push(manifest_for(program_id, "file:///nonexistent/idl.json".to_string()));
+        let err = registry.validate_deep().await.unwrap_err();
+        assert!(err.to_string().contains("2 issues"));
+    }
+}
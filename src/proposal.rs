@@ -0,0 +1,98 @@
+use serde::Serialize;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::pubkey::Pubkey;
+use crate::outcome::{note, Notes};
+
+#[derive(Debug, Serialize)]
+pub struct ProposalAccount {
+    pub pubkey: String,
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+impl From<&AccountMeta> for ProposalAccount {
+    fn from(meta: &AccountMeta) -> Self {
+        Self {
+            pubkey: meta.pubkey.to_string(),
+            is_signer: meta.is_signer,
+            is_writable: meta.is_writable,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProposalInstruction {
+    pub program_id: String,
+    pub accounts: Vec<ProposalAccount>,
+    /// Base58-encoded instruction data
+    pub data: String,
+}
+
+/// Instructions for a multisig (e.g. Squads) to wrap in a transaction proposal.
+#[derive(Debug, Serialize)]
+pub struct Proposal {
+    pub multisig: String,
+    pub instructions: Vec<ProposalInstruction>,
+    /// Every account the proposal touches (programs included), once each with its most privileged flags
+    pub accounts: Vec<ProposalAccount>,
+}
+
+impl Proposal {
+    pub fn new(multisig: &Pubkey, instructions: &[Instruction]) -> Self {
+        let touched = instructions.iter()
+            .flat_map(|ix| ix.accounts.iter().cloned().chain([AccountMeta::new_readonly(ix.program_id, false)]))
+            .collect();
+        Self {
+            multisig: multisig.to_string(),
+            instructions: instructions.iter().map(|ix| ProposalInstruction {
+                program_id: ix.program_id.to_string(),
+                accounts: ix.accounts.iter().map(ProposalAccount::from).collect(),
+                data: bs58::encode(&ix.data).into_string(),
+            }).collect(),
+            accounts: crate::account_resolver::dedup_account_metas(touched).iter().map(ProposalAccount::from).collect(),
+        }
+    }
+}
+
+/// Under `--propose-to` (`propose_to`), report the proposal JSON and return `true`: the caller must not send.
+/// Call before adding compute budget instructions; the multisig sets its own when it executes.
+pub fn propose_if_requested(notes: &Notes, propose_to: Option<Pubkey>, instructions: &[Instruction]) -> anyhow::Result<bool> {
+    let Some(multisig) = propose_to else {
+        return Ok(false);
+    };
+    note!(notes, "🗳️  Not sending: proposal for multisig {} ({} instruction(s)):", multisig, instructions.len());
+    notes.document(&Proposal::new(&multisig, instructions))?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_proposal_json() {
+        let multisig = Pubkey::new_unique();
+        let program_id = Pubkey::new_unique();
+        let vault = Pubkey::new_unique();
+        let instruction = Instruction {
+            program_id,
+            accounts: vec![AccountMeta::new(vault, true), AccountMeta::new_readonly(program_id, false)],
+            data: vec![1, 2, 3],
+        };
+
+        let json = serde_json::to_value(Proposal::new(&multisig, &[instruction])).unwrap();
+        assert_eq!(json["multisig"], multisig.to_string());
+        let ix = &json["instructions"][0];
+        assert_eq!(ix["program_id"], program_id.to_string());
+        assert_eq!(ix["data"], bs58::encode([1u8, 2, 3]).into_string());
+        assert_eq!(ix["accounts"][0]["pubkey"], vault.to_string());
+        assert_eq!(ix["accounts"][0]["is_signer"], true);
+        assert_eq!(ix["accounts"][1]["is_writable"], false);
+
+        // The program appears as an instruction account and as the program id: listed once
+        let accounts = json["accounts"].as_array().unwrap();
+        assert_eq!(accounts.len(), 2);
+        assert_eq!(accounts[0]["pubkey"], vault.to_string());
+        assert_eq!(accounts[1]["pubkey"], program_id.to_string());
+    }
+}
@@ -0,0 +1,125 @@
+use anyhow::Result;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::epoch_info::EpochInfo;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+const APPROX_SLOT_MS: u64 = 400;
+
+/// Rent-exemption minimums keyed by account size, valid until the current epoch ends.
+pub struct RentCache {
+    state: Mutex<RentCacheState>,
+}
+
+struct RentCacheState {
+    valid_until: Option<Instant>,
+    entries: HashMap<usize, u64>,
+}
+
+impl RentCache {
+    pub fn new() -> Self {
+        Self {
+            state: Mutex::new(RentCacheState {
+                valid_until: None,
+                entries: HashMap::new(),
+            }),
+        }
+    }
+
+    pub fn minimum_balance(&self, rpc_client: &RpcClient, account_size: usize) -> Result<u64> {
+        self.get_or_fetch(
+            account_size,
+            || Ok(rpc_client.get_epoch_info()?),
+            || Ok(rpc_client.get_minimum_balance_for_rent_exemption(account_size)?),
+        )
+    }
+
+    fn get_or_fetch<E, F>(&self, account_size: usize, fetch_epoch: E, fetch_rent: F) -> Result<u64>
+    where
+        E: FnOnce() -> Result<EpochInfo>,
+        F: FnOnce() -> Result<u64>,
+    {
+        let mut state = self.state.lock().unwrap();
+
+        let expired = state.valid_until.map(|t| Instant::now() >= t).unwrap_or(true);
+        if expired {
+            state.entries.clear();
+            state.valid_until = None;
+        } else if let Some(rent) = state.entries.get(&account_size) {
+            return Ok(*rent);
+        }
+
+        if state.valid_until.is_none() {
+            // Without an epoch window we can't bound staleness, so don't cache
+            let Ok(epoch_info) = fetch_epoch() else {
+                return fetch_rent();
+            };
+            let remaining_slots = epoch_info.slots_in_epoch.saturating_sub(epoch_info.slot_index);
+            state.valid_until = Some(Instant::now() + Duration::from_millis(remaining_slots * APPROX_SLOT_MS));
+        }
+
+        let rent = fetch_rent()?;
+        state.entries.insert(account_size, rent);
+        Ok(rent)
+    }
+}
+
+impl Default for RentCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    fn epoch_info(slot_index: u64) -> EpochInfo {
+        EpochInfo {
+            epoch: 500,
+            slot_index,
+            slots_in_epoch: 432_000,
+            absolute_slot: 216_000_000 + slot_index,
+            block_height: 200_000_000,
+            transaction_count: None,
+        }
+    }
+
+    #[test]
+    fn test_second_lookup_is_cached() {
+        let cache = RentCache::new();
+        let rent_calls = Cell::new(0);
+        let epoch_calls = Cell::new(0);
+
+        for _ in 0..2 {
+            let rent = cache.get_or_fetch(
+                165,
+                || { epoch_calls.set(epoch_calls.get() + 1); Ok(epoch_info(1_000)) },
+                || { rent_calls.set(rent_calls.get() + 1); Ok(2_039_280) },
+            ).unwrap();
+            assert_eq!(rent, 2_039_280);
+        }
+
+        assert_eq!(rent_calls.get(), 1);
+        assert_eq!(epoch_calls.get(), 1);
+    }
+
+    #[test]
+    fn test_epoch_boundary_invalidates() {
+        let cache = RentCache::new();
+        let rent_calls = Cell::new(0);
+
+        // Epoch is ending right now, so the entry expires immediately
+        for _ in 0..2 {
+            cache.get_or_fetch(
+                165,
+                || Ok(epoch_info(432_000)),
+                || { rent_calls.set(rent_calls.get() + 1); Ok(2_039_280) },
+            ).unwrap();
+        }
+
+        assert_eq!(rent_calls.get(), 2);
+    }
+}
@@ -0,0 +1,389 @@
+use anyhow::{anyhow, Result};
+use clap::ValueEnum;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use solana_client::client_error::{ClientErrorKind, Result as ClientResult};
+use solana_client::http_sender::HttpSender;
+use solana_client::rpc_client::{RpcClient, RpcClientConfig, SerializableTransaction};
+use solana_client::rpc_config::RpcSendTransactionConfig;
+use solana_sdk::account::Account;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::hash::Hash;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_sdk::transaction::TransactionError;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use crate::outcome::{note, Notes};
+
+/// `getMultipleAccounts` rejects requests for more keys than this.
+pub const MAX_MULTIPLE_ACCOUNTS: usize = 100;
+
+/// `get_multiple_accounts` for any number of addresses, in requests of at most [`MAX_MULTIPLE_ACCOUNTS`].
+pub fn get_multiple_accounts_chunked(rpc_client: &RpcClient, addresses: &[Pubkey], commitment: CommitmentConfig) -> ClientResult<Vec<Option<Account>>> {
+    let mut accounts = Vec::with_capacity(addresses.len());
+    for chunk in addresses.chunks(MAX_MULTIPLE_ACCOUNTS) {
+        accounts.extend(rpc_client.get_multiple_accounts_with_commitment(chunk, commitment)?.value);
+    }
+    Ok(accounts)
+}
+
+/// Default `--rpc-timeout-secs`, matching `RpcClient`'s own default.
+pub const DEFAULT_RPC_TIMEOUT_SECS: u64 = 30;
+
+/// Commitment level accepted by `--read-commitment` / `--confirm-commitment`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Commitment {
+    Processed,
+    Confirmed,
+    Finalized,
+}
+
+impl Commitment {
+    pub fn config(self) -> CommitmentConfig {
+        match self {
+            Commitment::Processed => CommitmentConfig::processed(),
+            Commitment::Confirmed => CommitmentConfig::confirmed(),
+            Commitment::Finalized => CommitmentConfig::finalized(),
+        }
+    }
+}
+
+/// Warning for sends confirmed at `commitment`: a `processed` transaction has only been seen by the
+/// RPC node and can still be dropped if its fork is abandoned, so transfers and swaps should wait for
+/// `confirmed` (supermajority vote) or `finalized`. Reads can stay at `processed` for speed.
+pub fn weak_commitment_warning(commitment: CommitmentConfig) -> Option<&'static str> {
+    if commitment.is_at_least_confirmed() {
+        return None;
+    }
+    Some("Sends confirm at 'processed': the transaction can still be rolled back by a fork. \
+          Use --confirm-commitment confirmed (default) or finalized for value-moving sends")
+}
+
+/// Average slot time, for turning a block height deadline into seconds.
+pub const APPROX_SLOT_MS: u64 = 400;
+
+/// A recent blockhash and the last block height at which a transaction using it can land.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockhashWindow {
+    pub blockhash: Hash,
+    pub last_valid_block_height: u64,
+}
+
+impl BlockhashWindow {
+    /// Approximate seconds left at `block_height`; 0 once expired.
+    pub fn expires_in_secs(&self, block_height: u64) -> u64 {
+        self.last_valid_block_height.saturating_sub(block_height) * APPROX_SLOT_MS / 1000
+    }
+}
+
+/// Fetch the latest blockhash with its `last_valid_block_height`.
+pub fn latest_blockhash(rpc_client: &RpcClient) -> ClientResult<BlockhashWindow> {
+    let (blockhash, last_valid_block_height) = rpc_client.get_latest_blockhash_with_commitment(rpc_client.commitment())?;
+    Ok(BlockhashWindow { blockhash, last_valid_block_height })
+}
+
+/// How many transactions a run has submitted, shared with `AccountCache` so it knows cached state may be stale.
+#[derive(Debug, Clone, Default)]
+pub struct SendCounter(Arc<AtomicU64>);
+
+impl SendCounter {
+    /// Note that a transaction was submitted.
+    pub fn record(&self) {
+        self.0.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn get(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// How long a sent transaction may go unseen by `getSignatureStatuses` before it counts as dropped.
+const DROPPED_GRACE: Duration = Duration::from_secs(5);
+/// Rebroadcasts of a dropped transaction before waiting out its blockhash.
+pub const MAX_REBROADCASTS: u32 = 5;
+const CONFIRM_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// What the confirmation loop does after one status poll.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfirmStep {
+    Confirmed,
+    /// Landed with an error; resending can't help
+    Failed(TransactionError),
+    Wait,
+    /// Not seen past the grace period while its blockhash is still valid
+    Rebroadcast,
+    /// Never landed and can't anymore
+    Expired,
+}
+
+/// Decide the next step from a status poll: `None` if the RPC has never seen the signature, else whether it
+/// failed or reached the confirm commitment. `blockhash_valid` is only asked once the transaction looks dropped.
+pub fn confirm_step(
+    status: Option<std::result::Result<bool, TransactionError>>,
+    since_send: Duration,
+    rebroadcasts: u32,
+    blockhash_valid: impl FnOnce() -> ClientResult<bool>,
+) -> ClientResult<ConfirmStep> {
+    Ok(match status {
+        Some(Err(err)) => ConfirmStep::Failed(err),
+        Some(Ok(true)) => ConfirmStep::Confirmed,
+        Some(Ok(false)) => ConfirmStep::Wait,
+        None if since_send < DROPPED_GRACE => ConfirmStep::Wait,
+        None if !blockhash_valid()? => ConfirmStep::Expired,
+        None if rebroadcasts < MAX_REBROADCASTS => ConfirmStep::Rebroadcast,
+        None => ConfirmStep::Wait,
+    })
+}
+
+/// Submit `transaction` once: with `config`, or the client's own preflight settings.
+/// Sending policy (`--build-only`, `--idempotency-key`) lives in `tx_sender`; go through it instead.
+pub fn submit(
+    rpc_client: &RpcClient,
+    transaction: &impl SerializableTransaction,
+    config: Option<RpcSendTransactionConfig>,
+) -> ClientResult<Signature> {
+    match config {
+        Some(config) => rpc_client.send_transaction_with_config(transaction, config),
+        None => rpc_client.send_transaction(transaction),
+    }
+}
+
+/// Poll a sent transaction until `commitment`. One the cluster hasn't seen after a grace period is
+/// rebroadcast (the same signed bytes, so it can land at most once) while its blockhash is valid; one that
+/// landed with an error stops immediately.
+fn confirm_with_rebroadcast(
+    rpc_client: &RpcClient,
+    transaction: &impl SerializableTransaction,
+    signature: Signature,
+    commitment: CommitmentConfig,
+    notes: Option<&Notes>,
+) -> ClientResult<Signature> {
+    let resend_config = RpcSendTransactionConfig { skip_preflight: true, ..RpcSendTransactionConfig::default() };
+    let mut sent_at = Instant::now();
+    let mut rebroadcasts = 0;
+    loop {
+        std::thread::sleep(CONFIRM_POLL_INTERVAL);
+        let status = rpc_client.get_signature_statuses(&[signature])?.value.into_iter().next().flatten()
+            .map(|status| match status.err {
+                Some(err) => Err(err),
+                None => Ok(status.satisfies_commitment(commitment)),
+            });
+        let step = confirm_step(status, sent_at.elapsed(), rebroadcasts, || {
+            rpc_client.is_blockhash_valid(transaction.get_recent_blockhash(), CommitmentConfig::processed())
+        })?;
+        match step {
+            ConfirmStep::Confirmed => return Ok(signature),
+            ConfirmStep::Failed(err) => return Err(err.into()),
+            ConfirmStep::Wait => {}
+            ConfirmStep::Rebroadcast => {
+                rebroadcasts += 1;
+                if let Some(notes) = notes {
+                    note!(notes, "🔁 {} not seen after {}s; rebroadcasting ({}/{})",
+                         signature, sent_at.elapsed().as_secs(), rebroadcasts, MAX_REBROADCASTS);
+                }
+                rpc_client.send_transaction_with_config(transaction, resend_config)?;
+                sent_at = Instant::now();
+            }
+            ConfirmStep::Expired => {
+                return Err(ClientErrorKind::Custom(format!(
+                    "Transaction {} was dropped: its blockhash expired before it landed ({} rebroadcast(s))",
+                    signature, rebroadcasts
+                )).into());
+            }
+        }
+    }
+}
+
+/// Wait for an already-sent transaction to reach `commitment`, rebroadcasting it while dropped.
+/// Fails once its blockhash expires without it landing.
+pub fn confirm_sent(rpc_client: &RpcClient, transaction: &impl SerializableTransaction, commitment: CommitmentConfig, notes: &Notes) -> ClientResult<Signature> {
+    confirm_with_rebroadcast(rpc_client, transaction, *transaction.get_signature(), commitment, Some(notes))
+}
+
+/// `confirm_sent` without progress output, for concurrent sends.
+pub fn confirm_sent_quiet(rpc_client: &RpcClient, transaction: &impl SerializableTransaction, commitment: CommitmentConfig) -> ClientResult<Signature> {
+    confirm_with_rebroadcast(rpc_client, transaction, *transaction.get_signature(), commitment, None)
+}
+
+/// Poll until `signature` lands (at any commitment) or its `blockhash` expires. `None` means it never landed
+/// and no longer can, so a replacement can't double-execute.
+pub fn wait_until_landed_or_expired(
+    rpc_client: &RpcClient,
+    signature: &Signature,
+    blockhash: &Hash,
+) -> ClientResult<Option<std::result::Result<(), TransactionError>>> {
+    let landed = |rpc_client: &RpcClient| -> ClientResult<_> {
+        Ok(rpc_client.get_signature_statuses(&[*signature])?.value.into_iter().next().flatten()
+            .map(|status| status.err.map_or(Ok(()), Err)))
+    };
+    loop {
+        if let Some(status) = landed(rpc_client)? {
+            return Ok(Some(status));
+        }
+        if !rpc_client.is_blockhash_valid(blockhash, CommitmentConfig::processed())? {
+            // It may have landed in the blockhash's last valid block
+            return landed(rpc_client);
+        }
+        std::thread::sleep(CONFIRM_POLL_INTERVAL);
+    }
+}
+
+/// Endpoint plus any extra HTTP headers (e.g. API keys) sent with every RPC request.
+#[derive(Clone)]
+pub struct RpcSettings {
+    pub url: String,
+    pub headers: HeaderMap,
+    /// Commitment for reads (`get_account`, `get_balance`, ...) made through `client()`
+    pub read_commitment: CommitmentConfig,
+    /// Per-request timeout for every client built from these settings
+    pub timeout: Duration,
+}
+
+impl RpcSettings {
+    pub fn new(url: String, raw_headers: &[String]) -> Result<Self> {
+        Ok(Self {
+            url,
+            headers: parse_rpc_headers(raw_headers)?,
+            read_commitment: CommitmentConfig::confirmed(),
+            timeout: Duration::from_secs(DEFAULT_RPC_TIMEOUT_SECS),
+        })
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn with_read_commitment(mut self, commitment: Commitment) -> Self {
+        self.read_commitment = commitment.config();
+        self
+    }
+
+    /// WebSocket endpoint for subscriptions: http(s) → ws(s), local 8899 → 8900.
+    pub fn ws_url(&self) -> Result<String> {
+        let ws = if let Some(rest) = self.url.strip_prefix("https://") {
+            format!("wss://{}", rest)
+        } else if let Some(rest) = self.url.strip_prefix("http://") {
+            format!("ws://{}", rest)
+        } else {
+            return Err(anyhow!("Can't derive a WebSocket URL from '{}'; pass --ws-url", self.url));
+        };
+        Ok(ws.replacen(":8899", ":8900", 1))
+    }
+
+    pub fn client(&self) -> RpcClient {
+        self.client_with_commitment(self.read_commitment)
+    }
+
+    pub fn client_with_commitment(&self, commitment: CommitmentConfig) -> RpcClient {
+        if self.headers.is_empty() {
+            return RpcClient::new_with_timeout_and_commitment(self.url.clone(), self.timeout, commitment);
+        }
+
+        let mut headers = HttpSender::default_headers();
+        headers.extend(self.headers.clone());
+        let http_client = reqwest::Client::builder()
+            .default_headers(headers)
+            .timeout(self.timeout)
+            .pool_idle_timeout(self.timeout)
+            .build()
+            .expect("build rpc http client");
+
+        RpcClient::new_sender(
+            HttpSender::new_with_client(self.url.clone(), http_client),
+            RpcClientConfig::with_commitment(commitment),
+        )
+    }
+}
+
+/// Parse repeated `--rpc-header "Name: value"` options into a header map.
+pub fn parse_rpc_headers(raw_headers: &[String]) -> Result<HeaderMap> {
+    let mut headers = HeaderMap::new();
+    for raw in raw_headers {
+        let (name, value) = raw.split_once(':')
+            .ok_or_else(|| anyhow!("Invalid RPC header '{}': expected 'Name: value'", raw))?;
+        let name = name.trim();
+        let value = value.trim();
+        if name.is_empty() {
+            return Err(anyhow!("Invalid RPC header '{}': empty header name", raw));
+        }
+
+        let header_name = HeaderName::from_bytes(name.as_bytes())
+            .map_err(|_| anyhow!("Invalid RPC header name: '{}'", name))?;
+        let mut header_value = HeaderValue::from_str(value)
+            .map_err(|_| anyhow!("Invalid value for RPC header '{}'", name))?;
+        // Keep API keys out of debug output
+        header_value.set_sensitive(true);
+        headers.append(header_name, header_value);
+    }
+    Ok(headers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_rpc_headers() {
+        let headers = parse_rpc_headers(&[
+            "X-API-Key: abc123".to_string(),
+            "Authorization:Bearer token".to_string(),
+        ]).unwrap();
+        assert_eq!(headers.get("x-api-key").unwrap(), "abc123");
+        assert_eq!(headers.get("authorization").unwrap(), "Bearer token");
+        assert!(headers.get("x-api-key").unwrap().is_sensitive());
+
+        assert!(parse_rpc_headers(&["NoColon".to_string()]).is_err());
+        assert!(parse_rpc_headers(&[": value".to_string()]).is_err());
+        assert!(parse_rpc_headers(&["Bad Name: value".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_commitment_config() {
+        assert_eq!(Commitment::Processed.config(), CommitmentConfig::processed());
+        assert_eq!(Commitment::Finalized.config(), CommitmentConfig::finalized());
+
+        let settings = RpcSettings::new("http://localhost:8899".to_string(), &[]).unwrap();
+        assert_eq!(settings.read_commitment, CommitmentConfig::confirmed());
+        let settings = settings.with_read_commitment(Commitment::Processed);
+        assert_eq!(settings.read_commitment, CommitmentConfig::processed());
+        assert_eq!(settings.timeout, Duration::from_secs(DEFAULT_RPC_TIMEOUT_SECS));
+        assert_eq!(settings.with_timeout(Duration::from_secs(5)).timeout, Duration::from_secs(5));
+
+        assert!(weak_commitment_warning(Commitment::Processed.config()).is_some());
+        assert!(weak_commitment_warning(Commitment::Confirmed.config()).is_none());
+        assert!(weak_commitment_warning(Commitment::Finalized.config()).is_none());
+
+        let window = BlockhashWindow { blockhash: Hash::default(), last_valid_block_height: 1_150 };
+        assert_eq!(window.expires_in_secs(1_000), 60);
+        assert_eq!(window.expires_in_secs(1_200), 0);
+    }
+
+    #[test]
+    fn test_confirm_step() {
+        let valid = || Ok(true);
+        let expired = || Ok(false);
+        let early = Duration::from_secs(1);
+        let late = DROPPED_GRACE + Duration::from_secs(1);
+
+        assert_eq!(confirm_step(Some(Ok(true)), early, 0, valid).unwrap(), ConfirmStep::Confirmed);
+        assert_eq!(confirm_step(Some(Ok(false)), late, 0, valid).unwrap(), ConfirmStep::Wait);
+        let failed = confirm_step(Some(Err(TransactionError::AccountNotFound)), late, 0, valid).unwrap();
+        assert_eq!(failed, ConfirmStep::Failed(TransactionError::AccountNotFound));
+
+        // Unseen: wait out the grace period, then rebroadcast while the blockhash lives
+        assert_eq!(confirm_step(None, early, 0, || panic!("not asked during grace")).unwrap(), ConfirmStep::Wait);
+        assert_eq!(confirm_step(None, late, 0, valid).unwrap(), ConfirmStep::Rebroadcast);
+        assert_eq!(confirm_step(None, late, MAX_REBROADCASTS, valid).unwrap(), ConfirmStep::Wait);
+        assert_eq!(confirm_step(None, late, 1, expired).unwrap(), ConfirmStep::Expired);
+    }
+
+    #[test]
+    fn test_ws_url() {
+        let ws = |url: &str| RpcSettings::new(url.to_string(), &[]).unwrap().ws_url();
+        assert_eq!(ws("https://api.devnet.solana.com").unwrap(), "wss://api.devnet.solana.com");
+        assert_eq!(ws("http://127.0.0.1:8899").unwrap(), "ws://127.0.0.1:8900");
+        assert!(ws("api.devnet.solana.com").is_err());
+    }
+}
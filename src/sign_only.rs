@@ -0,0 +1,155 @@
+use anyhow::{anyhow, Result};
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use solana_sdk::message::VersionedMessage;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signature};
+use solana_sdk::signer::Signer;
+use solana_sdk::transaction::VersionedTransaction;
+use std::str::FromStr;
+use crate::rpc::BlockhashWindow;
+
+/// One required signer of the message, in signature-slot order.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SignerSlot {
+    pub pubkey: String,
+    /// `None` until the signer's holder signs
+    pub signature: Option<String>,
+}
+
+/// `--sign-only` output: the message and whichever signatures this machine could make. Each party signs
+/// the same message; `combine` merges their files.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PartiallySigned {
+    /// Base64 wire-format message
+    pub message: String,
+    pub recent_blockhash: String,
+    /// The transaction can't land after this block height
+    pub last_valid_block_height: u64,
+    pub signers: Vec<SignerSlot>,
+}
+
+fn encode_message(message: &VersionedMessage) -> Result<String> {
+    let bytes = bincode::serde::encode_to_vec(message, bincode::config::legacy())?;
+    Ok(base64::engine::general_purpose::STANDARD.encode(bytes))
+}
+
+fn decode_message(encoded: &str) -> Result<VersionedMessage> {
+    let bytes = base64::engine::general_purpose::STANDARD.decode(encoded)
+        .map_err(|e| anyhow!("Message is not valid base64: {}", e))?;
+    let (message, _) = bincode::serde::decode_from_slice(&bytes, bincode::config::legacy())
+        .map_err(|e| anyhow!("Message does not decode: {}", e))?;
+    Ok(message)
+}
+
+/// Sign every required slot of `message` that one of `keypairs` holds; other slots stay empty.
+pub fn partial_sign(message: &VersionedMessage, keypairs: &[&Keypair], blockhash: &BlockhashWindow) -> Result<PartiallySigned> {
+    let bytes = message.serialize();
+    let required = message.header().num_required_signatures as usize;
+    let signers = message.static_account_keys()[..required].iter()
+        .map(|pubkey| SignerSlot {
+            pubkey: pubkey.to_string(),
+            signature: keypairs.iter()
+                .find(|k| k.pubkey() == *pubkey)
+                .map(|k| k.sign_message(&bytes).to_string()),
+        })
+        .collect();
+    Ok(PartiallySigned {
+        message: encode_message(message)?,
+        recent_blockhash: blockhash.blockhash.to_string(),
+        last_valid_block_height: blockhash.last_valid_block_height,
+        signers,
+    })
+}
+
+/// Merge partial signatures of one message into a fully signed transaction. Signatures are placed by the
+/// message's own signer order (not the files'), each is verified, and every slot must end up filled.
+pub fn combine(parts: &[PartiallySigned]) -> Result<VersionedTransaction> {
+    let first = parts.first().ok_or_else(|| anyhow!("No partially signed transactions to combine"))?;
+    if let Some(other) = parts.iter().find(|p| p.message != first.message) {
+        return Err(anyhow!(
+            "Partial signatures are for different messages (blockhash {} vs {}); every signer must sign the same --sign-only output",
+            first.recent_blockhash, other.recent_blockhash
+        ));
+    }
+    let message = decode_message(&first.message)?;
+    let bytes = message.serialize();
+    let required = message.header().num_required_signatures as usize;
+    let slot_keys = &message.static_account_keys()[..required];
+
+    let mut signatures: Vec<Option<Signature>> = vec![None; required];
+    for slot in parts.iter().flat_map(|p| &p.signers) {
+        let Some(signature) = &slot.signature else { continue };
+        let pubkey = Pubkey::from_str(&slot.pubkey).map_err(|_| anyhow!("Invalid signer pubkey: {}", slot.pubkey))?;
+        let index = slot_keys.iter().position(|k| *k == pubkey)
+            .ok_or_else(|| anyhow!("{} is not a signer of this message", pubkey))?;
+        let signature = Signature::from_str(signature).map_err(|_| anyhow!("Invalid signature for {}: {}", pubkey, signature))?;
+        if !signature.verify(pubkey.as_ref(), &bytes) {
+            return Err(anyhow!("Signature for {} does not verify against the message", pubkey));
+        }
+        match signatures[index] {
+            Some(existing) if existing != signature => return Err(anyhow!("Conflicting signatures for {}", pubkey)),
+            _ => signatures[index] = Some(signature),
+        }
+    }
+
+    let missing: Vec<String> = slot_keys.iter().zip(&signatures)
+        .filter(|(_, signature)| signature.is_none())
+        .map(|(pubkey, _)| pubkey.to_string())
+        .collect();
+    if !missing.is_empty() {
+        return Err(anyhow!("Missing signatures from: {}", missing.join(", ")));
+    }
+    Ok(VersionedTransaction {
+        signatures: signatures.into_iter().flatten().collect(),
+        message,
+    })
+}
+
+/// Read `--sign-only` JSON files for `combine`.
+pub fn read_parts(paths: &[String]) -> Result<Vec<PartiallySigned>> {
+    paths.iter().map(|path| {
+        let content = std::fs::read_to_string(path).map_err(|e| anyhow!("Cannot read {}: {}", path, e))?;
+        serde_json::from_str(&content).map_err(|e| anyhow!("{} is not --sign-only output: {}", path, e))
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::hash::Hash;
+    use solana_sdk::instruction::{AccountMeta, Instruction};
+    use solana_sdk::message::Message;
+
+    #[test]
+    fn test_partial_sign_and_combine() {
+        let fee_payer = Keypair::new();
+        let cosigner = Keypair::new();
+        let instruction = Instruction {
+            program_id: Pubkey::new_unique(),
+            accounts: vec![AccountMeta::new(cosigner.pubkey(), true), AccountMeta::new(fee_payer.pubkey(), true)],
+            data: vec![7],
+        };
+        let blockhash = BlockhashWindow { blockhash: Hash::new_unique(), last_valid_block_height: 500 };
+        let message = VersionedMessage::Legacy(Message::new_with_blockhash(&[instruction], Some(&fee_payer.pubkey()), &blockhash.blockhash));
+
+        let mine = partial_sign(&message, &[&fee_payer], &blockhash).unwrap();
+        let theirs = partial_sign(&message, &[&cosigner], &blockhash).unwrap();
+        assert_eq!(mine.signers[0].pubkey, fee_payer.pubkey().to_string());
+        assert!(mine.signers[0].signature.is_some() && mine.signers[1].signature.is_none());
+        assert!(combine(std::slice::from_ref(&mine)).unwrap_err().to_string().contains(&cosigner.pubkey().to_string()));
+
+        // File order doesn't matter; slots follow the message
+        let transaction = combine(&[theirs.clone(), mine.clone()]).unwrap();
+        assert_eq!(transaction.message, message);
+        assert!(transaction.verify_with_results().iter().all(|ok| *ok));
+
+        let mut forged = theirs.clone();
+        forged.signers[1].signature = mine.signers[0].signature.clone();
+        assert!(combine(&[mine.clone(), forged]).is_err());
+
+        let other_blockhash = BlockhashWindow { blockhash: Hash::new_unique(), ..blockhash };
+        let other = partial_sign(&VersionedMessage::Legacy(Message::new_with_blockhash(&[], Some(&cosigner.pubkey()), &other_blockhash.blockhash)), &[&cosigner], &other_blockhash).unwrap();
+        assert!(combine(&[mine, other]).is_err());
+    }
+}
@@ -1,67 +1,288 @@
 use anyhow::Result;
-use solana_client::rpc_client::RpcClient;
-use solana_client::rpc_config::{RpcSimulateTransactionConfig, RpcSendTransactionConfig};
+use serde::Serialize;
+use solana_client::client_error::{ClientError, ClientErrorKind};
+use solana_client::rpc_client::{RpcClient, SerializableTransaction};
+use solana_client::rpc_config::{RpcSimulateTransactionAccountsConfig, RpcSimulateTransactionConfig, RpcSendTransactionConfig};
+use solana_client::rpc_response::RpcSimulateTransactionResult;
+use solana_sdk::account::Account;
 use solana_sdk::commitment_config::CommitmentConfig;
-use solana_sdk::transaction::{Transaction, VersionedTransaction};
+use solana_sdk::message::VersionedMessage;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_sdk::transaction::{TransactionError, VersionedTransaction};
 use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use crate::account_inspector::format_post_accounts;
+use crate::error_decoder;
+use crate::idl_loader::IdlLoader;
+use crate::lamports::format_token_amount;
+use crate::tx_sender::{self, SendSettings};
+use crate::outcome::note;
+
+const TOKEN_2022_PROGRAM_ID: &str = "TokenzQdBNbLqU2YPbVmjYVBRhCF9dDid1i9QpZ5dKQ";
+// SPL Token instruction tags
+const TOKEN_TRANSFER: u8 = 3;
+const TOKEN_TRANSFER_CHECKED: u8 = 12;
+
+const LAMPORTS_PER_SIGNATURE: u64 = 5_000;
+/// Most compute units a transaction can request; the fee heuristic never charges for more
+const MAX_TRANSACTION_COMPUTE_UNITS: u64 = 1_400_000;
+
+/// Default `--simulate-retries`.
+pub const DEFAULT_SIMULATE_RETRIES: u32 = 2;
+
+/// Where a simulation's fee figure came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FeeConfidence {
+    /// The RPC's `getFeeForMessage` for this exact message (base + priority)
+    Exact,
+    /// `heuristic_fee`, used when the RPC couldn't price the message: a rough lower bound
+    #[default]
+    Estimated,
+}
 
-pub struct TransactionSimulator {
-    rpc_client: RpcClient,
+impl FeeConfidence {
+    /// `fee` in lamports, labeled as a lower bound when it's the heuristic.
+    pub fn describe(self, fee: u64) -> String {
+        match self {
+            FeeConfidence::Exact => format!("{} lamports", fee),
+            FeeConfidence::Estimated => format!(">= {} lamports (rough lower bound; the RPC could not price the message)", fee),
+        }
+    }
 }
 
-impl TransactionSimulator {
-    pub fn new(rpc_client: RpcClient) -> Self {
-        Self { rpc_client }
+/// Offline fee guess: the base fee per signature plus 100 lamports per started 1000 compute units, with
+/// compute capped at the transaction limit. Nonzero compute never rounds down to a free transaction.
+pub fn heuristic_fee(signatures: usize, compute_units: u64) -> u64 {
+    let signature_fee = (signatures as u64).saturating_mul(LAMPORTS_PER_SIGNATURE);
+    let compute_fee = compute_units.min(MAX_TRANSACTION_COMPUTE_UNITS).div_ceil(1000) * 100;
+    signature_fee.saturating_add(compute_fee)
+}
+
+/// The RPC's fee for `message` when it can price it, else `heuristic_fee` from the simulated compute units.
+pub fn estimate_fee(rpc_client: &RpcClient, message: &VersionedMessage, compute_units: u64) -> (u64, FeeConfidence) {
+    match tx_sender::message_fee(rpc_client, message) {
+        Ok(fee) => (fee, FeeConfidence::Exact),
+        Err(_) => {
+            let signatures = message.header().num_required_signatures as usize;
+            (heuristic_fee(signatures, compute_units), FeeConfidence::Estimated)
+        }
     }
+}
 
-   
-    pub fn simulate_transaction(&self, transaction: &Transaction) -> Result<SimulationResult> {
-        let config = RpcSimulateTransactionConfig {
-            sig_verify: true,
-            replace_recent_blockhash: true,
+/// The request never got an answer (connection, timeout, HTTP status), as opposed to the RPC rejecting it.
+fn is_transport_error(err: &ClientError) -> bool {
+    matches!(err.kind(), ClientErrorKind::Io(_) | ClientErrorKind::Reqwest(_))
+}
+
+/// Index of the instruction a transaction error points at.
+pub fn failed_instruction(err: Option<&TransactionError>) -> Option<usize> {
+    match err {
+        Some(TransactionError::InstructionError(index, _)) => Some(*index as usize),
+        _ => None,
+    }
+}
+
+/// SPL Token transfer executed during a simulation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenTransfer {
+    /// Base units; `None` for transfers made via CPI
+    pub amount: Option<u64>,
+    /// Known only for `TransferChecked`
+    pub mint: Option<Pubkey>,
+    pub decimals: Option<u8>,
+}
+
+impl TokenTransfer {
+    pub fn describe(&self) -> String {
+        match (self.amount, self.mint, self.decimals) {
+            (Some(amount), Some(mint), Some(decimals)) =>
+                format!("transferred {} tokens of mint {}", format_token_amount(amount, decimals), mint),
+            (Some(amount), _, _) => format!("transferred {} tokens (base units; mint not in the instruction)", amount),
+            _ => "token transfer via CPI (amount not logged)".to_string(),
+        }
+    }
+}
+
+fn is_token_program(program_id: &str) -> bool {
+    program_id == spl_token::id().to_string() || program_id == TOKEN_2022_PROGRAM_ID
+}
+
+/// Token transfers in log order. The Token program logs only `Instruction: Transfer[Checked]`, so amounts
+/// (and the mint, for TransferChecked) are decoded from the matching top-level instruction.
+pub fn token_transfers(logs: &[String], message: &VersionedMessage) -> Vec<TokenTransfer> {
+    let mut transfers = Vec::new();
+    let mut invoked: Vec<&str> = Vec::new();
+    let mut top_level: Option<usize> = None;
+    for log in logs {
+        if let Some(name) = log.strip_prefix("Program log: Instruction: ") {
+            let in_token_program = invoked.last().is_some_and(|id| is_token_program(id));
+            if in_token_program && (name == "Transfer" || name == "TransferChecked") {
+                let top_level_ix = top_level.filter(|_| invoked.len() == 1)
+                    .and_then(|index| message.instructions().get(index));
+                transfers.push(top_level_ix
+                    .and_then(|ix| decode_token_transfer(&ix.data, &ix.accounts, message.static_account_keys()))
+                    .unwrap_or(TokenTransfer { amount: None, mint: None, decimals: None }));
+            }
+            continue;
+        }
+        let mut words = log.split_whitespace();
+        let (Some("Program"), Some(program_id), Some(event)) = (words.next(), words.next(), words.next()) else {
+            continue;
+        };
+        match event {
+            "invoke" => {
+                if invoked.is_empty() {
+                    top_level = Some(top_level.map_or(0, |index| index + 1));
+                }
+                invoked.push(program_id);
+            }
+            "success" | "failed:" => {
+                invoked.pop();
+            }
+            _ => {}
+        }
+    }
+    transfers
+}
+
+/// `token_transfers` for a simulation preview, one per line; empty when there are none.
+pub fn format_token_transfers(logs: &[String], message: &VersionedMessage) -> String {
+    let transfers = token_transfers(logs, message);
+    if transfers.is_empty() {
+        return String::new();
+    }
+    let mut out = "🪙 Token transfers:\n".to_string();
+    for transfer in transfers {
+        out.push_str(&format!("  {}\n", transfer.describe()));
+    }
+    out
+}
+
+fn decode_token_transfer(data: &[u8], accounts: &[u8], keys: &[Pubkey]) -> Option<TokenTransfer> {
+    let amount = u64::from_le_bytes(data.get(1..9)?.try_into().ok()?);
+    match data[0] {
+        TOKEN_TRANSFER => Some(TokenTransfer { amount: Some(amount), mint: None, decimals: None }),
+        TOKEN_TRANSFER_CHECKED => Some(TokenTransfer {
+            amount: Some(amount),
+            mint: accounts.get(1).and_then(|index| keys.get(*index as usize)).copied(),
+            decimals: data.get(9).copied(),
+        }),
+        _ => None,
+    }
+}
+
+/// Pair the requested `return_accounts` with the post-simulation state the RPC returned.
+pub fn post_accounts(value: &RpcSimulateTransactionResult, return_accounts: &[Pubkey]) -> Vec<(Pubkey, Option<Account>)> {
+    let returned = value.accounts.as_deref().unwrap_or(&[]);
+    return_accounts.iter()
+        .enumerate()
+        .map(|(i, pubkey)| (*pubkey, returned.get(i).and_then(|a| a.as_ref()).and_then(|a| a.decode())))
+        .collect()
+}
+
+/// How closely simulation should mirror a real send.
+///
+/// `Lenient` lets the RPC swap in a fresh blockhash and skips signature checks, so unsigned or
+/// stale transactions still simulate. `Strict` keeps the transaction's own blockhash and verifies
+/// signatures, which catches expired blockhashes and missing signers but requires a fully signed
+/// transaction built against a recent blockhash.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SimulationMode {
+    #[default]
+    Lenient,
+    Strict,
+}
+
+impl SimulationMode {
+    /// Simulation config that also asks for `return_accounts`' post-state.
+    pub fn config(&self, return_accounts: &[Pubkey]) -> RpcSimulateTransactionConfig {
+        let strict = *self == SimulationMode::Strict;
+        RpcSimulateTransactionConfig {
+            // sig_verify can't be combined with replace_recent_blockhash
+            sig_verify: strict,
+            replace_recent_blockhash: !strict,
             commitment: Some(CommitmentConfig::processed()),
             encoding: None,
-            accounts: None,
+            accounts: (!return_accounts.is_empty()).then(|| RpcSimulateTransactionAccountsConfig {
+                // RPC defaults to base64 for returned accounts
+                encoding: None,
+                addresses: return_accounts.iter().map(|p| p.to_string()).collect(),
+            }),
             min_context_slot: None,
             inner_instructions: true,
-        };
-
-        let response = self.rpc_client.simulate_transaction_with_config(transaction, config)?;
+        }
+    }
+}
 
-        let mut result = SimulationResult {
-            success: response.value.err.is_none(),
-            error_message: None,
-            compute_units_consumed: 0,
-            fee_estimate: 0,
-            logs: response.value.logs.unwrap_or_default(),
-            account_changes: HashMap::new(),
-            warnings: Vec::new(),
-        };
+pub struct TransactionSimulator {
+    rpc_client: RpcClient,
+    mode: SimulationMode,
+    idl_loader: Option<IdlLoader>,
+    settings: Arc<SendSettings>,
+    retries: u32,
+}
 
-        // Extract error message if failed
-        if let Some(err) = response.value.err {
-            result.error_message = Some(format!("{:?}", err));
+impl TransactionSimulator {
+    pub fn new(rpc_client: RpcClient) -> Self {
+        Self {
+            rpc_client,
+            mode: SimulationMode::default(),
+            idl_loader: None,
+            settings: Arc::new(SendSettings::default()),
+            retries: DEFAULT_SIMULATE_RETRIES,
         }
+    }
 
-        // Extract compute units consumed
-        if let Some(units) = response.value.units_consumed {
-            result.compute_units_consumed = units;
-        }
+    /// Run-wide send settings for the sends it makes and `--return-accounts`
+    pub fn with_send_settings(mut self, settings: Arc<SendSettings>) -> Self {
+        self.settings = settings;
+        self
+    }
 
-        // Parse logs for useful information
-        result.parse_logs();
+    /// Retry a simulation this many times on RPC transport errors (`--simulate-retries`)
+    pub fn with_retries(mut self, retries: u32) -> Self {
+        self.retries = retries;
+        self
+    }
 
-        // Estimate fee (5000 lamports per signature + compute units)
-        let signature_fee = transaction.signatures.len() as u64 * 5000;
-        let compute_fee = (result.compute_units_consumed / 1000) * 100; // Rough estimate
-        result.fee_estimate = signature_fee + compute_fee;
+    pub fn with_mode(mut self, mode: SimulationMode) -> Self {
+        self.mode = mode;
+        self
+    }
 
-        Ok(result)
+    /// IDLs used to decode `--return-accounts` post-state
+    pub fn with_idl_loader(mut self, idl_loader: IdlLoader) -> Self {
+        self.idl_loader = Some(idl_loader);
+        self
     }
 
-   
-    pub fn validate_transaction(&self, transaction: &Transaction) -> Result<ValidationResult> {
-        let simulation = self.simulate_transaction(transaction)?;
+    /// Simulate, retrying transport errors; a program failure comes back as `Ok` with `err` set.
+    fn simulate_with_retries(&self, transaction: &impl SerializableTransaction, config: RpcSimulateTransactionConfig) -> Result<RpcSimulateTransactionResult> {
+        let retries = self.retries;
+        let mut attempt = 0;
+        loop {
+            match self.rpc_client.simulate_transaction_with_config(transaction, config.clone()) {
+                Ok(response) => return Ok(response.value),
+                Err(e) if is_transport_error(&e) && attempt < retries => {
+                    attempt += 1;
+                    note!(self.settings.notes, "⚠️  Simulation RPC error ({}); retrying {}/{}...", e, attempt, retries);
+                    std::thread::sleep(Duration::from_millis(500 * attempt as u64));
+                }
+                Err(e) => {
+                    return Err(anyhow::anyhow!(
+                        "Simulation could not be performed after {} attempt(s): {} (the RPC did not run the transaction; it was not sent)",
+                        attempt + 1, e
+                    ));
+                }
+            }
+        }
+    }
+
+    pub fn validate_transaction(&self, transaction: &VersionedTransaction) -> Result<ValidationResult> {
+        let simulation = self.simulate_versioned_transaction(transaction)?;
         let mut issues = Vec::new();
         let mut warnings = Vec::new();
 
@@ -112,12 +333,13 @@ impl TransactionSimulator {
     }
 
    
-    pub fn preview_transaction(&self, transaction: &Transaction) -> Result<TransactionPreview> {
-        let simulation = self.simulate_transaction(transaction)?;
+    pub fn preview_transaction(&self, transaction: &VersionedTransaction) -> Result<TransactionPreview> {
+        let simulation = self.simulate_versioned_transaction(transaction)?;
         
         let mut preview = TransactionPreview {
             will_succeed: simulation.success,
             estimated_fee: simulation.fee_estimate,
+            fee_confidence: simulation.fee_confidence,
             compute_units: simulation.compute_units_consumed,
             account_changes: Vec::new(),
             program_logs: Vec::new(),
@@ -133,6 +355,9 @@ impl TransactionSimulator {
         }
 
         // Analyze account changes from logs
+        for transfer in token_transfers(&simulation.logs, &transaction.message) {
+            preview.account_changes.push(transfer.describe());
+        }
         for log in &simulation.logs {
             if log.contains("balance:") {
                 preview.account_changes.push(format!("Balance change detected: {}", log));
@@ -147,15 +372,18 @@ impl TransactionSimulator {
 
    
     pub fn safe_send_versioned_transaction(&self, transaction: &VersionedTransaction) -> Result<SafeSendResult> {
-        println!("🔍 Simulating versioned transaction before sending...");
+        note!(self.settings.notes, "🔍 Simulating versioned transaction before sending...");
         
-        let validation = self.validate_versioned_transaction(transaction)?;
+        let mut validation = self.validate_versioned_transaction(transaction)?;
+        if self.settings.promote_warnings(&mut validation.issues, &mut validation.warnings) {
+            validation.safe_to_send = false;
+        }
         
         // Print validation results
         if !validation.safe_to_send {
-            println!("❌ Transaction validation failed:");
+            note!(self.settings.notes, "❌ Transaction validation failed:");
             for issue in &validation.issues {
-                println!("  🚨 {}", issue);
+                note!(self.settings.notes, "  🚨 {}", issue);
             }
             return Ok(SafeSendResult {
                 sent: false,
@@ -167,32 +395,34 @@ impl TransactionSimulator {
 
         // Print warnings but continue
         if !validation.warnings.is_empty() {
-            println!("⚠️  Transaction warnings:");
+            note!(self.settings.notes, "⚠️  Transaction warnings:");
             for warning in &validation.warnings {
-                println!("  ⚠️  {}", warning);
+                note!(self.settings.notes, "  ⚠️  {}", warning);
             }
         }
 
         // Print success preview
-        println!("✅ Transaction simulation successful!");
-        println!("💰 Estimated fee: {} lamports", validation.simulation.fee_estimate);
-        println!("⚡ Compute units: {}", validation.simulation.compute_units_consumed);
+        note!(self.settings.notes, "✅ Transaction simulation successful!");
+        note!(self.settings.notes, "💰 Fee: {}", validation.simulation.fee_confidence.describe(validation.simulation.fee_estimate));
+        note!(self.settings.notes, "⚡ Compute units: {}", validation.simulation.compute_units_consumed);
         
         if !validation.simulation.logs.is_empty() {
-            println!("📋 Expected program logs:");
+            note!(self.settings.notes, "📋 Expected program logs:");
             for log in &validation.simulation.logs {
                 if log.contains("Program log:") {
-                    println!("  📝 {}", log.replace("Program log: ", ""));
+                    note!(self.settings.notes, "  📝 {}", log.replace("Program log: ", ""));
                 }
             }
         }
+        self.settings.notes.text(format_token_transfers(&validation.simulation.logs, &transaction.message));
 
-        println!("🚀 Sending versioned transaction to blockchain...");
+        note!(self.settings.notes, "🚀 Sending versioned transaction to blockchain...");
 
         // Send the transaction
-        match self.rpc_client.send_and_confirm_transaction(transaction) {
+        match tx_sender::send_and_confirm(&self.rpc_client, &self.settings, transaction) {
             Ok(signature) => {
-                println!("✅ Transaction confirmed: {}", signature);
+                note!(self.settings.notes, "✅ Transaction confirmed: {}", signature);
+                self.settings.explorer.note_transaction_url(&self.settings.notes, &signature);
                 Ok(SafeSendResult {
                     sent: true,
                     signature: Some(signature),
@@ -201,7 +431,7 @@ impl TransactionSimulator {
                 })
             }
             Err(e) => {
-                println!("❌ Transaction failed to send: {}", e);
+                note!(self.settings.notes, "❌ Transaction failed to send: {}", e);
                 Ok(SafeSendResult {
                     sent: false,
                     signature: None,
@@ -214,27 +444,32 @@ impl TransactionSimulator {
 
    
     pub fn send_versioned_transaction_direct(&self, transaction: &VersionedTransaction) -> Result<SafeSendResult> {
-        println!("🚀 Sending versioned transaction directly to blockchain (skipping simulation)...");
-        println!("ℹ️  Simulation skipped due to Address Lookup Tables not available on local RPC");
+        note!(self.settings.notes, "🚀 Sending versioned transaction directly to blockchain (skipping simulation)...");
+        note!(self.settings.notes, "ℹ️  Simulation skipped due to Address Lookup Tables not available on local RPC");
 
        
-        println!("🔍 Attempting to send transaction to RPC...");
-        match self.rpc_client.send_transaction_with_config(
-            transaction, 
-            RpcSendTransactionConfig {
+        note!(self.settings.notes, "🔍 Attempting to send transaction to RPC...");
+        // Refuse up front; a failed submit below is reported, not returned
+        tx_sender::ensure_sending_allowed(&self.rpc_client, &self.settings, &transaction.signatures[0])?;
+        match tx_sender::submit(
+            &self.rpc_client,
+            &self.settings,
+            transaction,
+            Some(RpcSendTransactionConfig {
                 skip_preflight: true,
                 max_retries: Some(3),
                 ..Default::default()
-            }
+            }),
         ) {
             Ok(signature) => {
-                println!("📤 Transaction submitted: {}", signature);
-                println!("⏳ Waiting for confirmation...");
-                
-               
-                match self.rpc_client.confirm_transaction(&signature) {
+                note!(self.settings.notes, "📤 Transaction submitted: {}", signature);
+                note!(self.settings.notes, "⏳ Waiting for confirmation...");
+
+                // Confirmed only once it reaches the confirm commitment; dropped sends are rebroadcast
+                match tx_sender::confirm_sent(&self.rpc_client, &self.settings, transaction) {
                     Ok(_) => {
-                        println!("✅ Transaction confirmed: {}", signature);
+                        note!(self.settings.notes, "✅ Transaction confirmed: {}", signature);
+                        self.settings.explorer.note_transaction_url(&self.settings.notes, &signature);
                         Ok(SafeSendResult {
                             sent: true,
                             signature: Some(signature),
@@ -244,14 +479,17 @@ impl TransactionSimulator {
                                 logs: vec!["Direct send and confirmation successful (simulation skipped)".to_string()],
                                 compute_units_consumed: 0,
                                 fee_estimate: 0,
+                                fee_confidence: FeeConfidence::Estimated,
                                 error_message: None,
                                 account_changes: HashMap::new(),
                                 warnings: Vec::new(),
+                                post_accounts: Vec::new(),
+                                failed_instruction: None,
                             },
                         })
                     }
                     Err(confirm_err) => {
-                        println!("⚠️  Transaction sent but confirmation failed: {}", confirm_err);
+                        note!(self.settings.notes, "⚠️  Transaction sent but confirmation failed: {}", confirm_err);
                         Ok(SafeSendResult {
                             sent: true,
                             signature: Some(signature),
@@ -261,16 +499,19 @@ impl TransactionSimulator {
                                 logs: vec!["Direct send successful but confirmation failed (simulation skipped)".to_string()],
                                 compute_units_consumed: 0,
                                 fee_estimate: 0,
+                                fee_confidence: FeeConfidence::Estimated,
                                 error_message: Some(confirm_err.to_string()),
                                 account_changes: HashMap::new(),
                                 warnings: Vec::new(),
+                                post_accounts: Vec::new(),
+                                failed_instruction: None,
                             },
                         })
                     }
                 }
             }
             Err(e) => {
-                println!("❌ Transaction failed to send: {}", e);
+                note!(self.settings.notes, "❌ Transaction failed to send: {}", e);
                 Ok(SafeSendResult {
                     sent: false,
                     signature: None,
@@ -280,9 +521,12 @@ impl TransactionSimulator {
                         logs: vec!["Direct send failed (simulation skipped)".to_string()],
                         compute_units_consumed: 0,
                         fee_estimate: 0,
+                        fee_confidence: FeeConfidence::Estimated,
                         error_message: Some(e.to_string()),
                         account_changes: HashMap::new(),
                         warnings: Vec::new(),
+                        post_accounts: Vec::new(),
+                        failed_instruction: None,
                     },
                 })
             }
@@ -343,60 +587,68 @@ impl TransactionSimulator {
 
    
     pub fn simulate_versioned_transaction(&self, transaction: &VersionedTransaction) -> Result<SimulationResult> {
-        let config = RpcSimulateTransactionConfig {
-            sig_verify: false, // Can't use with replace_recent_blockhash
-            replace_recent_blockhash: true,
-            commitment: Some(CommitmentConfig::processed()),
-            encoding: None,
-            accounts: None,
-            min_context_slot: None,
-            inner_instructions: true,
-        };
+        let response = self.simulate_with_retries(transaction, self.mode.config(&self.settings.return_accounts))?;
+        Ok(self.versioned_result(&transaction.message, response))
+    }
 
-        let response = self.rpc_client.simulate_transaction_with_config(transaction, config)?;
+    /// Simulate an unsigned message against the latest blockhash (lenient, whatever the mode), for previews
+    /// that are signed only when sent.
+    pub fn simulate_message(&self, message: &VersionedMessage) -> Result<SimulationResult> {
+        let transaction = VersionedTransaction {
+            signatures: vec![Signature::default(); message.header().num_required_signatures as usize],
+            message: message.clone(),
+        };
+        let response = self.simulate_with_retries(&transaction, SimulationMode::Lenient.config(&self.settings.return_accounts))?;
+        Ok(self.versioned_result(message, response))
+    }
 
+    fn versioned_result(&self, message: &VersionedMessage, response: RpcSimulateTransactionResult) -> SimulationResult {
         let mut result = SimulationResult {
-            success: response.value.err.is_none(),
+            success: response.err.is_none(),
             error_message: None,
             compute_units_consumed: 0,
             fee_estimate: 0,
-            logs: response.value.logs.unwrap_or_default(),
+            fee_confidence: FeeConfidence::Estimated,
+            post_accounts: post_accounts(&response, &self.settings.return_accounts),
+            failed_instruction: failed_instruction(response.err.as_ref()),
+            logs: response.logs.clone().unwrap_or_default(),
             account_changes: HashMap::new(),
             warnings: Vec::new(),
         };
 
         // Extract error message if failed
-        if let Some(err) = response.value.err {
-            result.error_message = Some(format!("{:?}", err));
+        if let Some(err) = &response.err {
+            let program_ids = error_decoder::message_program_ids(message);
+            result.error_message = Some(error_decoder::describe_failure(self.idl_loader.as_ref(), &program_ids, err, &result.logs));
         }
 
         // Extract compute units consumed
-        if let Some(units) = response.value.units_consumed {
+        if let Some(units) = response.units_consumed {
             result.compute_units_consumed = units;
         }
 
         // Parse logs for useful information
         result.parse_logs();
 
-        // Estimate fee (5000 lamports per signature + compute units)
-        let signature_fee = transaction.signatures.len() as u64 * 5000;
-        let compute_fee = (result.compute_units_consumed / 1000) * 100; // Rough estimate
-        result.fee_estimate = signature_fee + compute_fee;
+        (result.fee_estimate, result.fee_confidence) = estimate_fee(&self.rpc_client, message, result.compute_units_consumed);
 
-        Ok(result)
+        result
     }
 
    
-    pub fn safe_send_transaction(&self, transaction: &Transaction) -> Result<SafeSendResult> {
-        println!("🔍 Simulating transaction before sending...");
+    pub fn safe_send_transaction(&self, transaction: &VersionedTransaction) -> Result<SafeSendResult> {
+        note!(self.settings.notes, "🔍 Simulating transaction before sending...");
         
-        let validation = self.validate_transaction(transaction)?;
+        let mut validation = self.validate_transaction(transaction)?;
+        if self.settings.promote_warnings(&mut validation.issues, &mut validation.warnings) {
+            validation.safe_to_send = false;
+        }
         
         // Print validation results
         if !validation.safe_to_send {
-            println!("❌ Transaction validation failed:");
+            note!(self.settings.notes, "❌ Transaction validation failed:");
             for issue in &validation.issues {
-                println!("  🚨 {}", issue);
+                note!(self.settings.notes, "  🚨 {}", issue);
             }
             return Ok(SafeSendResult {
                 sent: false,
@@ -408,41 +660,50 @@ impl TransactionSimulator {
 
         // Print warnings but continue
         if !validation.warnings.is_empty() {
-            println!("⚠️  Transaction warnings:");
+            note!(self.settings.notes, "⚠️  Transaction warnings:");
             for warning in &validation.warnings {
-                println!("  ⚠️  {}", warning);
+                note!(self.settings.notes, "  ⚠️  {}", warning);
             }
         }
 
         // Print success preview
-        println!("✅ Transaction simulation successful!");
-        println!("💰 Estimated fee: {} lamports", validation.simulation.fee_estimate);
-        println!("⚡ Compute units: {}", validation.simulation.compute_units_consumed);
+        note!(self.settings.notes, "✅ Transaction simulation successful!");
+        note!(self.settings.notes, "💰 Fee: {}", validation.simulation.fee_confidence.describe(validation.simulation.fee_estimate));
+        note!(self.settings.notes, "⚡ Compute units: {}", validation.simulation.compute_units_consumed);
         
         if !validation.simulation.logs.is_empty() {
-            println!("📋 Expected program logs:");
+            note!(self.settings.notes, "📋 Expected program logs:");
             for log in &validation.simulation.logs {
                 if log.contains("Program log:") {
-                    println!("  📝 {}", log.replace("Program log: ", ""));
+                    note!(self.settings.notes, "  📝 {}", log.replace("Program log: ", ""));
                 }
             }
         }
+        self.settings.notes.text(format_post_accounts(self.idl_loader.as_ref(), &validation.simulation.post_accounts));
 
-        println!("🚀 Sending transaction to blockchain...");
-
-        // Send the transaction
-        match self.rpc_client.send_and_confirm_transaction(transaction) {
-            Ok(signature) => {
-                println!("✅ Transaction confirmed: {}", signature);
-                Ok(SafeSendResult {
-                    sent: true,
-                    signature: Some(signature),
-                    validation_issues: Vec::new(),
-                    simulation: validation.simulation,
-                })
-            }
+        if self.settings.dry_run {
+            note!(self.settings.notes, "🧪 Dry run: simulation succeeded, not sending");
+            return Ok(SafeSendResult {
+                sent: false,
+                signature: None,
+                validation_issues: Vec::new(),
+                simulation: validation.simulation,
+            });
+        }
+        tx_sender::check_max_fee(&self.rpc_client, &self.settings, &transaction.message)?;
+        note!(self.settings.notes, "🚀 Sending transaction to blockchain...");
+
+        // Already simulated above; send with the shared retry and log handling
+        let opts = tx_sender::SendOptions { idl_loader: self.idl_loader.as_ref(), ..Default::default() };
+        match tx_sender::send_signed(&self.rpc_client, &self.settings, transaction, opts) {
+            Ok(signature) => Ok(SafeSendResult {
+                sent: signature.is_some(),
+                signature,
+                validation_issues: Vec::new(),
+                simulation: validation.simulation,
+            }),
             Err(e) => {
-                println!("❌ Transaction failed to send: {}", e);
+                note!(self.settings.notes, "❌ Transaction failed to send: {}", e);
                 Ok(SafeSendResult {
                     sent: false,
                     signature: None,
@@ -460,9 +721,14 @@ pub struct SimulationResult {
     pub error_message: Option<String>,
     pub compute_units_consumed: u64,
     pub fee_estimate: u64,
+    pub fee_confidence: FeeConfidence,
     pub logs: Vec<String>,
     pub account_changes: HashMap<String, String>,
     pub warnings: Vec<String>,
+    /// Post-simulation state of each `--return-accounts` address (`None` if it doesn't exist)
+    pub post_accounts: Vec<(Pubkey, Option<Account>)>,
+    /// Index of the instruction that failed, when the error names one
+    pub failed_instruction: Option<usize>,
 }
 
 impl SimulationResult {
@@ -500,12 +766,29 @@ pub struct ValidationResult {
 pub struct TransactionPreview {
     pub will_succeed: bool,
     pub estimated_fee: u64,
+    pub fee_confidence: FeeConfidence,
     pub compute_units: u64,
     pub account_changes: Vec<String>,
     pub program_logs: Vec<String>,
     pub error_summary: Option<String>,
 }
 
+impl TransactionPreview {
+    /// The integer a program logged as its result: the last number on the last log line mentioning "result" or "sum".
+    pub fn logged_result(&self) -> Option<i64> {
+        self.program_logs.iter().rev()
+            .filter(|log| {
+                let lower = log.to_lowercase();
+                lower.contains("result") || lower.contains("sum")
+            })
+            .find_map(|log| {
+                log.split(|c: char| !(c.is_ascii_digit() || c == '-'))
+                    .rev()
+                    .find_map(|token| token.parse::<i64>().ok())
+            })
+    }
+}
+
 #[derive(Debug)]
 pub struct SafeSendResult {
     pub sent: bool,
@@ -517,8 +800,6 @@ pub struct SafeSendResult {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use solana_sdk::signature::Keypair;
-    use solana_sdk::system_instruction;
 
     #[test]
     fn test_simulation_result() {
@@ -527,12 +808,114 @@ mod tests {
             error_message: None,
             compute_units_consumed: 1000,
             fee_estimate: 5000,
+            fee_confidence: FeeConfidence::Exact,
             logs: vec!["Program log: Test".to_string()],
             account_changes: HashMap::new(),
             warnings: Vec::new(),
+            post_accounts: Vec::new(),
+            failed_instruction: None,
         };
 
         assert!(result.is_success());
         assert!(result.get_error_message().is_none());
     }
+
+    #[test]
+    fn test_heuristic_fee() {
+        assert_eq!(heuristic_fee(1, 0), 5_000);
+        // Under 1000 units used to round to a free compute charge
+        assert_eq!(heuristic_fee(1, 150), 5_100);
+        assert_eq!(heuristic_fee(2, 200_000), 30_000);
+        assert_eq!(heuristic_fee(1, u64::MAX), 5_000 + 140_000);
+        assert_eq!(heuristic_fee(usize::MAX, u64::MAX), u64::MAX);
+        assert!(FeeConfidence::Estimated.describe(5_100).contains("lower bound"));
+        assert_eq!(FeeConfidence::Exact.describe(5_000), "5000 lamports");
+    }
+
+    #[test]
+    fn test_simulation_mode_config() {
+        let lenient = SimulationMode::default().config(&[]);
+        assert!(lenient.replace_recent_blockhash);
+        assert!(!lenient.sig_verify);
+
+        let strict = SimulationMode::Strict.config(&[]);
+        assert!(!strict.replace_recent_blockhash);
+        assert!(strict.sig_verify);
+        assert!(strict.accounts.is_none());
+
+        let returned = Pubkey::new_unique();
+        let addresses = SimulationMode::Lenient.config(&[returned]).accounts.unwrap().addresses;
+        assert_eq!(addresses, vec![returned.to_string()]);
+    }
+
+    #[test]
+    fn test_logged_result() {
+        let preview = |logs: &[&str]| TransactionPreview {
+            will_succeed: true,
+            estimated_fee: 5_000,
+            fee_confidence: FeeConfidence::Exact,
+            compute_units: 1_000,
+            account_changes: Vec::new(),
+            program_logs: logs.iter().map(|l| l.to_string()).collect(),
+            error_summary: None,
+        };
+
+        assert_eq!(preview(&["Instruction: Add", "Adding 2 + 40", "Result: 42"]).logged_result(), Some(42));
+        assert_eq!(preview(&["Sum of -5 and 3 is -2"]).logged_result(), Some(-2));
+        assert_eq!(preview(&["Instruction: Add", "Adding 2 + 40"]).logged_result(), None);
+    }
+
+    #[test]
+    fn test_is_transport_error() {
+        let io = ClientError::from(std::io::Error::new(std::io::ErrorKind::TimedOut, "timed out"));
+        assert!(is_transport_error(&io));
+
+        let rejected = ClientError::from(ClientErrorKind::Custom("invalid transaction".to_string()));
+        assert!(!is_transport_error(&rejected));
+    }
+
+    #[test]
+    fn test_token_transfers_from_logs() {
+        use solana_sdk::compute_budget::ComputeBudgetInstruction;
+        use solana_sdk::instruction::Instruction;
+        use solana_sdk::message::Message;
+
+        let payer = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let checked = spl_token::instruction::transfer_checked(
+            &spl_token::id(), &Pubkey::new_unique(), &mint, &Pubkey::new_unique(), &payer, &[], 2_500_000, 6,
+        ).unwrap();
+        let router = Instruction::new_with_bytes(Pubkey::new_unique(), &[1], vec![]);
+        let message = VersionedMessage::Legacy(Message::new(
+            &[ComputeBudgetInstruction::set_compute_unit_price(1), checked, router.clone()], Some(&payer),
+        ));
+
+        // Captured from a token transfer followed by a router that moves tokens via CPI
+        let logs: Vec<String> = [
+            "Program ComputeBudget111111111111111111111111111111 invoke [1]",
+            "Program ComputeBudget111111111111111111111111111111 success",
+            "Program TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA invoke [1]",
+            "Program log: Instruction: TransferChecked",
+            "Program TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA consumed 6200 of 399850 compute units",
+            "Program TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA success",
+            &format!("Program {} invoke [1]", router.program_id),
+            "Program log: Instruction: Route",
+            "Program TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA invoke [2]",
+            "Program log: Instruction: Transfer",
+            "Program TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA consumed 4645 of 380000 compute units",
+            "Program TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA success",
+            &format!("Program {} success", router.program_id),
+        ].iter().map(|l| l.to_string()).collect();
+
+        let transfers = token_transfers(&logs, &message);
+        let formatted = format_token_transfers(&logs, &message);
+        assert!(formatted.starts_with("🪙 Token transfers:\n"));
+        assert_eq!(formatted.lines().count(), 3);
+        assert_eq!(format_token_transfers(&[], &message), "");
+        assert_eq!(transfers, vec![
+            TokenTransfer { amount: Some(2_500_000), mint: Some(mint), decimals: Some(6) },
+            TokenTransfer { amount: None, mint: None, decimals: None },
+        ]);
+        assert_eq!(transfers[0].describe(), format!("transferred 2.5 tokens of mint {}", mint));
+    }
 }
@@ -0,0 +1,800 @@
+use anyhow::Result;
+use clap::ValueEnum;
+use serde::Serialize;
+use solana_client::rpc_client::{RpcClient, SerializableTransaction};
+use solana_client::rpc_config::{RpcSendTransactionConfig, RpcTransactionConfig};
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::address_lookup_table::state::AddressLookupTable;
+use solana_sdk::message::{v0, AddressLookupTableAccount, Message, VersionedMessage};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signature};
+use solana_sdk::signer::Signer;
+use solana_sdk::transaction::VersionedTransaction;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use crate::account_inspector::format_post_accounts;
+use crate::account_resolver::dedup_account_metas;
+use crate::idl_loader::IdlLoader;
+use crate::transaction_simulator::{failed_instruction, post_accounts, SimulationMode};
+use crate::explorer::ExplorerLinks;
+use crate::idempotency::IdempotencyKey;
+use crate::priority_fee::PriorityFees;
+use crate::rpc::SendCounter;
+use crate::{error_decoder, instruction_dump, proposal, rpc, sign_only};
+use crate::outcome::{note, Notes};
+
+/// Message format for transactions built by `build_and_send` (`--tx-version`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum TxVersion {
+    #[default]
+    Legacy,
+    V0,
+}
+
+/// Run-wide sending configuration from the global flags, passed to everything that builds or sends a transaction.
+#[derive(Debug)]
+pub struct SendSettings {
+    /// Simulate instead of sending (`--dry-run`)
+    pub dry_run: bool,
+    /// Print every sent transaction's logs after confirmation (`--confirm-and-show-logs`)
+    pub show_logs: bool,
+    /// Print every signed transaction instead of sending it (`--build-only`)
+    pub build_only: bool,
+    /// Sign with the local keypairs and print the partial signatures instead of sending (`--sign-only`)
+    pub sign_only: bool,
+    /// Print built instructions as a proposal for this multisig instead of signing them (`--propose-to`)
+    pub propose_to: Option<Pubkey>,
+    /// Print instructions before they are signed (`--dump-instruction`)
+    pub dump_instructions: bool,
+    /// Print each fetched blockhash's expiry
+    pub show_blockhash_expiry: bool,
+    /// `--send-retries`
+    pub send_retries: u32,
+    /// `--tx-version`
+    pub tx_version: TxVersion,
+    /// Tables used to compress every v0 message (`--alt`)
+    pub lookup_tables: Vec<AddressLookupTableAccount>,
+    /// Refuse a transaction whose estimated fee exceeds this (`--max-fee-lamports`)
+    pub max_fee_lamports: Option<u64>,
+    pub priority: PriorityFees,
+    /// Commitment sends wait for (`--confirm-commitment`)
+    pub confirm_commitment: CommitmentConfig,
+    /// Accounts whose post-state every simulation returns (`--return-accounts`)
+    pub return_accounts: Vec<Pubkey>,
+    /// Treat pre-send warnings (compute, fees, quote price impact) as blocking issues (`--fail-on-warning`)
+    pub fail_on_warning: bool,
+    pub explorer: ExplorerLinks,
+    pub idempotency: Option<IdempotencyKey>,
+    /// Transactions submitted so far
+    pub sends: SendCounter,
+    /// Where building and sending report progress
+    pub notes: Notes,
+    warned_commitment: AtomicBool,
+}
+
+impl Default for SendSettings {
+    fn default() -> Self {
+        Self {
+            dry_run: false,
+            show_logs: false,
+            build_only: false,
+            sign_only: false,
+            propose_to: None,
+            dump_instructions: false,
+            show_blockhash_expiry: false,
+            send_retries: 0,
+            tx_version: TxVersion::default(),
+            lookup_tables: Vec::new(),
+            max_fee_lamports: None,
+            priority: PriorityFees::default(),
+            confirm_commitment: CommitmentConfig::confirmed(),
+            return_accounts: Vec::new(),
+            fail_on_warning: false,
+            explorer: ExplorerLinks::default(),
+            idempotency: None,
+            sends: SendCounter::default(),
+            notes: Notes::default(),
+            warned_commitment: AtomicBool::new(false),
+        }
+    }
+}
+
+impl SendSettings {
+    /// Under `--fail-on-warning`, move every warning into `issues`. Returns whether any were moved.
+    pub fn promote_warnings(&self, issues: &mut Vec<String>, warnings: &mut Vec<String>) -> bool {
+        if !self.fail_on_warning || warnings.is_empty() {
+            return false;
+        }
+        issues.extend(warnings.drain(..).map(|w| format!("{} (--fail-on-warning)", w)));
+        true
+    }
+}
+
+/// Fetch the latest blockhash, printing roughly how long it stays valid when expiry output is on.
+pub fn latest_blockhash(rpc_client: &RpcClient, settings: &SendSettings) -> Result<rpc::BlockhashWindow> {
+    let window = rpc::latest_blockhash(rpc_client)?;
+    if settings.show_blockhash_expiry {
+        let block_height = rpc_client.get_block_height_with_commitment(rpc_client.commitment())?;
+        note!(settings.notes, "⏳ Blockhash {} valid through block height {} (~{}s)",
+             window.blockhash, window.last_valid_block_height, window.expires_in_secs(block_height));
+    }
+    Ok(window)
+}
+
+/// Sending policy, checked before every send: refuse one that doesn't go through `build_and_send` (Jupiter,
+/// safe-send, batch) under `--build-only` or `--sign-only`, and one `--idempotency-key` says already went out.
+/// `signature` is the transaction's own, so resubmitting the same bytes stays allowed.
+pub fn ensure_sending_allowed(rpc_client: &RpcClient, settings: &SendSettings, signature: &Signature) -> Result<()> {
+    if settings.build_only {
+        return Err(anyhow::anyhow!("--build-only is not supported by this command; nothing was sent"));
+    }
+    if settings.sign_only {
+        return Err(anyhow::anyhow!("--sign-only is not supported by this command; nothing was sent"));
+    }
+    match &settings.idempotency {
+        Some(key) => key.check(rpc_client, signature, &settings.notes),
+        None => Ok(()),
+    }
+}
+
+/// Submit once, after `ensure_sending_allowed`, and record the signature for `--idempotency-key` as soon as the
+/// RPC accepts it. Follow with `confirm_sent`.
+pub fn submit(
+    rpc_client: &RpcClient,
+    settings: &SendSettings,
+    transaction: &impl SerializableTransaction,
+    config: Option<RpcSendTransactionConfig>,
+) -> Result<Signature> {
+    settings.sends.record();
+    let signature = rpc::submit(rpc_client, transaction, config)?;
+    if let Some(key) = &settings.idempotency {
+        key.record(&signature, transaction.get_recent_blockhash(), &settings.notes);
+    }
+    Ok(signature)
+}
+
+/// Warn once per run when sends confirm below `confirmed`.
+fn warn_weak_commitment(settings: &SendSettings) {
+    if let Some(warning) = rpc::weak_commitment_warning(settings.confirm_commitment) {
+        if !settings.warned_commitment.swap(true, Ordering::Relaxed) {
+            note!(settings.notes, "⚠️  {}", warning);
+        }
+    }
+}
+
+/// Wait for a submitted transaction to reach the confirm commitment, rebroadcasting it while dropped.
+pub fn confirm_sent(rpc_client: &RpcClient, settings: &SendSettings, transaction: &impl SerializableTransaction) -> Result<Signature> {
+    warn_weak_commitment(settings);
+    Ok(rpc::confirm_sent(rpc_client, transaction, settings.confirm_commitment, &settings.notes)?)
+}
+
+/// `ensure_sending_allowed`, `submit` with the client's preflight, then `confirm_sent`.
+pub fn send_and_confirm(rpc_client: &RpcClient, settings: &SendSettings, transaction: &impl SerializableTransaction) -> Result<Signature> {
+    ensure_sending_allowed(rpc_client, settings, transaction.get_signature())?;
+    submit(rpc_client, settings, transaction, None)?;
+    confirm_sent(rpc_client, settings, transaction)
+}
+
+/// `send_and_confirm` without progress output, for concurrent sends.
+pub fn send_and_confirm_quiet(rpc_client: &RpcClient, settings: &SendSettings, transaction: &impl SerializableTransaction) -> Result<Signature> {
+    ensure_sending_allowed(rpc_client, settings, transaction.get_signature())?;
+    submit(rpc_client, settings, transaction, None)?;
+    warn_weak_commitment(settings);
+    Ok(rpc::confirm_sent_quiet(rpc_client, transaction, settings.confirm_commitment)?)
+}
+
+/// `--build-only` output: a signed transaction for someone else to submit before its blockhash expires.
+#[derive(Debug, Serialize)]
+pub struct BuiltTransaction {
+    /// Base64 wire-format transaction
+    pub transaction: String,
+    pub signature: String,
+    pub recent_blockhash: String,
+    /// The transaction can't land after this block height
+    pub last_valid_block_height: u64,
+}
+
+impl BuiltTransaction {
+    pub fn new(transaction: &VersionedTransaction, blockhash: &rpc::BlockhashWindow) -> Result<Self> {
+        use base64::Engine;
+        let bytes = bincode::serde::encode_to_vec(transaction, bincode::config::legacy())?;
+        Ok(Self {
+            transaction: base64::engine::general_purpose::STANDARD.encode(bytes),
+            signature: transaction.signatures.first().map(|s| s.to_string()).unwrap_or_default(),
+            recent_blockhash: blockhash.blockhash.to_string(),
+            last_valid_block_height: blockhash.last_valid_block_height,
+        })
+    }
+}
+
+fn fee_within_limit(fee: u64, max_fee: u64) -> Result<()> {
+    if fee > max_fee {
+        return Err(anyhow::anyhow!(
+            "Estimated fee {} lamports exceeds --max-fee-lamports {}; not sending", fee, max_fee
+        ));
+    }
+    Ok(())
+}
+
+/// Under `--max-fee-lamports`, error unless the RPC's fee for `message` (base + priority) is within the limit.
+pub fn check_max_fee(rpc_client: &RpcClient, settings: &SendSettings, message: &VersionedMessage) -> Result<()> {
+    let Some(max_fee) = settings.max_fee_lamports else {
+        return Ok(());
+    };
+    let fee = message_fee(rpc_client, message)
+        .map_err(|e| anyhow::anyhow!("Could not estimate the fee for --max-fee-lamports: {}", e))?;
+    fee_within_limit(fee, max_fee)?;
+    note!(settings.notes, "💸 Estimated fee: {} lamports (limit {})", fee, max_fee);
+    Ok(())
+}
+
+/// Fee the cluster would charge for `message` (base + priority), in lamports.
+pub fn message_fee(rpc_client: &RpcClient, message: &VersionedMessage) -> Result<u64> {
+    Ok(match message {
+        VersionedMessage::Legacy(message) => rpc_client.get_fee_for_message(message),
+        VersionedMessage::V0(message) => rpc_client.get_fee_for_message(message),
+    }?)
+}
+
+/// Fetch and parse `--alt` address lookup tables.
+pub fn fetch_lookup_tables(rpc_client: &RpcClient, addresses: &[Pubkey]) -> Result<Vec<AddressLookupTableAccount>> {
+    addresses.iter().map(|key| {
+        let account = rpc_client.get_account(key)
+            .map_err(|e| anyhow::anyhow!("Failed to fetch lookup table {}: {}", key, e))?;
+        let table = AddressLookupTable::deserialize(&account.data)
+            .map_err(|e| anyhow::anyhow!("{} is not an address lookup table: {}", key, e))?;
+        Ok(AddressLookupTableAccount { key: *key, addresses: table.addresses.to_vec() })
+    }).collect()
+}
+
+/// Error if a table holds none of the accounts the instructions reference; returns how many accounts it covers.
+fn check_lookup_tables(instructions: &[Instruction], tables: &[AddressLookupTableAccount]) -> Result<usize> {
+    let mut referenced: Vec<Pubkey> = instructions.iter()
+        .flat_map(|ix| ix.accounts.iter().map(|meta| meta.pubkey))
+        .collect();
+    referenced.sort();
+    referenced.dedup();
+
+    for table in tables {
+        if !referenced.iter().any(|key| table.addresses.contains(key)) {
+            return Err(anyhow::anyhow!("Lookup table {} contains none of this transaction's accounts", table.key));
+        }
+    }
+    Ok(referenced.iter()
+        .filter(|key| tables.iter().any(|t| t.addresses.contains(key)))
+        .count())
+}
+
+#[derive(Default, Clone, Copy)]
+pub struct SendOptions<'a> {
+    /// IDL used to decode the instruction dump and simulation errors
+    pub idl_loader: Option<&'a IdlLoader>,
+    /// Simulate before sending and stop if the simulation fails
+    pub preflight: bool,
+    /// Simulate only for this transaction, on top of `SendSettings::dry_run`
+    pub dry_run: bool,
+    /// Fetch and print the confirmed transaction's logs, on top of `SendSettings::show_logs`
+    pub show_logs: bool,
+    /// Names of the caller's instructions, to report which one failed simulation (compute budget ones excluded)
+    pub labels: &'a [String],
+}
+
+impl<'a> SendOptions<'a> {
+    pub fn with_idl(idl_loader: &'a IdlLoader) -> Self {
+        Self { idl_loader: Some(idl_loader), ..Self::default() }
+    }
+
+    pub fn with_preflight(mut self) -> Self {
+        self.preflight = true;
+        self
+    }
+
+    pub fn with_logs(mut self) -> Self {
+        self.show_logs = true;
+        self
+    }
+
+    pub fn with_labels(mut self, labels: &'a [String]) -> Self {
+        self.labels = labels;
+        self
+    }
+}
+
+/// The program named in a `Program <id> failed: custom program error` line.
+fn failing_program(logs: &[String]) -> Option<&str> {
+    logs.iter().find_map(|line| {
+        let rest = line.strip_prefix("Program ")?;
+        let (program_id, reason) = rest.split_once(' ')?;
+        reason.starts_with("failed: custom program error").then_some(program_id)
+    })
+}
+
+/// Fetch a confirmed transaction's log messages, retrying briefly while the RPC catches up.
+fn fetch_logs(rpc_client: &RpcClient, settings: &SendSettings, signature: &Signature) -> Result<Vec<String>> {
+    // getTransaction only serves confirmed or finalized transactions
+    let commitment = match settings.confirm_commitment {
+        c if c == CommitmentConfig::processed() => CommitmentConfig::confirmed(),
+        c => c,
+    };
+    let config = RpcTransactionConfig {
+        encoding: None,
+        commitment: Some(commitment),
+        max_supported_transaction_version: Some(0),
+    };
+    let mut attempt = 0;
+    loop {
+        match rpc_client.get_transaction_with_config(signature, config) {
+            Ok(tx) => {
+                let logs: Option<Vec<String>> = tx.transaction.meta.and_then(|meta| meta.log_messages.into());
+                return Ok(logs.unwrap_or_default());
+            }
+            Err(_) if attempt < 5 => {
+                attempt += 1;
+                std::thread::sleep(Duration::from_millis(500));
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+}
+
+/// Print the program logs of a confirmed transaction, decoding custom errors via the IDL.
+pub fn print_transaction_logs(rpc_client: &RpcClient, settings: &SendSettings, signature: &Signature, idl_loader: Option<&IdlLoader>) {
+    let logs = match fetch_logs(rpc_client, settings, signature) {
+        Ok(logs) => logs,
+        Err(e) => {
+            note!(settings.notes, "⚠️  Could not fetch transaction logs ({})", e);
+            note!(settings.notes, "🔍 Use: {}", settings.explorer.confirm_command(signature));
+            return;
+        }
+    };
+
+    note!(settings.notes, "📜 Program logs:");
+    for line in &logs {
+        if let Some(message) = line.strip_prefix("Program log: ") {
+            note!(settings.notes, "  📝 {}", message);
+        } else if line.starts_with("Program data: ") {
+            note!(settings.notes, "  📦 {}", line);
+        }
+    }
+    if failing_program(&logs).is_some() {
+        error_decoder::note_decoded_error(&settings.notes, idl_loader, &[], None, &logs);
+    }
+}
+
+/// Fee payer first, then each authority that isn't already in the set.
+pub fn signer_set<'a>(fee_payer: &'a Keypair, authorities: &[&'a Keypair]) -> Vec<&'a Keypair> {
+    let mut signers = vec![fee_payer];
+    for authority in authorities {
+        if !signers.iter().any(|s| s.pubkey() == authority.pubkey()) {
+            signers.push(authority);
+        }
+    }
+    signers
+}
+
+/// Build and sign a legacy or v0 transaction per `--tx-version`; `signers[0]` pays.
+pub fn sign_transaction(
+    settings: &SendSettings,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+    recent_blockhash: solana_sdk::hash::Hash,
+) -> Result<VersionedTransaction> {
+    sign_with_version(settings.tx_version, instructions, signers, recent_blockhash, &settings.lookup_tables, &settings.notes)
+}
+
+fn sign_with_version(
+    version: TxVersion,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+    recent_blockhash: solana_sdk::hash::Hash,
+    tables: &[AddressLookupTableAccount],
+    notes: &Notes,
+) -> Result<VersionedTransaction> {
+    let fee_payer = signers.first()
+        .ok_or_else(|| anyhow::anyhow!("A transaction needs at least one signer"))?;
+    let message = compile_with_version(version, instructions, &fee_payer.pubkey(), recent_blockhash, tables, notes)?;
+    VersionedTransaction::try_new(message, signers)
+        .map_err(|e| anyhow::anyhow!("Failed to sign transaction: {}", e))
+}
+
+/// Compile an unsigned legacy or v0 message per `--tx-version`; `fee_payer` pays.
+pub fn compile_message(
+    settings: &SendSettings,
+    instructions: &[Instruction],
+    fee_payer: &Pubkey,
+    recent_blockhash: solana_sdk::hash::Hash,
+) -> Result<VersionedMessage> {
+    compile_with_version(settings.tx_version, instructions, fee_payer, recent_blockhash, &settings.lookup_tables, &settings.notes)
+}
+
+/// `instructions` with every use of a pubkey carrying the flags `dedup_account_metas` merges for it, so a
+/// key that is readonly in one place and writable in another is writable everywhere. Positions are kept:
+/// programs read their accounts by index.
+fn merge_account_flags(instructions: &[Instruction]) -> Vec<Instruction> {
+    let merged = dedup_account_metas(instructions.iter().flat_map(|ix| ix.accounts.iter().cloned()).collect());
+    instructions.iter()
+        .map(|ix| Instruction {
+            program_id: ix.program_id,
+            accounts: ix.accounts.iter()
+                .map(|meta| merged.iter().find(|m| m.pubkey == meta.pubkey).cloned().expect("merged above"))
+                .collect(),
+            data: ix.data.clone(),
+        })
+        .collect()
+}
+
+fn compile_with_version(
+    version: TxVersion,
+    instructions: &[Instruction],
+    fee_payer: &Pubkey,
+    recent_blockhash: solana_sdk::hash::Hash,
+    tables: &[AddressLookupTableAccount],
+    notes: &Notes,
+) -> Result<VersionedMessage> {
+    let instructions = &merge_account_flags(instructions);
+    match version {
+        TxVersion::Legacy if !tables.is_empty() => Err(anyhow::anyhow!("Lookup tables need --tx-version v0")),
+        TxVersion::Legacy => Ok(VersionedMessage::Legacy(Message::new_with_blockhash(instructions, Some(fee_payer), &recent_blockhash))),
+        TxVersion::V0 => {
+            if !tables.is_empty() {
+                let covered = check_lookup_tables(instructions, tables)?;
+                note!(notes, "🗜️  {} account(s) found in {} lookup table(s)", covered, tables.len());
+            }
+            let message = v0::Message::try_compile(fee_payer, instructions, tables, recent_blockhash)
+                .map_err(|e| anyhow::anyhow!("Failed to compile v0 message: {}", e))?;
+            Ok(VersionedMessage::V0(message))
+        }
+    }
+}
+
+/// Compute units allowed for each instruction `prepend_instructions` adds (an ATA creation is ~25k).
+const PREPENDED_COMPUTE_UNITS: u32 = 30_000;
+
+/// The instructions of a compiled message, with lookup-table accounts resolved from `tables`. Account flags are the
+/// message's, so a key another instruction writes comes back writable everywhere.
+pub fn decompile_instructions(message: &VersionedMessage, tables: &[AddressLookupTableAccount]) -> Result<Vec<Instruction>> {
+    let header = message.header();
+    let static_keys = message.static_account_keys();
+    let signed = header.num_required_signatures as usize;
+    let mut keys: Vec<AccountMeta> = static_keys.iter().enumerate().map(|(i, key)| {
+        let writable = if i < signed {
+            i < signed - header.num_readonly_signed_accounts as usize
+        } else {
+            i < static_keys.len() - header.num_readonly_unsigned_accounts as usize
+        };
+        AccountMeta { pubkey: *key, is_signer: i < signed, is_writable: writable }
+    }).collect();
+    // Loaded addresses follow the static keys: every table's writable ones, then every table's readonly ones
+    let lookups = message.address_table_lookups().unwrap_or_default();
+    let mut readonly = Vec::new();
+    for lookup in lookups {
+        let table = tables.iter().find(|t| t.key == lookup.account_key)
+            .ok_or_else(|| anyhow::anyhow!("Lookup table {} was not loaded", lookup.account_key))?;
+        let address = |index: &u8| table.addresses.get(*index as usize).copied()
+            .ok_or_else(|| anyhow::anyhow!("Lookup table {} has no index {}", table.key, index));
+        for index in &lookup.writable_indexes {
+            keys.push(AccountMeta::new(address(index)?, false));
+        }
+        for index in &lookup.readonly_indexes {
+            readonly.push(AccountMeta::new_readonly(address(index)?, false));
+        }
+    }
+    keys.extend(readonly);
+
+    let key = |index: u8| keys.get(index as usize).cloned()
+        .ok_or_else(|| anyhow::anyhow!("Instruction references missing account index {}", index));
+    message.instructions().iter().map(|ix| Ok(Instruction {
+        program_id: key(ix.program_id_index)?.pubkey,
+        accounts: ix.accounts.iter().map(|index| key(*index)).collect::<Result<_>>()?,
+        data: ix.data.clone(),
+    })).collect()
+}
+
+/// `message` with `instructions` run first, e.g. to simulate ATA creation together with a prebuilt swap.
+/// Keeps the payer, blockhash and lookup tables, and raises any compute unit limit to cover the additions.
+pub fn prepend_instructions(rpc_client: &RpcClient, message: &VersionedMessage, instructions: &[Instruction]) -> Result<VersionedMessage> {
+    let table_keys: Vec<Pubkey> = message.address_table_lookups().unwrap_or_default().iter()
+        .map(|lookup| lookup.account_key)
+        .collect();
+    let tables = fetch_lookup_tables(rpc_client, &table_keys)?;
+    let mut combined: Vec<Instruction> = instructions.to_vec();
+    combined.extend(decompile_instructions(message, &tables)?);
+    for ix in &mut combined {
+        // SetComputeUnitLimit: tag 2, then a little-endian u32
+        if ix.program_id == solana_sdk::compute_budget::id() && ix.data.len() == 5 && ix.data[0] == 2 {
+            let limit = u32::from_le_bytes(ix.data[1..5].try_into()?)
+                .saturating_add(PREPENDED_COMPUTE_UNITS * instructions.len() as u32);
+            ix.data[1..5].copy_from_slice(&limit.to_le_bytes());
+        }
+    }
+    let payer = message.static_account_keys().first()
+        .ok_or_else(|| anyhow::anyhow!("Message has no fee payer"))?;
+    let compiled = v0::Message::try_compile(payer, &combined, &tables, *message.recent_blockhash())
+        .map_err(|e| anyhow::anyhow!("Failed to compile v0 message: {}", e))?;
+    Ok(VersionedMessage::V0(compiled))
+}
+
+/// Under `--sign-only`, sign `instructions` with whichever of `signers` are local (`signers[0]` pays), print the
+/// partial signatures, and return `true`: the caller must not send. Call after adding compute budget instructions.
+pub fn sign_only_if_requested(rpc_client: &RpcClient, settings: &SendSettings, instructions: &[Instruction], signers: &[&Keypair]) -> Result<bool> {
+    if !settings.sign_only {
+        return Ok(false);
+    }
+    let fee_payer = signers.first().ok_or_else(|| anyhow::anyhow!("A transaction needs at least one signer"))?;
+    let blockhash = latest_blockhash(rpc_client, settings)?;
+    let message = compile_message(settings, instructions, &fee_payer.pubkey(), blockhash.blockhash)?;
+    check_max_fee(rpc_client, settings, &message)?;
+    let signed = sign_only::partial_sign(&message, signers, &blockhash)?;
+    let made = signed.signers.iter().filter(|slot| slot.signature.is_some()).count();
+    note!(settings.notes, "✍️  Sign only: {} of {} signature(s) made here; `combine` every signer's output before block height {}",
+         made, signed.signers.len(), blockhash.last_valid_block_height);
+    settings.notes.document(&signed)?;
+    Ok(true)
+}
+
+/// Add the priority fee, dump, sign (`signers[0]` pays), check `--max-fee-lamports`, optionally simulate, then send and confirm.
+/// Returns `None` when the transaction was not sent (dry run, `--build-only`, `--sign-only`, `--propose-to`, or failed preflight).
+pub fn build_and_send(
+    rpc_client: &RpcClient,
+    settings: &SendSettings,
+    instructions: Vec<Instruction>,
+    signers: &[&Keypair],
+    opts: SendOptions,
+) -> Result<Option<Signature>> {
+    let Some((instructions, transaction)) = build_signed(rpc_client, settings, instructions, signers, opts.idl_loader)? else {
+        return Ok(None);
+    };
+    let resign = |blockhash| sign_transaction(settings, &instructions, signers, blockhash);
+    send_signed_with(rpc_client, settings, &transaction, opts, Some(&resign))
+}
+
+/// `build_and_send` up to the send, for callers that send their own way (safe-send's validation): the final
+/// instructions and signed transaction, or `None` once it was proposed, signed only, or printed under `--build-only`.
+pub fn build_signed(
+    rpc_client: &RpcClient,
+    settings: &SendSettings,
+    instructions: Vec<Instruction>,
+    signers: &[&Keypair],
+    idl_loader: Option<&IdlLoader>,
+) -> Result<Option<(Vec<Instruction>, VersionedTransaction)>> {
+    if proposal::propose_if_requested(&settings.notes, settings.propose_to, &instructions)? {
+        return Ok(None);
+    }
+    let instructions = settings.priority.with_compute_budget(rpc_client, &settings.notes, instructions)?;
+    if settings.dump_instructions {
+        instruction_dump::dump_instructions(&settings.notes, idl_loader, &instructions);
+    }
+    if sign_only_if_requested(rpc_client, settings, &instructions, signers)? {
+        return Ok(None);
+    }
+
+    let blockhash = latest_blockhash(rpc_client, settings)?;
+    let transaction = sign_transaction(settings, &instructions, signers, blockhash.blockhash)?;
+    check_max_fee(rpc_client, settings, &transaction.message)?;
+    if settings.build_only {
+        note!(settings.notes, "📦 Build only: signed transaction not sent; submit it before block height {}", blockhash.last_valid_block_height);
+        settings.notes.document(&BuiltTransaction::new(&transaction, &blockhash)?)?;
+        return Ok(None);
+    }
+    Ok(Some((instructions, transaction)))
+}
+
+/// Simulate under dry-run or preflight, then send and confirm with `--send-retries`. Returns `None` when the
+/// transaction was not sent (dry run or failed preflight). For transactions signed outside `build_and_send`, so
+/// retries stop once the blockhash expires: only the signers could re-sign it.
+pub fn send_signed(rpc_client: &RpcClient, settings: &SendSettings, transaction: &VersionedTransaction, opts: SendOptions) -> Result<Option<Signature>> {
+    send_signed_with(rpc_client, settings, transaction, opts, None)
+}
+
+/// `send_signed`, with `resign` rebuilding the transaction on a fresh blockhash once a retry finds it expired.
+fn send_signed_with(
+    rpc_client: &RpcClient,
+    settings: &SendSettings,
+    transaction: &VersionedTransaction,
+    opts: SendOptions,
+    resign: Option<&dyn Fn(solana_sdk::hash::Hash) -> Result<VersionedTransaction>>,
+) -> Result<Option<Signature>> {
+    let dry_run = opts.dry_run || settings.dry_run;
+    if dry_run || opts.preflight {
+        let sim = rpc_client.simulate_transaction_with_config(
+            transaction,
+            SimulationMode::Lenient.config(&settings.return_accounts),
+        );
+        match sim {
+            Ok(sim) => {
+                if let Some(err) = sim.value.err.as_ref() {
+                    note!(settings.notes, "❌ Simulation failed: {:?}", err);
+                    let prepended = transaction.message.instructions().len().saturating_sub(opts.labels.len());
+                    let failed = failed_instruction(Some(err))
+                        .and_then(|index| crate::bundle::bundle_index(index, prepended))
+                        .and_then(|index| opts.labels.get(index).map(|label| (index, label)));
+                    if let Some((index, label)) = failed {
+                        note!(settings.notes, "❌ Instruction #{} ({}) failed; nothing was sent", index, label);
+                    }
+                    let program_ids = error_decoder::message_program_ids(&transaction.message);
+                    error_decoder::note_decoded_error(&settings.notes, opts.idl_loader, &program_ids, Some(err), sim.value.logs.as_deref().unwrap_or(&[]));
+                    return Ok(None);
+                }
+                if dry_run {
+                    note!(settings.notes, "🧪 Dry run: simulation succeeded ({} CU), not sending",
+                         sim.value.units_consumed.unwrap_or(0));
+                    for line in sim.value.logs.iter().flatten() {
+                        note!(settings.notes, "  {}", line);
+                    }
+                    settings.notes.text(format_post_accounts(opts.idl_loader, &post_accounts(&sim.value, &settings.return_accounts)));
+                    return Ok(None);
+                }
+            }
+            Err(e) if dry_run => return Err(anyhow::anyhow!("Dry-run simulation failed: {}", e)),
+            // Preflight is best-effort; the RPC's own preflight still runs on send
+            Err(_) => {}
+        }
+    }
+
+    let retries = settings.send_retries;
+    let mut transaction = transaction.clone();
+    let mut attempt = 0;
+    loop {
+        let error = match send_and_confirm(rpc_client, settings, &transaction) {
+            Ok(signature) => return Ok(Some(report_sent(rpc_client, settings, signature, &opts))),
+            Err(e) if attempt < retries => e,
+            Err(e) => return Err(e),
+        };
+        attempt += 1;
+        // Resubmitting the same signed transaction can't double-spend, but only helps while its blockhash is valid
+        let blockhash = *transaction.message.recent_blockhash();
+        if !rpc_client.is_blockhash_valid(&blockhash, CommitmentConfig::processed()).unwrap_or(true) {
+            let Some(resign) = resign else {
+                return Err(anyhow::anyhow!("{}; not retrying: the blockhash expired and only the signers can re-sign", error));
+            };
+            // A new blockhash makes a new transaction, so the old one must be unable to land first
+            let signature = transaction.signatures[0];
+            match rpc::wait_until_landed_or_expired(rpc_client, &signature, &blockhash)? {
+                Some(Ok(())) => {
+                    note!(settings.notes, "✅ {} landed after all", signature);
+                    return Ok(Some(report_sent(rpc_client, settings, signature, &opts)));
+                }
+                Some(Err(err)) => return Err(anyhow::anyhow!("Transaction {} landed with an error: {}", signature, err)),
+                None => {}
+            }
+            transaction = resign(latest_blockhash(rpc_client, settings)?.blockhash)?;
+            note!(settings.notes, "🔄 Blockhash expired; re-signed as {}", transaction.signatures[0]);
+        }
+        note!(settings.notes, "⚠️  Send failed ({}); retrying {}/{}...", error, attempt, retries);
+    }
+}
+
+/// Print a confirmed send's signature, explorer link, and (when asked) its logs.
+fn report_sent(rpc_client: &RpcClient, settings: &SendSettings, signature: Signature, opts: &SendOptions) -> Signature {
+    note!(settings.notes, "✅ Transaction signature: {}", signature);
+    settings.explorer.note_transaction_url(&settings.notes, &signature);
+    if opts.show_logs || settings.show_logs {
+        print_transaction_logs(rpc_client, settings, &signature, opts.idl_loader);
+    }
+    signature
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::hash::Hash;
+    use solana_sdk::system_instruction;
+
+    #[test]
+    fn test_sign_with_version() {
+        let payer = Keypair::new();
+        let recipient = Pubkey::new_unique();
+        let instructions = vec![system_instruction::transfer(&payer.pubkey(), &recipient, 1)];
+
+        let legacy = sign_with_version(TxVersion::Legacy, &instructions, &[&payer], Hash::default(), &[], &Notes::new()).unwrap();
+        assert!(matches!(legacy.message, VersionedMessage::Legacy(_)));
+
+        let v0 = sign_with_version(TxVersion::V0, &instructions, &[&payer], Hash::default(), &[], &Notes::new()).unwrap();
+        assert!(matches!(v0.message, VersionedMessage::V0(_)));
+        assert_eq!(v0.message.static_account_keys()[0], payer.pubkey());
+        assert!(v0.verify_with_results().iter().all(|ok| *ok));
+
+        assert!(sign_with_version(TxVersion::V0, &instructions, &[], Hash::default(), &[], &Notes::new()).is_err());
+    }
+
+    #[test]
+    fn test_failing_program() {
+        let program_id = Pubkey::new_unique().to_string();
+        let logs = vec![
+            format!("Program {} invoke [1]", program_id),
+            "Program log: Instruction: SendSol".to_string(),
+            format!("Program {} consumed 4000 of 200000 compute units", program_id),
+            format!("Program {} failed: custom program error: 0x1771", program_id),
+        ];
+        assert_eq!(failing_program(&logs), Some(program_id.as_str()));
+        assert_eq!(failing_program(&logs[..3]), None);
+    }
+
+    #[test]
+    fn test_sign_with_lookup_table() {
+        let payer = Keypair::new();
+        let recipient = Pubkey::new_unique();
+        let instructions = vec![system_instruction::transfer(&payer.pubkey(), &recipient, 1)];
+        let table = AddressLookupTableAccount { key: Pubkey::new_unique(), addresses: vec![recipient] };
+
+        let tx = sign_with_version(TxVersion::V0, &instructions, &[&payer], Hash::default(), &[table.clone()], &Notes::new()).unwrap();
+        let VersionedMessage::V0(message) = &tx.message else { panic!("expected v0") };
+        assert_eq!(message.address_table_lookups.len(), 1);
+        assert_eq!(message.address_table_lookups[0].writable_indexes, vec![0]);
+        assert!(!message.account_keys.contains(&recipient));
+
+        let unrelated = AddressLookupTableAccount { key: Pubkey::new_unique(), addresses: vec![Pubkey::new_unique()] };
+        assert!(sign_with_version(TxVersion::V0, &instructions, &[&payer], Hash::default(), &[unrelated], &Notes::new()).is_err());
+        assert!(sign_with_version(TxVersion::Legacy, &instructions, &[&payer], Hash::default(), &[table], &Notes::new()).is_err());
+    }
+
+    #[test]
+    fn test_decompile_instructions() {
+        let payer = Keypair::new();
+        let (recipient, oracle, program) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let instructions = vec![
+            system_instruction::transfer(&payer.pubkey(), &recipient, 1),
+            Instruction::new_with_bytes(program, &[7], vec![AccountMeta::new_readonly(oracle, false), AccountMeta::new(recipient, false)]),
+        ];
+        let table = AddressLookupTableAccount { key: Pubkey::new_unique(), addresses: vec![oracle, recipient] };
+
+        let tx = sign_with_version(TxVersion::V0, &instructions, &[&payer], Hash::default(), &[table.clone()], &Notes::new()).unwrap();
+        assert_eq!(decompile_instructions(&tx.message, &[table]).unwrap(), instructions);
+        assert!(decompile_instructions(&tx.message, &[]).is_err());
+
+        let legacy = sign_with_version(TxVersion::Legacy, &instructions, &[&payer], Hash::default(), &[], &Notes::new()).unwrap();
+        assert_eq!(decompile_instructions(&legacy.message, &[]).unwrap(), instructions);
+    }
+
+    #[test]
+    fn test_merge_account_flags() {
+        let (shared, other, program) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let instructions = vec![
+            Instruction::new_with_bytes(program, &[0], vec![AccountMeta::new_readonly(shared, false), AccountMeta::new_readonly(shared, false)]),
+            Instruction::new_with_bytes(program, &[1], vec![AccountMeta::new_readonly(other, false), AccountMeta::new(shared, false)]),
+        ];
+
+        let merged = merge_account_flags(&instructions);
+        // Every position survives; each use of `shared` is now writable
+        assert_eq!(merged[0].accounts, vec![AccountMeta::new(shared, false), AccountMeta::new(shared, false)]);
+        assert_eq!(merged[1].accounts, vec![AccountMeta::new_readonly(other, false), AccountMeta::new(shared, false)]);
+        assert_eq!(merged[1].data, vec![1]);
+    }
+
+    #[test]
+    fn test_built_transaction() {
+        let payer = Keypair::new();
+        let instructions = vec![system_instruction::transfer(&payer.pubkey(), &Pubkey::new_unique(), 1)];
+        let transaction = sign_with_version(TxVersion::Legacy, &instructions, &[&payer], Hash::new_unique(), &[], &Notes::new()).unwrap();
+        let window = rpc::BlockhashWindow { blockhash: *transaction.message.recent_blockhash(), last_valid_block_height: 300 };
+
+        let built = BuiltTransaction::new(&transaction, &window).unwrap();
+        assert_eq!(built.signature, transaction.signatures[0].to_string());
+        assert_eq!(built.last_valid_block_height, 300);
+        use base64::Engine;
+        let bytes = base64::engine::general_purpose::STANDARD.decode(&built.transaction).unwrap();
+        let (decoded, _): (VersionedTransaction, usize) = bincode::serde::decode_from_slice(&bytes, bincode::config::legacy()).unwrap();
+        assert_eq!(decoded, transaction);
+    }
+
+    #[test]
+    fn test_promote_warnings() {
+        let mut issues = Vec::new();
+        let mut warnings = vec!["Moderate compute usage".to_string()];
+        assert!(!SendSettings::default().promote_warnings(&mut issues, &mut warnings));
+        assert_eq!(warnings.len(), 1);
+
+        let settings = SendSettings { fail_on_warning: true, ..SendSettings::default() };
+        assert!(settings.promote_warnings(&mut issues, &mut warnings));
+        assert!(warnings.is_empty());
+        assert_eq!(issues, vec!["Moderate compute usage (--fail-on-warning)".to_string()]);
+    }
+
+    #[test]
+    fn test_fee_within_limit() {
+        assert!(fee_within_limit(5_000, 5_000).is_ok());
+        let err = fee_within_limit(2_005_000, 100_000).unwrap_err().to_string();
+        assert!(err.contains("2005000") && err.contains("--max-fee-lamports 100000"));
+    }
+}